@@ -2,7 +2,10 @@ use eframe::egui::{self, Color32};
 use egui::{ImageSource, Vec2};
 use rfd::FileDialog;
 use rosu_renderer::{
-    layout::mania::{NoteShape, NoteStyle},
+    layout::mania::{BarlineStyle, FitPolicy, NoteShape, NoteStyle, ReceptorStyle},
+    overview::OverviewView,
+    sections::{SectionKind, StartBehavior},
+    ui::{column_balance, difficulty_picker, overview, save_image, screenshot_button, MapSummary},
     Player,
 };
 use rosu_map::Beatmap;
@@ -11,7 +14,6 @@ use std::path::PathBuf;
 struct ManiaApp {
     player: Player,
     playback_time: f64,
-    map_duration: f64,
     playback_speed: f64,
     scroll_speed: f32,
     note_style_idx: usize,
@@ -19,35 +21,24 @@ struct ManiaApp {
     note_color: Color32,
     hold_body_color: Color32,
     hold_cap_color: Color32,
+    show_receptors: bool,
+    receptor_color: Color32,
     column_width: f32,
     note_size: f32,
-    beatmap_ln: Beatmap,
-    beatmap_normal: Beatmap,
-    using_ln: bool,
+    beatmaps: Vec<Beatmap>,
+    summaries: Vec<MapSummary>,
+    selected_map: usize,
     render_position: [f32; 2],
+    overview_zoom: f64,
+    // Second map shown next to the first one in split view
+    split_player: Option<Player>,
 }
 
 impl ManiaApp {
-    fn new(
-        beatmap_ln: Beatmap,
-        beatmap_normal: Beatmap,
-        column_width: f32,
-        note_size: f32,
-        height: f32,
-    ) -> Option<Self> {
-        // Calculate total map duration including hold notes
-        let map_duration = beatmap_ln
-            .hit_objects
-            .iter()
-            .map(|obj| match &obj.kind {
-                rosu_map::section::hit_objects::HitObjectKind::Hold(h) => {
-                    obj.start_time + h.duration
-                }
-                _ => obj.start_time,
-            })
-            .fold(0.0, f64::max);
+    fn new(beatmaps: Vec<Beatmap>, column_width: f32, note_size: f32, height: f32) -> Option<Self> {
+        let summaries = beatmaps.iter().map(MapSummary::from_beatmap).collect();
 
-        Player::new(beatmap_ln.clone(), column_width, note_size, height).map(|mut player| {
+        Player::new(beatmaps[0].clone(), column_width, note_size, height).map(|mut player| {
             let note_color = Color32::from_rgb(0, 174, 255);
             let hold_body_color = Color32::from_rgb(200, 200, 200);
             let hold_cap_color = Color32::from_rgb(0, 174, 255);
@@ -64,7 +55,6 @@ impl ManiaApp {
             Self {
                 player,
                 playback_time: 0.0,
-                map_duration,
                 playback_speed: 1.0,
                 scroll_speed: 1000.0,
                 note_style_idx: 0,
@@ -72,38 +62,75 @@ impl ManiaApp {
                 note_color,
                 hold_body_color,
                 hold_cap_color,
+                show_receptors: false,
+                receptor_color: ReceptorStyle::default().color,
                 column_width,
                 note_size,
-                beatmap_ln,
-                beatmap_normal,
-                using_ln: true,
+                beatmaps,
+                summaries,
+                selected_map: 0,
                 render_position: [50.0, 50.0],
+                overview_zoom: 1.0,
+                split_player: None,
             }
         })
     }
 
-    fn reload_player(&mut self) {
-        self.reload_player_with_reset(true);
+    fn switch_map(&mut self, index: usize) {
+        let same_keycount =
+            self.summaries[index].keycount == self.summaries[self.selected_map].keycount;
+        self.selected_map = index;
+        self.player.set_beatmap(self.beatmaps[index].clone());
+
+        // Keep the playback position when the layout stays the same
+        if !same_keycount {
+            self.playback_time = 0.0;
+            self.player.set_current_time(0.0);
+        }
     }
 
-    fn reload_player_with_reset(&mut self, should_reset: bool) {
-        let beatmap = if self.using_ln {
-            self.beatmap_ln.clone()
-        } else {
-            self.beatmap_normal.clone()
-        };
+    fn players_mut(&mut self) -> impl Iterator<Item = &mut Player> {
+        std::iter::once(&mut self.player).chain(self.split_player.as_mut())
+    }
 
-        // Update map duration for the new map
-        self.map_duration = beatmap
-            .hit_objects
-            .iter()
-            .map(|obj| match &obj.kind {
-                rosu_map::section::hit_objects::HitObjectKind::Hold(h) => {
-                    obj.start_time + h.duration
-                }
-                _ => obj.start_time,
-            })
-            .fold(0.0, f64::max);
+    fn toggle_split_view(&mut self, enabled: bool) {
+        if !enabled {
+            self.split_player = None;
+            self.player.set_fit_policy(FitPolicy::Clip);
+            return;
+        }
+
+        let other = (self.selected_map + 1) % self.beatmaps.len();
+        let mut split_player = Player::new(
+            self.beatmaps[other].clone(),
+            self.column_width,
+            self.note_size,
+            800.0,
+        )
+        .expect("Failed to create player");
+        split_player.set_note_style(self.get_note_style(self.note_style_idx));
+        split_player.set_speed(self.playback_speed);
+        split_player.set_scroll_time(self.scroll_speed);
+        split_player.set_receptors(self.receptor_style());
+        self.split_player = Some(split_player);
+
+        let playback_time = self.playback_time;
+        for player in self.players_mut() {
+            player.set_fit_policy(FitPolicy::ScaleToFit);
+            player.set_current_time(playback_time);
+        }
+    }
+
+    // Receptors in the note shape, colored independently from the notes
+    fn receptor_style(&self) -> Option<ReceptorStyle> {
+        self.show_receptors.then(|| ReceptorStyle {
+            color: self.receptor_color,
+            ..Default::default()
+        })
+    }
+
+    fn reload_player_with_reset(&mut self, should_reset: bool) {
+        let beatmap = self.beatmaps[self.selected_map].clone();
 
         // Reset playback time only when switching maps
         if should_reset {
@@ -117,6 +144,10 @@ impl ManiaApp {
         self.player.set_current_time(self.playback_time);
         self.player.set_speed(self.playback_speed);
         self.player.set_scroll_time(self.scroll_speed);
+        self.player.set_receptors(self.receptor_style());
+        if self.split_player.is_some() {
+            self.toggle_split_view(true);
+        }
     }
 
     fn get_note_style(&self, idx: usize) -> NoteStyle {
@@ -171,17 +202,40 @@ impl ManiaApp {
             ui.vertical(|ui| {
                 ui.horizontal(|ui| {
                     ui.label("Position:");
-                    if ui
-                        .add(
-                            egui::Slider::new(&mut self.playback_time, 0.0..=self.map_duration)
-                                .suffix(" ms"),
-                        )
-                        .changed()
-                    {
-                        self.player.set_current_time(self.playback_time);
+                    let (response, fraction) = seek_bar(ui, &self.player);
+                    // Frames jump around while dragging
+                    let scrubbing = response.dragged();
+                    for player in self.players_mut() {
+                        player.set_scrub_hint(scrubbing);
+                        if let Some(fraction) = fraction {
+                            player.seek_fraction(fraction);
+                        }
+                    }
+                    if fraction.is_some() {
+                        self.playback_time = self.player.current_time();
                     }
                 });
 
+                ui.horizontal(|ui| {
+                    ui.label("Overview:");
+                    let view = OverviewView {
+                        zoom: self.overview_zoom,
+                        ..OverviewView::of(self.player.hit_objects())
+                    };
+                    let (_, seek) = overview(ui, &self.player, &view, Vec2::new(300.0, 40.0));
+                    if let Some(time) = seek {
+                        for player in self.players_mut() {
+                            player.set_current_time(time);
+                        }
+                        self.playback_time = time;
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut self.overview_zoom, 1.0..=32.0)
+                            .logarithmic(true)
+                            .text("Zoom"),
+                    );
+                });
+
                 ui.horizontal(|ui| {
                     ui.label("Column Width:");
                     let mut changed = false;
@@ -238,17 +292,41 @@ impl ManiaApp {
             // Center controls
             ui.vertical(|ui| {
                 ui.horizontal(|ui| {
-                    if ui.selectable_label(self.using_ln, "LN Map").clicked() && !self.using_ln {
-                        self.using_ln = true;
-                        self.reload_player();
-                    }
-                    if ui.selectable_label(!self.using_ln, "Normal Map").clicked() && self.using_ln
-                    {
-                        self.using_ln = false;
-                        self.reload_player();
+                    ui.label("Difficulty:");
+                    if let Some(index) = difficulty_picker(ui, &self.summaries) {
+                        self.switch_map(index);
                     }
                 });
 
+                ui.label("Left/Right: previous/next note");
+
+                let mut split_view = self.split_player.is_some();
+                if ui.checkbox(&mut split_view, "Split view").changed() {
+                    self.toggle_split_view(split_view);
+                }
+
+                let mut skip_intro = self.player.start_behavior() != StartBehavior::FromZero;
+                if ui.checkbox(&mut skip_intro, "Skip intro").changed() {
+                    let behavior = if skip_intro {
+                        StartBehavior::FromFirstNote { lead_ms: 2000.0 }
+                    } else {
+                        StartBehavior::FromZero
+                    };
+                    for player in self.players_mut() {
+                        player.set_start_behavior(behavior);
+                        player.reset_time();
+                    }
+                    self.playback_time = self.player.current_time();
+                }
+
+                let mut barlines = self.player.barlines().is_some();
+                if ui.checkbox(&mut barlines, "Barlines").changed() {
+                    let style = barlines.then(BarlineStyle::default);
+                    for player in self.players_mut() {
+                        player.set_barlines(style);
+                    }
+                }
+
                 // Show error message if any
                 if let Some(error) = &self.last_error {
                     ui.colored_label(Color32::RED, error);
@@ -263,7 +341,10 @@ impl ManiaApp {
                         .add(egui::Slider::new(&mut self.playback_speed, 0.5..=2.0).suffix("x"))
                         .changed()
                     {
-                        self.player.set_speed(self.playback_speed);
+                        let playback_speed = self.playback_speed;
+                        for player in self.players_mut() {
+                            player.set_speed(playback_speed);
+                        }
                     }
                 });
 
@@ -275,7 +356,10 @@ impl ManiaApp {
                         )
                         .changed()
                     {
-                        self.player.set_scroll_time(self.scroll_speed);
+                        let scroll_speed = self.scroll_speed;
+                        for player in self.players_mut() {
+                            player.set_scroll_time(scroll_speed);
+                        }
                     }
                 });
             });
@@ -300,6 +384,21 @@ impl ManiaApp {
                     self.load_image(path);
                 }
             }
+            if let Some(image) = screenshot_button(ui, &self.player) {
+                if let Some(path) = FileDialog::new()
+                    .add_filter("PNG", &["png"])
+                    .set_file_name("playfield.png")
+                    .save_file()
+                {
+                    if let Err(error) = save_image(&image, path) {
+                        self.last_error = Some(format!("Failed to save screenshot: {error}"));
+                    }
+                }
+            }
+        });
+
+        ui.collapsing("Column balance", |ui| {
+            column_balance(ui, self.player.column_counts());
         });
 
         // Colors in a collapsing section
@@ -337,25 +436,111 @@ impl ManiaApp {
                 self.player
                     .set_note_style(self.get_note_style(self.note_style_idx));
             }
+
+            let mut receptors_changed = ui
+                .checkbox(&mut self.show_receptors, "Show receptors")
+                .changed();
+            ui.horizontal(|ui| {
+                ui.label("Receptor Color:");
+                if ui
+                    .color_edit_button_srgba(&mut self.receptor_color)
+                    .changed()
+                {
+                    receptors_changed = true;
+                }
+            });
+            if receptors_changed {
+                let style = self.receptor_style();
+                for player in self.players_mut() {
+                    player.set_receptors(style.clone());
+                }
+            }
         });
     }
 }
 
+// Seek bar split into the map's sections, like video chapters. Returns its
+// response and the fraction to seek to when clicked or dragged.
+fn seek_bar(ui: &mut egui::Ui, player: &Player) -> (egui::Response, Option<f64>) {
+    let (rect, response) =
+        ui.allocate_exact_size(Vec2::new(300.0, 16.0), egui::Sense::click_and_drag());
+    let duration = player.duration();
+    if duration <= 0.0 {
+        return (response, None);
+    }
+
+    let x_at = |time: f64| rect.min.x + (time / duration) as f32 * rect.width();
+    for section in player.sections() {
+        let color = match section.kind {
+            SectionKind::Intro | SectionKind::Outro => Color32::from_gray(70),
+            SectionKind::Notes => Color32::from_rgb(0, 120, 180),
+            SectionKind::Break => Color32::from_gray(110),
+            SectionKind::Kiai => Color32::from_rgb(230, 140, 0),
+        };
+        let section_rect = egui::Rect::from_x_y_ranges(
+            x_at(section.start_ms)..=x_at(section.end_ms) - 1.0,
+            rect.y_range(),
+        );
+        ui.painter().rect_filled(section_rect, 0.0, color);
+    }
+    let playhead = x_at(player.current_time().min(duration));
+    ui.painter().line_segment(
+        [
+            egui::pos2(playhead, rect.min.y),
+            egui::pos2(playhead, rect.max.y),
+        ],
+        egui::Stroke::new(2.0, Color32::WHITE),
+    );
+
+    let fraction = response
+        .interact_pointer_pos()
+        .map(|pointer| ((pointer.x - rect.min.x) / rect.width()) as f64);
+    (response, fraction)
+}
+
 impl eframe::App for ManiaApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Arrow keys jump between notes, landing them on the judgment line
+        let (prev, next) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowLeft),
+                i.key_pressed(egui::Key::ArrowRight),
+            )
+        });
+        if (next && self.player.seek_next_note(true)) || (prev && self.player.seek_prev_note(true))
+        {
+            let playback_time = self.player.current_time();
+            for player in self.players_mut() {
+                player.set_current_time(playback_time);
+            }
+        }
+
         // Central panel for gameplay
         egui::CentralPanel::default()
             .show(ctx, |ui| {
-                // Use render_at with the specified position
-                self.player.render_at(ui, egui::pos2(self.render_position[0], self.render_position[1]));
+                if let Some(split_player) = &mut self.split_player {
+                    // Each map gets a fixed half of the panel
+                    let rect = ui.max_rect();
+                    let (left, right) = rect.split_left_right_at_fraction(0.5);
+                    self.player.render_in(ui, left);
+                    split_player.render_in(ui, right);
+                } else {
+                    // Use render_at with the specified position
+                    self.player.render_at(ui, egui::pos2(self.render_position[0], self.render_position[1]));
+                }
 
                 // Update time if not dragging the slider
                 if !ui.input(|i| i.pointer.primary_down()) {
                     self.playback_time = self.player.current_time();
                 }
 
-                // Request repaint for smooth animation
-                ctx.request_repaint();
+                // Full frame rate only while focused
+                let focused = ctx.input(|i| i.viewport().focused).unwrap_or(true);
+                let max_fps = if focused { None } else { Some(10.0) };
+                for player in self.players_mut() {
+                    player.set_max_fps(max_fps);
+                }
+                ctx.request_repaint_after(self.player.repaint_after());
             });
 
         // Bottom panel for controls
@@ -378,8 +563,13 @@ fn main() {
     let note_size = 100.0;
     let height = 800.0;
 
-    let app = ManiaApp::new(beatmap_ln, beatmap_normal, column_width, note_size, height)
-        .expect("Unsupported game mode");
+    let app = ManiaApp::new(
+        vec![beatmap_ln, beatmap_normal],
+        column_width,
+        note_size,
+        height,
+    )
+    .expect("Unsupported game mode");
     let mut size = app.player.get_required_size();
     size[1] += 100.0; // Add space for bottom controls
 
@@ -0,0 +1,128 @@
+use eframe::egui::{self, Color32};
+use rosu_renderer::layout::mania::{
+    HandColoring, HandSplit, ManiaRenderer, NoteShape, NoteStyle, WindowBandStyle,
+};
+
+const HEIGHT: f32 = 600.0;
+
+struct SkinPreviewApp {
+    renderer: ManiaRenderer,
+    keycount: usize,
+    column_width: f32,
+    note_size: f32,
+    note_color: Color32,
+    hold_body_color: Color32,
+    hold_cap_color: Color32,
+    rectangle_notes: bool,
+    hand_coloring: bool,
+    window_bands: bool,
+}
+
+impl SkinPreviewApp {
+    fn new() -> Self {
+        let mut app = Self {
+            renderer: ManiaRenderer::with_sizes(80.0, 80.0, HEIGHT),
+            keycount: 4,
+            column_width: 80.0,
+            note_size: 80.0,
+            note_color: Color32::from_rgb(0, 174, 255),
+            hold_body_color: Color32::from_rgb(200, 200, 200),
+            hold_cap_color: Color32::from_rgb(0, 174, 255),
+            rectangle_notes: false,
+            hand_coloring: false,
+            window_bands: false,
+        };
+        app.apply_styles();
+        app
+    }
+
+    fn apply_styles(&mut self) {
+        self.renderer = ManiaRenderer::with_sizes(self.column_width, self.note_size, HEIGHT);
+        let shape = if self.rectangle_notes {
+            NoteShape::Rectangle {
+                width: self.note_size,
+                height: self.note_size / 3.0,
+            }
+        } else {
+            NoteShape::Circle
+        };
+        self.renderer.set_note_style(NoteStyle {
+            shape,
+            color: self.note_color,
+            hold_body_color: self.hold_body_color,
+            hold_cap_color: self.hold_cap_color,
+        });
+        self.renderer.set_hand_coloring(
+            self.hand_coloring
+                .then(|| HandColoring::new(HandSplit::by_column(self.keycount))),
+        );
+        self.renderer
+            .set_window_bands(self.window_bands.then(WindowBandStyle::default));
+    }
+
+    fn draw_controls(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        changed |= ui
+            .add(egui::Slider::new(&mut self.keycount, 1..=10).text("Keys"))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut self.column_width, 30.0..=150.0).text("Column width"))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut self.note_size, 30.0..=150.0).text("Note size"))
+            .changed();
+        ui.horizontal(|ui| {
+            changed |= ui.color_edit_button_srgba(&mut self.note_color).changed();
+            ui.label("Note");
+            changed |= ui
+                .color_edit_button_srgba(&mut self.hold_body_color)
+                .changed();
+            ui.label("Hold body");
+            changed |= ui
+                .color_edit_button_srgba(&mut self.hold_cap_color)
+                .changed();
+            ui.label("Hold cap");
+        });
+        changed |= ui
+            .checkbox(&mut self.rectangle_notes, "Rectangle notes")
+            .changed();
+        changed |= ui
+            .checkbox(&mut self.hand_coloring, "Hand coloring")
+            .changed();
+        changed |= ui
+            .checkbox(&mut self.window_bands, "Hit window bands")
+            .changed();
+
+        if changed {
+            self.apply_styles();
+        }
+    }
+}
+
+impl eframe::App for SkinPreviewApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::SidePanel::left("controls").show(ctx, |ui| self.draw_controls(ui));
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let position = ui.max_rect().min;
+            self.renderer
+                .render_stage_preview(ui, self.keycount, position);
+        });
+    }
+}
+
+fn main() {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([1000.0, 700.0]),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "osu!mania Skin Preview",
+        options,
+        Box::new(|cc| {
+            egui_extras::install_image_loaders(&cc.egui_ctx);
+            Ok(Box::new(SkinPreviewApp::new()))
+        }),
+    )
+    .expect("Failed to start application");
+}
@@ -0,0 +1,55 @@
+//! Rolling per-column note rate behind the activity meter, fed from the
+//! passed-note stream.
+
+use crate::events::{PassedKind, PassedNote};
+use std::collections::VecDeque;
+
+// Passes kept per column; past this the oldest are dropped early
+const MAX_COLUMN_PASSES: usize = 256;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ColumnActivity {
+    // Map times of recent heads, oldest first
+    passes: Vec<VecDeque<f64>>,
+}
+
+impl ColumnActivity {
+    pub(crate) fn record(&mut self, notes: &[PassedNote]) {
+        for note in notes.iter().filter(|n| n.kind != PassedKind::HoldTail) {
+            if note.column >= self.passes.len() {
+                self.passes.resize_with(note.column + 1, VecDeque::new);
+            }
+            let passes = &mut self.passes[note.column];
+            if passes.len() == MAX_COLUMN_PASSES {
+                passes.pop_front();
+            }
+            passes.push_back(note.time_ms);
+        }
+    }
+
+    /// Notes per second of each column at map time `now`. Each pass counts
+    /// fully when it happens and linearly less until it leaves the window,
+    /// so bars decay smoothly instead of dropping a step at a time.
+    pub(crate) fn nps(&mut self, now: f64, window_ms: f64, keycount: usize) -> Vec<f32> {
+        let mut nps = vec![0.0; keycount];
+        for (column, passes) in self.passes.iter_mut().enumerate() {
+            while passes.front().is_some_and(|time| now - time > window_ms) {
+                passes.pop_front();
+            }
+            let Some(nps) = nps.get_mut(column) else {
+                continue;
+            };
+            // Weights average 1/2 over the window, hence twice the count
+            let weight: f64 = passes
+                .iter()
+                .map(|time| 1.0 - ((now - time) / window_ms).clamp(0.0, 1.0))
+                .sum();
+            *nps = (2.0 * weight * 1000.0 / window_ms) as f32;
+        }
+        nps
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.passes.clear();
+    }
+}
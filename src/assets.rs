@@ -0,0 +1,78 @@
+//! Files a beatmap refers to, found relative to its .osu file. Names are
+//! matched case-insensitively when there's no exact match, as maps made on
+//! Windows often differ in case from the files shipped with them.
+
+use rosu_map::Beatmap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssetWarning {
+    /// No file matches `name`, even ignoring case.
+    NotFound { name: String },
+    /// `name` only matched `found` ignoring case.
+    CaseMismatch { name: String, found: PathBuf },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapAssets {
+    pub audio: Option<PathBuf>,
+    pub background: Option<PathBuf>,
+    pub warnings: Vec<AssetWarning>,
+}
+
+impl MapAssets {
+    /// Looks up the audio file and background of `beatmap`, which was
+    /// read from `osu_path`. Files the map doesn't name are `None` without
+    /// a warning.
+    pub fn resolve(osu_path: impl AsRef<Path>, beatmap: &Beatmap) -> Self {
+        let folder = osu_path.as_ref().parent().unwrap_or(Path::new(""));
+        let mut assets = Self::default();
+        assets.audio = assets.find(folder, &beatmap.audio_file);
+        assets.background = assets.find(folder, &beatmap.background_file);
+        assets
+    }
+
+    fn find(&mut self, folder: &Path, name: &str) -> Option<PathBuf> {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let exact = folder.join(name);
+        if exact.is_file() {
+            return Some(exact);
+        }
+
+        let found = find_ignoring_case(folder, name);
+        self.warnings.push(match &found {
+            Some(found) => AssetWarning::CaseMismatch {
+                name: name.to_owned(),
+                found: found.clone(),
+            },
+            None => AssetWarning::NotFound {
+                name: name.to_owned(),
+            },
+        });
+        found
+    }
+}
+
+// Walks `name` one component at a time, each matched ignoring case
+fn find_ignoring_case(folder: &Path, name: &str) -> Option<PathBuf> {
+    let mut path = folder.to_path_buf();
+    for component in name.split(['/', '\\']).filter(|c| !c.is_empty()) {
+        let exact = path.join(component);
+        if exact.exists() {
+            path = exact;
+            continue;
+        }
+        let entry = fs::read_dir(&path).ok()?.flatten().find(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|entry| entry.to_lowercase() == component.to_lowercase())
+        })?;
+        path = entry.path();
+    }
+    path.is_file().then_some(path)
+}
@@ -0,0 +1,113 @@
+//! Offset calibration from taps against the beat. Taps are map times, as
+//! from `Player::wall_to_map_time`, so the playback rate doesn't skew them.
+
+use crate::snap::nearest_line;
+use rosu_map::section::timing_points::ControlPoints;
+
+// Taps farther than this many deviations from the median are dropped
+const OUTLIER_DEVIATIONS: f64 = 3.0;
+// Scales the median absolute deviation to a standard deviation
+const MAD_TO_STDDEV: f64 = 1.4826;
+// Floor of the rejection spread, so consistent taps don't reject each other
+const MIN_SPREAD_MS: f64 = 5.0;
+
+/// Result of `Player::calibrate_offset`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OffsetCalibration {
+    /// How late taps land after the beat, in ms; negative when early.
+    pub median: f64,
+    pub stddev: f64,
+    /// Taps kept after outlier rejection.
+    pub samples: usize,
+    pub rejected: usize,
+}
+
+/// `None` without taps near a beat of the map's timing points.
+pub(crate) fn calibrate(control_points: &ControlPoints, taps: &[f64]) -> Option<OffsetCalibration> {
+    let offsets: Vec<f64> = taps
+        .iter()
+        .filter_map(|tap| Some(tap - nearest_line(control_points, *tap, 1.0)?))
+        .collect();
+    calibrate_offsets(offsets)
+}
+
+fn calibrate_offsets(mut offsets: Vec<f64>) -> Option<OffsetCalibration> {
+    let total = offsets.len();
+    let center = median(&mut offsets)?;
+    let mut deviations: Vec<f64> = offsets.iter().map(|o| (o - center).abs()).collect();
+    let spread = (median(&mut deviations)? * MAD_TO_STDDEV).max(MIN_SPREAD_MS);
+    offsets.retain(|offset| (offset - center).abs() <= OUTLIER_DEVIATIONS * spread);
+
+    let median_ms = median(&mut offsets)?;
+    let mean = offsets.iter().sum::<f64>() / offsets.len() as f64;
+    let variance = offsets.iter().map(|o| (o - mean).powi(2)).sum::<f64>() / offsets.len() as f64;
+    Some(OffsetCalibration {
+        median: median_ms,
+        stddev: variance.sqrt(),
+        samples: offsets.len(),
+        rejected: total - offsets.len(),
+    })
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    match values.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((values[middle - 1] + values[middle]) / 2.0),
+        _ => Some(values[middle]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::{TimeSignature, TimingPoint};
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [3.0, -1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 2.0]), Some(2.5));
+    }
+
+    #[test]
+    fn outliers_are_rejected() {
+        let mut offsets = vec![18.0, 22.0, 20.0, 19.0, 21.0, 20.0];
+        offsets.extend([250.0, -230.0]);
+        let calibration = calibrate_offsets(offsets).unwrap();
+        assert_eq!(calibration.median, 20.0);
+        assert_eq!(calibration.samples, 6);
+        assert_eq!(calibration.rejected, 2);
+        assert!((calibration.stddev - (5.0f64 / 3.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn consistent_taps_do_not_reject_each_other() {
+        // No deviation at all, the spread floor keeps the 4 ms tap
+        let calibration = calibrate_offsets(vec![-10.0, -10.0, -10.0, -10.0, -6.0]).unwrap();
+        assert_eq!(calibration.median, -10.0);
+        assert_eq!(calibration.samples, 5);
+        assert_eq!(calibration.rejected, 0);
+
+        let calibration = calibrate_offsets(vec![-10.0, -10.0, -10.0, -10.0, 20.0]).unwrap();
+        assert_eq!(calibration.rejected, 1);
+        assert_eq!(calibration.stddev, 0.0);
+    }
+
+    #[test]
+    fn taps_are_measured_from_the_nearest_beat() {
+        let mut control_points = ControlPoints::default();
+        let signature = TimeSignature::new_simple_quadruple();
+        control_points.add(TimingPoint::new(1000.0, 500.0, false, signature));
+        // Around 15 ms late, then a tap between beats
+        let taps = [1515.0, 1995.0, 2520.0, 3015.0, 3240.0];
+        let calibration = calibrate(&control_points, &taps).unwrap();
+        assert_eq!(calibration.median, 15.0);
+        assert_eq!(calibration.samples, 4);
+        assert_eq!(calibration.rejected, 1);
+
+        assert_eq!(calibrate(&ControlPoints::default(), &taps), None);
+        assert_eq!(calibrate(&control_points, &[]), None);
+    }
+}
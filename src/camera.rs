@@ -0,0 +1,79 @@
+use egui::Vec2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps linear progress in `0.0..=1.0` to eased progress.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Zoom about the playfield center, then a pan in screen pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub zoom: f32,
+    pub offset: Vec2,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraKeyframe {
+    /// Clock time, as in `Player::current_time`.
+    pub time_ms: f64,
+    pub zoom: f32,
+    pub offset: Vec2,
+    /// Easing used when moving towards this keyframe.
+    pub easing: Easing,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraTrack {
+    // Sorted by time
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraTrack {
+    pub fn new(mut keyframes: Vec<CameraKeyframe>) -> Self {
+        keyframes.sort_by(|a, b| a.time_ms.total_cmp(&b.time_ms));
+        Self { keyframes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Camera at clock time `time_ms`, `None` (identity) outside the track.
+    pub fn at(&self, time_ms: f64) -> Option<Camera> {
+        let next = self.keyframes.iter().position(|k| k.time_ms > time_ms)?;
+        let from = self.keyframes.get(next.checked_sub(1)?)?;
+        let to = &self.keyframes[next];
+
+        let t = ((time_ms - from.time_ms) / (to.time_ms - from.time_ms)) as f32;
+        let t = to.easing.apply(t);
+        Some(Camera {
+            zoom: from.zoom + (to.zoom - from.zoom) * t,
+            offset: from.offset + (to.offset - from.offset) * t,
+        })
+    }
+}
@@ -0,0 +1,57 @@
+//! Fallbacks for beatmap fields the renderer consumes, applied at load so
+//! old or hand-edited files behave like modern ones. Every fallback applied
+//! is pushed to the `LoadReport`.
+//!
+//! | Field                   | Invalid when            | Fallback                  |
+//! |-------------------------|-------------------------|---------------------------|
+//! | `OverallDifficulty`     | not finite              | 5                         |
+//! | `OverallDifficulty`     | outside 0..=10          | clamped to 0..=10         |
+//! | `CircleSize` (keycount) | outside 1..=18          | clamped, `KeycountClamped`|
+//! | Break periods           | not finite, end <= start| dropped                   |
+//! | Hit object time         | not finite              | object dropped            |
+//!
+//! Fields missing from a file already hold rosu-map's defaults (5 for
+//! `OverallDifficulty` and `CircleSize`), which match osu!'s.
+
+use crate::report::{LoadIssue, LoadReport};
+use rosu_map::Beatmap;
+
+const DEFAULT_OVERALL_DIFFICULTY: f32 = 5.0;
+
+pub(crate) fn normalize_defaults(beatmap: &mut Beatmap, report: &mut LoadReport) {
+    let od = beatmap.overall_difficulty;
+    let fallback = if od.is_finite() {
+        od.clamp(0.0, 10.0)
+    } else {
+        DEFAULT_OVERALL_DIFFICULTY
+    };
+    if fallback != od {
+        beatmap.overall_difficulty = fallback;
+        report.push(LoadIssue::FieldDefaulted {
+            field: "OverallDifficulty",
+            value: od as f64,
+            fallback: fallback as f64,
+        });
+    }
+
+    beatmap.breaks.retain(|b| {
+        let valid = b.start_time.is_finite() && b.end_time.is_finite() && b.end_time > b.start_time;
+        if !valid {
+            report.push(LoadIssue::BreakDropped {
+                start: b.start_time,
+                end: b.end_time,
+            });
+        }
+        valid
+    });
+
+    let mut index = 0;
+    beatmap.hit_objects.retain(|h| {
+        let valid = h.start_time.is_finite();
+        if !valid {
+            report.push(LoadIssue::ObjectDropped { index });
+        }
+        index += 1;
+        valid
+    });
+}
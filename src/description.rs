@@ -0,0 +1,137 @@
+//! A frame as plain data, for engines drawing notes with their own sprites
+//! while keeping this crate's layout, see `Player::frame_description`.
+//! Positions are in points, the same ones `render*` draws at; colors are
+//! sRGBA without premultiplied alpha.
+
+/// A position, in points.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// An axis-aligned rectangle, `min` being its top left corner.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteKind {
+    Note,
+    HoldHead,
+    /// Rung of a hold drawn as a ladder, see `HoldBodyStyle::Ladder`.
+    LadderRung,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoteDescription {
+    pub column: usize,
+    pub center: Point,
+    /// Size of the note shape; images and circles are square.
+    pub size: Size,
+    pub color: [u8; 4],
+    pub kind: NoteKind,
+    /// Index in `Player::hit_objects`, the hold's for ladder rungs.
+    pub note_index: usize,
+}
+
+/// A hold's body, dropped part and end cap, each clipped to the stage and
+/// absent when fully out of it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct HoldDescription {
+    pub column: usize,
+    pub note_index: usize,
+    /// `None` for ladders, whose rungs are notes.
+    pub body: Option<Bounds>,
+    pub body_color: [u8; 4],
+    /// Stripes on the body, see `HoldBodyPattern::Striped`.
+    pub stripes: Vec<Bounds>,
+    pub stripe_color: [u8; 4],
+    /// From the break to the tail of a broken hold.
+    pub dropped: Option<Bounds>,
+    pub cap: Option<Bounds>,
+    pub cap_color: [u8; 4],
+    pub broken: bool,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReceptorDescription {
+    pub column: usize,
+    pub center: Point,
+    pub size: Size,
+    pub color: [u8; 4],
+}
+
+/// A lane cover panel, see `ManiaRenderer::set_lane_cover`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CoverDescription {
+    pub bounds: Bounds,
+    pub color: [u8; 4],
+}
+
+/// Everything a stage shows at one clock time. Holds are listed before
+/// notes, each in object order; stacking them is up to the host, as with
+/// `LayerOrder`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameDescription {
+    /// Clock time described, as in `Player::current_time`.
+    pub time_ms: f64,
+    pub playfield: Bounds,
+    /// The stage inside its padding.
+    pub stage: Bounds,
+    pub column_width: f32,
+    /// Judgment line without the per-column offsets.
+    pub judgment_line_y: f32,
+    /// y of the measure lines, each across the stage.
+    pub barlines: Vec<f32>,
+    pub receptors: Vec<ReceptorDescription>,
+    /// Drawn over everything else of the stage.
+    pub covers: Vec<CoverDescription>,
+    pub holds: Vec<HoldDescription>,
+    pub notes: Vec<NoteDescription>,
+}
+
+impl From<egui::Pos2> for Point {
+    fn from(pos: egui::Pos2) -> Self {
+        Self { x: pos.x, y: pos.y }
+    }
+}
+
+impl From<egui::Vec2> for Size {
+    fn from(size: egui::Vec2) -> Self {
+        Self {
+            width: size.x,
+            height: size.y,
+        }
+    }
+}
+
+impl From<egui::Rect> for Bounds {
+    fn from(rect: egui::Rect) -> Self {
+        Self {
+            min: rect.min.into(),
+            max: rect.max.into(),
+        }
+    }
+}
+
+pub(crate) fn rgba(color: egui::Color32) -> [u8; 4] {
+    color.to_srgba_unmultiplied()
+}
@@ -0,0 +1,50 @@
+use rosu_map::Beatmap;
+
+/// Values replacing the beatmap's own difficulty settings; `None` keeps the map value.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DifficultyOverride {
+    pub od: Option<f32>,
+    pub ar: Option<f32>,
+    pub cs: Option<f32>,
+    pub hp: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyValue {
+    pub value: f32,
+    pub overridden: bool,
+}
+
+impl DifficultyValue {
+    fn resolve(map_value: f32, value_override: Option<f32>) -> Self {
+        match value_override {
+            Some(value) => Self {
+                value,
+                overridden: true,
+            },
+            None => Self {
+                value: map_value,
+                overridden: false,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifficultySettings {
+    pub od: DifficultyValue,
+    pub ar: DifficultyValue,
+    pub cs: DifficultyValue,
+    pub hp: DifficultyValue,
+}
+
+impl DifficultySettings {
+    pub fn resolve(beatmap: &Beatmap, difficulty_override: &DifficultyOverride) -> Self {
+        Self {
+            od: DifficultyValue::resolve(beatmap.overall_difficulty, difficulty_override.od),
+            ar: DifficultyValue::resolve(beatmap.approach_rate, difficulty_override.ar),
+            cs: DifficultyValue::resolve(beatmap.circle_size, difficulty_override.cs),
+            hp: DifficultyValue::resolve(beatmap.hp_drain_rate, difficulty_override.hp),
+        }
+    }
+}
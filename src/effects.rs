@@ -0,0 +1,132 @@
+/// Clock for visual effects, driven only by map time: pausing freezes every
+/// effect, seeking backwards replays them, and rendering the same frame twice
+/// yields identical progress. Never read wall-clock time for effects.
+#[derive(Clone, Debug, Default)]
+pub struct EffectClock {
+    now: f64,
+    next_id: u64,
+    effects: Vec<ScheduledEffect>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EffectId(u64);
+
+#[derive(Clone, Copy, Debug)]
+struct ScheduledEffect {
+    id: EffectId,
+    start_ms: f64,
+    duration_ms: f64,
+}
+
+impl EffectClock {
+    /// Map time of the last rendered frame.
+    pub fn now(&self) -> f64 {
+        self.now
+    }
+
+    pub(crate) fn set_time(&mut self, map_time: f64) {
+        self.now = map_time;
+    }
+
+    /// Schedules an effect starting at map time `at_ms`. Effects already
+    /// over at the current time are never shown, so they aren't kept: after a
+    /// throttled or skipped stretch of frames only the ones still running play.
+    pub fn spawn(&mut self, at_ms: f64, duration_ms: f64) -> EffectId {
+        let id = EffectId(self.next_id);
+        self.next_id += 1;
+        let duration_ms = duration_ms.max(0.0);
+        if at_ms + duration_ms >= self.now {
+            self.effects.push(ScheduledEffect {
+                id,
+                start_ms: at_ms,
+                duration_ms,
+            });
+        }
+        id
+    }
+
+    /// Progress in `0.0..=1.0` at the current map time, or `None` if the
+    /// effect hasn't started, is over, or doesn't exist.
+    pub fn progress(&self, id: EffectId) -> Option<f32> {
+        let effect = self.effects.iter().find(|e| e.id == id)?;
+        let elapsed = self.now - effect.start_ms;
+        if elapsed < 0.0 || elapsed > effect.duration_ms {
+            return None;
+        }
+        if effect.duration_ms == 0.0 {
+            return Some(1.0);
+        }
+        Some((elapsed / effect.duration_ms) as f32)
+    }
+
+    pub fn remove(&mut self, id: EffectId) {
+        self.effects.retain(|e| e.id != id);
+    }
+
+    /// Drops effects that ended before `map_time`.
+    pub fn prune_before(&mut self, map_time: f64) {
+        self.effects
+            .retain(|e| e.start_ms + e.duration_ms >= map_time);
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Progress of every effect at each of `times`
+    fn progress_at(clock: &mut EffectClock, ids: &[EffectId], times: &[f64]) -> Vec<Option<f32>> {
+        let mut progress = Vec::new();
+        for &time in times {
+            clock.set_time(time);
+            progress.extend(ids.iter().map(|&id| clock.progress(id)));
+        }
+        progress
+    }
+
+    #[test]
+    fn pausing_freezes_progress() {
+        let mut clock = EffectClock::default();
+        let id = clock.spawn(1000.0, 200.0);
+        clock.set_time(1050.0);
+        assert_eq!(clock.progress(id), Some(0.25));
+        // Paused, every frame sees the same map time
+        for _ in 0..10 {
+            clock.set_time(1050.0);
+            assert_eq!(clock.progress(id), Some(0.25));
+        }
+        clock.set_time(1150.0);
+        assert_eq!(clock.progress(id), Some(0.75));
+    }
+
+    #[test]
+    fn seeking_back_replays_the_same_progress() {
+        let mut clock = EffectClock::default();
+        let ids = [clock.spawn(1000.0, 200.0), clock.spawn(1100.0, 0.0)];
+        let times: Vec<f64> = (0..30).map(|i| 950.0 + 10.0 * i as f64).collect();
+        let first = progress_at(&mut clock, &ids, &times);
+        assert!(first.iter().any(Option::is_some));
+        assert_eq!(clock.progress(ids[0]), None);
+
+        clock.set_time(900.0);
+        assert_eq!(progress_at(&mut clock, &ids, &times), first);
+    }
+
+    #[test]
+    fn same_frame_twice_gives_the_same_state() {
+        let mut clock = EffectClock::default();
+        let ids: Vec<EffectId> = (0..5)
+            .map(|i| clock.spawn(1000.0 + 30.0 * i as f64, 100.0))
+            .collect();
+        for time in [990.0, 1045.5, 1120.0, 1300.0] {
+            let first = progress_at(&mut clock, &ids, &[time]);
+            let state = format!("{clock:?}");
+            assert_eq!(progress_at(&mut clock, &ids, &[time]), first);
+            assert_eq!(format!("{clock:?}"), state);
+        }
+    }
+}
@@ -0,0 +1,440 @@
+use crate::hitsounds::{resolved_sample, ResolvedSample};
+use crate::layout::mania::{column_for_x, Layer};
+use crate::sections::SectionKind;
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+use rosu_map::section::timing_points::ControlPoints;
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+// Events emitted while the queue already holds this many of their kind (lag
+// spikes, huge jumps, nobody draining) are dropped and set
+// `EventQueue::truncated`. Each kind has its own cap, so a flood of notes
+// doesn't crowd out the rarer events.
+pub const MAX_PASSED_NOTES: usize = 1024;
+/// Cap of every kind of event other than passed notes.
+pub const MAX_QUEUED_EVENTS: usize = 256;
+// Variants of `Event`
+const EVENT_KINDS: usize = 11;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PassedKind {
+    Note,
+    HoldHead,
+    HoldTail,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PassedNote {
+    /// Index into `Player::hit_objects`.
+    pub note_index: usize,
+    pub column: usize,
+    /// Exact map time of the head or tail.
+    pub time_ms: f64,
+    pub kind: PassedKind,
+    /// Hitsound to play, see `hitsounds::resolved_sample`.
+    pub sample: ResolvedSample,
+}
+
+/// Frame drawn longer after the previous one than the hitch threshold,
+/// see `Player::set_hitch_threshold`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameHitch {
+    /// Map time between the two frames.
+    pub delta_ms: f64,
+    /// Map time at the judgment line on the late frame.
+    pub at_map_time: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PassedNotes {
+    /// In chronological order.
+    pub notes: Vec<PassedNote>,
+    pub truncated: bool,
+    /// Hitches of the frames since the last drain, in order. Notes passed
+    /// during a hitch are still in `notes`.
+    pub hitches: Vec<FrameHitch>,
+}
+
+/// User callback that panicked, see `Event::CallbackPanicked`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallbackKind {
+    LayerHook(Layer),
+    /// The `SharedFormatter` of a text overlay's style.
+    Formatter,
+    /// The `ImageProvider`, see `ManiaRenderer::set_image_provider`.
+    ImageProvider,
+    /// Event callback, by the order it was added in.
+    Event(usize),
+}
+
+/// Cache derived from the object list, see `PlayerBuilder::parallel_load`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheKind {
+    ColumnStats,
+    Composition,
+    Sections,
+    HitsoundIssues,
+}
+
+/// Something that happened during playback, see `Player::events`. Unlike
+/// in 1.2, not `Copy`: `LayoutChanged` and `CallbackPanicked` own their
+/// data, so clone events to keep them around.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    NotePassed(PassedNote),
+    HoldHead(PassedNote),
+    HoldTail(PassedNote),
+    /// The clock wrapped back to 0, see `EndBehavior::Loop`.
+    LoopWrapped {
+        at_map_time: f64,
+    },
+    /// The clock reached `Player::end_time`, see `Player::is_finished`.
+    Finished {
+        at_map_time: f64,
+    },
+    FrameHitch(FrameHitch),
+    SectionEntered {
+        kind: SectionKind,
+        at_map_time: f64,
+    },
+    /// A press matched no note, see `Player::report_ghost_tap`.
+    GhostTap {
+        column: usize,
+        at_map_time: f64,
+    },
+    /// A layout edit drop applied this column mapping, see
+    /// `Player::set_layout_edit`.
+    LayoutChanged {
+        mapping: Vec<usize>,
+        at_map_time: f64,
+    },
+    /// A callback panicked. It was disabled and the frame kept rendering.
+    CallbackPanicked {
+        which: CallbackKind,
+        message: String,
+        at_map_time: f64,
+    },
+    /// A cache `PlayerBuilder::parallel_load` built in the background is
+    /// in use from now on.
+    CacheReady {
+        cache: CacheKind,
+        at_map_time: f64,
+    },
+}
+
+impl Event {
+    fn passed(note: PassedNote) -> Self {
+        match note.kind {
+            PassedKind::Note => Self::NotePassed(note),
+            PassedKind::HoldHead => Self::HoldHead(note),
+            PassedKind::HoldTail => Self::HoldTail(note),
+        }
+    }
+
+    // Index of the variant, for the per-kind caps of `EventQueue`
+    fn kind_index(&self) -> usize {
+        match self {
+            Self::NotePassed(_) => 0,
+            Self::HoldHead(_) => 1,
+            Self::HoldTail(_) => 2,
+            Self::LoopWrapped { .. } => 3,
+            Self::Finished { .. } => 4,
+            Self::FrameHitch(_) => 5,
+            Self::SectionEntered { .. } => 6,
+            Self::GhostTap { .. } => 7,
+            Self::LayoutChanged { .. } => 8,
+            Self::CallbackPanicked { .. } => 9,
+            Self::CacheReady { .. } => 10,
+        }
+    }
+
+    fn queue_cap(&self) -> usize {
+        match self {
+            Self::NotePassed(_) | Self::HoldHead(_) | Self::HoldTail(_) => MAX_PASSED_NOTES,
+            _ => MAX_QUEUED_EVENTS,
+        }
+    }
+
+    /// Map time the event happened at.
+    pub fn map_time(&self) -> f64 {
+        match self {
+            Self::NotePassed(note) | Self::HoldHead(note) | Self::HoldTail(note) => note.time_ms,
+            Self::FrameHitch(hitch) => hitch.at_map_time,
+            Self::LoopWrapped { at_map_time }
+            | Self::Finished { at_map_time }
+            | Self::SectionEntered { at_map_time, .. }
+            | Self::GhostTap { at_map_time, .. }
+            | Self::LayoutChanged { at_map_time, .. }
+            | Self::CallbackPanicked { at_map_time, .. }
+            | Self::CacheReady { at_map_time, .. } => *at_map_time,
+        }
+    }
+}
+
+pub type EventCallback = Arc<dyn Fn(&Event) + Send + Sync>;
+
+/// Events emitted while rendering, in the order they happened. Seeking
+/// forward emits the notes skipped over on the next frame, seeking back
+/// none; each one is emitted once even across loops.
+#[derive(Default)]
+pub struct EventQueue {
+    queued: Vec<Event>,
+    // Queued events of each kind, by `Event::kind_index`
+    queued_kinds: [usize; EVENT_KINDS],
+    truncated: bool,
+    // `None` once disabled by a panic, keeping the indices of the others
+    callbacks: Vec<Option<EventCallback>>,
+}
+
+impl EventQueue {
+    /// Calls `callback` with every event as it's emitted, during `render*`
+    /// or the `report_*` call, callbacks in the order they were added.
+    /// Events are still queued. A callback that panics is disabled.
+    pub fn on_event(&mut self, callback: EventCallback) {
+        self.callbacks.push(Some(callback));
+    }
+
+    pub fn clear_callbacks(&mut self) {
+        self.callbacks.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Event> {
+        self.queued.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    /// Events were dropped since the last `drain` because the queue held as
+    /// many of their kind as it can. Callbacks still saw them.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Every queued event, in order.
+    pub fn drain(&mut self) -> Vec<Event> {
+        self.truncated = false;
+        self.queued_kinds = [0; EVENT_KINDS];
+        std::mem::take(&mut self.queued)
+    }
+
+    pub(crate) fn push(&mut self, event: Event) {
+        let mut panicked = Vec::new();
+        for (index, slot) in self.callbacks.iter_mut().enumerate() {
+            let Some(callback) = slot else {
+                continue;
+            };
+            if let Err(payload) = catch_unwind(AssertUnwindSafe(|| callback(&event))) {
+                *slot = None;
+                panicked.push(Event::CallbackPanicked {
+                    which: CallbackKind::Event(index),
+                    message: panic_message(payload),
+                    at_map_time: event.map_time(),
+                });
+            }
+        }
+        let kind = event.kind_index();
+        if self.queued_kinds[kind] < event.queue_cap() {
+            self.queued_kinds[kind] += 1;
+            self.queued.push(event);
+        } else {
+            self.truncated = true;
+        }
+        for event in panicked {
+            self.push(event);
+        }
+    }
+
+    // Events were left out before reaching the queue
+    pub(crate) fn mark_truncated(&mut self) {
+        self.truncated = true;
+    }
+
+    pub(crate) fn push_passed(&mut self, note: PassedNote) {
+        self.push(Event::passed(note));
+    }
+
+    // Takes the note and hitch events out, leaving the others queued
+    pub(crate) fn drain_passed_notes(&mut self) -> PassedNotes {
+        let mut passed = PassedNotes {
+            truncated: std::mem::take(&mut self.truncated),
+            ..PassedNotes::default()
+        };
+        self.queued.retain(|event| {
+            match *event {
+                Event::NotePassed(note) | Event::HoldHead(note) | Event::HoldTail(note) => {
+                    passed.notes.push(note)
+                }
+                Event::FrameHitch(hitch) => passed.hitches.push(hitch),
+                _ => return true,
+            }
+            self.queued_kinds[event.kind_index()] -= 1;
+            false
+        });
+        passed
+    }
+}
+
+/// When hold tails count as passed, see `EventTiming`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GraceSource {
+    /// At the tail's time.
+    #[default]
+    None,
+    /// Once the release miss window is over, so a host judging releases
+    /// can still accept a late one. Follows the OD and window mode.
+    ReleaseWindow,
+    /// This many ms after the tail's time.
+    Fixed(f64),
+}
+
+/// When passed-note events are emitted, see `Player::set_event_timing`. The
+/// drawn notes aren't affected.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EventTiming {
+    /// Delay of `HoldTail` events. Their `time_ms` stays the tail's time.
+    pub tail_grace: GraceSource,
+}
+
+#[derive(Default)]
+pub(crate) struct PassedNoteTracker {
+    // Every head/tail event sorted by time
+    index: Vec<PassedNote>,
+    // The same split into notes and hold heads, and hold tails
+    heads: Vec<PassedNote>,
+    tails: Vec<PassedNote>,
+    last_time: Option<f64>,
+    // Time up to which tails passed, `tail_grace` behind `last_time`
+    last_tail_time: Option<f64>,
+}
+
+impl PassedNoteTracker {
+    pub(crate) fn rebuild(
+        &mut self,
+        hit_objects: &[HitObject],
+        control_points: &ControlPoints,
+        keycount: usize,
+    ) {
+        self.index.clear();
+        for (note_index, hit_object) in hit_objects.iter().enumerate() {
+            let (x, duration) = match &hit_object.kind {
+                HitObjectKind::Circle(h) => (h.pos.x, None),
+                HitObjectKind::Hold(h) => (h.pos_x, Some(h.duration)),
+                _ => continue,
+            };
+            let column = column_for_x(x, keycount);
+            let sample = resolved_sample(hit_object, control_points);
+            let head_kind = match duration {
+                Some(_) => PassedKind::HoldHead,
+                None => PassedKind::Note,
+            };
+            self.index.push(PassedNote {
+                note_index,
+                column,
+                time_ms: hit_object.start_time,
+                kind: head_kind,
+                sample,
+            });
+            if let Some(duration) = duration {
+                self.index.push(PassedNote {
+                    note_index,
+                    column,
+                    time_ms: hit_object.start_time + duration,
+                    kind: PassedKind::HoldTail,
+                    sample,
+                });
+            }
+        }
+        // Stable, so a head always precedes its own tail
+        self.index.sort_by(|a, b| a.time_ms.total_cmp(&b.time_ms));
+        (self.tails, self.heads) = self
+            .index
+            .iter()
+            .partition(|note| note.kind == PassedKind::HoldTail);
+        self.resync();
+    }
+
+    /// Every head in `(last time, map_time]` and every tail in the same span
+    /// `tail_grace` ms earlier, in the order they pass. Past
+    /// `MAX_PASSED_NOTES`, e.g. on a seek across the map, only the first
+    /// ones are returned along with `true`. The last time only moves
+    /// forward, so notes the line passes again without a seek, e.g. while
+    /// the scroll time animates, aren't returned twice. So does the tail
+    /// time when the grace grows, and a smaller grace, e.g. 0 to flush the
+    /// tails at the end of the map, returns the tails it skips at once.
+    pub(crate) fn advance(&mut self, map_time: f64, tail_grace: f64) -> (Vec<PassedNote>, bool) {
+        let (Some(last_time), Some(last_tail_time)) = (self.last_time, self.last_tail_time) else {
+            self.last_time = Some(map_time);
+            self.last_tail_time = Some(map_time - tail_grace);
+            return (Vec::new(), false);
+        };
+        let map_time = map_time.max(last_time);
+        let tail_time = (map_time - tail_grace).max(last_tail_time);
+        if map_time == last_time && tail_time == last_tail_time {
+            return (Vec::new(), false);
+        }
+        self.last_time = Some(map_time);
+        self.last_tail_time = Some(tail_time);
+
+        let heads = between(&self.heads, last_time, map_time);
+        let tails = between(&self.tails, last_tail_time, tail_time);
+        let truncated = heads.len() + tails.len() > MAX_PASSED_NOTES;
+        // Both are sorted, so the first ones to pass are among their firsts
+        let mut passed = heads[..heads.len().min(MAX_PASSED_NOTES)].to_vec();
+        if !tails.is_empty() {
+            passed.extend_from_slice(&tails[..tails.len().min(MAX_PASSED_NOTES)]);
+            let pass_time = |note: &PassedNote| match note.kind {
+                PassedKind::HoldTail => (note.time_ms + tail_grace).max(last_time),
+                _ => note.time_ms,
+            };
+            // Stable, so with no grace a head still precedes its own tail
+            passed.sort_by(|a, b| pass_time(a).total_cmp(&pass_time(b)));
+            passed.truncate(MAX_PASSED_NOTES);
+        }
+        (passed, truncated)
+    }
+
+    /// Keeps the last position when seeking forward, so the next frame
+    /// emits the notes skipped over, and forgets it when seeking back.
+    pub(crate) fn seek(&mut self, map_time: f64) {
+        if self.last_time.is_some_and(|last_time| map_time < last_time) {
+            self.resync();
+        }
+    }
+
+    /// Forgets the last position so the next frame doesn't emit everything
+    /// between the old and new time.
+    pub(crate) fn resync(&mut self) {
+        self.last_time = None;
+        self.last_tail_time = None;
+    }
+
+    /// Every head and tail, sorted by time.
+    pub(crate) fn events(&self) -> &[PassedNote] {
+        &self.index
+    }
+}
+
+/// Text of a caught panic, for `Event::CallbackPanicked`.
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast_ref::<&str>() {
+            Some(message) => (*message).to_owned(),
+            None => "unknown panic".to_owned(),
+        },
+    }
+}
+
+// Notes in `(after, up_to]` of a list sorted by time
+fn between(notes: &[PassedNote], after: f64, up_to: f64) -> &[PassedNote] {
+    let start = notes.partition_point(|note| note.time_ms <= after);
+    let end = notes.partition_point(|note| note.time_ms <= up_to);
+    &notes[start..end.max(start)]
+}
@@ -0,0 +1,123 @@
+//! Rate and offset independent chart fingerprints, for finding re-uploads
+//! and cuts of the same chart.
+//!
+//! Notes starting within `CHORD_TOLERANCE_MS` form one onset. Each onset
+//! becomes a token made of the columns it presses and the ratio between the
+//! gap after it and the gap before it, rounded to a quarter octave. Ratios
+//! don't change when the whole chart is sped up or shifted, and neither do
+//! columns, so a rate change or offset fix yields the same tokens, up to
+//! rounding of the object times.
+//!
+//! Every run of `SHINGLE_LEN` tokens is hashed and the set of hashes is
+//! summarized with MinHash: the fingerprint keeps, for each of
+//! `SIGNATURE_LEN` hash functions, the smallest hash of the set. The share
+//! of equal slots between two fingerprints estimates how many runs the
+//! charts have in common, so a cut scores about its share of the full
+//! chart. All hashing is fixed, fingerprints can be stored and compared
+//! across versions and platforms.
+
+use crate::layout::mania::column_for_x;
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+
+const CHORD_TOLERANCE_MS: f64 = 1.0;
+const RATIO_STEPS_PER_OCTAVE: f64 = 4.0;
+const MAX_RATIO_STEP: f64 = 16.0;
+const SHINGLE_LEN: usize = 4;
+const SIGNATURE_LEN: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChartFingerprint {
+    // Smallest hash per MinHash function, empty for charts too short to
+    // have a single run of tokens
+    signature: Vec<u64>,
+}
+
+impl ChartFingerprint {
+    /// Estimated share of note patterns the charts have in common, in
+    /// `0.0..=1.0`. Empty fingerprints only match each other.
+    pub fn similarity(&self, other: &ChartFingerprint) -> f32 {
+        if self.signature.is_empty() || other.signature.is_empty() {
+            return if self.signature == other.signature {
+                1.0
+            } else {
+                0.0
+            };
+        }
+        let equal = self
+            .signature
+            .iter()
+            .zip(&other.signature)
+            .filter(|(a, b)| a == b)
+            .count();
+        equal as f32 / SIGNATURE_LEN as f32
+    }
+
+    pub fn signature(&self) -> &[u64] {
+        &self.signature
+    }
+}
+
+pub(crate) fn fingerprint(hit_objects: &[HitObject], keycount: usize) -> ChartFingerprint {
+    // (time, pressed columns) of every onset
+    let mut onsets: Vec<(f64, u64)> = Vec::new();
+    let mut heads: Vec<(f64, usize)> = hit_objects
+        .iter()
+        .filter_map(|h| match &h.kind {
+            HitObjectKind::Circle(c) => Some((h.start_time, c.pos.x)),
+            HitObjectKind::Hold(hold) => Some((h.start_time, hold.pos_x)),
+            _ => None,
+        })
+        .map(|(time, x)| (time, column_for_x(x, keycount)))
+        .collect();
+    heads.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for (time, column) in heads {
+        match onsets.last_mut() {
+            Some((start, columns)) if time - *start <= CHORD_TOLERANCE_MS => {
+                *columns |= 1 << column.min(63);
+            }
+            _ => onsets.push((time, 1 << column.min(63))),
+        }
+    }
+
+    let tokens: Vec<u64> = onsets
+        .windows(3)
+        .map(|w| {
+            let ratio = (w[2].0 - w[1].0) / (w[1].0 - w[0].0);
+            let step = (ratio.log2() * RATIO_STEPS_PER_OCTAVE)
+                .round()
+                .clamp(-MAX_RATIO_STEP, MAX_RATIO_STEP);
+            mix(w[1].1 ^ ((step as i64 as u64) << 32))
+        })
+        .collect();
+
+    let shingles: Vec<u64> = tokens
+        .windows(SHINGLE_LEN)
+        .map(|run| run.iter().fold(0, |hash, token| mix(hash ^ token)))
+        .collect();
+    if shingles.is_empty() {
+        return ChartFingerprint {
+            signature: Vec::new(),
+        };
+    }
+
+    let signature = (0..SIGNATURE_LEN as u64)
+        .map(|seed| {
+            let seed = mix(seed);
+            shingles
+                .iter()
+                .map(|shingle| mix(shingle ^ seed))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect();
+    ChartFingerprint { signature }
+}
+
+// splitmix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
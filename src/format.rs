@@ -0,0 +1,100 @@
+//! Number formatting for the text drawn over the playfield, so hosts can
+//! plug in their locale's separators.
+
+use crate::events::panic_message;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub trait Formatter: Send + Sync {
+    /// `value` with `decimals` digits after the decimal separator. Signs are
+    /// added by the caller, `value` is never negative.
+    fn number(&self, value: f64, decimals: usize) -> String;
+}
+
+/// Rust's own formatting: a `.` separator and no grouping.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlainFormatter;
+
+impl Formatter for PlainFormatter {
+    fn number(&self, value: f64, decimals: usize) -> String {
+        format!("{value:.decimals$}")
+    }
+}
+
+/// Formatter held by the style structs. Two are equal when they share the
+/// same instance. A formatter that panics is replaced by `PlainFormatter`
+/// from then on, and the player reports it as `Event::CallbackPanicked`.
+#[derive(Clone)]
+pub struct SharedFormatter {
+    formatter: Arc<dyn Formatter>,
+    panic: Arc<FormatterPanic>,
+}
+
+#[derive(Default)]
+struct FormatterPanic {
+    panicked: AtomicBool,
+    // Until `take_panic`
+    message: Mutex<Option<String>>,
+}
+
+impl SharedFormatter {
+    pub fn new(formatter: impl Formatter + 'static) -> Self {
+        Self {
+            formatter: Arc::new(formatter),
+            panic: Arc::default(),
+        }
+    }
+
+    /// `value` with a leading `+` or `-`.
+    pub fn signed(&self, value: f64, decimals: usize) -> String {
+        let number = self.number(value.abs(), decimals);
+        if value < 0.0 {
+            format!("-{number}")
+        } else {
+            format!("+{number}")
+        }
+    }
+
+    /// Message of the panic that disabled the formatter, once.
+    pub fn take_panic(&self) -> Option<String> {
+        let mut message = self.panic.message.lock().unwrap_or_else(|e| e.into_inner());
+        message.take()
+    }
+}
+
+impl Formatter for SharedFormatter {
+    fn number(&self, value: f64, decimals: usize) -> String {
+        if !self.panic.panicked.load(Ordering::Relaxed) {
+            let number = catch_unwind(AssertUnwindSafe(|| self.formatter.number(value, decimals)));
+            match number {
+                Ok(number) => return number,
+                Err(payload) => {
+                    self.panic.panicked.store(true, Ordering::Relaxed);
+                    let mut message = self.panic.message.lock().unwrap_or_else(|e| e.into_inner());
+                    *message = Some(panic_message(payload));
+                }
+            }
+        }
+        PlainFormatter.number(value, decimals)
+    }
+}
+
+impl Default for SharedFormatter {
+    fn default() -> Self {
+        Self::new(PlainFormatter)
+    }
+}
+
+impl PartialEq for SharedFormatter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.formatter, &other.formatter)
+    }
+}
+
+impl fmt::Debug for SharedFormatter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedFormatter")
+    }
+}
@@ -0,0 +1,119 @@
+//! Color ramps shared by the features mapping a value to a color, so one
+//! gradient (e.g. a colorblind-friendly one) can be used everywhere.
+
+use egui::Color32;
+
+/// Colors at positions `t`, interpolated linearly in between. Lookups
+/// before the first stop or after the last one get that stop's color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    // Sorted by t, never empty
+    stops: Vec<(f32, Color32)>,
+}
+
+impl Gradient {
+    /// `None` without stops or with a non-finite position. Stops don't need
+    /// to be sorted.
+    pub fn new(mut stops: Vec<(f32, Color32)>) -> Option<Self> {
+        if stops.is_empty() || stops.iter().any(|(t, _)| !t.is_finite()) {
+            return None;
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Some(Self { stops })
+    }
+
+    pub fn stops(&self) -> &[(f32, Color32)] {
+        &self.stops
+    }
+
+    pub fn at(&self, t: f32) -> Color32 {
+        let after = self.stops.partition_point(|(stop, _)| *stop <= t);
+        if after == 0 {
+            return self.stops[0].1;
+        }
+        let (start, from) = self.stops[after - 1];
+        let Some(&(end, to)) = self.stops.get(after) else {
+            return from;
+        };
+        from.lerp_to_gamma(to, (t - start) / (end - start))
+    }
+
+    /// Dark purple through blue and green to yellow, readable with most
+    /// color vision deficiencies.
+    pub fn viridis() -> Self {
+        Self {
+            stops: vec![
+                (0.0, Color32::from_rgb(68, 1, 84)),
+                (0.25, Color32::from_rgb(59, 82, 139)),
+                (0.5, Color32::from_rgb(33, 145, 140)),
+                (0.75, Color32::from_rgb(94, 201, 98)),
+                (1.0, Color32::from_rgb(253, 231, 37)),
+            ],
+        }
+    }
+
+    /// Blue for early, white on time and orange for late, as in osu!'s
+    /// hit error meter.
+    pub fn osu_classic() -> Self {
+        Self {
+            stops: vec![
+                (0.0, Color32::from_rgb(90, 170, 255)),
+                (0.5, Color32::WHITE),
+                (1.0, Color32::from_rgb(255, 140, 70)),
+            ],
+        }
+    }
+
+    pub fn monochrome() -> Self {
+        Self {
+            stops: vec![(0.0, Color32::from_gray(40)), (1.0, Color32::WHITE)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn black_to_white() -> Gradient {
+        Gradient::new(vec![(1.0, Color32::WHITE), (0.0, Color32::BLACK)]).unwrap()
+    }
+
+    #[test]
+    fn stops_are_exact() {
+        let gradient = Gradient::osu_classic();
+        for &(t, color) in gradient.stops() {
+            assert_eq!(gradient.at(t), color);
+        }
+        assert_eq!(black_to_white().at(0.0), Color32::BLACK);
+        assert_eq!(black_to_white().at(1.0), Color32::WHITE);
+    }
+
+    #[test]
+    fn between_stops_is_interpolated() {
+        let gradient = black_to_white();
+        assert_eq!(gradient.at(0.5), Color32::from_gray(128));
+        assert_eq!(gradient.at(0.25), Color32::from_gray(64));
+
+        let classic = Gradient::osu_classic();
+        assert_eq!(classic.at(0.75), Color32::from_rgb(255, 198, 163));
+    }
+
+    #[test]
+    fn outside_stops_is_clamped() {
+        let gradient = black_to_white();
+        assert_eq!(gradient.at(-3.0), Color32::BLACK);
+        assert_eq!(gradient.at(f32::NEG_INFINITY), Color32::BLACK);
+        assert_eq!(gradient.at(7.0), Color32::WHITE);
+        assert_eq!(gradient.at(f32::NAN), Color32::BLACK);
+    }
+
+    #[test]
+    fn invalid_stops_are_refused() {
+        assert_eq!(Gradient::new(Vec::new()), None);
+        assert_eq!(Gradient::new(vec![(f32::NAN, Color32::RED)]), None);
+        let single = Gradient::new(vec![(0.3, Color32::RED)]).unwrap();
+        assert_eq!(single.at(0.0), Color32::RED);
+        assert_eq!(single.at(1.0), Color32::RED);
+    }
+}
@@ -0,0 +1,337 @@
+use rosu_map::section::hit_objects::hit_samples::{
+    HitSampleDefaultName, HitSampleInfo, HitSampleInfoName, SampleBank,
+};
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+use rosu_map::section::timing_points::{ControlPoints, SamplePoint};
+
+/// Sample volumes (in percent) below this are reported by default; 5% is
+/// the usual "silent" volume.
+pub const DEFAULT_VOLUME_THRESHOLD: i32 = 10;
+
+/// How a host plays hitsounds; the player itself has no audio output. The
+/// sample of each note comes with it, see `PassedNote::sample`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HitsoundConfig {
+    pan_strength: f32,
+}
+
+impl HitsoundConfig {
+    /// Pans each hitsound toward its column: the outer columns sit at
+    /// `-strength` and `strength`, the middle ones near the center. Clamped
+    /// to `0.0..=1.0`, 0 disables panning.
+    pub fn pan_by_column(mut self, strength: f32) -> Self {
+        self.pan_strength = if strength.is_nan() {
+            0.0
+        } else {
+            strength.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// Stereo pan in `-1.0..=1.0` for a note of the displayed `column`, e.g.
+    /// `PassedNote::column`, which already follows the arrangement.
+    pub fn pan(&self, column: usize, keycount: usize) -> f32 {
+        if keycount < 2 {
+            return 0.0;
+        }
+        // Column centers are evenly spaced, so the outer ones map to -1 and 1
+        let last = (keycount - 1) as f32;
+        let normalized = 2.0 * column.min(keycount - 1) as f32 / last - 1.0;
+        normalized * self.pan_strength
+    }
+}
+
+// Sample points this close after a note's end still apply to it, as in
+// osu!
+const SAMPLE_POINT_LENIENCY_MS: f64 = 5.0;
+
+/// What a note sounds like once the sample point under it fills in what
+/// the note leaves unset: banks, volume and custom sample index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResolvedSample {
+    /// Bank of the hitnormal.
+    pub set: SampleBank,
+    /// Bank of the whistle, finish and clap, `set` without any.
+    pub additions: SampleBank,
+    /// Loudest sample's volume, in percent.
+    pub volume: i32,
+    /// Custom sample index, e.g. 2 for `soft-hitnormal2.wav`; 0 and 1 are
+    /// the skin's samples.
+    pub index: i32,
+}
+
+/// Resolves the samples of `hit_object` against the sample point active at
+/// its end, a hold's tail, like `SamplePoint::apply`. Fields the note sets
+/// itself win; samples from a file only count for the volume.
+///
+/// rosu-map already applied the sample point to the samples of parsed maps,
+/// so for those this only fills in a note without any sample; the rest is
+/// for hand-built `HitObject`s.
+pub fn resolved_sample(hit_object: &HitObject, control_points: &ControlPoints) -> ResolvedSample {
+    let end_time = match &hit_object.kind {
+        HitObjectKind::Hold(hold) => hit_object.start_time + hold.duration,
+        HitObjectKind::Spinner(spinner) => hit_object.start_time + spinner.duration,
+        _ => hit_object.start_time,
+    };
+    let mut point = control_points
+        .sample_point_at(end_time + SAMPLE_POINT_LENIENCY_MS)
+        .map_or_else(SamplePoint::default, SamplePoint::clone);
+    point.sample_volume = point.sample_volume.clamp(0, 100);
+    let bank = |sample: &HitSampleInfo| {
+        if sample.bank_specified {
+            sample.bank
+        } else {
+            point.sample_bank
+        }
+    };
+    let volume = |sample: &HitSampleInfo| match sample.volume {
+        0 => point.sample_volume,
+        volume => volume,
+    };
+
+    let samples = &hit_object.samples;
+    let defaults = samples.iter().filter_map(|sample| match sample.name {
+        HitSampleInfoName::Default(name) => Some((name, sample)),
+        HitSampleInfoName::File(_) => None,
+    });
+    let normal = defaults
+        .clone()
+        .find(|(name, _)| *name == HitSampleDefaultName::Normal);
+    let addition = defaults
+        .clone()
+        .find(|(name, _)| *name != HitSampleDefaultName::Normal);
+    let set = normal.map_or(point.sample_bank, |(_, sample)| bank(sample));
+    let index = match normal.map_or(0, |(_, sample)| sample.custom_sample_bank) {
+        0 => point.custom_sample_bank,
+        index => index,
+    };
+
+    ResolvedSample {
+        set,
+        additions: addition.map_or(set, |(_, sample)| bank(sample)),
+        volume: samples
+            .iter()
+            .map(volume)
+            .max()
+            .unwrap_or(point.sample_volume),
+        index,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitsoundIssueKind {
+    /// Volume, after the sample point, below the threshold.
+    LowVolume { volume: i32 },
+    /// No sample bank, or no sample at all.
+    MissingBank,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HitsoundIssue {
+    /// Index into `Player::hit_objects`.
+    pub note_index: usize,
+    pub time_ms: f64,
+    pub kind: HitsoundIssueKind,
+}
+
+pub(crate) fn find_issues(
+    hit_objects: &[HitObject],
+    control_points: &ControlPoints,
+    volume_threshold: i32,
+) -> Vec<HitsoundIssue> {
+    let mut issues = Vec::new();
+    for (note_index, hit_object) in hit_objects.iter().enumerate() {
+        let (HitObjectKind::Circle(_) | HitObjectKind::Hold(_)) = hit_object.kind else {
+            continue;
+        };
+        let mut push = |kind| {
+            issues.push(HitsoundIssue {
+                note_index,
+                time_ms: hit_object.start_time,
+                kind,
+            })
+        };
+
+        let samples = &hit_object.samples;
+        let resolved = resolved_sample(hit_object, control_points);
+        if !samples.is_empty() && resolved.volume < volume_threshold {
+            push(HitsoundIssueKind::LowVolume {
+                volume: resolved.volume,
+            });
+        }
+        let banks = [resolved.set, resolved.additions];
+        if samples.is_empty() || banks.contains(&SampleBank::None) {
+            push(HitsoundIssueKind::MissingBank);
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::hit_objects::{HitObjectCircle, HitObjectHold};
+    use rosu_map::util::Pos;
+
+    fn circle(start_time: f64, samples: Vec<HitSampleInfo>) -> HitObject {
+        let kind = HitObjectKind::Circle(HitObjectCircle {
+            pos: Pos::new(64.0, 192.0),
+            new_combo: false,
+            combo_offset: 0,
+        });
+        HitObject {
+            start_time,
+            kind,
+            samples,
+        }
+    }
+
+    fn points(points: &[(f64, SampleBank, i32, i32)]) -> ControlPoints {
+        let mut control_points = ControlPoints::default();
+        for &(time, bank, volume, index) in points {
+            control_points.add(SamplePoint::new(time, bank, volume, index));
+        }
+        control_points
+    }
+
+    #[test]
+    fn object_overrides_win() {
+        let control_points = points(&[(0.0, SampleBank::Soft, 70, 3)]);
+        let note = circle(
+            1000.0,
+            vec![
+                HitSampleInfo::new(HitSampleInfo::HIT_NORMAL, Some(SampleBank::Drum), 2, 40),
+                HitSampleInfo::new(HitSampleInfo::HIT_WHISTLE, Some(SampleBank::Normal), 2, 40),
+            ],
+        );
+        let expected = ResolvedSample {
+            set: SampleBank::Drum,
+            additions: SampleBank::Normal,
+            volume: 40,
+            index: 2,
+        };
+        assert_eq!(resolved_sample(&note, &control_points), expected);
+    }
+
+    #[test]
+    fn timing_point_fills_unset_fields() {
+        let control_points = points(&[(0.0, SampleBank::Soft, 70, 3)]);
+        let expected = ResolvedSample {
+            set: SampleBank::Soft,
+            additions: SampleBank::Soft,
+            volume: 70,
+            index: 3,
+        };
+        let unset = vec![
+            HitSampleInfo::new(HitSampleInfo::HIT_NORMAL, None, 0, 0),
+            HitSampleInfo::new(HitSampleInfo::HIT_CLAP, None, 0, 0),
+        ];
+        assert_eq!(
+            resolved_sample(&circle(1000.0, unset), &control_points),
+            expected
+        );
+        // A note without samples sounds like the point
+        assert_eq!(
+            resolved_sample(&circle(1000.0, Vec::new()), &control_points),
+            expected
+        );
+
+        // Without a sample point, osu!'s defaults
+        let defaults = ResolvedSample {
+            set: SampleBank::Normal,
+            additions: SampleBank::Normal,
+            volume: 100,
+            index: 0,
+        };
+        let note = circle(1000.0, Vec::new());
+        assert_eq!(resolved_sample(&note, &ControlPoints::default()), defaults);
+    }
+
+    #[test]
+    fn custom_indexes_fall_back_to_the_point() {
+        let control_points = points(&[(0.0, SampleBank::Normal, 100, 4)]);
+        let index = |index| {
+            let samples = vec![HitSampleInfo::new(
+                HitSampleInfo::HIT_NORMAL,
+                None,
+                index,
+                0,
+            )];
+            resolved_sample(&circle(1000.0, samples), &control_points).index
+        };
+        assert_eq!(index(0), 4);
+        // 1 picks the skin's sample over the point's
+        assert_eq!(index(1), 1);
+        assert_eq!(index(7), 7);
+    }
+
+    #[test]
+    fn sample_point_volume_is_clamped() {
+        let mut control_points = ControlPoints::default();
+        control_points.sample_points.push(SamplePoint {
+            time: 0.0,
+            sample_bank: SampleBank::Normal,
+            sample_volume: 150,
+            custom_sample_bank: 0,
+        });
+        let note = circle(1000.0, Vec::new());
+        assert_eq!(resolved_sample(&note, &control_points).volume, 100);
+    }
+
+    #[test]
+    fn holds_resolve_at_their_tail() {
+        let control_points = points(&[
+            (0.0, SampleBank::Soft, 70, 0),
+            (1000.0, SampleBank::Drum, 30, 0),
+        ]);
+        let hold = HitObject {
+            start_time: 500.0,
+            kind: HitObjectKind::Hold(HitObjectHold {
+                pos_x: 64.0,
+                duration: 498.0,
+            }),
+            samples: Vec::new(),
+        };
+        let resolved = resolved_sample(&hold, &control_points);
+        assert_eq!((resolved.set, resolved.volume), (SampleBank::Drum, 30));
+        // Notes pick up a point starting within the leniency
+        let set = |time| resolved_sample(&circle(time, Vec::new()), &control_points).set;
+        assert_eq!(set(996.0), SampleBank::Drum);
+        assert_eq!(set(990.0), SampleBank::Soft);
+    }
+
+    #[test]
+    fn pan_spreads_the_columns_evenly() {
+        let config = HitsoundConfig::default().pan_by_column(1.0);
+        let pans: Vec<f32> = (0..4).map(|column| config.pan(column, 4)).collect();
+        let third = 1.0 / 3.0;
+        let expected = [-1.0, -third, third, 1.0];
+        for (pan, expected) in pans.iter().zip(expected) {
+            assert!((pan - expected).abs() < 1e-6, "{pans:?}");
+        }
+        // The middle column of an odd keycount is centered
+        assert_eq!(config.pan(3, 7), 0.0);
+    }
+
+    #[test]
+    fn pan_scales_with_the_strength() {
+        let config = HitsoundConfig::default().pan_by_column(0.5);
+        assert_eq!(config.pan(0, 7), -0.5);
+        assert_eq!(config.pan(6, 7), 0.5);
+        assert_eq!(HitsoundConfig::default().pan(0, 7), 0.0);
+        assert_eq!(HitsoundConfig::default().pan_by_column(0.0).pan(6, 7), 0.0);
+    }
+
+    #[test]
+    fn pan_ignores_invalid_input() {
+        for strength in [f32::NAN, -1.0] {
+            let config = HitsoundConfig::default().pan_by_column(strength);
+            assert_eq!(config.pan(0, 4), 0.0);
+        }
+        let config = HitsoundConfig::default().pan_by_column(3.0);
+        assert_eq!(config.pan(0, 4), -1.0);
+        // A single column is centered, columns past the last are clamped
+        assert_eq!(config.pan(0, 1), 0.0);
+        assert_eq!(config.pan(9, 4), 1.0);
+    }
+}
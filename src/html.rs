@@ -0,0 +1,208 @@
+//! Self-contained HTML page previewing a chart on a canvas, see
+//! `Player::export_html`. The page redraws the stage with the same column
+//! and scroll math as the mania renderer, not pixel for pixel.
+
+use crate::layout::mania::column_for_x;
+use egui::Color32;
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+use rosu_map::section::timing_points::ControlPoints;
+use std::fmt::Write as _;
+
+const TEMPLATE: &str = include_str!("html/preview.html");
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HtmlExportOptions {
+    /// Page title, the difficulty name when `None`.
+    pub title: Option<String>,
+}
+
+// Layout constants filled into the template, unscaled
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HtmlStage {
+    pub(crate) column_width: f32,
+    pub(crate) note_size: f32,
+    pub(crate) height: f32,
+    pub(crate) hit_position: f32,
+    // 1 when notes fall, -1 when they rise, see `ScrollDirection`
+    pub(crate) direction: f32,
+    pub(crate) scroll_time_ms: f32,
+    pub(crate) speed: f64,
+    pub(crate) note_color: Color32,
+    pub(crate) hold_body_color: Color32,
+    pub(crate) hold_cap_color: Color32,
+}
+
+/// Chart data embedded in the page: `notes` are `[column, time, duration]`
+/// with a duration of 0 for regular notes, `timing_points` are
+/// `[time, beat length, beats per measure, omit first bar line]` with the
+/// last 0 or 1. Times are map ms.
+pub fn chart_json(
+    hit_objects: &[HitObject],
+    control_points: &ControlPoints,
+    keycount: usize,
+) -> String {
+    let mut json = format!("{{\"keycount\":{keycount},\"notes\":[");
+    let notes = hit_objects.iter().filter_map(|h| match &h.kind {
+        HitObjectKind::Circle(c) => Some((c.pos.x, h.start_time, 0.0)),
+        HitObjectKind::Hold(hold) => Some((hold.pos_x, h.start_time, hold.duration)),
+        _ => None,
+    });
+    for (i, (x, time, duration)) in notes.enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let column = column_for_x(x, keycount);
+        let _ = write!(
+            json,
+            "{separator}[{column},{},{}]",
+            number(time),
+            number(duration)
+        );
+    }
+    json.push_str("],\"timing_points\":[");
+    for (i, point) in control_points.timing_points.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let _ = write!(
+            json,
+            "{separator}[{},{},{},{}]",
+            number(point.time),
+            number(point.beat_len),
+            point.time_signature.numerator.get(),
+            u8::from(point.omit_first_bar_line)
+        );
+    }
+    json.push_str("]}");
+    json
+}
+
+pub(crate) fn page(title: &str, chart_json: &str, stage: &HtmlStage) -> String {
+    let css = |c: Color32| format!("#{:02x}{:02x}{:02x}", c.r(), c.g(), c.b());
+    // Keeps the JSON from closing the script element
+    let chart = chart_json.replace("</", "<\\/");
+    [
+        ("{{CHART}}", chart),
+        ("{{COLUMN_WIDTH}}", number(stage.column_width.into())),
+        ("{{NOTE_SIZE}}", number(stage.note_size.into())),
+        ("{{HEIGHT}}", number(stage.height.into())),
+        ("{{HIT_POSITION}}", number(stage.hit_position.into())),
+        ("{{DIRECTION}}", number(stage.direction.into())),
+        ("{{SCROLL_TIME_MS}}", number(stage.scroll_time_ms.into())),
+        ("{{SPEED}}", number(stage.speed)),
+        ("{{NOTE_COLOR}}", css(stage.note_color)),
+        ("{{HOLD_BODY_COLOR}}", css(stage.hold_body_color)),
+        ("{{HOLD_CAP_COLOR}}", css(stage.hold_cap_color)),
+        // Last, so a title can't inject another placeholder
+        ("{{TITLE}}", escape_html(title)),
+    ]
+    .into_iter()
+    .fold(TEMPLATE.to_owned(), |page, (key, value)| {
+        page.replace(key, &value)
+    })
+}
+
+// JSON and JS have no NaN or infinity
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "0".to_owned()
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::hit_objects::{HitObjectCircle, HitObjectHold};
+    use rosu_map::section::timing_points::{TimeSignature, TimingPoint};
+
+    fn note(x: f32, time: f64) -> HitObject {
+        HitObject {
+            start_time: time,
+            kind: HitObjectKind::Circle(HitObjectCircle {
+                pos: rosu_map::util::Pos::new(x, 192.0),
+                new_combo: false,
+                combo_offset: 0,
+            }),
+            samples: Vec::new(),
+        }
+    }
+
+    fn stage(direction: f32) -> HtmlStage {
+        HtmlStage {
+            column_width: 80.0,
+            note_size: 60.0,
+            height: 600.0,
+            hit_position: 100.0,
+            direction,
+            scroll_time_ms: 1000.0,
+            speed: 1.5,
+            note_color: Color32::WHITE,
+            hold_body_color: Color32::GRAY,
+            hold_cap_color: Color32::from_rgb(1, 2, 3),
+        }
+    }
+
+    #[test]
+    fn chart_json_lists_notes_and_timing_points() {
+        let hold = HitObject {
+            start_time: 1250.5,
+            kind: HitObjectKind::Hold(HitObjectHold {
+                pos_x: 448.0,
+                duration: 300.0,
+            }),
+            samples: Vec::new(),
+        };
+        let hit_objects = [note(64.0, 1000.0), hold, note(192.0, f64::NAN)];
+        let mut control_points = ControlPoints::default();
+        control_points.add(TimingPoint::new(
+            500.0,
+            400.0,
+            true,
+            TimeSignature::new_simple_triple(),
+        ));
+        control_points.add(TimingPoint::new(
+            2000.0,
+            300.0,
+            false,
+            TimeSignature::new_simple_quadruple(),
+        ));
+
+        let json = chart_json(&hit_objects, &control_points, 4);
+        assert_eq!(
+            json,
+            "{\"keycount\":4,\"notes\":[[0,1000,0],[3,1250.5,300],[1,0,0]],\
+             \"timing_points\":[[500,400,3,1],[2000,300,4,0]]}"
+        );
+        let empty = chart_json(&[], &ControlPoints::default(), 7);
+        assert_eq!(empty, "{\"keycount\":7,\"notes\":[],\"timing_points\":[]}");
+    }
+
+    #[test]
+    fn page_fills_every_placeholder() {
+        let page = page("a <b> & \"c\"", "{\"x\":\"</script>\"}", &stage(1.0));
+        assert!(!page.contains("{{"), "placeholder left");
+        assert!(page.contains("<title>a &lt;b&gt; &amp; &quot;c&quot;</title>"));
+        assert!(page.contains("const chart = {\"x\":\"<\\/script>\"};"));
+        assert!(page.contains("const DIRECTION = 1;"));
+        assert!(page.contains("const SPEED = 1.5;"));
+        assert!(page.contains("const HOLD_CAP_COLOR = \"#010203\";"));
+    }
+
+    #[test]
+    fn titles_cannot_add_placeholders() {
+        let page = page("{{CHART}}", "{}", &stage(1.0));
+        assert!(page.contains("<title>{{CHART}}</title>"));
+    }
+
+    #[test]
+    fn page_flips_the_stage_in_upscroll() {
+        let page = page("Upscroll", "{}", &stage(-1.0));
+        assert!(page.contains("const DIRECTION = -1;"));
+        assert!(page.contains("const JUDGMENT_LINE_Y = DIRECTION > 0 ? HEIGHT - 100 : 100;"));
+    }
+}
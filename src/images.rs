@@ -0,0 +1,116 @@
+//! Where note and background images come from. Styles take plain
+//! `egui::Image`s; a provider turns a key into one, whether the image sits
+//! behind one of egui's URI loaders, in memory (e.g. unpacked from a skin
+//! archive) or is already uploaded.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use egui::load::{SizedTexture, TexturePoll};
+use egui::{ColorImage, TextureHandle, TextureId, TextureOptions, Vec2};
+
+/// An uploaded texture and its size in points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoadedTexture {
+    pub id: TextureId,
+    pub size: Vec2,
+}
+
+impl LoadedTexture {
+    /// For `NoteShape::Image` or `Letterbox::Image`.
+    pub fn to_image(self) -> egui::Image<'static> {
+        egui::Image::from_texture(SizedTexture::new(self.id, self.size))
+    }
+}
+
+pub trait ImageProvider: Send + Sync {
+    /// Texture for `key`, or `None` if it doesn't exist or isn't loaded
+    /// yet. Called again for keys still loading.
+    fn resolve(&self, ctx: &egui::Context, key: &str) -> Option<LoadedTexture>;
+}
+
+/// egui's loaders, keys being URIs such as `file://note.png`. Needs
+/// `egui_extras::install_image_loaders`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UriImages;
+
+impl ImageProvider for UriImages {
+    fn resolve(&self, ctx: &egui::Context, key: &str) -> Option<LoadedTexture> {
+        let poll = ctx
+            .try_load_texture(key, TextureOptions::default(), Default::default())
+            .ok()?;
+        match poll {
+            TexturePoll::Ready { texture } => Some(LoadedTexture {
+                id: texture.id,
+                size: texture.size,
+            }),
+            TexturePoll::Pending { .. } => None,
+        }
+    }
+}
+
+/// Encoded images (PNG, JPEG) by key, decoded and uploaded on first use.
+#[derive(Default)]
+pub struct MemoryImages {
+    bytes: HashMap<String, Vec<u8>>,
+    // Uploaded textures, `None` for images that failed to decode
+    textures: Mutex<HashMap<String, Option<TextureHandle>>>,
+}
+
+impl MemoryImages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, bytes: Vec<u8>) {
+        let key = key.into();
+        self.textures
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&key);
+        self.bytes.insert(key, bytes);
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.bytes.contains_key(key)
+    }
+}
+
+impl ImageProvider for MemoryImages {
+    fn resolve(&self, ctx: &egui::Context, key: &str) -> Option<LoadedTexture> {
+        let bytes = self.bytes.get(key)?;
+        let mut textures = self.textures.lock().unwrap_or_else(|e| e.into_inner());
+        let texture = textures.entry(key.to_owned()).or_insert_with(|| {
+            let image = image::load_from_memory(bytes).ok()?.to_rgba8();
+            let size = [image.width() as usize, image.height() as usize];
+            let image = ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+            Some(ctx.load_texture(key, image, TextureOptions::default()))
+        });
+        texture.as_ref().map(|texture| LoadedTexture {
+            id: texture.id(),
+            size: texture.size_vec2(),
+        })
+    }
+}
+
+/// Textures the host already uploaded, by key.
+#[derive(Clone, Debug, Default)]
+pub struct TextureImages {
+    textures: HashMap<String, LoadedTexture>,
+}
+
+impl TextureImages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, texture: LoadedTexture) {
+        self.textures.insert(key.into(), texture);
+    }
+}
+
+impl ImageProvider for TextureImages {
+    fn resolve(&self, _ctx: &egui::Context, key: &str) -> Option<LoadedTexture> {
+        self.textures.get(key).copied()
+    }
+}
@@ -0,0 +1,137 @@
+//! osu!mania hit windows. Everything that depends on a window size (bands,
+//! judgments, error bars, miss visuals) must read it from here.
+
+use crate::layout::standard::difficulty::difficulty_range;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowMode {
+    /// osu!stable. Offsets are whole milliseconds compared with `<=`, so every
+    /// window is an integer plus 0.5, Marv being a fixed ±16.5 ms.
+    #[default]
+    ScoreV1,
+    /// osu!stable ScoreV2: same windows, but LN releases get no leniency.
+    ScoreV2,
+    /// osu!lazer: continuous windows from the difficulty range.
+    Lazer,
+}
+
+/// Half-width of each judgment window in map milliseconds: an offset `x`
+/// gets the best judgment whose window satisfies `|x| <= window`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Windows {
+    pub marv: f32,
+    pub perfect: f32,
+    pub great: f32,
+    pub good: f32,
+    pub ok: f32,
+    pub miss: f32,
+}
+
+impl Windows {
+    /// Windows for LN releases, which are judged more leniently.
+    pub fn release(self, mode: WindowMode) -> Windows {
+        self.scaled(release_leniency(mode))
+    }
+
+    fn scaled(self, factor: f32) -> Windows {
+        Windows {
+            marv: self.marv * factor,
+            perfect: self.perfect * factor,
+            great: self.great * factor,
+            good: self.good * factor,
+            ok: self.ok * factor,
+            miss: self.miss * factor,
+        }
+    }
+}
+
+pub fn mania_windows(od: f32, mode: WindowMode) -> Windows {
+    match mode {
+        WindowMode::ScoreV1 | WindowMode::ScoreV2 => {
+            let stable = |base: f32| (base - 3.0 * od).floor().max(0.0) + 0.5;
+            Windows {
+                marv: 16.5,
+                perfect: stable(64.0),
+                great: stable(97.0),
+                good: stable(127.0),
+                ok: stable(151.0),
+                miss: stable(188.0),
+            }
+        }
+        WindowMode::Lazer => {
+            let lazer = |min, mid, max| difficulty_range(od, min, mid, max).max(0.0);
+            Windows {
+                marv: lazer(22.4, 19.4, 13.9),
+                perfect: lazer(64.0, 49.0, 34.0),
+                great: lazer(97.0, 82.0, 67.0),
+                good: lazer(127.0, 112.0, 97.0),
+                ok: lazer(151.0, 136.0, 121.0),
+                miss: lazer(188.0, 173.0, 158.0),
+            }
+        }
+    }
+}
+
+/// How much wider the windows are for LN releases.
+pub fn release_leniency(mode: WindowMode) -> f32 {
+    match mode {
+        WindowMode::ScoreV1 | WindowMode::Lazer => 1.5,
+        WindowMode::ScoreV2 => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // marv, perfect, great, good, ok, miss
+    fn assert_windows(windows: Windows, expected: [f32; 6]) {
+        let actual = [
+            windows.marv,
+            windows.perfect,
+            windows.great,
+            windows.good,
+            windows.ok,
+            windows.miss,
+        ];
+        for (actual, expected) in actual.into_iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-3,
+                "{windows:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn stable_windows() {
+        for mode in [WindowMode::ScoreV1, WindowMode::ScoreV2] {
+            let windows = |od| mania_windows(od, mode);
+            assert_windows(windows(0.0), [16.5, 64.5, 97.5, 127.5, 151.5, 188.5]);
+            assert_windows(windows(7.0), [16.5, 43.5, 76.5, 106.5, 130.5, 167.5]);
+            assert_windows(windows(8.2), [16.5, 39.5, 72.5, 102.5, 126.5, 163.5]);
+            assert_windows(windows(10.0), [16.5, 34.5, 67.5, 97.5, 121.5, 158.5]);
+        }
+    }
+
+    #[test]
+    fn lazer_windows() {
+        let windows = |od| mania_windows(od, WindowMode::Lazer);
+        assert_windows(windows(0.0), [22.4, 64.0, 97.0, 127.0, 151.0, 188.0]);
+        assert_windows(windows(7.0), [17.2, 43.0, 76.0, 106.0, 130.0, 167.0]);
+        assert_windows(windows(8.2), [15.88, 39.4, 72.4, 102.4, 126.4, 163.4]);
+        assert_windows(windows(10.0), [13.9, 34.0, 67.0, 97.0, 121.0, 158.0]);
+    }
+
+    #[test]
+    fn release_leniency_per_mode() {
+        let windows = mania_windows(10.0, WindowMode::ScoreV1);
+        assert_windows(
+            windows.release(WindowMode::ScoreV1),
+            [24.75, 51.75, 101.25, 146.25, 182.25, 237.75],
+        );
+        assert_eq!(windows.release(WindowMode::ScoreV2), windows);
+        let lazer = mania_windows(0.0, WindowMode::Lazer);
+        assert_eq!(lazer.release(WindowMode::Lazer).miss, 282.0);
+    }
+}
@@ -1,12 +1,140 @@
+use crate::camera::Camera;
+use crate::description::{
+    rgba, Bounds, CoverDescription, FrameDescription, HoldDescription, NoteDescription, NoteKind,
+    ReceptorDescription,
+};
+use crate::effects::{EffectClock, EffectId};
+use crate::events::panic_message;
+use crate::format::{Formatter, SharedFormatter};
+use crate::gradient::Gradient;
+use crate::images::{ImageProvider, LoadedTexture, UriImages};
+use crate::judge::windows::{mania_windows, WindowMode, Windows};
+use crate::rate::RateRamp;
+use crate::replay::{JudgmentLog, Recorded};
+use crate::scatter::{self, ScatterStyle};
+use crate::snap;
+use crate::stats::Composition;
+use crate::storyboard::BackgroundEvent;
+use crate::trace::trace_warn;
 use egui::{self, pos2, Color32, Rect, Vec2};
-use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+use rosu_map::section::hit_objects::{HitObject, HitObjectCircle, HitObjectHold, HitObjectKind};
+use rosu_map::util::Pos;
+use smallvec::SmallVec;
+use std::collections::BTreeMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 
+pub const MAX_KEYCOUNT: usize = 18;
+
+// Body and tail of a hold the host reported as dropped
+const BROKEN_HOLD_COLOR: Color32 = Color32::from_gray(70);
+
+// Space between stages drawn side by side
+const STAGE_GAP: f32 = 20.0;
+
+// 1K to 3K columns are widened, by at most MAX_COLUMN_STRETCH, so the stage
+// doesn't end up as a thin strip next to MIN_STAGE_COLUMNS regular columns
+const MIN_STAGE_COLUMNS: usize = 4;
+const MAX_COLUMN_STRETCH: f32 = 2.0;
+
+// Outline colors of the debug overlay, one per element class
+const DEBUG_PLAYFIELD_COLOR: Color32 = Color32::from_rgb(255, 0, 255);
+const DEBUG_CLIP_COLOR: Color32 = Color32::from_rgb(255, 60, 60);
+const DEBUG_COLUMN_COLOR: Color32 = Color32::from_rgb(0, 220, 220);
+const DEBUG_JUDGMENT_COLOR: Color32 = Color32::from_rgb(255, 230, 0);
+const DEBUG_NOTE_COLOR: Color32 = Color32::from_rgb(60, 255, 60);
+const DEBUG_HOLD_COLOR: Color32 = Color32::from_rgb(255, 150, 0);
+
+// Narrowest the top of a tilted stage gets, as a share of the bottom
+const MAX_STAGE_TILT: f32 = 0.8;
+
+// Width of a glyph in deterministic mode, relative to the font size
+const DETERMINISTIC_GLYPH_WIDTH: f32 = 0.6;
+
+// High contrast colors, see `AccessibilityOptions`
+const HIGH_CONTRAST_OUTLINE: Color32 = Color32::WHITE;
+const HIGH_CONTRAST_JUDGMENT_WIDTH: f32 = 4.0;
+
+/// Below this size `render_in` draws a placeholder instead of the stage,
+/// see `ManiaRenderer::set_min_render_size`.
+pub const DEFAULT_MIN_RENDER_SIZE: Vec2 = Vec2::new(80.0, 80.0);
+
+/// Distance from the judgment line to the stage edge behind it, see
+/// `ManiaRenderer::set_hit_position`.
+pub const DEFAULT_HIT_POSITION: f32 = 100.0;
+
+// Width of the shading along the edges of `render_in` hiding part of the stage
+const OVERFLOW_HINT_WIDTH: f32 = 6.0;
+
+// Stage preview: the judgment line sits at map time 0 and the top of the
+// stage at PREVIEW_SCROLL_MS, with a beat line every PREVIEW_BEAT_MS (240 BPM)
+const PREVIEW_SCROLL_MS: f32 = 1000.0;
+const PREVIEW_BEAT_MS: f64 = 250.0;
+
+/// Keycount of a mania map with this circle size, clamped to
+/// `1..=MAX_KEYCOUNT`; NaN gives a single column.
+pub fn keycount_for(circle_size: f32) -> usize {
+    if circle_size.is_nan() {
+        return 1;
+    }
+    (circle_size as usize).clamp(1, MAX_KEYCOUNT)
+}
+
+/// Column of a mania object, the way osu! computes it:
+/// `floor(x * keycount / 512)` clamped to the valid columns, so x = 512 (or
+/// anything beyond) lands in the last column instead of wrapping around.
+pub fn column_for_x(x: f32, keycount: usize) -> usize {
+    let column = (x * keycount as f32 / 512.0).floor();
+    (column.max(0.0) as usize).min(keycount.saturating_sub(1))
+}
+
+/// Factor applied to the column width for `keycount` keys: 2 for 1K and 2K,
+/// 4/3 for 3K and 1 from 4K up.
+pub fn column_stretch(keycount: usize) -> f32 {
+    if keycount == 0 || keycount >= MIN_STAGE_COLUMNS {
+        return 1.0;
+    }
+    (MIN_STAGE_COLUMNS as f32 / keycount as f32).min(MAX_COLUMN_STRETCH)
+}
+
+/// x osu! writes for the center of `column`, e.g. 64/192/320/448 in 4K and
+/// 36/109/182/256/329/402/475 in 7K.
+pub fn column_center_x(column: usize, keycount: usize) -> f32 {
+    ((column as f32 + 0.5) * 512.0 / keycount as f32).floor()
+}
+
+/// `Keyed` notes are the image of that key in the renderer's provider, see
+/// `ManiaRenderer::set_image_provider`, drawn as circles until it resolves.
 #[derive(Clone)]
 pub enum NoteShape {
     Circle,
     Rectangle { width: f32, height: f32 },
     Arrow { width: f32, height: f32 },
     Image(egui::Image<'static>),
+    Keyed(String),
+}
+
+impl NoteShape {
+    /// Image note from `provider`, `None` while `key` isn't available.
+    pub fn from_provider(
+        provider: &dyn ImageProvider,
+        ctx: &egui::Context,
+        key: &str,
+    ) -> Option<Self> {
+        let texture = provider.resolve(ctx, key)?;
+        Some(NoteShape::Image(texture.to_image()))
+    }
+
+    // Size of the shape drawn at note size `size`
+    fn bounds(&self, size: f32) -> Vec2 {
+        match self {
+            NoteShape::Rectangle { width, height } | NoteShape::Arrow { width, height } => {
+                Vec2::new(size * width, size * height)
+            }
+            NoteShape::Circle | NoteShape::Image(_) | NoteShape::Keyed(_) => Vec2::splat(size),
+        }
+    }
 }
 
 pub struct NoteStyle {
@@ -30,125 +158,2725 @@ impl Default for NoteStyle {
     }
 }
 
-pub struct ManiaRenderer {
-    column_width: f32,
-    note_size: f32,
-    speed: f64,
-    height: f32,
-    note_style: NoteStyle,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+    Middle,
 }
 
-impl ManiaRenderer {
-    pub fn with_sizes(column_width: f32, note_size: f32, height: f32) -> Self {
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandSplit {
+    hands: Vec<Hand>,
+}
+
+impl HandSplit {
+    /// Left half / right half, with the center column of odd keycounts as `Middle`.
+    pub fn by_column(keycount: usize) -> Self {
+        let hands = (0..keycount)
+            .map(|column| {
+                if keycount % 2 == 1 && column == keycount / 2 {
+                    Hand::Middle
+                } else if column < keycount / 2 {
+                    Hand::Left
+                } else {
+                    Hand::Right
+                }
+            })
+            .collect();
+
+        Self { hands }
+    }
+
+    pub fn custom(hands: Vec<Hand>) -> Self {
+        Self { hands }
+    }
+
+    pub fn hand(&self, column: usize) -> Option<Hand> {
+        self.hands.get(column).copied()
+    }
+
+    /// Number of columns the split covers.
+    pub fn len(&self) -> usize {
+        self.hands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hands.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HandColoring {
+    pub split: HandSplit,
+    pub left_color: Color32,
+    pub right_color: Color32,
+    pub middle_color: Color32,
+}
+
+impl HandColoring {
+    pub fn new(split: HandSplit) -> Self {
         Self {
-            column_width,
-            note_size,
-            speed: 1.0,
-            height,
-            note_style: NoteStyle::default(),
+            split,
+            left_color: Color32::from_rgb(255, 90, 90),
+            right_color: Color32::from_rgb(90, 140, 255),
+            middle_color: Color32::from_rgb(255, 210, 80),
+        }
+    }
+
+    pub fn color(&self, column: usize) -> Option<Color32> {
+        self.split.hand(column).map(|hand| match hand {
+            Hand::Left => self.left_color,
+            Hand::Right => self.right_color,
+            Hand::Middle => self.middle_color,
+        })
+    }
+}
+
+/// Colors note heads by the beat division they sit on, Stepmania style.
+/// Takes precedence over the note style and hand coloring.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapColoring {
+    /// (divisor, color), the first division a note sits on wins, so coarser
+    /// ones go first.
+    pub palette: Vec<(u32, Color32)>,
+    /// Notes on none of the palette's divisions.
+    pub unsnapped: Color32,
+    /// How far, in ms, a note may be from a line and still sit on it.
+    pub tolerance_ms: f64,
+    /// Colors hold bodies and caps too, like the head.
+    pub holds: bool,
+}
+
+impl Default for SnapColoring {
+    fn default() -> Self {
+        Self {
+            palette: vec![
+                (1, Color32::from_rgb(255, 60, 60)),
+                (2, Color32::from_rgb(60, 110, 255)),
+                (3, Color32::from_rgb(170, 70, 255)),
+                (4, Color32::from_rgb(255, 220, 60)),
+                (6, Color32::from_rgb(255, 110, 200)),
+                (8, Color32::from_rgb(255, 150, 40)),
+                (12, Color32::from_rgb(60, 230, 230)),
+                (16, Color32::from_rgb(60, 220, 90)),
+            ],
+            unsnapped: Color32::from_gray(160),
+            tolerance_ms: 2.0,
+            holds: false,
+        }
+    }
+}
+
+impl SnapColoring {
+    // Color of a note at map time `time`, against the lines of the timing
+    // point active then, or the first one before any
+    fn color(&self, beat_lengths: &[(f64, f64)], time: f64) -> Color32 {
+        let snapped = self.palette.iter().find(|(divisor, _)| {
+            snap::off_line_ms(beat_lengths, time, *divisor)
+                .is_some_and(|off_line_ms| off_line_ms <= self.tolerance_ms)
+        });
+        snapped.map_or(self.unsnapped, |(_, color)| *color)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowTier {
+    Marv,
+    Perfect,
+    Great,
+    Good,
+    Ok,
+    Miss,
+}
+
+impl WindowTier {
+    pub const ALL: [WindowTier; 6] = [
+        WindowTier::Marv,
+        WindowTier::Perfect,
+        WindowTier::Great,
+        WindowTier::Good,
+        WindowTier::Ok,
+        WindowTier::Miss,
+    ];
+
+    /// Half-width of this tier's window in map milliseconds.
+    pub fn window_ms(self, windows: &Windows) -> f32 {
+        match self {
+            WindowTier::Marv => windows.marv,
+            WindowTier::Perfect => windows.perfect,
+            WindowTier::Great => windows.great,
+            WindowTier::Good => windows.good,
+            WindowTier::Ok => windows.ok,
+            WindowTier::Miss => windows.miss,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowBandStyle {
+    /// Indexed by `WindowTier as usize`.
+    pub colors: [Color32; 6],
+    pub tiers: Vec<WindowTier>,
+}
+
+impl WindowBandStyle {
+    pub fn color(&self, tier: WindowTier) -> Color32 {
+        self.colors[tier as usize]
+    }
+
+    pub fn with_tiers(mut self, tiers: Vec<WindowTier>) -> Self {
+        self.tiers = tiers;
+        self
+    }
+}
+
+impl Default for WindowBandStyle {
+    fn default() -> Self {
+        Self {
+            colors: [
+                Color32::from_rgba_unmultiplied(255, 255, 255, 40),
+                Color32::from_rgba_unmultiplied(255, 220, 60, 35),
+                Color32::from_rgba_unmultiplied(60, 220, 90, 30),
+                Color32::from_rgba_unmultiplied(60, 140, 255, 25),
+                Color32::from_rgba_unmultiplied(170, 90, 255, 20),
+                Color32::from_rgba_unmultiplied(255, 60, 60, 15),
+            ],
+            tiers: WindowTier::ALL.to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MotionBlur {
+    /// Ghost copies drawn behind each note.
+    pub samples: u32,
+    /// Opacity of the nearest ghost, in 0..=1.
+    pub strength: f32,
+}
+
+impl Default for MotionBlur {
+    fn default() -> Self {
+        Self {
+            samples: 3,
+            strength: 0.5,
+        }
+    }
+}
+
+/// Length of one stripe of a `HoldBodyPattern::Striped` body.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Period {
+    /// Follows the BPM at each point of the hold.
+    Beats(f32),
+    Ms(f32),
+}
+
+/// How hold bodies are filled. Stripes start at the hold head and scroll
+/// with the body, alternating `color_a` and `color_b`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HoldBodyPattern {
+    #[default]
+    Solid,
+    Striped {
+        period: Period,
+        color_a: Color32,
+        color_b: Color32,
+    },
+}
+
+/// How holds are drawn between their head and tail.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HoldBodyStyle {
+    /// A body filled as `HoldBodyPattern` says.
+    #[default]
+    Body,
+    /// No body: a copy of the note, `shape_scale` times its size, on each
+    /// 1/`divisor` beat line within the hold, following BPM changes.
+    Ladder { divisor: u32, shape_scale: f32 },
+}
+
+// Brightness of storyboard backgrounds outside breaks, and how long they
+// take to light up when a break starts or ends
+const BACKGROUND_BRIGHTNESS: f32 = 0.3;
+const BREAK_FADE_MS: f64 = 500.0;
+
+// Stripes drawn per hold body, and stripe boundaries walked from the
+// visible start to find them
+const MAX_HOLD_STRIPES: usize = 64;
+const MAX_STRIPE_STEPS: usize = 4096;
+
+// Rungs drawn per ladder hold
+const MAX_LADDER_RUNGS: usize = 256;
+
+// Measure lines drawn per stage
+const MAX_BARLINES: usize = 256;
+// A measure line this close before the next timing point is left to it
+const BARLINE_EPSILON_MS: f64 = 1.0;
+
+/// Measure lines across the stage, see `ManiaRenderer::set_barlines`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BarlineStyle {
+    pub color: Color32,
+    pub thickness: f32,
+}
+
+impl Default for BarlineStyle {
+    fn default() -> Self {
+        Self {
+            color: Color32::from_gray(90),
+            thickness: 1.0,
+        }
+    }
+}
+
+// Measures of an uninherited timing point, from its time to the next one
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Measures {
+    pub(crate) start: f64,
+    // Beat length times the time signature
+    pub(crate) length: f64,
+    pub(crate) omit_first: bool,
+}
+
+// Map times of the measure lines from `from` to `to`. Each timing point
+// restarts the count at its own time; there are none before the first one.
+fn barline_times(measures: &[Measures], from: f64, to: f64) -> impl Iterator<Item = f64> + '_ {
+    let ends = measures.iter().skip(1).map(|next| next.start);
+    let ends = ends.chain([f64::INFINITY]);
+    measures.iter().zip(ends).flat_map(move |(measure, end)| {
+        let Measures {
+            start,
+            length,
+            omit_first,
+        } = *measure;
+        let skipped = if omit_first { 1.0 } else { 0.0 };
+        let first = ((from - start) / length).ceil().max(skipped);
+        (0..)
+            .map(move |k| start + (first + k as f64) * length)
+            .take_while(move |time| *time <= to && *time + BARLINE_EPSILON_MS < end)
+    })
+}
+
+// Map time `beats` beats after `time`, switching beat length at each timing
+// point crossed on the way
+fn advance_beats(beat_lengths: &[(f64, f64)], mut time: f64, mut beats: f64) -> f64 {
+    let (_, mut beat_len, next) = snap::active_beat_length(beat_lengths, time);
+    for &(start, len) in &beat_lengths[next..] {
+        let reached = time + beats * beat_len;
+        if reached <= start {
+            return reached;
+        }
+        beats -= (start - time) / beat_len;
+        time = start;
+        beat_len = len;
+    }
+    time + beats * beat_len
+}
+
+// Beats from `from` to a later `to`, the inverse of `advance_beats`
+fn beats_between(beat_lengths: &[(f64, f64)], from: f64, to: f64) -> f64 {
+    let (_, mut beat_len, next) = snap::active_beat_length(beat_lengths, from);
+    let (mut time, mut beats) = (from, 0.0);
+    for &(start, len) in &beat_lengths[next..] {
+        if to <= start {
+            break;
+        }
+        beats += (start - time) / beat_len;
+        time = start;
+        beat_len = len;
+    }
+    beats + (to - time) / beat_len
+}
+
+// Frame gaps longer than this are treated as seeks/hitches and never smeared
+const MAX_BLUR_FRAME_MS: f64 = 100.0;
+
+/// What the last `render*` call drew, summed over its stages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    pub visible_notes: usize,
+    /// Image notes were drawn as circles, see `set_image_note_limit`.
+    pub degraded: bool,
+    /// The frame came after a hitch, see `Player::set_hitch_threshold`.
+    pub hitch: bool,
+}
+
+/// Time spent by each step of `warm_up`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WarmUpTimings {
+    pub textures: Duration,
+    pub glyphs: Duration,
+    /// Drawing and tessellating notes of every column in a scratch context.
+    pub frame: Duration,
+}
+
+// Every character the overlays draw
+const OVERLAY_GLYPHS: &str = "+-0123456789 ms";
+const COMPOSITION_GLYPHS: &str = "LN% · max chord0123456789.";
+
+/// Signed hit offset readout, e.g. "-12 ms", shown above the judgment line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimingTextStyle {
+    pub early_color: Color32,
+    pub late_color: Color32,
+    pub font_size: f32,
+    /// Map time over which a readout fades out.
+    pub duration_ms: f64,
+    /// One readout above the stage center instead of one per column.
+    pub shared: bool,
+    /// Where the shared readout goes, above the stage center when `None`.
+    pub anchor: Option<OverlayAnchor>,
+    /// Replaces the early and late colors: the miss window maps to `0.0`
+    /// early through `1.0` late, `0.5` being on time.
+    pub gradient: Option<Gradient>,
+    pub formatter: SharedFormatter,
+}
+
+impl Default for TimingTextStyle {
+    fn default() -> Self {
+        Self {
+            early_color: Color32::from_rgb(90, 170, 255),
+            late_color: Color32::from_rgb(255, 140, 70),
+            font_size: 14.0,
+            duration_ms: 400.0,
+            shared: false,
+            anchor: None,
+            gradient: None,
+            formatter: SharedFormatter::default(),
+        }
+    }
+}
+
+/// Bar per column showing its recent note rate, e.g. for stream overlays.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActivityMeterStyle {
+    /// Map time over which passed notes count, in ms.
+    pub window_ms: f64,
+    /// Notes per second filling a whole bar.
+    pub max_nps: f32,
+    pub height: f32,
+    pub color: Color32,
+    /// Colors bars by how full they are instead of `color`.
+    pub gradient: Option<Gradient>,
+    /// Where the row of bars goes, as wide as the columns of one stage.
+    pub anchor: OverlayAnchor,
+}
+
+impl Default for ActivityMeterStyle {
+    fn default() -> Self {
+        Self {
+            window_ms: 1000.0,
+            max_nps: 10.0,
+            height: 40.0,
+            color: Color32::from_rgb(0, 174, 255),
+            gradient: None,
+            anchor: OverlayAnchor {
+                relative_to: AnchorTarget::Stage,
+                alignment: egui::Align2::CENTER_TOP,
+                offset: Vec2::ZERO,
+            },
+        }
+    }
+}
+
+impl ActivityMeterStyle {
+    /// Window and full-bar rate must be positive.
+    pub fn is_valid(&self) -> bool {
+        self.window_ms > 0.0 && self.max_nps > 0.0
+    }
+}
+
+/// One line summary of the chart, e.g. "LN 43% · max chord 4", for stream
+/// and preview cards.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositionTextStyle {
+    pub font_size: f32,
+    pub color: Color32,
+    pub anchor: OverlayAnchor,
+    pub formatter: SharedFormatter,
+}
+
+impl Default for CompositionTextStyle {
+    fn default() -> Self {
+        Self {
+            font_size: 14.0,
+            color: Color32::WHITE,
+            anchor: OverlayAnchor {
+                relative_to: AnchorTarget::Stage,
+                alignment: egui::Align2::LEFT_TOP,
+                offset: Vec2::new(4.0, 4.0),
+            },
+            formatter: SharedFormatter::default(),
+        }
+    }
+}
+
+/// Accessibility settings, each usable on its own.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccessibilityOptions {
+    /// Turns off motion blur, the camera and column swap animations.
+    pub reduce_motion: bool,
+    /// Black columns, a thicker judgment line and outlined notes and holds.
+    pub high_contrast: bool,
+    /// Smallest note size drawn, in points, whatever the note size and
+    /// column width.
+    pub min_note_px: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnchorTarget {
+    /// Every stage drawn, following the camera.
+    #[default]
+    Stage,
+    /// The whole rect the `Ui` offers.
+    Window,
+}
+
+/// Position of an overlay: `alignment` picks both the point of the target
+/// rect and the side of the overlay placed on it, so `RIGHT_TOP` keeps the
+/// overlay inside the top right corner. `offset` is added after.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OverlayAnchor {
+    pub relative_to: AnchorTarget,
+    pub alignment: egui::Align2,
+    pub offset: Vec2,
+}
+
+impl OverlayAnchor {
+    pub fn resolve(&self, stage: Rect, window: Rect) -> egui::Pos2 {
+        let target = match self.relative_to {
+            AnchorTarget::Stage => stage,
+            AnchorTarget::Window => window,
+        };
+        self.alignment.pos_in_rect(&target) + self.offset
+    }
+}
+
+/// Flash across a column at the judgment line for a press that matched no
+/// note, see `ManiaRenderer::report_ghost_tap`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GhostTapStyle {
+    pub color: Color32,
+    pub thickness: f32,
+    /// Map time over which a flash fades out.
+    pub duration_ms: f64,
+}
+
+impl Default for GhostTapStyle {
+    fn default() -> Self {
+        Self {
+            color: Color32::from_rgb(190, 90, 255),
+            thickness: 3.0,
+            duration_ms: 150.0,
+        }
+    }
+}
+
+/// Key receptor drawn in every column at the judgment line, under the notes.
+#[derive(Clone)]
+pub struct ReceptorStyle {
+    /// `None` uses the note shape. Images are tinted with `color`.
+    pub shape: Option<NoteShape>,
+    pub color: Color32,
+    pub outline: egui::Stroke,
+}
+
+impl Default for ReceptorStyle {
+    fn default() -> Self {
+        Self {
+            shape: None,
+            color: Color32::from_gray(70),
+            outline: egui::Stroke::new(1.5, Color32::from_gray(150)),
+        }
+    }
+}
+
+/// Panels hiding notes where they enter the stage (`sudden`) and as they
+/// reach the judgment line (`hidden`), each a share of the stage height.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LaneCover {
+    pub sudden: f32,
+    pub hidden: f32,
+    pub color: Color32,
+}
+
+impl Default for LaneCover {
+    fn default() -> Self {
+        Self {
+            sudden: 0.25,
+            hidden: 0.0,
+            color: Color32::from_gray(10),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct GhostTap {
+    column: usize,
+    effect: EffectId,
+}
+
+#[derive(Clone)]
+struct TimingReadout {
+    column: usize,
+    offset_ms: f64,
+    effect: EffectId,
+    text: CachedText,
+}
+
+impl TimingReadout {
+    fn text(style: &TimingTextStyle, offset_ms: f64) -> CachedText {
+        CachedText::new(format!("{} ms", style.formatter.signed(offset_ms, 0)))
+    }
+}
+
+type FontAtlas = Weak<egui::mutex::Mutex<egui::epaint::TextureAtlas>>;
+
+// Overlay text laid out once and drawn from its galley until the font or
+// the font atlas changes. egui starts a new atlas when the scale factor
+// changes or the old one fills up, and older galleys point into the old one.
+// The atlas is held weakly, so its address isn't reused while cached.
+#[derive(Clone, Default)]
+struct CachedText {
+    text: String,
+    layout: Option<(egui::FontId, FontAtlas, Arc<egui::Galley>)>,
+}
+
+impl CachedText {
+    fn new(text: String) -> Self {
+        Self { text, layout: None }
+    }
+
+    fn galley(&mut self, painter: &egui::Painter, font: egui::FontId) -> Arc<egui::Galley> {
+        let atlas = Arc::downgrade(&painter.fonts(|fonts| fonts.texture_atlas()));
+        match &self.layout {
+            Some((laid_out, old_atlas, galley))
+                if *laid_out == font && old_atlas.ptr_eq(&atlas) =>
+            {
+                galley.clone()
+            }
+            _ => {
+                let galley =
+                    painter.layout_no_wrap(self.text.clone(), font.clone(), Color32::PLACEHOLDER);
+                self.layout = Some((font, atlas, galley.clone()));
+                galley
+            }
+        }
+    }
+}
+
+// Box of about the size of `text`, drawn instead of it in deterministic mode
+fn draw_text_box(
+    painter: &egui::Painter,
+    pos: egui::Pos2,
+    align: egui::Align2,
+    text: &str,
+    font: &egui::FontId,
+    color: Color32,
+) {
+    let size = Vec2::new(
+        text.chars().count() as f32 * font.size * DETERMINISTIC_GLYPH_WIDTH,
+        font.size,
+    );
+    painter.rect_filled(align.anchor_size(pos, size), 0.0, color);
+}
+
+#[derive(Clone)]
+pub enum Letterbox {
+    Color(Color32),
+    Image(egui::Image<'static>),
+}
+
+impl Letterbox {
+    /// Background image from `provider`, `None` while `key` isn't available.
+    pub fn from_provider(
+        provider: &dyn ImageProvider,
+        ctx: &egui::Context,
+        key: &str,
+    ) -> Option<Self> {
+        let texture = provider.resolve(ctx, key)?;
+        Some(Letterbox::Image(texture.to_image()))
+    }
+}
+
+/// What happens to per-column settings (pressed columns, column visibility,
+/// hand split) when a map with another keycount is loaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeycountChangePolicy {
+    /// Settings are truncated, or extended with defaults. A hand split of
+    /// the wrong size is derived again with `HandSplit::by_column`.
+    #[default]
+    Adapt,
+    /// Every per-column setting goes back to its default.
+    Reset,
+    /// Maps with another keycount are refused.
+    Reject,
+}
+
+/// Which way notes travel to the judgment line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScrollDirection {
+    /// Notes fall to a judgment line near the bottom.
+    #[default]
+    Down,
+    /// Notes rise to a judgment line near the top.
+    Up,
+}
+
+impl ScrollDirection {
+    // Screen direction notes travel in, along y
+    fn sign(self) -> f32 {
+        match self {
+            ScrollDirection::Down => 1.0,
+            ScrollDirection::Up => -1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LayerOrder {
+    /// Hold bodies first, then every note head on top.
+    #[default]
+    HoldsBelowNotes,
+    NotesBelowHolds,
+    /// Sorted by time so later objects draw on top regardless of kind.
+    Interleaved,
+}
+
+/// Drawing steps of a stage, bottom to top, see `set_layer_hook`. Semi-stable:
+/// layers may be added, the existing ones keep their relative order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    /// Stage background, columns, window bands and judgment line.
+    StageBackground,
+    /// Measure lines, or beat lines in the stage preview.
+    Barlines,
+    /// Under the objects so notes pass over them.
+    Receptors,
+    HoldBodies,
+    /// Notes and hold heads.
+    Notes,
+    /// Timing readouts and the activity meter, on the last stage.
+    Effects,
+    /// Lane covers, see `ManiaRenderer::set_lane_cover`.
+    Covers,
+    /// Composition text and the debug overlay, after every stage.
+    Overlays,
+}
+
+impl Layer {
+    /// Every layer, in drawing order.
+    pub const ALL: [Layer; 8] = [
+        Layer::StageBackground,
+        Layer::Barlines,
+        Layer::Receptors,
+        Layer::HoldBodies,
+        Layer::Notes,
+        Layer::Effects,
+        Layer::Covers,
+        Layer::Overlays,
+    ];
+}
+
+/// What a layer hook draws against.
+#[derive(Clone, Copy, Debug)]
+pub struct LayerContext {
+    /// 0 for the only stage, or the original one when comparing.
+    pub stage: usize,
+    /// Columns area of the stage.
+    pub stage_rect: Rect,
+    /// Scroll, clock time and playfield of the stage in this frame.
+    pub transform: FrameTransform,
+}
+
+/// Columns of a stage as laid out in a frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnLayout {
+    /// Left edge of the first column.
+    pub x: f32,
+    /// Width of one column, scale and stretch included.
+    pub column_width: f32,
+    pub keycount: usize,
+}
+
+impl ColumnLayout {
+    /// Left edge of `column`.
+    pub fn column_x(&self, column: usize) -> f32 {
+        self.x + column as f32 * self.column_width
+    }
+
+    pub fn column_center_x(&self, column: usize) -> f32 {
+        self.column_x(column) + self.column_width / 2.0
+    }
+}
+
+/// Math a stage was drawn with in a frame, to line drawings up with its
+/// notes. Positions are those of the shapes before the stage tilt, and
+/// ignore column swaps, drags, per-column hit offsets and the minimum visual
+/// gap between notes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameTransform {
+    pub scroll: ScrollTransform,
+    pub columns: ColumnLayout,
+    /// Clock time of the frame.
+    pub current_time: f64,
+    /// Playback rate at the judgment line.
+    pub rate: f64,
+    /// Notes are drawn centered on `y_for` their time, which is this on
+    /// the judgment line.
+    pub judgment_line_y: f32,
+    pub direction: ScrollDirection,
+    /// Height notes travel over in one scroll time.
+    pub height: f32,
+    /// Every stage, as returned by `render_in`.
+    pub playfield: Rect,
+}
+
+impl FrameTransform {
+    /// Y of an object at `map_time`, the same value its shapes were drawn at.
+    pub fn y_for(&self, map_time: f64) -> f32 {
+        let distance = self.scroll.distance(map_time, self.current_time) * self.height;
+        self.judgment_line_y - distance * self.direction.sign()
+    }
+
+    /// Position of a note of `column` at `map_time`.
+    pub fn note_pos(&self, column: usize, map_time: f64) -> egui::Pos2 {
+        egui::pos2(self.columns.column_center_x(column), self.y_for(map_time))
+    }
+
+    /// Map time on the judgment line.
+    pub fn line_time(&self) -> f64 {
+        self.scroll.map_time_at_line(self.current_time)
+    }
+}
+
+/// Draws over a layer once it is done, see `ManiaRenderer::set_layer_hook`.
+pub type LayerHook = Arc<dyn Fn(&egui::Painter, &LayerContext) + Send + Sync>;
+
+/// How the stage fills a rect handed to `render_in`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitPolicy {
+    /// Keeps the configured sizes and clips whatever doesn't fit.
+    #[default]
+    Clip,
+    /// Scales the stage down or up until it fits entirely.
+    ScaleToFit,
+    /// Scales the stage until it covers the rect, clipping the overflow.
+    ScaleToFill,
+}
+
+impl FitPolicy {
+    /// Scale applied to a stage of `size` drawn into `rect`.
+    pub fn scale(self, size: Vec2, rect: Rect) -> f32 {
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return 1.0;
+        }
+        let (x, y) = (rect.width() / size.x, rect.height() / size.y);
+        match self {
+            FitPolicy::Clip => 1.0,
+            FitPolicy::ScaleToFit => x.min(y),
+            FitPolicy::ScaleToFill => x.max(y),
+        }
+    }
+}
+
+/// Time mapping shared by the renderer and timing queries: a note crosses
+/// the judgment line when the player clock reaches
+/// `clock_time(map_time) + scroll_time_ms`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollTransform {
+    pub speed: f64,
+    pub scroll_time_ms: f32,
+    /// Replaces `speed` when set.
+    pub rate_ramp: Option<RateRamp>,
+}
+
+impl ScrollTransform {
+    /// Clock time at which playback reaches `map_time`.
+    pub fn clock_time(&self, map_time: f64) -> f64 {
+        match &self.rate_ramp {
+            Some(ramp) => ramp.clock_time(map_time),
+            None => map_time / self.speed,
+        }
+    }
+
+    /// Inverse of `clock_time`.
+    pub fn map_time(&self, clock_time: f64) -> f64 {
+        match &self.rate_ramp {
+            Some(ramp) => ramp.map_time(clock_time),
+            None => clock_time * self.speed,
+        }
+    }
+
+    /// Playback rate at `map_time`.
+    pub fn rate_at(&self, map_time: f64) -> f64 {
+        match &self.rate_ramp {
+            Some(ramp) => ramp.rate_at(map_time),
+            None => self.speed,
+        }
+    }
+
+    pub fn crossing_time(&self, map_time: f64) -> f64 {
+        self.clock_time(map_time) + self.scroll_time_ms as f64
+    }
+
+    /// Inverse of `crossing_time`: map time sitting on the judgment line.
+    pub fn map_time_at_line(&self, current_time: f64) -> f64 {
+        self.map_time(current_time - self.scroll_time_ms as f64)
+    }
+
+    /// Distance above the judgment line, in stage heights.
+    pub fn distance(&self, map_time: f64, current_time: f64) -> f32 {
+        (self.crossing_time(map_time) - current_time) as f32 / self.scroll_time_ms
+    }
+}
+
+// Per-frame values shared by every object drawn in that frame
+struct Frame {
+    position: egui::Pos2,
+    judgment_line_y: f32,
+    total_height: f32,
+    current_time: f64,
+    scroll: ScrollTransform,
+    direction: ScrollDirection,
+    // Map times at the top and bottom edges of the visible range
+    visible_start: f64,
+    visible_end: f64,
+    keycount: usize,
+    blur_travel: Option<f32>,
+    // (object index, y) of notes moved by the minimum visual gap, by index
+    nudged_y: Vec<(usize, f32)>,
+    // Image notes are drawn as circles, see `set_image_note_limit`
+    degraded: bool,
+}
+
+impl Frame {
+    // Objects are drawn between the stage edge they come from and the
+    // judgment line
+    fn spawn_y(&self) -> f32 {
+        match self.direction {
+            ScrollDirection::Down => self.position.y,
+            ScrollDirection::Up => self.position.y + self.total_height,
+        }
+    }
+
+    // Same expression as `FrameTransform::y_for`
+    fn y_for(&self, map_time: f64) -> f32 {
+        let distance = self.scroll.distance(map_time, self.current_time) * self.total_height;
+        self.judgment_line_y - distance * self.direction.sign()
+    }
+
+    // `y` is further along the way notes travel than `edge`
+    fn is_beyond(&self, y: f32, edge: f32) -> bool {
+        (y - edge) * self.direction.sign() > 0.0
+    }
+
+    fn is_hold_visible(&self, h: &HitObject) -> bool {
+        let HitObjectKind::Hold(hold) = &h.kind else {
+            return false;
+        };
+        let (visible_start, visible_end) = (self.visible_start, self.visible_end);
+        let obj_time = h.start_time;
+        let obj_end_time = h.start_time + hold.duration;
+        // Show if either start or end is visible, or the note spans the visible area
+        (obj_time >= visible_start && obj_time <= visible_end)
+            || (obj_end_time >= visible_start && obj_end_time <= visible_end)
+            || (obj_time <= visible_start && obj_end_time >= visible_end)
+    }
+
+    fn is_note_visible(&self, h: &HitObject) -> bool {
+        h.start_time >= self.visible_start && h.start_time <= self.visible_end
+    }
+
+    fn note_y(&self, index: usize, h: &HitObject) -> f32 {
+        match self.nudged_y.binary_search_by_key(&index, |(i, _)| *i) {
+            Ok(i) => self.nudged_y[i].1,
+            Err(_) => self.y_for(h.start_time),
+        }
+    }
+}
+
+// How `paint_shape` fills a note shape
+#[derive(Clone, Copy)]
+struct ShapePaint {
+    color: Color32,
+    tint: Color32,
+    outline: egui::Stroke,
+}
+
+// Visible stripes of a hold body, on the stack unless there are many
+type Stripes = SmallVec<[Rect; 16]>;
+
+// Parts of a hold as drawn, each clipped between the stage edge the hold
+// comes from and its column's judgment line
+struct HoldParts {
+    column: usize,
+    // Map time of the head
+    head: f64,
+    // Without a body, ladders draw rungs instead
+    body: Option<Rect>,
+    stripes: Stripes,
+    dropped: Option<Rect>,
+    cap: Option<Rect>,
+    broken: bool,
+}
+
+// Spacing and breakage of the rungs of a ladder hold
+#[derive(Clone, Copy)]
+struct Ladder {
+    divisor: u32,
+    shape_scale: f32,
+    break_time: Option<f64>,
+}
+
+// Colors resolved for a single note. Precedence, highest first: snap
+// coloring (the head, and the body and cap with `SnapColoring::holds`),
+// hand coloring, then the NoteStyle colors.
+struct NoteColors {
+    head: Color32,
+    hold_body: Color32,
+    hold_cap: Color32,
+}
+
+// An image provider that panicked, skipped until replaced
+struct ImagePanic {
+    images: Arc<dyn ImageProvider>,
+    // Until `take_image_panic`
+    message: Option<String>,
+}
+
+pub struct ManiaRenderer {
+    column_width: f32,
+    note_size: f32,
+    // Playback rate at the judgment line this frame
+    speed: f64,
+    rate_ramp: Option<RateRamp>,
+    height: f32,
+    note_style: NoteStyle,
+    hand_coloring: Option<HandColoring>,
+    snap_coloring: Option<SnapColoring>,
+    hold_body_pattern: HoldBodyPattern,
+    hold_body_style: HoldBodyStyle,
+    // (time, beat length) of each timing point, for beat-based stripes
+    beat_lengths: Vec<(f64, f64)>,
+    measures: Vec<Measures>,
+    barlines: Option<BarlineStyle>,
+    overall_difficulty: f32,
+    window_mode: WindowMode,
+    window_bands: Option<WindowBandStyle>,
+    pressed: Vec<bool>,
+    letterbox: Option<Letterbox>,
+    images: Arc<dyn ImageProvider>,
+    image_panic: Mutex<Option<ImagePanic>>,
+    background_events: Vec<BackgroundEvent>,
+    // (start, end) of each break, in map time
+    background_breaks: Vec<(f64, f64)>,
+    padding: egui::Margin,
+    clip_margin: egui::Margin,
+    min_render_size: Vec2,
+    hit_position: f32,
+    // Columns area of the last rendered frame, for hit-testing
+    stage_rect: Option<Rect>,
+    frame_transform: Option<FrameTransform>,
+    // Buffers kept across frames so steady frames don't allocate
+    scratch_contexts: Vec<LayerContext>,
+    scratch_nudged: Vec<(usize, f32)>,
+    scratch_visible: Vec<(usize, bool)>,
+    motion_blur: Option<MotionBlur>,
+    last_frame_time: Option<f64>,
+    layer_order: LayerOrder,
+    scroll_direction: ScrollDirection,
+    layer_hooks: BTreeMap<Layer, LayerHook>,
+    // Hooks that panicked, skipped until `take_hook_panics` removes them
+    hook_panics: Mutex<Vec<(Layer, LayerHook, String)>>,
+    effect_clock: EffectClock,
+    timing_text: Option<TimingTextStyle>,
+    timing_readouts: Vec<TimingReadout>,
+    ghost_tap_style: Option<GhostTapStyle>,
+    ghost_taps: Vec<GhostTap>,
+    receptors: Option<ReceptorStyle>,
+    lane_cover: Option<LaneCover>,
+    activity_meter: Option<ActivityMeterStyle>,
+    column_nps: Vec<f32>,
+    composition_text: Option<CompositionTextStyle>,
+    // Formatted on the first frame after the text or composition changes
+    composition_label: Option<CachedText>,
+    composition: Composition,
+    hit_error_scatter: Option<ScatterStyle>,
+    // Every report, for the scatter overlay and the player's replays and stats
+    judgment_log: JudgmentLog,
+    debug_overlay: bool,
+    deterministic: bool,
+    // `column_stretch` of the keycount being drawn
+    column_stretch: f32,
+    scrubbing: bool,
+    column_visibility: Vec<bool>,
+    column_note_sizes: Vec<f32>,
+    column_shapes: Vec<NoteShape>,
+    column_hit_offsets: Vec<f32>,
+    stage_tilt: Option<f32>,
+    note_overflow: bool,
+    accessibility: AccessibilityOptions,
+    image_note_limit: Option<usize>,
+    frame_stats: FrameStats,
+    playfield: Option<Rect>,
+    fit_policy: FitPolicy,
+    camera: Option<Camera>,
+    min_visual_gap: Option<f32>,
+    // Map time each dropped hold was released at, by object index
+    hold_breaks: BTreeMap<usize, f64>,
+    // Objects flagged with a marker, by index
+    marked: Vec<bool>,
+    // Horizontal shift of each column's objects, in columns
+    column_offsets: Vec<f32>,
+    // Scale of the last rendered frame, 1.0 outside of `render_in`
+    scale: f32,
+}
+
+impl ManiaRenderer {
+    pub fn with_sizes(column_width: f32, note_size: f32, height: f32) -> Self {
+        Self {
+            column_width,
+            note_size,
+            speed: 1.0,
+            rate_ramp: None,
+            height,
+            note_style: NoteStyle::default(),
+            hand_coloring: None,
+            snap_coloring: None,
+            hold_body_pattern: HoldBodyPattern::Solid,
+            hold_body_style: HoldBodyStyle::Body,
+            beat_lengths: Vec::new(),
+            measures: Vec::new(),
+            barlines: None,
+            overall_difficulty: 5.0,
+            window_mode: WindowMode::default(),
+            window_bands: None,
+            pressed: Vec::new(),
+            letterbox: None,
+            images: Arc::new(UriImages),
+            image_panic: Mutex::new(None),
+            background_events: Vec::new(),
+            background_breaks: Vec::new(),
+            padding: egui::Margin::ZERO,
+            clip_margin: egui::Margin::ZERO,
+            min_render_size: DEFAULT_MIN_RENDER_SIZE,
+            hit_position: DEFAULT_HIT_POSITION,
+            stage_rect: None,
+            frame_transform: None,
+            scratch_contexts: Vec::new(),
+            scratch_nudged: Vec::new(),
+            scratch_visible: Vec::new(),
+            motion_blur: None,
+            last_frame_time: None,
+            layer_order: LayerOrder::default(),
+            scroll_direction: ScrollDirection::default(),
+            layer_hooks: BTreeMap::new(),
+            hook_panics: Mutex::new(Vec::new()),
+            effect_clock: EffectClock::default(),
+            timing_text: None,
+            timing_readouts: Vec::new(),
+            ghost_tap_style: Some(GhostTapStyle::default()),
+            receptors: None,
+            lane_cover: None,
+            ghost_taps: Vec::new(),
+            activity_meter: None,
+            column_nps: Vec::new(),
+            composition_text: None,
+            composition_label: None,
+            hit_error_scatter: None,
+            judgment_log: JudgmentLog::default(),
+            composition: Composition::default(),
+            debug_overlay: false,
+            deterministic: false,
+            column_stretch: 1.0,
+            scrubbing: false,
+            column_visibility: Vec::new(),
+            column_note_sizes: Vec::new(),
+            column_shapes: Vec::new(),
+            column_hit_offsets: Vec::new(),
+            stage_tilt: None,
+            note_overflow: false,
+            accessibility: AccessibilityOptions::default(),
+            image_note_limit: None,
+            frame_stats: FrameStats::default(),
+            playfield: None,
+            fit_policy: FitPolicy::default(),
+            camera: None,
+            min_visual_gap: None,
+            hold_breaks: BTreeMap::new(),
+            marked: Vec::new(),
+            column_offsets: Vec::new(),
+            scale: 1.0,
+        }
+    }
+
+    pub fn set_note_style(&mut self, style: NoteStyle) {
+        self.note_style = style;
+    }
+
+    pub fn note_style(&self) -> &NoteStyle {
+        &self.note_style
+    }
+
+    /// Colors notes by the hand playing their column. Holds follow the color
+    /// of their head. Hand colors take precedence over `NoteStyle`, and snap
+    /// coloring over both, see `set_snap_coloring`.
+    pub fn set_hand_coloring(&mut self, coloring: Option<HandColoring>) {
+        self.hand_coloring = coloring;
+    }
+
+    /// Colors note heads by beat snap, over hand coloring. `None`, the
+    /// default, turns it off.
+    pub fn set_snap_coloring(&mut self, coloring: Option<SnapColoring>) {
+        self.snap_coloring = coloring;
+    }
+
+    pub fn set_overall_difficulty(&mut self, od: f32) {
+        self.overall_difficulty = od;
+    }
+
+    pub fn set_window_mode(&mut self, mode: WindowMode) {
+        self.window_mode = mode;
+    }
+
+    /// Windows hits are judged with at the current OD and mode.
+    pub fn windows(&self) -> Windows {
+        mania_windows(self.overall_difficulty, self.window_mode)
+    }
+
+    /// Windows LN releases are judged with at the current OD and mode.
+    pub fn release_windows(&self) -> Windows {
+        self.windows().release(self.window_mode)
+    }
+
+    pub fn set_window_bands(&mut self, style: Option<WindowBandStyle>) {
+        self.window_bands = style;
+    }
+
+    /// Marks a column as held by the host's own input handling. Pressed
+    /// columns get a lighter background until released or `clear_pressed`.
+    /// Columns past `MAX_KEYCOUNT` are ignored.
+    pub fn set_column_pressed(&mut self, column: usize, pressed: bool) {
+        if column >= MAX_KEYCOUNT {
+            return;
+        }
+        if column >= self.pressed.len() {
+            if !pressed {
+                return;
+            }
+            self.pressed.resize(column + 1, false);
+        }
+        self.pressed[column] = pressed;
+    }
+
+    pub fn clear_pressed(&mut self) {
+        self.pressed.clear();
+    }
+
+    /// Fits per-column settings to `keycount` according to `policy`.
+    /// `Reject` is handled by the caller and leaves everything as is.
+    pub fn resize_columns(&mut self, keycount: usize, policy: KeycountChangePolicy) {
+        match policy {
+            KeycountChangePolicy::Adapt => {
+                self.pressed.truncate(keycount);
+                self.column_visibility.truncate(keycount);
+                self.column_note_sizes.truncate(keycount);
+                self.column_shapes.truncate(keycount);
+                self.column_hit_offsets.truncate(keycount);
+            }
+            KeycountChangePolicy::Reset => {
+                self.pressed.clear();
+                self.column_visibility.clear();
+                self.column_note_sizes.clear();
+                self.column_shapes.clear();
+                self.column_hit_offsets.clear();
+            }
+            KeycountChangePolicy::Reject => return,
+        }
+        if let Some(coloring) = &mut self.hand_coloring {
+            let fits = coloring.split.len() == keycount;
+            if !fits || policy == KeycountChangePolicy::Reset {
+                coloring.split = HandSplit::by_column(keycount);
+            }
+        }
+        self.column_offsets.clear();
+    }
+
+    pub fn is_column_pressed(&self, column: usize) -> bool {
+        self.pressed.get(column).copied().unwrap_or(false)
+    }
+
+    /// Visibility of each displayed column, i.e. after the arrangement is
+    /// applied. Hidden columns keep their place in the stage, dimmed and
+    /// without objects. Columns past the end of `visible` are shown.
+    pub fn set_column_visibility(&mut self, visible: Vec<bool>) {
+        self.column_visibility = visible;
+    }
+
+    pub fn is_column_visible(&self, column: usize) -> bool {
+        self.column_visibility.get(column).copied().unwrap_or(true)
+    }
+
+    /// Multipliers on the note size of each displayed column, e.g. a smaller
+    /// scratch lane note. Columns past the end of `sizes` use 1.0.
+    pub fn set_column_note_sizes(&mut self, sizes: Vec<f32>) {
+        self.column_note_sizes = sizes;
+    }
+
+    /// Shape of each displayed column's notes, e.g. a skin's per-column
+    /// images. Columns past the end of `shapes` use `NoteStyle::shape`.
+    pub fn set_column_shapes(&mut self, shapes: Vec<NoteShape>) {
+        self.column_shapes = shapes;
+    }
+
+    fn column_shape(&self, column: usize) -> &NoteShape {
+        self.column_shapes
+            .get(column)
+            .unwrap_or(&self.note_style.shape)
+    }
+
+    /// Lets column note size multipliers grow notes wider than their column.
+    pub fn set_note_overflow(&mut self, allow: bool) {
+        self.note_overflow = allow;
+    }
+
+    pub fn set_accessibility(&mut self, options: AccessibilityOptions) {
+        self.accessibility = options;
+    }
+
+    pub fn accessibility(&self) -> AccessibilityOptions {
+        self.accessibility
+    }
+
+    /// Fills the space the `Ui` offers outside the stage frame.
+    pub fn set_letterbox(&mut self, letterbox: Option<Letterbox>) {
+        self.letterbox = letterbox;
+    }
+
+    /// Where every image is fetched from: `NoteShape::Keyed` notes, and the
+    /// storyboard backgrounds keyed by `BackgroundEvent::file`, drawn dimmed
+    /// except during breaks. The letterbox shows until the first background
+    /// is available. `UriImages` by default. A provider that panics is
+    /// skipped from then on, its images drawn as if missing.
+    pub fn set_image_provider(&mut self, provider: Arc<dyn ImageProvider>) {
+        self.images = provider;
+    }
+
+    pub fn image_provider(&self) -> &Arc<dyn ImageProvider> {
+        &self.images
+    }
+
+    pub(crate) fn set_background_events(
+        &mut self,
+        events: Vec<BackgroundEvent>,
+        breaks: Vec<(f64, f64)>,
+    ) {
+        self.background_events = events;
+        self.background_breaks = breaks;
+    }
+
+    // Storyboard background at `map_time`, crossfading from the previous
+    // one. `false` if none is available yet.
+    fn draw_background_events(&self, ui: &egui::Ui, map_time: f64) -> bool {
+        let shown = self
+            .background_events
+            .partition_point(|event| event.time_ms <= map_time);
+        let Some(index) = shown.checked_sub(1) else {
+            return false;
+        };
+        let event = &self.background_events[index];
+        let fade = if event.fade_ms > 0.0 {
+            ((map_time - event.time_ms) / event.fade_ms).clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        };
+        let gray = (255.0 * self.background_brightness(map_time)) as u8;
+        let paint = |file: &str, opacity: f32| {
+            let Some(texture) = self.resolve_image(ui.ctx(), file) else {
+                return false;
+            };
+            let tint = Color32::from_gray(gray).gamma_multiply(opacity);
+            texture.to_image().tint(tint).paint_at(ui, ui.max_rect());
+            true
+        };
+
+        let previous = index.checked_sub(1).filter(|_| fade < 1.0);
+        let previous_drawn =
+            previous.is_some_and(|previous| paint(&self.background_events[previous].file, 1.0));
+        paint(&event.file, fade) || previous_drawn
+    }
+
+    // Full brightness during breaks, faded in and out at their edges
+    fn background_brightness(&self, map_time: f64) -> f32 {
+        let lit = self
+            .background_breaks
+            .iter()
+            .map(|(start, end)| {
+                let faded_in = (map_time - start) / BREAK_FADE_MS;
+                let faded_out = (end - map_time) / BREAK_FADE_MS;
+                faded_in.min(faded_out).clamp(0.0, 1.0)
+            })
+            .fold(0.0, f64::max) as f32;
+        BACKGROUND_BRIGHTNESS + (1.0 - BACKGROUND_BRIGHTNESS) * lit
+    }
+
+    /// Space inside the stage frame around the columns.
+    pub fn set_playfield_padding(&mut self, padding: egui::Margin) {
+        self.padding = padding;
+    }
+
+    /// Lets the stage draw this far outside its clip rect, e.g. so outlines
+    /// of the edge columns aren't cut when the stage fills it. The clip rect
+    /// is the rect of `render_in`, and the `Ui`'s own for `render_at` and
+    /// the stage preview. The letterbox still only covers the rect. Zero by
+    /// default.
+    pub fn set_clip_margin(&mut self, margin: egui::Margin) {
+        self.clip_margin = margin;
+    }
+
+    /// Area of `column` in the last rendered frame, ignoring the stage tilt.
+    pub fn column_rect(&self, column: usize) -> Option<Rect> {
+        let stage_rect = self.stage_rect?;
+        let x = stage_rect.min.x + column as f32 * self.column_width();
+        (x < stage_rect.max.x)
+            .then(|| Rect::from_x_y_ranges(x..=x + self.column_width(), stage_rect.y_range()))
+    }
+
+    /// Column under `pos` in the last rendered frame. Padding maps to no column.
+    pub fn column_at(&self, pos: egui::Pos2) -> Option<usize> {
+        let pos = self.untilt(pos);
+        let stage_rect = self.stage_rect?;
+        if !stage_rect.contains(pos) || self.column_width() <= 0.0 {
+            return None;
+        }
+        let keycount = (stage_rect.width() / self.column_width()).round() as usize;
+        let column = ((pos.x - stage_rect.min.x) / self.column_width()) as usize;
+        Some(column.min(keycount.saturating_sub(1)))
+    }
+
+    /// Narrows the stage toward its top, as if tilted away from the viewer:
+    /// at the top, widths are `1 - tilt` times their width at the bottom.
+    /// Clamped to `0.0..=MAX_STAGE_TILT`; `None` or 0 draws it flat.
+    pub fn set_stage_tilt(&mut self, tilt: Option<f32>) {
+        self.stage_tilt = tilt
+            .filter(|tilt| *tilt > 0.0)
+            .map(|tilt| tilt.min(MAX_STAGE_TILT));
+    }
+
+    // Width factor of the tilted stage at height `y`
+    fn tilt_factor(&self, y: f32) -> Option<(f32, f32)> {
+        let (tilt, playfield) = self.stage_tilt.zip(self.playfield)?;
+        let height = (playfield.max.y - y) / playfield.height().max(1.0);
+        Some((playfield.center().x, 1.0 - tilt * height.clamp(0.0, 1.0)))
+    }
+
+    // Position on the flat stage drawn at `pos` on the tilted one
+    fn untilt(&self, pos: egui::Pos2) -> egui::Pos2 {
+        match self.tilt_factor(pos.y) {
+            Some((center_x, factor)) => pos2(center_x + (pos.x - center_x) / factor, pos.y),
+            None => pos,
+        }
+    }
+
+    // Turns the shapes drawn since `start` into meshes narrowed toward the
+    // top. Only x moves, so horizontal edges stay horizontal and rects
+    // become trapezoids.
+    fn tilt_shapes(&self, ui: &egui::Ui, start: egui::layers::ShapeIdx) {
+        if self.stage_tilt.is_none() {
+            return;
+        }
+        let ctx = ui.ctx();
+        let mut tessellator = egui::epaint::Tessellator::new(
+            ctx.pixels_per_point(),
+            ctx.tessellation_options(|options| *options),
+            ctx.fonts(|fonts| fonts.font_image_size()),
+            Vec::new(),
+        );
+        ctx.graphics_mut(|graphics| {
+            let shapes = graphics.entry(ui.layer_id());
+            for idx in start.0..shapes.next_idx().0 {
+                shapes.mutate_shape(egui::layers::ShapeIdx(idx), |clipped| {
+                    if matches!(clipped.shape, egui::Shape::Callback(_)) {
+                        return;
+                    }
+                    let mut mesh = egui::Mesh::default();
+                    let shape = std::mem::replace(&mut clipped.shape, egui::Shape::Noop);
+                    tessellator.tessellate_shape(shape, &mut mesh);
+                    for vertex in &mut mesh.vertices {
+                        if let Some((center_x, factor)) = self.tilt_factor(vertex.pos.y) {
+                            vertex.pos.x = center_x + (vertex.pos.x - center_x) * factor;
+                        }
+                    }
+                    clipped.shape = egui::Shape::mesh(mesh);
+                });
+            }
+        });
+    }
+
+    /// Approximates motion blur at fast scroll speeds by drawing fading copies
+    /// of each note along the distance it travelled since the last frame.
+    pub fn set_motion_blur(&mut self, motion_blur: Option<MotionBlur>) {
+        self.motion_blur = motion_blur;
+    }
+
+    /// Forgets the previous frame time so a seek doesn't get smeared.
+    pub fn notify_seek(&mut self) {
+        self.last_frame_time = None;
+        self.clear_timing_readouts();
+        self.clear_ghost_taps();
+    }
+
+    /// Above `limit` visible notes in a stage, image notes are drawn as
+    /// circles of the note color for that frame, to keep dense sections
+    /// smooth. `None` never falls back.
+    pub fn set_image_note_limit(&mut self, limit: Option<usize>) {
+        self.image_note_limit = limit;
+    }
+
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats
+    }
+
+    /// Starts loading the note image, caches the glyphs of the enabled
+    /// overlays and draws a frame of notes in a scratch context, work the
+    /// first frame would otherwise do. Optional, and cheap to call again.
+    /// Glyphs need the fonts `ctx` gets from its first pass, so before that
+    /// they are left to the first frame.
+    pub fn warm_up(&self, ctx: &egui::Context, keycount: usize) -> WarmUpTimings {
+        let start = Instant::now();
+        for shape in std::iter::once(&self.note_style.shape).chain(&self.column_shapes) {
+            match shape {
+                // Pending or failed loads are retried by the first paint
+                NoteShape::Image(image) => {
+                    let _ = image.load_for_size(ctx, Vec2::splat(self.note_size));
+                }
+                NoteShape::Keyed(key) => {
+                    let _ = self.resolve_image(ctx, key);
+                }
+                _ => {}
+            }
+        }
+        let textures = start.elapsed();
+
+        let start = Instant::now();
+        let mut glyphs = Vec::new();
+        if let Some(style) = &self.timing_text {
+            let font = egui::FontId::proportional(style.font_size);
+            glyphs.push((OVERLAY_GLYPHS, font));
+        }
+        if let Some(style) = &self.composition_text {
+            let font = egui::FontId::proportional(style.font_size);
+            glyphs.push((COMPOSITION_GLYPHS, font));
+        }
+        if self.debug_overlay {
+            glyphs.push((OVERLAY_GLYPHS, egui::FontId::monospace(10.0)));
+        }
+        if ctx.cumulative_pass_nr() > 0 {
+            ctx.fonts(|f| {
+                for (text, font) in glyphs {
+                    f.layout_no_wrap(text.to_owned(), font, Color32::WHITE);
+                }
+            });
+        }
+        let glyphs = start.elapsed();
+
+        let start = Instant::now();
+        self.draw_scratch_frame(keycount);
+        WarmUpTimings {
+            textures,
+            glyphs,
+            frame: start.elapsed(),
+        }
+    }
+
+    // A note per column, drawn and tessellated in a context of its own so the
+    // host's frame is left alone
+    fn draw_scratch_frame(&self, keycount: usize) {
+        let scratch = egui::Context::default();
+        let output = scratch.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                for column in 0..keycount {
+                    let x = (column as f32 + 0.5) * self.column_width;
+                    let color = self.note_colors(column, None).head;
+                    let size = self.column_note_size(column);
+                    self.draw_note_sized(ui, column, x, self.height / 2.0, color, size, 1.0, false);
+                }
+            });
+        });
+        let _ = scratch.tessellate(output.shapes, output.pixels_per_point);
+    }
+
+    /// Screen rect of every stage drawn by the last `render*` call.
+    pub fn playfield_rect(&self) -> Option<Rect> {
+        self.playfield
+    }
+
+    fn degrades_images(&self, visible_notes: usize) -> bool {
+        let Some(limit) = self.image_note_limit else {
+            return false;
+        };
+        let image = |shape: &NoteShape| matches!(shape, NoteShape::Image(_) | NoteShape::Keyed(_));
+        let images = image(&self.note_style.shape) || self.column_shapes.iter().any(image);
+        images && visible_notes > limit
+    }
+
+    /// While set, e.g. as a seek slider is dragged, frames jump around: no
+    /// motion blur is drawn and reported hits spawn no readout.
+    pub fn set_scrub_hint(&mut self, scrubbing: bool) {
+        self.scrubbing = scrubbing;
+        if scrubbing {
+            self.notify_seek();
+        }
+    }
+
+    // Pixels a note travelled since the previous frame, if it should be blurred
+    fn blur_travel(&mut self, current_time: f64, scroll_time_ms: f32) -> Option<f32> {
+        if self.scrubbing || self.accessibility.reduce_motion {
+            return None;
+        }
+        let last_frame_time = self.last_frame_time.replace(current_time)?;
+        self.motion_blur?;
+        let delta = current_time - last_frame_time;
+        if delta <= 0.0 || delta > MAX_BLUR_FRAME_MS {
+            return None;
+        }
+        Some(delta as f32 / scroll_time_ms * self.height())
+    }
+
+    /// Map-time clock every visual effect must be timed with. Hosts can
+    /// schedule their own effects on it too.
+    pub fn effect_clock(&self) -> &EffectClock {
+        &self.effect_clock
+    }
+
+    pub fn effect_clock_mut(&mut self) -> &mut EffectClock {
+        &mut self.effect_clock
+    }
+
+    pub fn set_layer_order(&mut self, layer_order: LayerOrder) {
+        self.layer_order = layer_order;
+    }
+
+    /// Upscroll draws the stage flipped vertically, judgment line included.
+    pub fn set_scroll_direction(&mut self, direction: ScrollDirection) {
+        self.scroll_direction = direction;
+    }
+
+    pub fn scroll_direction(&self) -> ScrollDirection {
+        self.scroll_direction
+    }
+
+    /// Calls `hook` right after `layer` is drawn, in every stage, replacing
+    /// the previous hook of that layer. Hooks paint on the stage's layer,
+    /// under whatever the later layers draw, and are tilted with the stage
+    /// except for `Overlays`.
+    pub fn set_layer_hook(&mut self, layer: Layer, hook: LayerHook) {
+        self.layer_hooks.insert(layer, hook);
+    }
+
+    pub fn remove_layer_hook(&mut self, layer: Layer) {
+        self.layer_hooks.remove(&layer);
+    }
+
+    /// Layers whose hook panicked since the last call, with the panic
+    /// message. Those hooks were skipped for the rest of the frame and are
+    /// removed now. `Player` turns them into `Event::CallbackPanicked`.
+    pub fn take_hook_panics(&mut self) -> Vec<(Layer, String)> {
+        let panics = self
+            .hook_panics
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner());
+        std::mem::take(panics)
+            .into_iter()
+            .map(|(layer, hook, message)| {
+                // Unless it was replaced in the meantime
+                let current = self.layer_hooks.get(&layer);
+                if current.is_some_and(|h| Arc::ptr_eq(h, &hook)) {
+                    self.layer_hooks.remove(&layer);
+                }
+                (layer, message)
+            })
+            .collect()
+    }
+
+    /// Message of the image provider's panic, once. The provider isn't
+    /// called again until `set_image_provider` replaces it.
+    pub fn take_image_panic(&self) -> Option<String> {
+        let mut panic = self.image_panic.lock().unwrap_or_else(|e| e.into_inner());
+        panic.as_mut().and_then(|panic| panic.message.take())
+    }
+
+    /// Messages of the text overlay formatters that panicked since the last
+    /// call. They format with `PlainFormatter` from then on.
+    pub fn take_formatter_panics(&self) -> Vec<String> {
+        let timing = self.timing_text.as_ref().map(|style| &style.formatter);
+        let composition = self.composition_text.as_ref().map(|style| &style.formatter);
+        timing
+            .into_iter()
+            .chain(composition)
+            .filter_map(SharedFormatter::take_panic)
+            .collect()
+    }
+
+    fn run_layer_hook(&self, ui: &egui::Ui, layer: Layer, context: &LayerContext) {
+        let Some(hook) = self.layer_hooks.get(&layer) else {
+            return;
+        };
+        let mut panics = self.hook_panics.lock().unwrap_or_else(|e| e.into_inner());
+        if panics.iter().any(|panic| Arc::ptr_eq(&panic.1, hook)) {
+            return;
+        }
+        // A panicking hook mustn't take the rest of the frame down with it
+        if let Err(payload) = catch_unwind(AssertUnwindSafe(|| hook(ui.painter(), context))) {
+            panics.push((layer, hook.clone(), panic_message(payload)));
+        }
+    }
+
+    // `self.images`, unless it panicked before. A panic mustn't take the
+    // frame down either
+    fn resolve_image(&self, ctx: &egui::Context, key: &str) -> Option<LoadedTexture> {
+        let mut panic = self.image_panic.lock().unwrap_or_else(|e| e.into_inner());
+        let disabled = panic.as_ref().map(|panic| &panic.images);
+        if disabled.is_some_and(|images| Arc::ptr_eq(images, &self.images)) {
+            return None;
+        }
+        match catch_unwind(AssertUnwindSafe(|| self.images.resolve(ctx, key))) {
+            Ok(texture) => texture,
+            Err(payload) => {
+                *panic = Some(ImagePanic {
+                    images: self.images.clone(),
+                    message: Some(panic_message(payload)),
+                });
+                None
+            }
+        }
+    }
+
+    // Hook context of the stage `frame` was laid out for
+    fn layer_context(&self, frame: &Frame, stage: usize, playfield: Rect) -> LayerContext {
+        LayerContext {
+            stage,
+            stage_rect: self.stage_rect.unwrap_or(playfield),
+            transform: self.transform(frame, playfield),
+        }
+    }
+
+    fn transform(&self, frame: &Frame, playfield: Rect) -> FrameTransform {
+        let line_time = frame.scroll.map_time_at_line(frame.current_time);
+        FrameTransform {
+            scroll: frame.scroll,
+            columns: ColumnLayout {
+                x: frame.position.x,
+                column_width: self.column_width(),
+                keycount: frame.keycount,
+            },
+            current_time: frame.current_time,
+            rate: frame.scroll.rate_at(line_time),
+            judgment_line_y: frame.judgment_line_y,
+            direction: frame.direction,
+            height: frame.total_height,
+            playfield,
+        }
+    }
+
+    /// Math the first stage of the last rendered frame was drawn with.
+    pub fn frame_transform(&self) -> Option<FrameTransform> {
+        self.frame_transform
+    }
+
+    /// What `render*` draws of `hit_objects` as stage `stage` at
+    /// `current_time`, laid out with the scale and position of the last
+    /// render, or at the origin before any. Leaves out effects, overlays,
+    /// motion blur trails, markers and the stage tilt.
+    #[allow(clippy::too_many_arguments)]
+    pub fn frame_description(
+        &self,
+        hit_objects: &[HitObject],
+        current_time: f64,
+        scroll_time_ms: f32,
+        speed: f64,
+        keycount: usize,
+        stage: usize,
+    ) -> FrameDescription {
+        let scroll = ScrollTransform {
+            speed,
+            scroll_time_ms,
+            rate_ramp: self.rate_ramp,
+        };
+        let playfield = self.playfield.unwrap_or_else(|| {
+            let width = self.required_stages_width(keycount, stage + 1);
+            let size = Vec2::new(width, self.required_height()) * self.scale;
+            Rect::from_min_size(pos2(0.0, 0.0), size)
+        });
+        let stage_offset = (self.required_width(keycount) + STAGE_GAP) * self.scale;
+        let position = playfield.min + Vec2::new(stage as f32 * stage_offset, 0.0);
+        let mut frame = self.frame_at(current_time, scroll, keycount, position);
+        let mut nudged_y = Vec::new();
+        self.nudge_notes(&frame, hit_objects, &mut nudged_y);
+        frame.nudged_y = nudged_y;
+
+        let shape = &self.note_style.shape;
+        let note = |column: usize, center: egui::Pos2, size: f32, color: Color32| NoteDescription {
+            column,
+            center: center.into(),
+            size: shape.bounds(size).into(),
+            color: rgba(color),
+            kind: NoteKind::Note,
+            note_index: 0,
+        };
+
+        let receptors = match &self.receptors {
+            Some(style) => {
+                let shape = style.shape.as_ref().unwrap_or(shape);
+                let receptors = self.receptor_layout(&frame);
+                receptors
+                    .map(|(column, center, size)| ReceptorDescription {
+                        column,
+                        center: center.into(),
+                        size: shape.bounds(size).into(),
+                        color: rgba(style.color),
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        let barlines = match self.barlines {
+            Some(_) => self.barline_ys(&frame).collect(),
+            None => Vec::new(),
+        };
+        let covers = match self.lane_cover {
+            Some(cover) => self
+                .lane_cover_rects(&frame, cover)
+                .map(|rect| CoverDescription {
+                    bounds: rect.into(),
+                    color: rgba(cover.color),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let (mut holds, mut notes) = (Vec::new(), Vec::new());
+        let objects = hit_objects.iter().enumerate();
+        for (index, hit_object) in objects.clone().filter(|(_, h)| frame.is_hold_visible(h)) {
+            let Some(parts) = self.hold_parts(&frame, index, hit_object) else {
+                continue;
+            };
+            let column = parts.column;
+            let (body_color, stripe_color) = self.hold_body_colors(column, parts.head);
+            let cap_color = match parts.broken {
+                true => BROKEN_HOLD_COLOR,
+                false => self.note_colors(column, Some(parts.head)).hold_cap,
+            };
+            holds.push(HoldDescription {
+                column,
+                note_index: index,
+                body: parts.body.map(Bounds::from),
+                body_color: rgba(body_color),
+                stripes: parts.stripes.into_iter().map(Bounds::from).collect(),
+                stripe_color: rgba(stripe_color),
+                dropped: parts.dropped.map(Bounds::from),
+                cap: parts.cap.map(Bounds::from),
+                cap_color: rgba(cap_color),
+                broken: parts.broken,
+            });
+
+            let (Some(ladder), HitObjectKind::Hold(h)) = (self.ladder(index), &hit_object.kind)
+            else {
+                continue;
+            };
+            let x = self.object_x(&frame, column) + self.column_width() / 2.0;
+            let size = self.column_note_size(column) * ladder.shape_scale.max(0.0);
+            let head = hit_object.start_time;
+            let color = self.rung_color(column, head);
+            let rungs = self.ladder_rungs(&frame, head, h.duration, column, ladder);
+            notes.extend(rungs.map(|(y, opacity)| NoteDescription {
+                kind: NoteKind::LadderRung,
+                note_index: index,
+                ..note(column, pos2(x, y), size, color.gamma_multiply(opacity))
+            }));
+        }
+        for (index, hit_object) in objects.filter(|(_, h)| frame.is_note_visible(h)) {
+            let Some((column, x, y)) = self.note_placement(&frame, index, hit_object) else {
+                continue;
+            };
+            let kind = match hit_object.kind {
+                HitObjectKind::Hold(_) => NoteKind::HoldHead,
+                _ => NoteKind::Note,
+            };
+            let center = pos2(x + self.column_width() / 2.0, y);
+            let color = self.note_colors(column, Some(hit_object.start_time)).head;
+            let size = self.column_note_size(column);
+            notes.push(NoteDescription {
+                kind,
+                note_index: index,
+                ..note(column, center, size, color)
+            });
+        }
+
+        let stage_size = Vec2::new(self.column_width() * keycount as f32, frame.total_height);
+        FrameDescription {
+            time_ms: current_time,
+            playfield: playfield.into(),
+            stage: Rect::from_min_size(frame.position, stage_size).into(),
+            column_width: self.column_width(),
+            judgment_line_y: frame.judgment_line_y,
+            barlines,
+            receptors,
+            covers,
+            holds,
+            notes,
+        }
+    }
+
+    pub fn set_fit_policy(&mut self, fit_policy: FitPolicy) {
+        self.fit_policy = fit_policy;
+    }
+
+    /// Rects handed to `render_in` narrower or shorter than this only get a
+    /// "too small" placeholder while the fit policy is `FitPolicy::Clip`;
+    /// the scaling policies shrink the stage instead.
+    /// `DEFAULT_MIN_RENDER_SIZE` by default.
+    pub fn set_min_render_size(&mut self, size: Vec2) {
+        self.min_render_size = size.max(Vec2::ZERO);
+    }
+
+    /// Zooms and pans the stages on top of the fit scale. Hit-testing and
+    /// the rect returned by `render_in` follow the camera.
+    pub fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera;
+    }
+
+    /// Varies the playback rate over the map instead of using the `speed`
+    /// passed to `render*`.
+    pub fn set_rate_ramp(&mut self, rate_ramp: Option<RateRamp>) {
+        self.rate_ramp = rate_ramp;
+    }
+
+    /// Keeps notes in a column at least `px` apart on screen so dense dumps
+    /// stay countable. Only affects drawing, never timing or events.
+    pub fn set_min_visual_gap(&mut self, px: Option<f32>) {
+        self.min_visual_gap = px;
+    }
+
+    /// Draws the hold at `note_index` as dropped from map time `at_time`:
+    /// the rest of its body is greyed out and its tail cap hollow.
+    pub fn report_hold_break(&mut self, note_index: usize, at_time: f64) {
+        self.hold_breaks.insert(note_index, at_time);
+    }
+
+    /// Forgets breaks reported after `map_time`, e.g. after seeking back.
+    pub fn clear_hold_breaks_after(&mut self, map_time: f64) {
+        self.hold_breaks.retain(|_, time| *time <= map_time);
+    }
+
+    pub fn clear_judgments(&mut self) {
+        self.hold_breaks.clear();
+        self.clear_timing_readouts();
+        self.clear_ghost_taps();
+        self.judgment_log.clear();
+    }
+
+    pub fn set_timing_text(&mut self, style: Option<TimingTextStyle>) {
+        match &style {
+            None => self.clear_timing_readouts(),
+            Some(style) => {
+                for readout in &mut self.timing_readouts {
+                    readout.text = TimingReadout::text(style, readout.offset_ms);
+                }
+            }
+        }
+        self.timing_text = style;
+    }
+
+    /// Outlines the playfield, clip rect, columns, judgment line and the
+    /// bounds of every visible object, labelled with its index, on top of
+    /// the normal frame.
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.debug_overlay = enabled;
+    }
+
+    /// Draws text overlays as filled boxes of about the text's size. Glyph
+    /// shaping and rasterization are the only part of a frame that may
+    /// differ between platforms, so frames compared against stored ones
+    /// (golden tests) should be drawn with this on.
+    pub fn set_deterministic(&mut self, enabled: bool) {
+        self.deterministic = enabled;
+    }
+
+    /// Shows the signed offset of a hit in `column` judged at map time
+    /// `at_time`, negative when early. Replaces the readout still showing
+    /// for that column, or the shared one.
+    pub fn report_hit(&mut self, column: usize, offset_ms: f64, at_time: f64) {
+        let Some(style) = self.timing_text.as_ref().filter(|_| !self.scrubbing) else {
+            return;
+        };
+        let (shared, duration_ms) = (style.shared, style.duration_ms);
+        let text = TimingReadout::text(style, offset_ms);
+        let (replaced, kept) = std::mem::take(&mut self.timing_readouts)
+            .into_iter()
+            .partition(|readout| shared || readout.column == column);
+        self.timing_readouts = kept;
+        for readout in replaced {
+            self.effect_clock.remove(readout.effect);
+        }
+
+        let effect = self.effect_clock.spawn(at_time, duration_ms);
+        self.timing_readouts.push(TimingReadout {
+            column,
+            offset_ms,
+            effect,
+            text,
+        });
+    }
+
+    fn clear_timing_readouts(&mut self) {
+        for readout in self.timing_readouts.drain(..) {
+            self.effect_clock.remove(readout.effect);
+        }
+    }
+
+    /// `None` hides ghost tap flashes. On with the default style.
+    pub fn set_ghost_taps(&mut self, style: Option<GhostTapStyle>) {
+        if style.is_none() {
+            self.clear_ghost_taps();
+        }
+        self.ghost_tap_style = style;
+    }
+
+    /// `None`, the default, draws no receptors.
+    pub fn set_receptors(&mut self, style: Option<ReceptorStyle>) {
+        self.receptors = style;
+    }
+
+    pub fn receptors(&self) -> Option<&ReceptorStyle> {
+        self.receptors.as_ref()
+    }
+
+    /// `None`, the default, covers nothing. Shares outside 0 to 1 are
+    /// clamped.
+    pub fn set_lane_cover(&mut self, cover: Option<LaneCover>) {
+        let share = |share: f32| match share {
+            share if share.is_nan() => {
+                trace_warn!(share, "invalid lane cover share, uncovering");
+                0.0
+            }
+            share => share.clamp(0.0, 1.0),
+        };
+        self.lane_cover = cover.map(|cover| LaneCover {
+            sudden: share(cover.sudden),
+            hidden: share(cover.hidden),
+            ..cover
+        });
+    }
+
+    pub fn lane_cover(&self) -> Option<LaneCover> {
+        self.lane_cover
+    }
+
+    /// Flashes `column` at the judgment line for a press at map time
+    /// `at_time` that matched no note. Replaces the column's previous flash.
+    /// Columns past `MAX_KEYCOUNT` are ignored, and those past the drawn
+    /// keycount aren't drawn.
+    pub fn report_ghost_tap(&mut self, column: usize, at_time: f64) {
+        if column >= MAX_KEYCOUNT {
+            return;
+        }
+        let Some(style) = self.ghost_tap_style.filter(|_| !self.scrubbing) else {
+            return;
+        };
+        if let Some(i) = self.ghost_taps.iter().position(|tap| tap.column == column) {
+            let replaced = self.ghost_taps.swap_remove(i);
+            self.effect_clock.remove(replaced.effect);
+        }
+        let effect = self.effect_clock.spawn(at_time, style.duration_ms);
+        self.ghost_taps.push(GhostTap { column, effect });
+    }
+
+    fn clear_ghost_taps(&mut self) {
+        for tap in self.ghost_taps.drain(..) {
+            self.effect_clock.remove(tap.effect);
+        }
+    }
+
+    pub fn set_activity_meter(&mut self, style: Option<ActivityMeterStyle>) {
+        self.activity_meter = style;
+    }
+
+    pub fn activity_meter(&self) -> Option<&ActivityMeterStyle> {
+        self.activity_meter.as_ref()
+    }
+
+    pub(crate) fn set_column_nps(&mut self, nps: Vec<f32>) {
+        self.column_nps = nps;
+    }
+
+    pub fn set_composition_text(&mut self, style: Option<CompositionTextStyle>) {
+        self.composition_text = style;
+        self.composition_label = None;
+    }
+
+    /// Plots the hits recorded with `record_judgment` over map time, see
+    /// `scatter::ScatterStyle`.
+    pub fn set_hit_error_scatter(&mut self, style: Option<ScatterStyle>) {
+        self.hit_error_scatter = style;
+    }
+
+    /// Keeps a report for the scatter overlay until `clear_judgments`.
+    pub(crate) fn record_judgment(&mut self, at_time: f64, recorded: Recorded) {
+        self.judgment_log.record(at_time, recorded);
+    }
+
+    pub(crate) fn judgment_log(&self) -> &JudgmentLog {
+        &self.judgment_log
+    }
+
+    pub(crate) fn set_composition(&mut self, composition: Composition) {
+        self.composition = composition;
+        self.composition_label = None;
+    }
+
+    /// Striped bodies take their colors from the pattern, over the note
+    /// style and hand coloring.
+    pub fn set_hold_body_pattern(&mut self, pattern: HoldBodyPattern) {
+        self.hold_body_pattern = pattern;
+    }
+
+    pub fn hold_body_pattern(&self) -> HoldBodyPattern {
+        self.hold_body_pattern
+    }
+
+    /// Ladders replace the body and its pattern, heads and caps are drawn as
+    /// usual.
+    pub fn set_hold_body_style(&mut self, style: HoldBodyStyle) {
+        self.hold_body_style = style;
+    }
+
+    pub fn hold_body_style(&self) -> HoldBodyStyle {
+        self.hold_body_style
+    }
+
+    pub(crate) fn set_beat_lengths(&mut self, beat_lengths: Vec<(f64, f64)>) {
+        self.beat_lengths = beat_lengths;
+    }
+
+    pub(crate) fn set_measures(&mut self, measures: Vec<Measures>) {
+        self.measures = measures;
+    }
+
+    /// Measure lines from the timing points, scrolling with the notes.
+    /// `None`, the default, draws none.
+    pub fn set_barlines(&mut self, style: Option<BarlineStyle>) {
+        self.barlines = style;
+    }
+
+    pub fn barlines(&self) -> Option<BarlineStyle> {
+        self.barlines
+    }
+
+    pub fn hold_breaks(&self) -> &BTreeMap<usize, f64> {
+        &self.hold_breaks
+    }
+
+    pub(crate) fn set_marked(&mut self, marked: Vec<bool>) {
+        self.marked = marked;
+    }
+
+    pub(crate) fn set_column_offsets(&mut self, offsets: Vec<f32>) {
+        self.column_offsets = offsets;
+    }
+
+    /// Raises the judgment line of each displayed column by that many
+    /// points, e.g. for a staggered line with higher outer columns. Notes
+    /// and holds of a column reach its line at the usual time.
+    pub fn set_column_hit_offsets(&mut self, offsets: Vec<f32>) {
+        self.column_hit_offsets = offsets;
+    }
+
+    // How far `column`'s judgment line moves toward the notes in the current
+    // frame, as a y to subtract: lowered rather than raised in upscroll
+    fn hit_offset(&self, column: usize) -> f32 {
+        let offset = self.column_hit_offsets.get(column).copied().unwrap_or(0.0);
+        offset * self.scale * self.scroll_direction.sign()
+    }
+
+    // Left edge of the objects drawn in `column`
+    fn object_x(&self, frame: &Frame, column: usize) -> f32 {
+        let offset = self.column_offsets.get(column).copied().unwrap_or(0.0);
+        frame.position.x + (column as f32 + offset) * self.column_width()
+    }
+
+    // Sizes as drawn in the current frame
+    fn column_width(&self) -> f32 {
+        self.column_width * self.column_stretch * self.scale
+    }
+
+    fn note_size(&self) -> f32 {
+        self.note_size * self.scale
+    }
+
+    // Note size of `column` with its multiplier. Unless overflow is allowed,
+    // a multiplier can't grow the note past the column width.
+    fn column_note_size(&self, column: usize) -> f32 {
+        let base = self.note_size();
+        let size = base * self.column_note_sizes.get(column).copied().unwrap_or(1.0);
+        if self.note_overflow {
+            return size.max(self.accessibility.min_note_px.unwrap_or(0.0));
+        }
+        let widest = match self.column_shape(column) {
+            NoteShape::Rectangle { width, .. } | NoteShape::Arrow { width, .. } => *width,
+            NoteShape::Circle | NoteShape::Image(_) | NoteShape::Keyed(_) => 1.0,
+        };
+        let size = if widest > 0.0 {
+            size.min((self.column_width() / widest).max(base))
+        } else {
+            size
+        };
+        match self.accessibility.min_note_px {
+            Some(min) => size.max(min),
+            None => size,
+        }
+    }
+
+    fn note_outline(&self) -> egui::Stroke {
+        if self.accessibility.high_contrast {
+            egui::Stroke::new(2.0 * self.scale, HIGH_CONTRAST_OUTLINE)
+        } else {
+            egui::Stroke::NONE
+        }
+    }
+
+    fn height(&self) -> f32 {
+        self.height * self.scale
+    }
+
+    fn render_window_bands(
+        &self,
+        ui: &mut egui::Ui,
+        x_pos: f32,
+        width: f32,
+        judgment_line_y: f32,
+        scroll_time_ms: f32,
+    ) {
+        let Some(style) = &self.window_bands else {
+            return;
+        };
+
+        // Widest tier first so narrower bands stack on top
+        let windows = mania_windows(self.overall_difficulty, self.window_mode);
+        for tier in WindowTier::ALL.iter().rev() {
+            if !style.tiers.contains(tier) {
+                continue;
+            }
+            let window_ms = tier.window_ms(&windows);
+            let half_height =
+                (window_ms as f64 / self.speed) as f32 / scroll_time_ms * self.height();
+            let rect = Rect::from_min_max(
+                pos2(x_pos, judgment_line_y - half_height),
+                pos2(x_pos + width, judgment_line_y + half_height),
+            );
+            ui.painter().rect_filled(rect, 0.0, style.color(*tier));
+        }
+    }
+
+    // Colors of an object of `column` starting at map time `time`, `None`
+    // leaving snap coloring out
+    fn note_colors(&self, column: usize, time: Option<f64>) -> NoteColors {
+        let mut colors = match self.hand_coloring.as_ref().and_then(|c| c.color(column)) {
+            Some(color) => NoteColors {
+                head: color,
+                hold_body: color.gamma_multiply(0.6),
+                hold_cap: color,
+            },
+            None => NoteColors {
+                head: self.note_style.color,
+                hold_body: self.note_style.hold_body_color,
+                hold_cap: self.note_style.hold_cap_color,
+            },
+        };
+        let snap = self.snap_coloring.as_ref().zip(time);
+        if let Some((coloring, time)) = snap {
+            let color = coloring.color(&self.beat_lengths, time);
+            colors.head = color;
+            if coloring.holds {
+                colors.hold_body = color.gamma_multiply(0.6);
+                colors.hold_cap = color;
+            }
+        }
+        colors
+    }
+
+    // Rungs stand in for the body, so they only follow snap coloring along
+    // with hold bodies
+    fn rung_color(&self, column: usize, head: f64) -> Color32 {
+        let holds = self.snap_coloring.as_ref().is_some_and(|c| c.holds);
+        self.note_colors(column, holds.then_some(head)).head
+    }
+
+    // `color` is the note's before `opacity`
+    #[allow(clippy::too_many_arguments)]
+    fn draw_note_sized(
+        &self,
+        ui: &mut egui::Ui,
+        column: usize,
+        x_pos: f32,
+        y_pos: f32,
+        color: Color32,
+        note_size: f32,
+        opacity: f32,
+        degraded: bool,
+    ) {
+        let center_x = x_pos + self.column_width() / 2.0;
+        let color = color.gamma_multiply(opacity);
+
+        let circle = NoteShape::Circle;
+        let shape = match self.column_shape(column) {
+            NoteShape::Image(_) | NoteShape::Keyed(_) if degraded => &circle,
+            shape => shape,
+        };
+        let tint = if self.hand_coloring.is_some() || self.snap_coloring.is_some() {
+            color
+        } else {
+            Color32::WHITE.gamma_multiply(opacity)
+        };
+        let paint = ShapePaint {
+            color,
+            tint,
+            outline: self.note_outline(),
+        };
+        self.paint_shape(ui, shape, pos2(center_x, y_pos), note_size, paint);
+    }
+
+    // `shape` centered on `center`, `size` being the note size. Images are
+    // tinted with `paint.tint` instead of filled.
+    fn paint_shape(
+        &self,
+        ui: &mut egui::Ui,
+        shape: &NoteShape,
+        center: egui::Pos2,
+        size: f32,
+        paint: ShapePaint,
+    ) {
+        let ShapePaint {
+            color,
+            tint,
+            outline,
+        } = paint;
+        match shape {
+            NoteShape::Circle => {
+                ui.painter().circle(center, size / 2.0, color, outline);
+            }
+            NoteShape::Rectangle { width, height } => {
+                let rect = Rect::from_center_size(center, Vec2::new(size * width, size * height));
+                ui.painter()
+                    .rect(rect, 0.0, color, outline, egui::StrokeKind::Inside);
+            }
+            NoteShape::Arrow { width, height } => {
+                let note_width = size * width;
+                let note_height = size * height * self.scroll_direction.sign();
+                let (x, y) = (center.x, center.y);
+                let points = vec![
+                    pos2(x, y - note_height / 2.0),                    // Pointe
+                    pos2(x + note_width / 2.0, y + note_height / 2.0), // Droite
+                    pos2(x - note_width / 2.0, y + note_height / 2.0), // Gauche
+                ];
+                ui.painter()
+                    .add(egui::Shape::convex_polygon(points, color, outline));
+            }
+            NoteShape::Keyed(key) => match self.resolve_image(ui.ctx(), key) {
+                Some(texture) => {
+                    let image = NoteShape::Image(texture.to_image());
+                    self.paint_shape(ui, &image, center, size, paint);
+                }
+                None => self.paint_shape(ui, &NoteShape::Circle, center, size, paint),
+            },
+            NoteShape::Image(image) => {
+                let mut image = image.clone().tint(tint);
+                if self.scroll_direction == ScrollDirection::Up {
+                    image = image.uv(Rect::from_min_max(pos2(0.0, 1.0), pos2(1.0, 0.0)));
+                }
+                let rect = Rect::from_center_size(center, Vec2::splat(size));
+                image.paint_at(ui, rect);
+                ui.painter()
+                    .rect_stroke(rect, 0.0, outline, egui::StrokeKind::Inside);
+            }
+        }
+    }
+
+    // Fill and stripe colors of a hold body in `column`, its head at `head`
+    fn hold_body_colors(&self, column: usize, head: f64) -> (Color32, Color32) {
+        match self.hold_body_pattern {
+            HoldBodyPattern::Striped {
+                color_a, color_b, ..
+            } => (color_a, color_b),
+            HoldBodyPattern::Solid => {
+                let hold_body = self.note_colors(column, Some(head)).hold_body;
+                (hold_body, hold_body)
+            }
+        }
+    }
+
+    fn render_hold(&self, ui: &mut egui::Ui, parts: &HoldParts) {
+        if let Some(body) = parts.body {
+            let (fill, stripe_color) = self.hold_body_colors(parts.column, parts.head);
+            ui.painter().rect_filled(body, 0.0, fill);
+            for stripe in &parts.stripes {
+                ui.painter().rect_filled(*stripe, 0.0, stripe_color);
+            }
+            ui.painter()
+                .rect_stroke(body, 0.0, self.note_outline(), egui::StrokeKind::Inside);
+        }
+
+        if let Some(dropped) = parts.dropped {
+            ui.painter().rect_filled(dropped, 0.0, BROKEN_HOLD_COLOR);
+        }
+
+        if let Some(cap) = parts.cap {
+            if parts.broken {
+                ui.painter().rect_stroke(
+                    cap,
+                    0.0,
+                    egui::Stroke::new(2.0, BROKEN_HOLD_COLOR),
+                    egui::StrokeKind::Inside,
+                );
+            } else {
+                let hold_cap = self.note_colors(parts.column, Some(parts.head)).hold_cap;
+                ui.painter().rect_filled(cap, 0.0, hold_cap);
+            }
+        }
+    }
+
+    // Where the parts of the hold `hit_object` go, `None` when it isn't drawn
+    fn hold_parts(&self, frame: &Frame, index: usize, hit_object: &HitObject) -> Option<HoldParts> {
+        let HitObjectKind::Hold(h) = &hit_object.kind else {
+            return None;
+        };
+        let column = column_for_x(h.pos_x, frame.keycount);
+        if !self.is_column_visible(column) {
+            return None;
+        }
+        let x_pos = self.object_x(frame, column);
+        let lift = self.hit_offset(column);
+
+        let start_y = frame.y_for(hit_object.start_time) - lift;
+        let end_y = frame.y_for(hit_object.start_time + h.duration) - lift;
+        let break_y = self
+            .hold_breaks
+            .get(&index)
+            .map(|time| frame.y_for(*time) - lift);
+        let (spawn_y, line_y) = (frame.spawn_y(), frame.judgment_line_y - lift);
+        let (top_y, bottom_y) = (spawn_y.min(line_y), spawn_y.max(line_y));
+
+        let note_width = self.column_note_size(column) * 0.8;
+        let x_center = x_pos + (self.column_width() - note_width) / 2.0;
+
+        // Body, dropped part and cap are all clipped to [top_y, bottom_y]
+        let clip = |y_min: f32, y_max: f32| {
+            let (y_min, y_max) = (y_min.max(top_y), y_max.min(bottom_y));
+            (y_min < y_max).then(|| {
+                Rect::from_min_max(pos2(x_center, y_min), pos2(x_center + note_width, y_max))
+            })
+        };
+
+        let y_start = start_y.min(end_y);
+        let y_end = start_y.max(end_y);
+        let clip_body = |from: f32, to: f32| clip(from.max(y_start), to.min(y_end));
+        // Ladders have rungs instead, see `draw_ladder`
+        let ladder = matches!(self.hold_body_style, HoldBodyStyle::Ladder { .. });
+        let body = clip(y_start, y_end).filter(|_| !ladder);
+        let stripes = match body {
+            Some(_) => self.hold_stripes(frame, hit_object.start_time, h.duration, lift, clip_body),
+            None => Stripes::new(),
+        };
+
+        // Dropped part of the body, from the break to the tail
+        let dropped = break_y
+            .filter(|_| !ladder)
+            .and_then(|break_y| clip_body(break_y.min(end_y), break_y.max(end_y)));
+
+        // Hold end cap, on the head's side of the tail
+        let cap_height = note_width * 0.3;
+        let cap = match self.scroll_direction {
+            ScrollDirection::Down => clip(end_y, end_y + cap_height),
+            ScrollDirection::Up => clip(end_y - cap_height, end_y),
+        };
+
+        Some(HoldParts {
+            column,
+            head: hit_object.start_time,
+            body,
+            stripes,
+            dropped,
+            cap,
+            broken: break_y.is_some(),
+        })
+    }
+
+    fn draw_hold_object(
+        &self,
+        ui: &mut egui::Ui,
+        frame: &Frame,
+        index: usize,
+        hit_object: &HitObject,
+    ) {
+        let HitObjectKind::Hold(h) = &hit_object.kind else {
+            return;
+        };
+        let Some(parts) = self.hold_parts(frame, index, hit_object) else {
+            return;
+        };
+        self.render_hold(ui, &parts);
+        if let Some(ladder) = self.ladder(index) {
+            let (head, column) = (hit_object.start_time, parts.column);
+            self.draw_ladder(ui, frame, head, h.duration, column, ladder);
         }
     }
 
-    pub fn set_note_style(&mut self, style: NoteStyle) {
-        self.note_style = style;
+    // Rungs of the hold at `index`, `None` unless holds are drawn as ladders
+    fn ladder(&self, index: usize) -> Option<Ladder> {
+        let HoldBodyStyle::Ladder {
+            divisor,
+            shape_scale,
+        } = self.hold_body_style
+        else {
+            return None;
+        };
+        Some(Ladder {
+            divisor,
+            shape_scale,
+            break_time: self.hold_breaks.get(&index).copied(),
+        })
+    }
+
+    // Small notes on the snap lines of a hold, up to the judgment line. The
+    // dropped part of a broken hold is faded.
+    fn draw_ladder(
+        &self,
+        ui: &mut egui::Ui,
+        frame: &Frame,
+        head: f64,
+        duration: f64,
+        column: usize,
+        ladder: Ladder,
+    ) {
+        let x_pos = self.object_x(frame, column);
+        let size = self.column_note_size(column) * ladder.shape_scale.max(0.0);
+        let color = self.rung_color(column, head);
+        for (y, opacity) in self.ladder_rungs(frame, head, duration, column, ladder) {
+            self.draw_note_sized(ui, column, x_pos, y, color, size, opacity, frame.degraded);
+        }
     }
 
-    fn draw_note(&self, ui: &mut egui::Ui, x_pos: f32, y_pos: f32) {
-        let center_x = x_pos + self.column_width / 2.0;
+    // (y, opacity) of the rungs `draw_ladder` draws
+    fn ladder_rungs<'a>(
+        &'a self,
+        frame: &'a Frame,
+        head: f64,
+        duration: f64,
+        column: usize,
+        ladder: Ladder,
+    ) -> impl Iterator<Item = (f32, f32)> + 'a {
+        let lift = self.hit_offset(column);
+        let line_y = frame.judgment_line_y - lift;
+        // Rungs past the line aren't drawn, so they don't count to the cap
+        let line_time = frame.scroll.map_time_at_line(frame.current_time);
+        let from = head.max(line_time);
+        let to = (head + duration).min(frame.visible_end);
+        snap::lines_between(&self.beat_lengths, from, to, ladder.divisor)
+            .take(MAX_LADDER_RUNGS)
+            .map(move |time| (time, frame.y_for(time) - lift))
+            .filter(move |(_, y)| !frame.is_beyond(*y, line_y))
+            .map(move |(time, y)| {
+                let dropped = ladder.break_time.is_some_and(|broken| time >= broken);
+                (y, if dropped { 0.3 } else { 1.0 })
+            })
+    }
 
-        match &self.note_style.shape {
-            NoteShape::Circle => {
-                let circle_radius = self.note_size / 2.0;
-                ui.painter().circle_filled(
-                    pos2(center_x, y_pos),
-                    circle_radius,
-                    self.note_style.color,
-                );
+    // The `color_b` stripes of a hold body crossing the visible range, as
+    // `clip` cuts them
+    fn hold_stripes(
+        &self,
+        frame: &Frame,
+        head: f64,
+        duration: f64,
+        lift: f32,
+        clip: impl Fn(f32, f32) -> Option<Rect>,
+    ) -> Stripes {
+        let HoldBodyPattern::Striped { period, .. } = self.hold_body_pattern else {
+            return Stripes::new();
+        };
+        let tail = head + duration;
+        let next = |time: f64| match period {
+            Period::Ms(ms) => time + f64::from(ms),
+            Period::Beats(beats) => advance_beats(&self.beat_lengths, time, f64::from(beats)),
+        };
+
+        // Whole stripes above the visible start are skipped, so the step
+        // limit only counts the visible ones however long the hold is
+        let hidden = match period {
+            Period::Ms(ms) => (frame.visible_start - head) / f64::from(ms),
+            Period::Beats(beats) => {
+                beats_between(&self.beat_lengths, head, frame.visible_start) / f64::from(beats)
             }
-            NoteShape::Rectangle { width, height } => {
-                let note_width = self.note_size * width;
-                let note_height = self.note_size * height;
-                let rect = Rect::from_center_size(
-                    pos2(center_x, y_pos),
-                    Vec2::new(note_width, note_height),
-                );
-                ui.painter().rect_filled(rect, 0.0, self.note_style.color);
+        };
+        let skipped = if hidden.is_finite() && hidden > 0.0 {
+            hidden.floor()
+        } else {
+            0.0
+        };
+        let mut start = match period {
+            Period::Ms(ms) => head + skipped * f64::from(ms),
+            Period::Beats(beats) => {
+                advance_beats(&self.beat_lengths, head, skipped * f64::from(beats))
             }
-            NoteShape::Arrow { width, height } => {
-                let note_width = self.note_size * width;
-                let note_height = self.note_size * height;
-                let points = vec![
-                    pos2(center_x, y_pos - note_height / 2.0), // Pointe
-                    pos2(center_x + note_width / 2.0, y_pos + note_height / 2.0), // Droite
-                    pos2(center_x - note_width / 2.0, y_pos + note_height / 2.0), // Gauche
-                ];
-                ui.painter().add(egui::Shape::convex_polygon(
-                    points,
-                    self.note_style.color,
-                    egui::Stroke::NONE,
-                ));
+        };
+        let odd_skipped = usize::from(skipped % 2.0 == 1.0);
+
+        let mut stripes = Stripes::new();
+        let mut steps = 0;
+        // Stripe `i` spans [start, end), the odd ones take `color_b`
+        while start < tail.min(frame.visible_end) && stripes.len() < MAX_HOLD_STRIPES {
+            let end = next(start);
+            if end.is_nan() || end <= start || steps == MAX_STRIPE_STEPS {
+                break;
             }
-            NoteShape::Image(image) => {
-                image.paint_at(
-                    ui,
-                    Rect::from_min_size(
-                        pos2(
-                            center_x - self.note_size / 2.0,
-                            y_pos - self.note_size / 2.0,
-                        ),
-                        Vec2::new(self.note_size, self.note_size),
-                    ),
-                );
+            if (steps + odd_skipped) % 2 == 1 && end > frame.visible_start {
+                let (from, to) = (frame.y_for(start) - lift, frame.y_for(end) - lift);
+                stripes.extend(clip(from.min(to), from.max(to)));
             }
+            start = end;
+            steps += 1;
         }
+        stripes
     }
 
-    fn render_hold(
+    fn draw_note_object(
         &self,
         ui: &mut egui::Ui,
-        x_pos: f32,
-        start_y: f32,
-        end_y: f32,
-        judgment_line_y: f32,
+        frame: &Frame,
+        index: usize,
+        hit_object: &HitObject,
     ) {
-        let note_width = self.note_size * 0.8;
-        let x_center = x_pos + (self.column_width - note_width) / 2.0;
+        let Some((column, x_pos, y_pos)) = self.note_placement(frame, index, hit_object) else {
+            return;
+        };
+        let color = self.note_colors(column, Some(hit_object.start_time)).head;
+        let size = self.column_note_size(column);
+        if let (Some(travel), Some(blur)) = (frame.blur_travel, self.motion_blur) {
+            let sign = frame.direction.sign();
+            for sample in (1..=blur.samples).rev() {
+                let t = sample as f32 / blur.samples as f32;
+                let opacity = blur.strength * (1.0 - t + 1.0 / blur.samples as f32);
+                let y = y_pos - travel * t * sign;
+                self.draw_note_sized(ui, column, x_pos, y, color, size, opacity, frame.degraded);
+            }
+        }
+        self.draw_note_sized(ui, column, x_pos, y_pos, color, size, 1.0, frame.degraded);
+        if self.marked.get(index).copied().unwrap_or(false) {
+            self.draw_marker(ui, x_pos, y_pos, column);
+        }
+    }
 
-        let y_start = start_y.min(end_y);
-        let y_end = (start_y.max(end_y)).min(judgment_line_y);
-        let visible_height = (y_end - y_start).abs();
-
-        // Hold body
-        ui.painter().rect_filled(
-            Rect::from_min_size(
-                pos2(x_center, y_start),
-                Vec2::new(note_width, visible_height),
-            ),
-            0.0,
-            self.note_style.hold_body_color,
-        );
+    // Column, left edge and y of the head `draw_note_object` draws, `None`
+    // when it's past the judgment line or not on the stage yet
+    fn note_placement(
+        &self,
+        frame: &Frame,
+        index: usize,
+        hit_object: &HitObject,
+    ) -> Option<(usize, f32, f32)> {
+        let column = match &hit_object.kind {
+            HitObjectKind::Circle(h) => column_for_x(h.pos.x, frame.keycount),
+            HitObjectKind::Hold(h) => column_for_x(h.pos_x, frame.keycount),
+            _ => return None,
+        };
+        let lift = self.hit_offset(column);
+        let y_pos = frame.note_y(index, hit_object) - lift;
+        if frame.is_beyond(y_pos, frame.judgment_line_y - lift) || !self.is_column_visible(column) {
+            return None;
+        }
 
-        // Hold end cap
-        let cap_height = note_width * 0.3;
-        if end_y <= judgment_line_y {
-            ui.painter().rect_filled(
-                Rect::from_min_size(pos2(x_center, end_y), Vec2::new(note_width, cap_height)),
-                0.0,
-                self.note_style.hold_cap_color,
-            );
+        // Draw notes when they start entering the stage (considering note height)
+        let note_height = self.column_note_size(column) * 0.25; // Approximate note height
+        let sign = frame.direction.sign();
+        if frame.is_beyond(frame.spawn_y() - note_height * sign, y_pos) {
+            return None;
         }
+        Some((column, self.object_x(frame, column), y_pos))
+    }
+
+    // Warning dot on the top right corner of a note
+    fn draw_marker(&self, ui: &mut egui::Ui, x_pos: f32, y_pos: f32, column: usize) {
+        let note_size = self.column_note_size(column);
+        let center = pos2(
+            x_pos + self.column_width() / 2.0 + note_size * 0.3,
+            y_pos - note_size * 0.15 * self.scroll_direction.sign(),
+        );
+        let radius = (note_size * 0.1).max(3.0);
+        ui.painter().circle(
+            center,
+            radius,
+            Color32::from_rgb(255, 90, 0),
+            egui::Stroke::new(1.0, Color32::BLACK),
+        );
     }
 
     pub fn set_height(&mut self, height: f32) {
         self.height = height;
     }
 
+    /// Moves the judgment line `offset_px` away from the bottom of the stage,
+    /// or from its top in upscroll. Clamped to the stage height when drawn.
+    pub fn set_hit_position(&mut self, offset_px: f32) {
+        self.hit_position = match offset_px {
+            offset_px if !offset_px.is_finite() => {
+                trace_warn!(offset_px, "invalid hit position, using the default");
+                DEFAULT_HIT_POSITION
+            }
+            offset_px => offset_px,
+        };
+    }
+
+    pub fn hit_position(&self) -> f32 {
+        self.hit_position
+    }
+
+    /// Stage width, with the columns of 1K to 3K widened by `column_stretch`.
     pub fn required_width(&self, keycount: usize) -> f32 {
-        self.column_width * keycount as f32
+        self.column_width * column_stretch(keycount) * keycount as f32 + self.padding.sum().x
+    }
+
+    /// Width of `stages` stages drawn side by side.
+    pub fn required_stages_width(&self, keycount: usize, stages: usize) -> f32 {
+        let stages = stages.max(1) as f32;
+        self.required_width(keycount) * stages + STAGE_GAP * (stages - 1.0)
     }
 
     pub fn required_height(&self) -> f32 {
-        self.height
+        self.height + self.padding.sum().y
+    }
+
+    #[cfg(feature = "html-export")]
+    pub(crate) fn html_stage(
+        &self,
+        keycount: usize,
+        scroll_time_ms: f32,
+        speed: f64,
+    ) -> crate::html::HtmlStage {
+        crate::html::HtmlStage {
+            column_width: self.column_width * column_stretch(keycount),
+            note_size: self.note_size,
+            height: self.height,
+            hit_position: self.hit_position.clamp(0.0, self.height),
+            direction: self.scroll_direction.sign(),
+            scroll_time_ms,
+            speed,
+            note_color: self.note_style.color,
+            hold_body_color: self.note_style.hold_body_color,
+            hold_cap_color: self.note_style.hold_cap_color,
+        }
     }
 
     pub fn render(
@@ -163,6 +2891,7 @@ impl ManiaRenderer {
         self.render_at(ui, hit_objects, current_time, scroll_time_ms, speed, keycount, pos2(0.0, 0.0))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn render_at(
         &mut self,
         ui: &mut egui::Ui,
@@ -173,115 +2902,915 @@ impl ManiaRenderer {
         keycount: usize,
         position: egui::Pos2,
     ) {
-        self.speed = speed;
+        self.render_stages_at(
+            ui,
+            &[hit_objects],
+            current_time,
+            scroll_time_ms,
+            speed,
+            keycount,
+            position,
+        );
+    }
+
+    /// Draws one stage per object list, left to right, from the same clock.
+    /// `column_at` hit-tests the last stage.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_stages_at(
+        &mut self,
+        ui: &mut egui::Ui,
+        stages: &[&[HitObject]],
+        current_time: f64,
+        scroll_time_ms: f32,
+        speed: f64,
+        keycount: usize,
+        position: egui::Pos2,
+    ) {
+        self.scale = 1.0;
+        let clip_rect = self.widen_clip(ui);
+        self.draw_stages(
+            ui,
+            stages,
+            current_time,
+            scroll_time_ms,
+            speed,
+            keycount,
+            position,
+        );
+        ui.set_clip_rect(clip_rect);
+    }
+
+    /// Draws the stage inside `rect` according to the fit policy, centered,
+    /// and returns the rect the stage frame occupies. Nothing is drawn outside
+    /// of `rect` grown by the clip margin and the `Ui` layout is left
+    /// untouched. Edges hiding part of the stage are shaded, and when
+    /// clipping below the minimum render size only a placeholder is drawn and
+    /// `rect` returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_in(
+        &mut self,
+        ui: &mut egui::Ui,
+        hit_objects: &[HitObject],
+        current_time: f64,
+        scroll_time_ms: f32,
+        speed: f64,
+        keycount: usize,
+        rect: Rect,
+    ) -> Rect {
+        self.render_stages_in(
+            ui,
+            &[hit_objects],
+            current_time,
+            scroll_time_ms,
+            speed,
+            keycount,
+            rect,
+        )
+    }
+
+    /// `render_stages_at` inside `rect`, fitting all stages together.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_stages_in(
+        &mut self,
+        ui: &mut egui::Ui,
+        stages: &[&[HitObject]],
+        current_time: f64,
+        scroll_time_ms: f32,
+        speed: f64,
+        keycount: usize,
+        rect: Rect,
+    ) -> Rect {
+        let too_small =
+            rect.width() < self.min_render_size.x || rect.height() < self.min_render_size.y;
+        if too_small && self.fit_policy == FitPolicy::Clip {
+            self.draw_too_small(ui, rect);
+            return rect;
+        }
+        let size = Vec2::new(
+            self.required_stages_width(keycount, stages.len()),
+            self.required_height(),
+        );
+        self.scale = self.fit_policy.scale(size, rect);
+        let playfield = Rect::from_center_size(rect.center(), size * self.scale);
+
+        let mut child = ui.new_child(egui::UiBuilder::new().max_rect(rect));
+        child.set_clip_rect((rect + self.clip_margin).intersect(ui.clip_rect()));
+        let drawn = self.draw_stages(
+            &mut child,
+            stages,
+            current_time,
+            scroll_time_ms,
+            speed,
+            keycount,
+            playfield.min,
+        );
+        self.draw_overflow_hints(&child, rect, drawn);
+        drawn
+    }
+
+    // Lets `ui` draw up to the clip margin further, returning the clip rect
+    // to restore. A child `Ui` would allocate every frame.
+    fn widen_clip(&self, ui: &mut egui::Ui) -> Rect {
+        let clip_rect = ui.clip_rect();
+        ui.set_clip_rect(clip_rect + self.clip_margin);
+        clip_rect
+    }
+
+    // Placeholder of `render_in` below the minimum render size
+    fn draw_too_small(&mut self, ui: &egui::Ui, rect: Rect) {
+        self.stage_rect = None;
+        self.frame_transform = None;
+        self.playfield = None;
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, Color32::from_gray(20));
+        self.draw_text(
+            &painter,
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Too small".to_owned(),
+            egui::FontId::proportional(10.0),
+            Color32::from_gray(120),
+        );
+    }
+
+    // Shades the edges of `rect` past which the stage continues
+    fn draw_overflow_hints(&self, ui: &egui::Ui, rect: Rect, stage: Rect) {
+        let painter = ui.painter_at(rect);
+        let color = Color32::from_black_alpha(120);
+        let (width, min, max) = (OVERFLOW_HINT_WIDTH, rect.min, rect.max);
+        // Ignores rounding of scaled stages that exactly fit
+        let stage = stage.shrink(0.5);
+        let shade = |overflows: bool, edge: Rect| {
+            if overflows {
+                painter.rect_filled(edge, 0.0, color);
+            }
+        };
+        shade(stage.min.x < min.x, rect.with_max_x(min.x + width));
+        shade(stage.max.x > max.x, rect.with_min_x(max.x - width));
+        shade(stage.min.y < min.y, rect.with_max_y(min.y + width));
+        shade(stage.max.y > max.y, rect.with_min_y(max.y - width));
+    }
+
+    /// Draws the stage with placeholder notes and holds and beat lines at a
+    /// fixed tempo, without a beatmap, to preview the configured styles.
+    /// Judgment visuals of the rendered map (hold breaks, markers) are left
+    /// out. Returns the rect of the stage.
+    pub fn render_stage_preview(
+        &mut self,
+        ui: &mut egui::Ui,
+        keycount: usize,
+        position: egui::Pos2,
+    ) -> Rect {
+        self.scale = 1.0;
+        self.column_stretch = column_stretch(keycount);
+        let scroll = ScrollTransform {
+            speed: 1.0,
+            scroll_time_ms: PREVIEW_SCROLL_MS,
+            rate_ramp: None,
+        };
+        let current_time = scroll.crossing_time(0.0);
+        let hit_objects = preview_objects(keycount);
+
+        // Keyed by object index, they would land on the placeholders
+        let hold_breaks = std::mem::take(&mut self.hold_breaks);
+        let marked = std::mem::take(&mut self.marked);
+        let column_offsets = std::mem::take(&mut self.column_offsets);
+
+        let stage = Rect::from_min_size(
+            position,
+            Vec2::new(self.required_width(keycount), self.required_height()),
+        );
+        let clip_rect = self.widen_clip(ui);
+        let frame = self.draw_stage_frame(ui, current_time, scroll, keycount, position);
+        let context = self.layer_context(&frame, 0, stage);
+        self.frame_transform = Some(context.transform);
+        self.run_layer_hook(ui, Layer::StageBackground, &context);
+        self.draw_beat_lines(ui, &frame);
+        self.run_layer_hook(ui, Layer::Barlines, &context);
+        self.draw_receptors(ui, &frame);
+        self.run_layer_hook(ui, Layer::Receptors, &context);
+        self.draw_objects(ui, &frame, &hit_objects, &context);
+        // The preview draws nothing past the objects
+        for layer in Layer::ALL.into_iter().filter(|layer| *layer > Layer::Notes) {
+            self.run_layer_hook(ui, layer, &context);
+        }
+
+        self.hold_breaks = hold_breaks;
+        self.marked = marked;
+        self.column_offsets = column_offsets;
+        ui.set_clip_rect(clip_rect);
+        stage
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_stages(
+        &mut self,
+        ui: &mut egui::Ui,
+        stages: &[&[HitObject]],
+        current_time: f64,
+        scroll_time_ms: f32,
+        speed: f64,
+        keycount: usize,
+        position: egui::Pos2,
+    ) -> Rect {
+        let blur_travel = self.blur_travel(current_time, scroll_time_ms);
+        self.column_stretch = column_stretch(keycount);
+
+        // The camera zooms about the center of the unzoomed stages
+        let size = Vec2::new(
+            self.required_stages_width(keycount, stages.len()),
+            self.required_height(),
+        ) * self.scale;
+        let mut playfield = Rect::from_min_size(position, size);
+        let camera = self.camera.filter(|_| !self.accessibility.reduce_motion);
+        if let Some(camera) = camera {
+            self.scale *= camera.zoom;
+            playfield =
+                Rect::from_center_size(playfield.center() + camera.offset, size * camera.zoom);
+        }
+        let position = playfield.min;
+
+        let scroll = ScrollTransform {
+            speed,
+            scroll_time_ms,
+            rate_ramp: self.rate_ramp,
+        };
+        let line_time = scroll.map_time_at_line(current_time);
+
+        if !self.draw_background_events(ui, line_time) {
+            match &self.letterbox {
+                Some(Letterbox::Color(color)) => {
+                    ui.painter().rect_filled(ui.max_rect(), 0.0, *color);
+                }
+                Some(Letterbox::Image(image)) => image.paint_at(ui, ui.max_rect()),
+                None => {}
+            }
+        }
+        self.speed = scroll.rate_at(line_time);
+        self.effect_clock.set_time(line_time);
+
+        self.frame_stats = FrameStats::default();
+        let stage_offset = (self.required_width(keycount) + STAGE_GAP) * self.scale;
+        let first_shape = ui.ctx().graphics(|graphics| {
+            let shapes = graphics.get(ui.layer_id());
+            shapes.map_or(egui::layers::ShapeIdx(0), |shapes| shapes.next_idx())
+        });
+        let mut contexts = std::mem::take(&mut self.scratch_contexts);
+        contexts.clear();
+        for (i, hit_objects) in stages.iter().enumerate() {
+            let position = position + Vec2::new(i as f32 * stage_offset, 0.0);
+            let mut frame = self.draw_stage_frame(ui, current_time, scroll, keycount, position);
+            let context = self.layer_context(&frame, i, playfield);
+            if i == 0 {
+                self.frame_transform = Some(context.transform);
+            }
+            self.run_layer_hook(ui, Layer::StageBackground, &context);
+            self.draw_barlines(ui, &frame);
+            self.run_layer_hook(ui, Layer::Barlines, &context);
+            self.draw_receptors(ui, &frame);
+            self.run_layer_hook(ui, Layer::Receptors, &context);
+            frame.blur_travel = blur_travel;
+            let mut nudged_y = std::mem::take(&mut self.scratch_nudged);
+            self.nudge_notes(&frame, hit_objects, &mut nudged_y);
+            frame.nudged_y = nudged_y;
+
+            let visible_notes = hit_objects
+                .iter()
+                .filter(|h| frame.is_note_visible(h))
+                .count();
+            frame.degraded = self.degrades_images(visible_notes);
+            self.frame_stats.visible_notes += visible_notes;
+            self.frame_stats.degraded |= frame.degraded;
+            self.draw_objects(ui, &frame, hit_objects, &context);
+            if i + 1 == stages.len() {
+                self.draw_ghost_taps(ui, &frame);
+                self.draw_timing_readouts(ui, &frame, playfield);
+                self.draw_activity_meter(ui, &frame, playfield);
+            }
+            self.run_layer_hook(ui, Layer::Effects, &context);
+            self.draw_lane_covers(ui, &frame);
+            self.run_layer_hook(ui, Layer::Covers, &context);
+            if self.debug_overlay {
+                self.draw_debug_overlay(ui, &frame, hit_objects);
+            }
+            contexts.push(context);
+            self.scratch_nudged = frame.nudged_y;
+        }
+        self.playfield = Some(playfield);
+        self.tilt_shapes(ui, first_shape);
+        self.draw_composition_text(ui, playfield);
+        self.draw_hit_error_scatter(ui, playfield);
+        if self.debug_overlay {
+            let painter = ui.painter();
+            painter.rect_stroke(
+                playfield,
+                0.0,
+                egui::Stroke::new(1.0, DEBUG_PLAYFIELD_COLOR),
+                egui::StrokeKind::Inside,
+            );
+            painter.rect_stroke(
+                ui.clip_rect(),
+                0.0,
+                egui::Stroke::new(1.0, DEBUG_CLIP_COLOR),
+                egui::StrokeKind::Inside,
+            );
+        }
+        for context in &contexts {
+            self.run_layer_hook(ui, Layer::Overlays, context);
+        }
+        self.scratch_contexts = contexts;
+        playfield
+    }
 
-        let total_width = self.required_width(keycount);
-        let total_height = self.required_height();
+    // Background, columns, window bands and judgment line of one stage
+    fn draw_stage_frame(
+        &mut self,
+        ui: &mut egui::Ui,
+        current_time: f64,
+        scroll: ScrollTransform,
+        keycount: usize,
+        position: egui::Pos2,
+    ) -> Frame {
+        let scroll_time_ms = scroll.scroll_time_ms;
 
         // Draw background rectangle at the specified position
-        let background_rect = egui::Rect::from_min_size(position, egui::Vec2::new(total_width, total_height));
-        ui.painter().rect_filled(background_rect, 0.0, egui::Color32::from_gray(20));
+        let background_rect = egui::Rect::from_min_size(
+            position,
+            egui::Vec2::new(self.required_width(keycount), self.required_height()) * self.scale,
+        );
+        let high_contrast = self.accessibility.high_contrast;
+        let background = if high_contrast { 0 } else { 20 };
+        ui.painter()
+            .rect_filled(background_rect, 0.0, egui::Color32::from_gray(background));
+
+        // Everything below is laid out inside the padding
+        let frame = self.frame_at(current_time, scroll, keycount, position);
+        let (position, judgment_line_y) = (frame.position, frame.judgment_line_y);
+        let total_width = self.column_width() * keycount as f32;
+        let total_height = frame.total_height;
+        self.stage_rect = Some(Rect::from_min_size(
+            position,
+            Vec2::new(total_width, total_height),
+        ));
 
         // Draw columns
         for i in 0..keycount {
             let column_rect = egui::Rect::from_min_size(
-                egui::pos2(position.x + i as f32 * self.column_width, position.y),
-                egui::Vec2::new(self.column_width, total_height),
+                egui::pos2(position.x + i as f32 * self.column_width(), position.y),
+                egui::Vec2::new(self.column_width(), total_height),
+            );
+            let column_color = if !self.is_column_visible(i) {
+                egui::Color32::from_gray(12)
+            } else if self.is_column_pressed(i) {
+                egui::Color32::from_gray(55)
+            } else if high_contrast {
+                egui::Color32::BLACK
+            } else {
+                egui::Color32::from_gray(30)
+            };
+            ui.painter().rect_filled(column_rect, 0.0, column_color);
+        }
+
+        let judgment_width = if high_contrast {
+            HIGH_CONTRAST_JUDGMENT_WIDTH
+        } else {
+            2.0
+        };
+        self.render_window_bands(ui, position.x, total_width, judgment_line_y, scroll_time_ms);
+        let judgment_stroke = egui::Stroke::new(judgment_width, egui::Color32::WHITE);
+        if self.column_hit_offsets.iter().all(|offset| *offset == 0.0) {
+            ui.painter().line_segment(
+                [
+                    egui::pos2(position.x, judgment_line_y),
+                    egui::pos2(position.x + total_width, judgment_line_y),
+                ],
+                judgment_stroke,
+            );
+        } else {
+            // One segment per column, each at its own height
+            for column in 0..keycount {
+                let x = position.x + column as f32 * self.column_width();
+                let y = judgment_line_y - self.hit_offset(column);
+                ui.painter().line_segment(
+                    [egui::pos2(x, y), egui::pos2(x + self.column_width(), y)],
+                    judgment_stroke,
+                );
+            }
+        }
+        frame
+    }
+
+    // Layout of a stage whose padding starts at `position`
+    fn frame_at(
+        &self,
+        current_time: f64,
+        scroll: ScrollTransform,
+        keycount: usize,
+        position: egui::Pos2,
+    ) -> Frame {
+        let position = position + self.padding.left_top() * self.scale;
+        let total_height = self.height();
+        let hit_position = self.hit_position.clamp(0.0, self.height) * self.scale;
+        let judgment_line_y = match self.scroll_direction {
+            ScrollDirection::Down => position.y + total_height - hit_position,
+            ScrollDirection::Up => position.y + hit_position,
+        };
+
+        // Visible from two scroll times ahead of the line to half one past it
+        let scroll_time = scroll.scroll_time_ms as f64;
+        Frame {
+            position,
+            judgment_line_y,
+            total_height,
+            current_time,
+            scroll,
+            direction: self.scroll_direction,
+            visible_start: scroll.map_time(current_time - scroll_time * 2.0),
+            visible_end: scroll.map_time(current_time + scroll_time * 0.5),
+            keycount,
+            blur_travel: None,
+            nudged_y: Vec::new(),
+            degraded: false,
+        }
+    }
+
+    // One receptor per visible column, centered on its judgment line
+    fn draw_receptors(&self, ui: &mut egui::Ui, frame: &Frame) {
+        let Some(style) = &self.receptors else {
+            return;
+        };
+        let shape = style.shape.as_ref().unwrap_or(&self.note_style.shape);
+        let paint = ShapePaint {
+            color: style.color,
+            tint: style.color,
+            outline: egui::Stroke::new(style.outline.width * self.scale, style.outline.color),
+        };
+        for (_, center, size) in self.receptor_layout(frame) {
+            self.paint_shape(ui, shape, center, size, paint);
+        }
+    }
+
+    // (column, center, note size) of every receptor `draw_receptors` draws
+    fn receptor_layout<'a>(
+        &'a self,
+        frame: &'a Frame,
+    ) -> impl Iterator<Item = (usize, egui::Pos2, f32)> + 'a {
+        (0..frame.keycount)
+            .filter(|column| self.is_column_visible(*column))
+            .map(|column| {
+                let center = pos2(
+                    self.object_x(frame, column) + self.column_width() / 2.0,
+                    frame.judgment_line_y - self.hit_offset(column),
+                );
+                (column, center, self.column_note_size(column))
+            })
+    }
+
+    fn draw_lane_covers(&self, ui: &mut egui::Ui, frame: &Frame) {
+        let Some(cover) = self.lane_cover else {
+            return;
+        };
+        for rect in self.lane_cover_rects(frame, cover) {
+            ui.painter().rect_filled(rect, 0.0, cover.color);
+        }
+    }
+
+    // Panels of `cover` as `draw_lane_covers` draws them, the empty ones
+    // left out
+    fn lane_cover_rects(&self, frame: &Frame, cover: LaneCover) -> impl Iterator<Item = Rect> {
+        let (left, sign) = (frame.position.x, frame.direction.sign());
+        let right = left + self.column_width() * frame.keycount as f32;
+        let panel = |edge: f32, share: f32| {
+            let far = edge - share * frame.total_height * sign;
+            Rect::from_two_pos(pos2(left, edge), pos2(right, far))
+        };
+        let sudden = panel(frame.spawn_y(), -cover.sudden);
+        let hidden = panel(frame.judgment_line_y, cover.hidden);
+        [sudden, hidden]
+            .into_iter()
+            .filter(|rect| rect.height() > 0.0)
+    }
+
+    // Ghost tap flashes of the last stage, fading out with their effect
+    fn draw_ghost_taps(&self, ui: &mut egui::Ui, frame: &Frame) {
+        let Some(style) = &self.ghost_tap_style else {
+            return;
+        };
+        for tap in &self.ghost_taps {
+            if tap.column >= frame.keycount {
+                continue;
+            }
+            let Some(progress) = self.effect_clock.progress(tap.effect) else {
+                continue;
+            };
+            let x = self.object_x(frame, tap.column);
+            let flash = Rect::from_center_size(
+                pos2(x + self.column_width() / 2.0, frame.judgment_line_y),
+                Vec2::new(self.column_width(), style.thickness * self.scale),
             );
             ui.painter()
-                .rect_filled(column_rect, 0.0, egui::Color32::from_gray(30));
+                .rect_filled(flash, 0.0, style.color.gamma_multiply(1.0 - progress));
+        }
+    }
+
+    // Hit offsets of the last stage, fading out with their effect
+    fn draw_timing_readouts(&mut self, ui: &mut egui::Ui, frame: &Frame, playfield: Rect) {
+        let mut readouts = std::mem::take(&mut self.timing_readouts);
+        self.draw_timing_readouts_of(&mut readouts, ui, frame, playfield);
+        self.timing_readouts = readouts;
+    }
+
+    fn draw_timing_readouts_of(
+        &self,
+        readouts: &mut [TimingReadout],
+        ui: &mut egui::Ui,
+        frame: &Frame,
+        playfield: Rect,
+    ) {
+        let Some(style) = &self.timing_text else {
+            return;
+        };
+        // Above the line, or below it in upscroll
+        let above_line = frame.judgment_line_y - 30.0 * self.scale * frame.direction.sign();
+        let above_align = match frame.direction {
+            ScrollDirection::Down => egui::Align2::CENTER_BOTTOM,
+            ScrollDirection::Up => egui::Align2::CENTER_TOP,
+        };
+        let miss = mania_windows(self.overall_difficulty, self.window_mode).miss as f64;
+        for readout in readouts {
+            let Some(progress) = self.effect_clock.progress(readout.effect) else {
+                continue;
+            };
+            let (pos, align) = match style.anchor {
+                Some(anchor) if style.shared => {
+                    (anchor.resolve(playfield, ui.max_rect()), anchor.alignment)
+                }
+                _ => {
+                    let x = if style.shared {
+                        frame.position.x + self.column_width() * frame.keycount as f32 / 2.0
+                    } else {
+                        self.object_x(frame, readout.column) + self.column_width() / 2.0
+                    };
+                    (pos2(x, above_line), above_align)
+                }
+            };
+            let color = match &style.gradient {
+                Some(gradient) => gradient.at(0.5 + (readout.offset_ms / miss / 2.0) as f32),
+                None if readout.offset_ms < 0.0 => style.early_color,
+                None => style.late_color,
+            };
+            self.draw_cached_text(
+                ui.painter(),
+                pos,
+                align,
+                &mut readout.text,
+                egui::FontId::proportional(style.font_size * self.scale),
+                color.gamma_multiply(1.0 - progress),
+            );
+        }
+    }
+
+    // Text of the overlays, or a box of about its size in deterministic mode
+    fn draw_text(
+        &self,
+        painter: &egui::Painter,
+        pos: egui::Pos2,
+        align: egui::Align2,
+        text: String,
+        font: egui::FontId,
+        color: Color32,
+    ) {
+        if !self.deterministic {
+            painter.text(pos, align, text, font, color);
+            return;
+        }
+        draw_text_box(painter, pos, align, &text, &font, color);
+    }
+
+    // `draw_text` of text kept across frames, only laid out when it changes
+    fn draw_cached_text(
+        &self,
+        painter: &egui::Painter,
+        pos: egui::Pos2,
+        align: egui::Align2,
+        text: &mut CachedText,
+        font: egui::FontId,
+        color: Color32,
+    ) {
+        if self.deterministic {
+            draw_text_box(painter, pos, align, &text.text, &font, color);
+            return;
+        }
+        let galley = text.galley(painter, font);
+        let min = align.anchor_size(pos, galley.size()).min;
+        painter.galley_with_override_text_color(min, galley, color);
+    }
+
+    fn draw_hit_error_scatter(&self, ui: &mut egui::Ui, playfield: Rect) {
+        let Some(style) = &self.hit_error_scatter else {
+            return;
+        };
+        let size = style.size * self.scale;
+        let pos = style.anchor.resolve(playfield, ui.max_rect());
+        let rect = style.anchor.alignment.anchor_size(pos, size);
+        let cursor = Some(self.effect_clock.now());
+        let (painter, windows) = (ui.painter(), self.windows());
+        let errors = self.judgment_log.hit_errors();
+        scatter::paint(painter, rect, errors, &windows, style, cursor);
+    }
+
+    fn draw_composition_text(&mut self, ui: &mut egui::Ui, playfield: Rect) {
+        let Some(style) = &self.composition_text else {
+            return;
+        };
+        let mut label = self.composition_label.take().unwrap_or_else(|| {
+            CachedText::new(format!(
+                "LN {}% · max chord {}",
+                style.formatter.number(self.composition.ln_percent, 0),
+                self.composition.max_chord_size
+            ))
+        });
+        self.draw_cached_text(
+            ui.painter(),
+            style.anchor.resolve(playfield, ui.max_rect()),
+            style.anchor.alignment,
+            &mut label,
+            egui::FontId::proportional(style.font_size * self.scale),
+            style.color,
+        );
+        self.composition_label = Some(label);
+    }
+
+    // Note rate bars, growing up from the bottom of the meter
+    fn draw_activity_meter(&self, ui: &mut egui::Ui, frame: &Frame, playfield: Rect) {
+        let Some(style) = &self.activity_meter else {
+            return;
+        };
+        let size = Vec2::new(
+            self.column_width() * frame.keycount as f32,
+            style.height * self.scale,
+        );
+        let anchor = style.anchor.resolve(playfield, ui.max_rect());
+        let meter = style.anchor.alignment.anchor_size(anchor, size);
+        for (column, nps) in self.column_nps.iter().enumerate().take(frame.keycount) {
+            let fill = (nps / style.max_nps).clamp(0.0, 1.0);
+            if fill <= 0.0 {
+                continue;
+            }
+            let x = meter.min.x + column as f32 * self.column_width();
+            let bar = Rect::from_min_max(
+                pos2(x + 2.0, meter.max.y - meter.height() * fill),
+                pos2(x + self.column_width() - 2.0, meter.max.y),
+            );
+            let color = match &style.gradient {
+                Some(gradient) => gradient.at(fill),
+                None => style.color,
+            };
+            ui.painter().rect_filled(bar, 0.0, color);
         }
+    }
 
-        let judgment_line_y = position.y + total_height - 100.0;
-        ui.painter().line_segment(
+    // Outlines of one stage and its visible objects, see `set_debug_overlay`
+    fn draw_debug_overlay(&self, ui: &mut egui::Ui, frame: &Frame, hit_objects: &[HitObject]) {
+        let painter = ui.painter();
+        let stroke = |color| egui::Stroke::new(1.0, color);
+        let label = |pos, text: String, color| {
+            self.draw_text(
+                painter,
+                pos,
+                egui::Align2::LEFT_TOP,
+                text,
+                egui::FontId::monospace(10.0),
+                color,
+            );
+        };
+
+        let width = self.column_width() * frame.keycount as f32;
+        let bottom = frame.position.y + frame.total_height;
+        for column in 0..=frame.keycount {
+            let x = frame.position.x + column as f32 * self.column_width();
+            painter.line_segment(
+                [pos2(x, frame.position.y), pos2(x, bottom)],
+                stroke(DEBUG_COLUMN_COLOR),
+            );
+        }
+        painter.line_segment(
             [
-                egui::pos2(position.x, judgment_line_y),
-                egui::pos2(position.x + total_width, judgment_line_y),
+                pos2(frame.position.x, frame.judgment_line_y),
+                pos2(frame.position.x + width, frame.judgment_line_y),
             ],
-            egui::Stroke::new(2.0, egui::Color32::WHITE),
+            stroke(DEBUG_JUDGMENT_COLOR),
         );
 
-        // Only process hit objects if there are any
-        if !hit_objects.is_empty() {
-            // Pre-calculate time bounds for visible objects
-            let visible_start_time = current_time - scroll_time_ms as f64 * 2.0;
-            let visible_end_time = current_time + scroll_time_ms as f64 * 0.5;
-            
-            // Draw hold notes first
-            for hit_object in hit_objects
-                .iter()
-                .filter(|h| {
-                    let obj_time = h.start_time / speed;
-                    // For hold notes, also check the end time
-                    let obj_end_time = if let HitObjectKind::Hold(hold) = &h.kind {
-                        (h.start_time + hold.duration) / speed
+        // Filled by `draw_objects` for this stage
+        for &(index, is_body) in &self.scratch_visible {
+            let hit_object = &hit_objects[index];
+            let (rect, color) = match &hit_object.kind {
+                HitObjectKind::Hold(h) if is_body => {
+                    let column = column_for_x(h.pos_x, frame.keycount);
+                    let note_width = self.column_note_size(column) * 0.8;
+                    let x = self.object_x(frame, column) + (self.column_width() - note_width) / 2.0;
+                    let start_y = frame.y_for(hit_object.start_time);
+                    let end_y = frame.y_for(hit_object.start_time + h.duration);
+                    let lift = self.hit_offset(column);
+                    let line_y = frame.judgment_line_y;
+                    let start_y = if frame.is_beyond(start_y, line_y) {
+                        line_y
                     } else {
-                        obj_time
+                        start_y
                     };
-                    // Show if either start or end is visible
-                    (obj_time >= visible_start_time && obj_time <= visible_end_time) ||
-                    (obj_end_time >= visible_start_time && obj_end_time <= visible_end_time) ||
-                    (obj_time <= visible_start_time && obj_end_time >= visible_end_time) // Note spans the visible area
-                })
-                .filter(|h| matches!(h.kind, HitObjectKind::Hold(_)))
-            {
-                if let HitObjectKind::Hold(h) = &hit_object.kind {
-                    let column = (h.pos_x / 512.0 * keycount as f32) as usize % keycount;
-                    let x_pos = position.x + column as f32 * self.column_width;
+                    let rect = Rect::from_two_pos(
+                        pos2(x, end_y - lift),
+                        pos2(x + note_width, start_y - lift),
+                    );
+                    (rect, DEBUG_HOLD_COLOR)
+                }
+                HitObjectKind::Circle(h) if !is_body => {
+                    let column = column_for_x(h.pos.x, frame.keycount);
+                    let center = pos2(
+                        self.object_x(frame, column) + self.column_width() / 2.0,
+                        frame.note_y(index, hit_object) - self.hit_offset(column),
+                    );
+                    let rect = Rect::from_center_size(center, self.note_bounds(column));
+                    (rect, DEBUG_NOTE_COLOR)
+                }
+                _ => continue,
+            };
+            painter.rect_stroke(rect, 0.0, stroke(color), egui::StrokeKind::Inside);
+            label(rect.left_top(), index.to_string(), color);
+        }
+    }
+
+    // Size of a note head as drawn with the current shape
+    fn note_bounds(&self, column: usize) -> Vec2 {
+        self.column_shape(column)
+            .bounds(self.column_note_size(column))
+    }
+
+    // Measure lines of one stage, from the edge notes come from to the
+    // judgment line
+    fn draw_barlines(&self, ui: &mut egui::Ui, frame: &Frame) {
+        let Some(style) = self.barlines else {
+            return;
+        };
+        let x = frame.position.x;
+        let width = self.column_width() * frame.keycount as f32;
+        let stroke = egui::Stroke::new(style.thickness * self.scale, style.color);
+        for y in self.barline_ys(frame) {
+            ui.painter()
+                .line_segment([pos2(x, y), pos2(x + width, y)], stroke);
+        }
+    }
 
-                    let note_time = hit_object.start_time / speed + scroll_time_ms as f64;
-                    let end_time =
-                        (hit_object.start_time + h.duration) / speed + scroll_time_ms as f64;
+    // y of the measure lines `draw_barlines` draws
+    fn barline_ys<'a>(&'a self, frame: &'a Frame) -> impl Iterator<Item = f32> + 'a {
+        barline_times(&self.measures, frame.visible_start, frame.visible_end)
+            .take(MAX_BARLINES)
+            .map(|time| frame.y_for(time))
+            .filter(|y| {
+                !frame.is_beyond(*y, frame.judgment_line_y) && !frame.is_beyond(frame.spawn_y(), *y)
+            })
+    }
 
-                    let time_diff = note_time - current_time;
-                    let end_time_diff = end_time - current_time;
+    // Beat lines of the stage preview, brighter on every fourth beat
+    fn draw_beat_lines(&self, ui: &mut egui::Ui, frame: &Frame) {
+        let width = self.column_width() * frame.keycount as f32;
+        let beats = (PREVIEW_SCROLL_MS as f64 / PREVIEW_BEAT_MS) as usize;
+        for beat in 1..=beats {
+            let y = frame.y_for(beat as f64 * PREVIEW_BEAT_MS);
+            let gray = if beat % 4 == 0 { 110 } else { 60 };
+            ui.painter().line_segment(
+                [pos2(frame.position.x, y), pos2(frame.position.x + width, y)],
+                egui::Stroke::new(1.0, Color32::from_gray(gray)),
+            );
+        }
+    }
 
-                    let y_pos =
-                        judgment_line_y - (time_diff as f32 / scroll_time_ms) * total_height;
-                    let end_y_pos = judgment_line_y
-                        - (end_time_diff as f32 / scroll_time_ms) * total_height;
+    // Visual only: pushes notes up their column until they are at least the
+    // minimum gap above the previous one. The earliest notes stay in place.
+    // Fills `nudged_y`, a buffer reused across frames.
+    fn nudge_notes(
+        &self,
+        frame: &Frame,
+        hit_objects: &[HitObject],
+        nudged_y: &mut Vec<(usize, f32)>,
+    ) {
+        nudged_y.clear();
+        let Some(gap) = self.min_visual_gap else {
+            return;
+        };
+        let gap = gap * self.scale;
+        let mut last_y = [None::<f32>; MAX_KEYCOUNT];
 
-                    if end_y_pos <= judgment_line_y {
-                        self.render_hold(ui, x_pos, y_pos, end_y_pos, judgment_line_y);
-                    }
-                }
+        for (index, hit_object) in hit_objects.iter().enumerate() {
+            let HitObjectKind::Circle(h) = &hit_object.kind else {
+                continue;
+            };
+            let y = frame.y_for(hit_object.start_time);
+            if !frame.is_note_visible(hit_object) || frame.is_beyond(y, frame.judgment_line_y) {
+                continue;
             }
+            let column = column_for_x(h.pos.x, frame.keycount);
+            let sign = frame.direction.sign();
+            let y = match last_y[column] {
+                Some(last) if (last - y) * sign < gap => {
+                    nudged_y.push((index, last - gap * sign));
+                    last - gap * sign
+                }
+                _ => y,
+            };
+            last_y[column] = Some(y);
+        }
+    }
 
-            // Then draw regular notes
-            for hit_object in hit_objects
-                .iter()
-                .filter(|h| {
-                    let obj_time = h.start_time / speed;
-                    obj_time >= visible_start_time && obj_time <= visible_end_time
-                })
-            {
-                let note_time = hit_object.start_time / speed + scroll_time_ms as f64;
-                let time_diff = note_time - current_time;
-                let y_pos =
-                    judgment_line_y - (time_diff as f32 / scroll_time_ms) * total_height;
-
-                if y_pos <= judgment_line_y {
-                    let x_pos = match &hit_object.kind {
-                        HitObjectKind::Circle(h) => {
-                            let column =
-                                (h.pos.x / 512.0 * keycount as f32) as usize % keycount;
-                            position.x + column as f32 * self.column_width
-                        }
-                        HitObjectKind::Hold(h) => {
-                            let column =
-                                (h.pos_x / 512.0 * keycount as f32) as usize % keycount;
-                            position.x + column as f32 * self.column_width
-                        }
-                        _ => continue,
-                    };
+    // Hold bodies and notes in the layer order, each followed by its hook.
+    // Interleaved objects run both hooks at the end.
+    fn draw_objects(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame: &Frame,
+        hit_objects: &[HitObject],
+        context: &LayerContext,
+    ) {
+        // (index, is hold body) of the visible objects, bodies first. Kept
+        // for the debug overlay, which then only goes over these
+        let mut visible = std::mem::take(&mut self.scratch_visible);
+        visible.clear();
+        let objects = hit_objects.iter().enumerate();
+        let holds = objects.clone().filter(|(_, h)| frame.is_hold_visible(h));
+        visible.extend(holds.map(|(i, _)| (i, true)));
+        let notes = objects.filter(|(_, h)| frame.is_note_visible(h));
+        visible.extend(notes.map(|(i, _)| (i, false)));
+        let body_count = visible.partition_point(|(_, is_body)| *is_body);
+        let (bodies, heads) = visible.split_at(body_count);
 
-                    // Draw notes when they start entering the screen (considering note height)
-                    let note_height = self.note_size * 0.25; // Approximate note height
-                    if y_pos >= -note_height {
-                        self.draw_note(ui, x_pos, y_pos);
+        match self.layer_order {
+            LayerOrder::HoldsBelowNotes => {
+                for &(i, _) in bodies {
+                    self.draw_hold_object(ui, frame, i, &hit_objects[i]);
+                }
+                self.run_layer_hook(ui, Layer::HoldBodies, context);
+                for &(i, _) in heads {
+                    self.draw_note_object(ui, frame, i, &hit_objects[i]);
+                }
+                self.run_layer_hook(ui, Layer::Notes, context);
+            }
+            LayerOrder::NotesBelowHolds => {
+                for &(i, _) in heads {
+                    self.draw_note_object(ui, frame, i, &hit_objects[i]);
+                }
+                self.run_layer_hook(ui, Layer::Notes, context);
+                for &(i, _) in bodies {
+                    self.draw_hold_object(ui, frame, i, &hit_objects[i]);
+                }
+                self.run_layer_hook(ui, Layer::HoldBodies, context);
+            }
+            LayerOrder::Interleaved => {
+                // A hold's body goes right below its own head.
+                // Unstable sorts don't allocate; the index keeps ties in order
+                visible.sort_unstable_by(|(a, a_body), (b, b_body)| {
+                    let start = |index: usize| hit_objects[index].start_time;
+                    start(*a)
+                        .total_cmp(&start(*b))
+                        .then(b_body.cmp(a_body))
+                        .then(a.cmp(b))
+                });
+                for &(index, is_body) in &visible {
+                    let hit_object = &hit_objects[index];
+                    if is_body {
+                        self.draw_hold_object(ui, frame, index, hit_object);
+                    } else {
+                        self.draw_note_object(ui, frame, index, hit_object);
                     }
                 }
+                self.run_layer_hook(ui, Layer::HoldBodies, context);
+                self.run_layer_hook(ui, Layer::Notes, context);
             }
         }
+        self.scratch_visible = visible;
     }
 }
+
+// Placeholders of the stage preview: a staircase of notes on the lower half,
+// then holds in every other column
+fn preview_objects(keycount: usize) -> Vec<HitObject> {
+    let step = 2.0 * PREVIEW_BEAT_MS / keycount.max(1) as f64;
+    let notes = (0..keycount).map(|column| HitObject {
+        start_time: PREVIEW_BEAT_MS / 2.0 + column as f64 * step,
+        kind: HitObjectKind::Circle(HitObjectCircle {
+            pos: Pos::new(column_center_x(column, keycount), 192.0),
+            new_combo: false,
+            combo_offset: 0,
+        }),
+        samples: Vec::new(),
+    });
+    let holds = (0..keycount).step_by(2).map(|column| HitObject {
+        start_time: 2.5 * PREVIEW_BEAT_MS,
+        kind: HitObjectKind::Hold(HitObjectHold {
+            pos_x: column_center_x(column, keycount),
+            duration: PREVIEW_BEAT_MS,
+        }),
+        samples: Vec::new(),
+    });
+    notes.chain(holds).collect()
+}
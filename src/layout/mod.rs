@@ -1 +1,2 @@
 pub mod mania;
+pub mod standard;
@@ -0,0 +1,66 @@
+//! AR/CS conversions matching osu!stable. The standard renderer must derive
+//! its timing and sizes from these helpers only.
+
+/// osu!'s piecewise-linear mapping of a 0..=10 difficulty value:
+/// `min` at 0, `mid` at 5 and `max` at 10, linear in between (and beyond).
+pub fn difficulty_range(value: f32, min: f32, mid: f32, max: f32) -> f32 {
+    if value > 5.0 {
+        mid + (max - mid) * (value - 5.0) / 5.0
+    } else if value < 5.0 {
+        mid - (mid - min) * (5.0 - value) / 5.0
+    } else {
+        mid
+    }
+}
+
+/// Time in ms an object is visible before its hit time:
+/// 1800 at AR0, 1200 at AR5, 450 at AR10.
+pub fn preempt_ms(ar: f32) -> f32 {
+    difficulty_range(ar, 1800.0, 1200.0, 450.0)
+}
+
+/// Duration in ms of the fade-in at the start of the preempt:
+/// 1200 at AR0, 800 at AR5, 300 at AR10.
+pub fn fade_in_ms(ar: f32) -> f32 {
+    difficulty_range(ar, 1200.0, 800.0, 300.0)
+}
+
+/// Circle radius in osu! pixels: `54.4 - 4.48 * CS`.
+pub fn circle_radius(cs: f32) -> f32 {
+    54.4 - 4.48 * cs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn preempt_and_fade_in() {
+        for (ar, preempt, fade_in) in [
+            (0.0, 1800.0, 1200.0),
+            (5.0, 1200.0, 800.0),
+            (9.3, 555.0, 370.0),
+            (10.0, 450.0, 300.0),
+        ] {
+            assert_close(preempt_ms(ar), preempt);
+            assert_close(fade_in_ms(ar), fade_in);
+        }
+    }
+
+    #[test]
+    fn radius() {
+        for (cs, radius) in [(2.0, 45.44), (4.0, 36.48), (7.0, 23.04)] {
+            assert_close(circle_radius(cs), radius);
+        }
+    }
+
+    #[test]
+    fn range_extends_past_ten() {
+        assert_close(preempt_ms(11.0), 300.0);
+        assert_close(preempt_ms(-1.0), 1920.0);
+    }
+}
@@ -1,16 +1,166 @@
+mod activity;
+#[cfg(feature = "alloc-audit")]
+pub mod alloc_audit;
+pub mod assets;
+pub mod calibration;
+pub mod camera;
+mod defaults;
+pub mod description;
+pub mod difficulty;
+pub mod effects;
+pub mod events;
+pub mod fingerprint;
+pub mod format;
+pub mod gradient;
+pub mod hash;
+pub mod hitsounds;
+#[cfg(feature = "html-export")]
+pub mod html;
+pub mod images;
+pub mod judge;
 pub mod layout;
+pub mod lint;
+pub mod overview;
+pub mod rate;
+mod replay;
+pub mod report;
+pub mod rng;
+pub mod scatter;
+pub mod score;
+pub mod scroll;
+pub mod sections;
+#[cfg(feature = "osk")]
+pub mod skin;
+pub mod snap;
+pub mod stats;
+#[cfg(feature = "settings-store")]
+pub mod store;
+pub mod storyboard;
+mod timeline;
+mod trace;
+pub mod transform;
+#[cfg(feature = "widgets")]
+pub mod ui;
 
-use crate::layout::mania::{ManiaRenderer, NoteStyle};
+use crate::activity::ColumnActivity;
+use crate::assets::MapAssets;
+use crate::calibration::OffsetCalibration;
+use crate::camera::{CameraKeyframe, CameraTrack, Easing};
+use crate::description::FrameDescription;
+use crate::difficulty::{DifficultyOverride, DifficultySettings};
+use crate::effects::EffectClock;
+use crate::events::{
+    CacheKind, CallbackKind, Event, EventQueue, EventTiming, FrameHitch, GraceSource, PassedNote,
+    PassedNoteTracker, PassedNotes,
+};
+use crate::fingerprint::ChartFingerprint;
+use crate::hitsounds::{HitsoundIssue, ResolvedSample, DEFAULT_VOLUME_THRESHOLD};
+use crate::images::ImageProvider;
+use crate::judge::windows::{WindowMode, Windows};
+use crate::layout::mania::{
+    column_center_x, column_for_x, keycount_for, AccessibilityOptions, ActivityMeterStyle,
+    BarlineStyle, CompositionTextStyle, FitPolicy, FrameStats, FrameTransform, GhostTapStyle,
+    HandColoring, HoldBodyPattern, HoldBodyStyle, KeycountChangePolicy, LaneCover, Layer,
+    LayerHook, LayerOrder, Letterbox, ManiaRenderer, Measures, MotionBlur, NoteShape, NoteStyle,
+    ReceptorStyle, ScrollDirection, ScrollTransform, SnapColoring, TimingTextStyle, WarmUpTimings,
+    WindowBandStyle, MAX_KEYCOUNT,
+};
+use crate::lint::{Lint, LintConfig};
+use crate::overview::OverviewMark;
+use crate::rate::RateRamp;
+use crate::replay::{JudgmentLog, Recorded, Replay};
+use crate::report::{LoadIssue, LoadReport};
+use crate::rng::SeededRng;
+use crate::scatter::{ScatterStyle, TimedError};
+use crate::sections::{EndBehavior, Section, StartBehavior};
+use crate::snap::Divisor;
+use crate::stats::{ColumnStats, Composition, LnPercent, SessionStats};
+#[cfg(feature = "settings-store")]
+use crate::store::{MapSettings, SavedShape, SettingsStore};
+use crate::storyboard::BackgroundEvent;
+use crate::timeline::Timeline;
+use crate::trace::{trace_span, trace_warn};
+use crate::transform::{Arrangement, ColumnDrag, ColumnSwap, DEFAULT_HOLD_EPSILON_MS};
 use rosu_map::section::general::GameMode;
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
 use rosu_map::Beatmap;
-use std::time::Instant;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+/// `Player` is `Send` and `Sync`: it can be built, configured and queried on
+/// any thread. Only the `render*` methods must run on the UI thread, as they
+/// paint into an `egui::Ui`; image note shapes are only loaded, by the `Ui`'s
+/// context, when first painted.
 pub struct Player {
     beatmap: Beatmap,
+    // Beatmap objects with the active transforms applied
+    hit_objects: Vec<HitObject>,
+    // Objects without the arrangement, drawn next to `hit_objects` when comparing
+    comparison: bool,
+    original_objects: Vec<HitObject>,
+    seed: u64,
+    arrangement: Arrangement,
+    mirror: bool,
+    special_lane_excluded: bool,
+    column_mapping: Vec<usize>,
+    swap_duration_ms: Option<f64>,
+    column_swap: Option<ColumnSwap>,
+    layout_edit: bool,
+    column_drag: Option<ColumnDrag>,
+    camera_track: CameraTrack,
+    hitsound_threshold: i32,
+    hitsound_markers: bool,
+    hitsound_issues: Vec<HitsoundIssue>,
+    column_stats: Vec<ColumnStats>,
+    composition: Composition,
+    ln_percent: LnPercent,
+    load_report: LoadReport,
+    strict_columns: bool,
+    hold_epsilon_ms: f64,
+    parallel_load: bool,
+    pending_caches: PendingCaches,
+    passed_notes: PassedNoteTracker,
+    events: EventQueue,
+    event_timing: EventTiming,
+    start_behavior: StartBehavior,
+    ghost_taps: usize,
+    replay: Option<Replay>,
+    // Whether `Event::Finished` was emitted for the current run
+    finished: bool,
+    // Index into `sections` of the last frame
+    current_section: Option<usize>,
+    column_activity: ColumnActivity,
+    hidden_column_events: bool,
+    keycount_policy: KeycountChangePolicy,
+    seek_snapping: Option<Divisor>,
+    beatmap_hash: Option<String>,
+    background_events: Vec<BackgroundEvent>,
+    assets: Option<MapAssets>,
+    sections: Vec<Section>,
     renderer: GameModeRenderer,
     start_time: Instant,
+    timeline: Timeline,
+    max_fps: Option<f32>,
+    last_frame: Option<Instant>,
     speed: f64,
+    rate_ramp: Option<RateRamp>,
+    scrubbing: bool,
+    // Judgment line map time and clock time of the last frame, and its
+    // distance to the one before, for hitch detection
+    last_line_time: Option<f64>,
+    last_frame_clock: Option<f64>,
+    frame_delta_ms: Option<f64>,
+    hitch_threshold_ms: Option<f64>,
+    hitch: bool,
+    #[cfg(feature = "alloc-audit")]
+    frame_allocations: usize,
     scroll_time_ms: f32,
+    scroll_animation_ms: Option<f64>,
+    scroll_animation: Option<ScrollAnimation>,
+    end_behavior: EndBehavior,
+    difficulty_override: DifficultyOverride,
 }
 
 enum GameModeRenderer {
@@ -21,12 +171,255 @@ enum GameModeRenderer {
     // Catch(CatchRenderer),
 }
 
+pub struct PlayerBuilder {
+    beatmap: Beatmap,
+    column_width: f32,
+    note_size: f32,
+    height: f32,
+    seed: Option<u64>,
+    strict_columns: bool,
+    hold_epsilon_ms: f64,
+    parallel_load: bool,
+    // Object lists built by tools rather than parsed from a .osu file
+    normalize_holds: bool,
+    beatmap_hash: Option<String>,
+    background_events: Vec<BackgroundEvent>,
+    ignored_events: usize,
+    assets: Option<MapAssets>,
+    start_behavior: StartBehavior,
+}
+
+const MIN_SCROLL_TIME_MS: f32 = 1.0;
+
+// Notes closer than this to the reference time don't count as next/previous
+const SEEK_NOTE_TOLERANCE_MS: f64 = 1.0;
+
+// Max distance, in osu! pixels, from a canonical column center before
+// `strict_columns` reports an object.
+const COLUMN_TOLERANCE: f32 = 2.0;
+
+// Drag handles drawn at the top of each column in layout edit mode
+const LAYOUT_HANDLE_HEIGHT: f32 = 24.0;
+
+impl PlayerBuilder {
+    pub fn new(beatmap: Beatmap) -> Self {
+        Self {
+            beatmap,
+            column_width: 100.0,
+            note_size: 100.0,
+            height: 800.0,
+            seed: None,
+            strict_columns: false,
+            hold_epsilon_ms: DEFAULT_HOLD_EPSILON_MS,
+            parallel_load: false,
+            normalize_holds: false,
+            beatmap_hash: None,
+            background_events: Vec::new(),
+            ignored_events: 0,
+            assets: None,
+            start_behavior: StartBehavior::default(),
+        }
+    }
+
+    /// Parses a .osu file, remembering its hash for `Player::beatmap_hash`.
+    pub fn from_bytes(osu_bytes: &[u8]) -> Option<Self> {
+        let beatmap = Beatmap::from_bytes(osu_bytes).ok()?;
+        let mut builder = Self::new(beatmap);
+        builder.beatmap_hash = Some(hash::beatmap_hash(osu_bytes));
+        (builder.background_events, builder.ignored_events) =
+            storyboard::parse_background_events(osu_bytes);
+        Some(builder)
+    }
+
+    /// `from_bytes` with the file at `path`, also resolving the audio and
+    /// background files next to it, see `Player::assets`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).ok()?;
+        let mut builder = Self::from_bytes(&bytes)?;
+        builder.assets = Some(MapAssets::resolve(path, &builder.beatmap));
+        Some(builder)
+    }
+
+    /// Starts from a bare object list, e.g. a synthetic chart, instead of a
+    /// parsed .osu file. `circle_size` is the keycount in mania. Hold timings
+    /// are normalized and the objects sorted by start time, see
+    /// `LoadIssue::HoldNormalized`.
+    pub fn from_hit_objects(mode: GameMode, hit_objects: Vec<HitObject>, circle_size: f32) -> Self {
+        let beatmap = Beatmap {
+            mode,
+            circle_size,
+            hit_objects,
+            ..Default::default()
+        };
+        Self {
+            normalize_holds: true,
+            ..Self::new(beatmap)
+        }
+    }
+
+    pub fn column_width(mut self, column_width: f32) -> Self {
+        self.column_width = column_width;
+        self
+    }
+
+    pub fn note_size(mut self, note_size: f32) -> Self {
+        self.note_size = note_size;
+        self
+    }
+
+    pub fn height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Seed for every randomized transform. Defaults to one derived from the
+    /// system time, readable afterwards through `Player::seed`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Reports every mania object whose x isn't within a couple of pixels of
+    /// a canonical column center in the `LoadReport`.
+    pub fn strict_columns(mut self, strict_columns: bool) -> Self {
+        self.strict_columns = strict_columns;
+        self
+    }
+
+    /// Holds shorter than this are treated as regular notes. Defaults to
+    /// `DEFAULT_HOLD_EPSILON_MS`.
+    pub fn hold_epsilon(mut self, ms: f64) -> Self {
+        self.hold_epsilon_ms = ms;
+        self
+    }
+
+    /// Builds the caches derived from the object list (column stats,
+    /// composition, sections, hitsound issues) on background threads, for
+    /// marathon maps. `build` returns once the note index is ready and the
+    /// player renders the notes right away; each cache is used from the
+    /// first frame after it's built, emitting `Event::CacheReady`, see
+    /// `Player::caches_ready`. They match the ones of a serial load. Also
+    /// applies when the object list is re-derived later, e.g. by
+    /// `Player::set_beatmap`.
+    pub fn parallel_load(mut self, parallel_load: bool) -> Self {
+        self.parallel_load = parallel_load;
+        self
+    }
+
+    /// Where the clock starts, see `Player::set_start_behavior`.
+    pub fn start_behavior(mut self, behavior: StartBehavior) -> Self {
+        self.start_behavior = behavior;
+        self
+    }
+
+    pub fn build(self) -> Option<Player> {
+        let _span = trace_span!("beatmap_load");
+        let seed = self.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        });
+
+        let mut player = Player::with_seed(
+            self.beatmap,
+            self.column_width,
+            self.note_size,
+            self.height,
+            seed,
+        )?;
+        player.strict_columns = self.strict_columns;
+        player.hold_epsilon_ms = self.hold_epsilon_ms;
+        player.parallel_load = self.parallel_load;
+        player.beatmap_hash = self.beatmap_hash;
+        player.background_events = self.background_events;
+        player.assets = self.assets;
+        if self.ignored_events > 0 {
+            player.load_report.push(LoadIssue::EventsIgnored {
+                count: self.ignored_events,
+            });
+        }
+        defaults::normalize_defaults(&mut player.beatmap, &mut player.load_report);
+        if self.normalize_holds {
+            transform::normalize_holds(&mut player.beatmap.hit_objects, &mut player.load_report);
+        }
+        player.check_columns();
+        player.check_holds();
+        player.apply_transforms();
+        player.sync_difficulty();
+        if self.start_behavior != StartBehavior::FromZero {
+            player.set_start_behavior(self.start_behavior);
+            player.reset_time();
+        }
+        Some(player)
+    }
+}
+
 impl Player {
     pub fn new(beatmap: Beatmap, column_width: f32, note_size: f32, height: f32) -> Option<Self> {
+        PlayerBuilder::new(beatmap)
+            .column_width(column_width)
+            .note_size(note_size)
+            .height(height)
+            .build()
+    }
+
+    /// `new` from the bytes of a .osu file. `None` if they don't parse or
+    /// the mode isn't supported.
+    pub fn from_bytes(
+        osu_bytes: &[u8],
+        column_width: f32,
+        note_size: f32,
+        height: f32,
+    ) -> Option<Self> {
+        PlayerBuilder::from_bytes(osu_bytes)?
+            .column_width(column_width)
+            .note_size(note_size)
+            .height(height)
+            .build()
+    }
+
+    /// `new` from the .osu file at `path`, see `PlayerBuilder::from_path`.
+    pub fn from_path(
+        path: impl AsRef<Path>,
+        column_width: f32,
+        note_size: f32,
+        height: f32,
+    ) -> Option<Self> {
+        PlayerBuilder::from_path(path)?
+            .column_width(column_width)
+            .note_size(note_size)
+            .height(height)
+            .build()
+    }
+
+    pub fn builder(beatmap: Beatmap) -> PlayerBuilder {
+        PlayerBuilder::new(beatmap)
+    }
+
+    /// Plays a bare object list with the default sizes. Use
+    /// `PlayerBuilder::from_hit_objects` to configure the player.
+    pub fn from_hit_objects(
+        mode: GameMode,
+        hit_objects: Vec<HitObject>,
+        circle_size: f32,
+    ) -> Option<Self> {
+        PlayerBuilder::from_hit_objects(mode, hit_objects, circle_size).build()
+    }
+
+    fn with_seed(
+        beatmap: Beatmap,
+        column_width: f32,
+        note_size: f32,
+        height: f32,
+        seed: u64,
+    ) -> Option<Self> {
         // Créer le renderer approprié en fonction du mode de jeu
         let renderer = match beatmap.mode {
             GameMode::Mania => {
-                GameModeRenderer::Mania(ManiaRenderer::with_sizes(column_width, note_size, height))
+                let mut mania = ManiaRenderer::with_sizes(column_width, note_size, height);
+                mania.set_overall_difficulty(beatmap.overall_difficulty);
+                GameModeRenderer::Mania(mania)
             }
             // TODO: Ajouter d'autres modes
             // GameMode::Osu => GameModeRenderer::Standard(...),
@@ -34,42 +427,1440 @@ impl Player {
             // GameMode::Catch => GameModeRenderer::Catch(...),
             _ => return None, // Mode non supporté
         };
+        let start_time = Instant::now();
+        let scroll = ScrollTransform {
+            speed: 1.0,
+            scroll_time_ms: 1000.0,
+            rate_ramp: None,
+        };
+
+        Some(Self {
+            hit_objects: beatmap.hit_objects.clone(),
+            comparison: false,
+            original_objects: Vec::new(),
+            beatmap,
+            seed,
+            arrangement: Arrangement::default(),
+            mirror: false,
+            special_lane_excluded: false,
+            column_mapping: Vec::new(),
+            swap_duration_ms: None,
+            column_swap: None,
+            layout_edit: false,
+            column_drag: None,
+            camera_track: CameraTrack::default(),
+            hitsound_threshold: DEFAULT_VOLUME_THRESHOLD,
+            hitsound_markers: false,
+            hitsound_issues: Vec::new(),
+            column_stats: Vec::new(),
+            composition: Composition::default(),
+            ln_percent: LnPercent::default(),
+            load_report: LoadReport::default(),
+            strict_columns: false,
+            hold_epsilon_ms: DEFAULT_HOLD_EPSILON_MS,
+            parallel_load: false,
+            pending_caches: PendingCaches::default(),
+            passed_notes: PassedNoteTracker::default(),
+            events: EventQueue::default(),
+            event_timing: EventTiming::default(),
+            start_behavior: StartBehavior::default(),
+            ghost_taps: 0,
+            replay: None,
+            finished: false,
+            current_section: None,
+            column_activity: ColumnActivity::default(),
+            hidden_column_events: true,
+            keycount_policy: KeycountChangePolicy::default(),
+            seek_snapping: None,
+            beatmap_hash: None,
+            background_events: Vec::new(),
+            assets: None,
+            sections: Vec::new(),
+            renderer,
+            start_time,
+            timeline: Timeline::new(Instant::now(), start_time, scroll),
+            max_fps: None,
+            last_frame: None,
+            speed: scroll.speed,
+            rate_ramp: scroll.rate_ramp,
+            scrubbing: false,
+            last_line_time: None,
+            last_frame_clock: None,
+            frame_delta_ms: None,
+            hitch_threshold_ms: None,
+            hitch: false,
+            #[cfg(feature = "alloc-audit")]
+            frame_allocations: 0,
+            scroll_time_ms: scroll.scroll_time_ms,
+            scroll_animation_ms: None,
+            scroll_animation: None,
+            end_behavior: EndBehavior::default(),
+            difficulty_override: DifficultyOverride::default(),
+        })
+    }
+
+    /// Swaps the beatmap in place, keeping the clock and every setting.
+    /// Returns `false`, leaving the current map, if the mode doesn't match
+    /// the player's renderer or the keycount changes under
+    /// `KeycountChangePolicy::Reject`. Per-column settings follow the
+    /// keycount change policy.
+    pub fn set_beatmap(&mut self, beatmap: Beatmap) -> bool {
+        let supported = match &self.renderer {
+            GameModeRenderer::Mania(_) => beatmap.mode == GameMode::Mania,
+        };
+        if !supported {
+            return false;
+        }
+
+        let previous_keycount = self.keycount();
+        let previous = std::mem::replace(&mut self.beatmap, beatmap);
+        let keycount = self.keycount();
+        if keycount != previous_keycount {
+            if self.keycount_policy == KeycountChangePolicy::Reject {
+                self.beatmap = previous;
+                return false;
+            }
+            // Offsets of a running swap are per column of the previous map
+            self.column_swap = None;
+            self.column_drag = None;
+            match &mut self.renderer {
+                GameModeRenderer::Mania(mania) => {
+                    mania.resize_columns(keycount, self.keycount_policy);
+                }
+            }
+        }
+
+        self.beatmap_hash = None;
+        self.background_events.clear();
+        self.assets = None;
+        self.ghost_taps = 0;
+        self.replay = None;
+        self.load_report = LoadReport::default();
+        defaults::normalize_defaults(&mut self.beatmap, &mut self.load_report);
+        self.check_columns();
+        self.check_holds();
+        self.apply_transforms();
+        self.sync_difficulty();
+        // Judgments point at the previous map's objects
+        self.clear_judgments();
+        true
+    }
+
+    /// `set_beatmap` from the bytes of a .osu file, keeping its hash.
+    pub fn set_beatmap_bytes(&mut self, osu_bytes: &[u8]) -> bool {
+        let Ok(beatmap) = Beatmap::from_bytes(osu_bytes) else {
+            return false;
+        };
+        if !self.set_beatmap(beatmap) {
+            return false;
+        }
+        self.beatmap_hash = Some(hash::beatmap_hash(osu_bytes));
+        let ignored_events;
+        (self.background_events, ignored_events) = storyboard::parse_background_events(osu_bytes);
+        if ignored_events > 0 {
+            self.load_report.push(LoadIssue::EventsIgnored {
+                count: ignored_events,
+            });
+        }
+        self.sync_background();
+        true
+    }
+
+    /// MD5 of the .osu file, as osu! computes it. Only known for maps
+    /// loaded from bytes.
+    pub fn beatmap_hash(&self) -> Option<&str> {
+        self.beatmap_hash.as_deref()
+    }
+
+    /// Settings `save_settings` stores for this map.
+    #[cfg(feature = "settings-store")]
+    pub fn map_settings(&self) -> MapSettings {
+        let GameModeRenderer::Mania(mania) = &self.renderer;
+        let style = mania.note_style();
+        MapSettings {
+            speed: self.speed,
+            scroll_time_ms: self.scroll_time_ms,
+            note_color: style.color,
+            hold_body_color: style.hold_body_color,
+            hold_cap_color: style.hold_cap_color,
+            shape: SavedShape::from_shape(&style.shape),
+        }
+    }
+
+    /// Remembers the speed, scroll time and note style for this map.
+    /// Fails if the map wasn't loaded from bytes.
+    #[cfg(feature = "settings-store")]
+    pub fn save_settings(&self, store: &SettingsStore) -> std::io::Result<()> {
+        let hash = self.beatmap_hash().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "unknown beatmap hash")
+        })?;
+        store.save(hash, &self.map_settings())
+    }
+
+    /// Writes a self-contained HTML page to `path` for scrubbing the chart
+    /// in a browser, with the current sizes, scroll time, speed and colors.
+    #[cfg(feature = "html-export")]
+    pub fn export_html(
+        &self,
+        path: impl AsRef<Path>,
+        options: &html::HtmlExportOptions,
+    ) -> std::io::Result<()> {
+        let keycount = self.keycount();
+        let chart = html::chart_json(&self.hit_objects, &self.beatmap.control_points, keycount);
+        let stage = match &self.renderer {
+            GameModeRenderer::Mania(mania) => {
+                mania.html_stage(keycount, self.scroll_time_ms, self.speed)
+            }
+        };
+        let title = options.title.as_deref().unwrap_or(&self.beatmap.version);
+        std::fs::write(path, html::page(title, &chart, &stage))
+    }
+
+    /// Reapplies what `save_settings` stored for this map. Returns `false`
+    /// if there is nothing usable to apply.
+    #[cfg(feature = "settings-store")]
+    pub fn apply_saved_settings(&mut self, store: &SettingsStore) -> bool {
+        let Some(settings) = self.beatmap_hash().and_then(|hash| store.load(hash)) else {
+            return false;
+        };
+        self.set_speed(settings.speed);
+        self.set_scroll_time(settings.scroll_time_ms);
+        let GameModeRenderer::Mania(mania) = &mut self.renderer;
+        let shape = match settings.shape {
+            Some(shape) => shape.to_shape(),
+            None => mania.note_style().shape.clone(),
+        };
+        mania.set_note_style(NoteStyle {
+            shape,
+            color: settings.note_color,
+            hold_body_color: settings.hold_body_color,
+            hold_cap_color: settings.hold_cap_color,
+        });
+        true
+    }
+
+    /// Draws each column's notes with the skin's note image for it at the
+    /// current keycount, making the skin's images the image provider.
+    /// Columns whose image is missing or can't be decoded keep the current
+    /// shape. Returns `false`, changing nothing, if no image is usable.
+    #[cfg(feature = "osk")]
+    pub fn apply_skin(&mut self, skin: &skin::Skin, ctx: &egui::Context) -> bool {
+        let config = skin.mania(self.keycount());
+        let GameModeRenderer::Mania(mania) = &mut self.renderer;
+        let shapes: Vec<_> = config
+            .note_images
+            .iter()
+            .map(|key| match skin.images().resolve(ctx, key) {
+                Some(_) => NoteShape::Keyed(key.clone()),
+                None => mania.note_style().shape.clone(),
+            })
+            .collect();
+        let keyed = |shape: &NoteShape| matches!(shape, NoteShape::Keyed(_));
+        if !shapes.iter().any(keyed) {
+            return false;
+        }
+        mania.set_image_provider(skin.images().clone());
+        mania.set_column_shapes(shapes);
+        true
+    }
+
+    pub fn set_keycount_change_policy(&mut self, policy: KeycountChangePolicy) {
+        self.keycount_policy = policy;
+    }
+
+    pub fn beatmap(&self) -> &Beatmap {
+        &self.beatmap
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_arrangement(&mut self, arrangement: Arrangement) {
+        self.arrangement = arrangement;
+        self.rearrange();
+    }
+
+    /// Mirrors the stage after the arrangement, see
+    /// `Arrangement::column_mapping_with`.
+    pub fn set_mirror(&mut self, mirror: bool) {
+        self.mirror = mirror;
+        self.rearrange();
+    }
+
+    pub fn is_mirrored(&self) -> bool {
+        self.mirror
+    }
+
+    /// Keeps the special lane, the first column of maps with
+    /// `SpecialStyle: 1`, in place: the arrangement and the mirror only move
+    /// the other columns. Does nothing on other maps.
+    pub fn set_special_lane_excluded(&mut self, excluded: bool) {
+        self.special_lane_excluded = excluded;
+        self.rearrange();
+    }
+
+    /// The special lane kept in place, if any.
+    pub fn special_lane(&self) -> Option<usize> {
+        let special = self.special_lane_excluded && self.beatmap.special_style;
+        special.then_some(0)
+    }
+
+    // Applies the arrangement settings, animating the change
+    fn rearrange(&mut self) {
+        self.column_drag = None;
+        let previous = std::mem::take(&mut self.column_mapping);
+        self.apply_transforms();
+
+        let animate = !self.accessibility().reduce_motion;
+        if let Some(duration_ms) = self.swap_duration_ms.filter(|_| animate) {
+            if previous.len() == self.column_mapping.len() {
+                self.column_swap = Some(ColumnSwap::new(
+                    &previous,
+                    &self.column_mapping,
+                    self.current_time(),
+                    duration_ms,
+                ));
+            }
+        }
+    }
+
+    /// Animates arrangement changes: notes slide from their old column to
+    /// the new one over `duration_ms` of playback. `None` (the default)
+    /// swaps instantly. Seeking finishes a running animation.
+    pub fn set_swap_animation(&mut self, duration_ms: Option<f64>) {
+        self.swap_duration_ms = match duration_ms {
+            Some(ms) if ms.is_nan() || ms <= 0.0 => {
+                trace_warn!(ms, "invalid swap animation duration, swapping instantly");
+                None
+            }
+            duration_ms => duration_ms,
+        };
+    }
+
+    pub fn arrangement(&self) -> &Arrangement {
+        &self.arrangement
+    }
+
+    /// Applies `arrangement` and draws the original chart next to the
+    /// transformed one, both from the same clock and with the same style.
+    pub fn with_comparison(mut self, arrangement: Arrangement) -> Self {
+        self.arrangement = arrangement;
+        self.comparison = true;
+        self.apply_transforms();
+        self
+    }
+
+    /// Shows or hides the original chart. Playback is left untouched.
+    pub fn set_comparison(&mut self, enabled: bool) {
+        self.comparison = enabled;
+        self.apply_transforms();
+    }
+
+    pub fn is_comparing(&self) -> bool {
+        self.comparison
+    }
+
+    // Re-derives the object list from the beatmap and the stored seed, so the
+    // same seed and settings always produce the same objects.
+    fn apply_transforms(&mut self) {
+        let _span = trace_span!("apply_transforms");
+        let keycount = self.keycount();
+        let mut rng = SeededRng::new(self.seed);
+        self.column_mapping = self.arrangement.column_mapping_with(
+            keycount,
+            self.mirror,
+            self.special_lane(),
+            &mut rng,
+        );
+        self.hit_objects = transform::apply_column_mapping(
+            &self.beatmap.hit_objects,
+            keycount,
+            &self.column_mapping,
+        );
+        transform::convert_short_holds(&mut self.hit_objects, self.hold_epsilon_ms);
+        self.original_objects.clear();
+        if self.comparison {
+            let identity: Vec<usize> = (0..keycount).collect();
+            self.original_objects =
+                transform::apply_column_mapping(&self.beatmap.hit_objects, keycount, &identity);
+            transform::convert_short_holds(&mut self.original_objects, self.hold_epsilon_ms);
+        }
+        self.column_activity.clear();
+        self.rebuild_caches(keycount);
+        self.sync_timing();
+        self.sync_background();
+    }
+
+    // Caches derived from `hit_objects`. They don't depend on each other, so
+    // with `parallel_load` each one is built on its own thread from copies of
+    // the same inputs as the serial path, and left empty until a later frame
+    // picks it up. The note index is needed to play, so it's built here.
+    fn rebuild_caches(&mut self, keycount: usize) {
+        let _span = trace_span!("rebuild_caches");
+        let scroll = self.scroll_transform();
+        let (ln_percent, threshold) = (self.ln_percent, self.hitsound_threshold);
+        let control_points = &self.beatmap.control_points;
+        self.passed_notes
+            .rebuild(&self.hit_objects, control_points, keycount);
+        if !self.parallel_load {
+            let (beatmap, hit_objects) = (&self.beatmap, &self.hit_objects);
+            let control_points = &beatmap.control_points;
+            self.column_stats = stats::column_stats(hit_objects, keycount);
+            let composition = stats::composition(hit_objects, ln_percent);
+            let sections = sections::compute_sections(beatmap, hit_objects, scroll);
+            self.hitsound_issues = hitsounds::find_issues(hit_objects, control_points, threshold);
+            self.store_composition(composition);
+            self.store_sections(sections);
+            self.sync_hitsound_markers();
+            return;
+        }
+
+        let hit_objects = Arc::new(self.hit_objects.clone());
+        // Sections and issues only need the breaks and control points
+        let map_objects = std::mem::take(&mut self.beatmap.hit_objects);
+        let beatmap = Arc::new(self.beatmap.clone());
+        self.beatmap.hit_objects = map_objects;
+
+        let objects = hit_objects.clone();
+        let column_stats = thread::spawn(move || stats::column_stats(&objects, keycount));
+        let objects = hit_objects.clone();
+        let composition = thread::spawn(move || stats::composition(&objects, ln_percent));
+        let (objects, map) = (hit_objects.clone(), beatmap.clone());
+        let sections = thread::spawn(move || sections::compute_sections(&map, &objects, scroll));
+        let issues = thread::spawn(move || {
+            hitsounds::find_issues(&hit_objects, &beatmap.control_points, threshold)
+        });
+        // Replacing the pending caches drops those of the previous objects
+        self.pending_caches = PendingCaches {
+            column_stats: Some(column_stats),
+            composition: Some(composition),
+            sections: Some(sections),
+            hitsound_issues: Some(issues),
+        };
+        self.column_stats = Vec::new();
+        self.store_composition(Composition::default());
+        self.store_sections(Vec::new());
+        self.hitsound_issues = Vec::new();
+        self.sync_hitsound_markers();
+    }
+
+    // Stores the caches whose threads are done, or all of them with `wait`,
+    // each announced by `Event::CacheReady`
+    fn poll_caches(&mut self, wait: bool) {
+        if let Some(column_stats) = finished(&mut self.pending_caches.column_stats, wait) {
+            self.column_stats = column_stats;
+            self.cache_ready(CacheKind::ColumnStats);
+        }
+        if let Some(composition) = finished(&mut self.pending_caches.composition, wait) {
+            self.store_composition(composition);
+            self.cache_ready(CacheKind::Composition);
+        }
+        if let Some(sections) = finished(&mut self.pending_caches.sections, wait) {
+            self.store_sections(sections);
+            self.cache_ready(CacheKind::Sections);
+        }
+        if let Some(issues) = finished(&mut self.pending_caches.hitsound_issues, wait) {
+            self.hitsound_issues = issues;
+            self.sync_hitsound_markers();
+            self.cache_ready(CacheKind::HitsoundIssues);
+        }
+    }
+
+    fn cache_ready(&mut self, cache: CacheKind) {
+        let at_map_time = self.last_line_time.unwrap_or(0.0);
+        self.events.push(Event::CacheReady { cache, at_map_time });
+    }
+
+    /// Whether the caches `PlayerBuilder::parallel_load` builds in the
+    /// background are all in use. Until then the column counts, composition,
+    /// sections and hitsound issues still being built are empty. Always
+    /// true without `parallel_load`.
+    pub fn caches_ready(&self) -> bool {
+        self.pending_caches.is_empty()
+    }
+
+    /// Blocks until every cache being built in the background is in use.
+    pub fn wait_for_caches(&mut self) {
+        self.poll_caches(true);
+    }
+
+    fn refresh_composition(&mut self) {
+        self.store_composition(stats::composition(&self.hit_objects, self.ln_percent));
+        if self.pending_caches.composition.take().is_some() {
+            self.cache_ready(CacheKind::Composition);
+        }
+    }
+
+    fn store_composition(&mut self, composition: Composition) {
+        self.composition = composition;
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_composition(self.composition);
+        }
+    }
+
+    fn sync_timing(&mut self) {
+        let timing_points = &self.beatmap.control_points.timing_points;
+        let beat_lengths = timing_points
+            .iter()
+            .map(|point| (point.time, point.beat_len))
+            .collect();
+        let measures = timing_points
+            .iter()
+            .map(|point| Measures {
+                start: point.time,
+                length: point.beat_len * f64::from(point.time_signature.numerator.get()),
+                omit_first: point.omit_first_bar_line,
+            })
+            .filter(|measures| measures.length.is_finite() && measures.length > 0.0)
+            .collect();
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_beat_lengths(beat_lengths);
+            mania.set_measures(measures);
+        }
+    }
+
+    fn sync_background(&mut self) {
+        let breaks = self
+            .beatmap
+            .breaks
+            .iter()
+            .map(|b| (b.start_time, b.end_time))
+            .collect();
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_background_events(self.background_events.clone(), breaks);
+        }
+    }
+
+    fn refresh_hitsound_issues(&mut self) {
+        self.hitsound_issues = hitsounds::find_issues(
+            &self.hit_objects,
+            &self.beatmap.control_points,
+            self.hitsound_threshold,
+        );
+        self.sync_hitsound_markers();
+        if self.pending_caches.hitsound_issues.take().is_some() {
+            self.cache_ready(CacheKind::HitsoundIssues);
+        }
+    }
+
+    fn sync_hitsound_markers(&mut self) {
+        let mut marked = Vec::new();
+        if self.hitsound_markers {
+            marked.resize(self.hit_objects.len(), false);
+            for issue in &self.hitsound_issues {
+                marked[issue.note_index] = true;
+            }
+        }
+        match &mut self.renderer {
+            GameModeRenderer::Mania(mania) => mania.set_marked(marked),
+        }
+    }
+
+    /// Notes with a near-silent sample volume or no sample bank, in object
+    /// order. Indexes refer to `hit_objects`.
+    pub fn hitsound_issues(&self) -> &[HitsoundIssue] {
+        &self.hitsound_issues
+    }
+
+    /// Banks, volume and sample index of `hit_objects[index]` with the
+    /// sample point applied, as `PassedNote::sample` carries it.
+    pub fn resolved_sample(&self, index: usize) -> Option<ResolvedSample> {
+        let hit_object = self.hit_objects.get(index)?;
+        Some(hitsounds::resolved_sample(
+            hit_object,
+            &self.beatmap.control_points,
+        ))
+    }
+
+    /// Volume, in percent, under which a note is reported. Defaults to
+    /// `DEFAULT_VOLUME_THRESHOLD`.
+    pub fn set_hitsound_threshold(&mut self, threshold: i32) {
+        self.hitsound_threshold = threshold;
+        self.refresh_hitsound_issues();
+    }
+
+    /// Draws a warning marker on every note listed by `hitsound_issues`.
+    pub fn set_hitsound_markers(&mut self, enabled: bool) {
+        self.hitsound_markers = enabled;
+        self.refresh_hitsound_issues();
+    }
+
+    fn refresh_sections(&mut self) {
+        let sections =
+            sections::compute_sections(&self.beatmap, &self.hit_objects, self.scroll_transform());
+        self.store_sections(sections);
+        if self.pending_caches.sections.take().is_some() {
+            self.cache_ready(CacheKind::Sections);
+        }
+    }
+
+    fn store_sections(&mut self, sections: Vec<Section>) {
+        self.sections = sections;
+        self.current_section = self.section_index(self.current_time());
+    }
+
+    fn section_index(&self, time: f64) -> Option<usize> {
+        self.sections
+            .iter()
+            .position(|section| (section.start_ms..section.end_ms).contains(&time))
+    }
+
+    fn check_columns(&mut self) {
+        let circle_size = self.beatmap.circle_size;
+        let keycount = self.keycount();
+        if circle_size.is_nan() || circle_size as usize != keycount {
+            self.load_report.push(LoadIssue::KeycountClamped {
+                circle_size,
+                keycount,
+            });
+        }
+
+        if !self.strict_columns {
+            return;
+        }
+        for (index, hit_object) in self.beatmap.hit_objects.iter().enumerate() {
+            let x = match &hit_object.kind {
+                HitObjectKind::Circle(h) => h.pos.x,
+                HitObjectKind::Hold(h) => h.pos_x,
+                _ => continue,
+            };
+            let column = column_for_x(x, keycount);
+            if (x - column_center_x(column, keycount)).abs() > COLUMN_TOLERANCE {
+                self.load_report.push(LoadIssue::OffCenterColumn {
+                    index,
+                    time: hit_object.start_time,
+                    x,
+                    column,
+                });
+            }
+        }
+    }
+
+    fn check_holds(&mut self) {
+        for (index, hit_object) in self.beatmap.hit_objects.iter().enumerate() {
+            if let HitObjectKind::Hold(h) = &hit_object.kind {
+                if h.duration < self.hold_epsilon_ms {
+                    self.load_report.push(LoadIssue::ShortHoldConverted {
+                        index,
+                        time: hit_object.start_time,
+                        duration: h.duration,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Notes, holds and hold time per displayed column, with the active
+    /// transforms applied.
+    pub fn column_counts(&self) -> &[ColumnStats] {
+        &self.column_stats
+    }
+
+    /// Object counts, LN share and chord size, with the active transforms
+    /// applied.
+    pub fn composition(&self) -> Composition {
+        self.composition
+    }
+
+    /// Switches `Composition::ln_percent` between counting holds and
+    /// weighting them by duration.
+    pub fn set_ln_percent(&mut self, mode: LnPercent) {
+        self.ln_percent = mode;
+        self.refresh_composition();
+    }
+
+    /// Shows `composition` as one line of text over the playfield.
+    pub fn set_composition_text(&mut self, style: Option<CompositionTextStyle>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_composition_text(style);
+        }
+    }
+
+    /// Fingerprint of the chart as loaded, ignoring the active transforms.
+    /// See `fingerprint::ChartFingerprint::similarity`.
+    pub fn fingerprint(&self) -> ChartFingerprint {
+        fingerprint::fingerprint(&self.beatmap.hit_objects, self.keycount())
+    }
+
+    /// How far `taps` land from the closest beat, e.g. taps to a metronome
+    /// played along with the map. `taps` are map times, see
+    /// `wall_to_map_time`; outliers such as missed beats are dropped.
+    pub fn calibrate_offset(&self, taps: &[f64]) -> Option<OffsetCalibration> {
+        calibration::calibrate(&self.beatmap.control_points, taps)
+    }
+
+    /// Map time of the beat closest to `map_time`, `None` without a usable
+    /// timing point.
+    pub fn nearest_beat(&self, map_time: f64) -> Option<f64> {
+        snap::nearest_line(&self.beatmap.control_points, map_time, 1.0)
+    }
+
+    /// Checks the chart, as currently transformed, against `config`.
+    /// Results are sorted by time.
+    pub fn lint(&self, config: &LintConfig) -> Vec<Lint> {
+        lint::lint(
+            &self.hit_objects,
+            &self.beatmap.control_points,
+            self.keycount(),
+            config,
+        )
+    }
+
+    /// Notes of the chart within map times `visible`, merged where closer
+    /// than a pixel of an overview strip `width_px` wide.
+    pub fn overview_marks(&self, visible: (f64, f64), width_px: f32) -> Vec<OverviewMark> {
+        overview::marks(&self.hit_objects, self.keycount(), visible, width_px)
+    }
+
+    pub fn load_report(&self) -> &LoadReport {
+        &self.load_report
+    }
+
+    pub fn hit_objects(&self) -> &[HitObject] {
+        &self.hit_objects
+    }
+
+    pub fn set_note_style(&mut self, style: NoteStyle) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_note_style(style);
+        }
+    }
+
+    pub fn set_hand_coloring(&mut self, coloring: Option<HandColoring>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_hand_coloring(coloring);
+        }
+    }
+
+    /// Colors note heads by the beat division they sit on, see
+    /// `SnapColoring`. `None` turns it off.
+    pub fn set_snap_coloring(&mut self, coloring: Option<SnapColoring>) {
+        let coloring = coloring.map(|mut coloring| {
+            let tolerance_ms = coloring.tolerance_ms;
+            if !tolerance_ms.is_finite() || tolerance_ms < 0.0 {
+                trace_warn!(tolerance_ms, "invalid snap tolerance, using the default");
+                coloring.tolerance_ms = SnapColoring::default().tolerance_ms;
+            }
+            coloring
+        });
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_snap_coloring(coloring);
+        }
+    }
+
+    /// Which game's hit windows the window bands show.
+    pub fn set_window_mode(&mut self, mode: WindowMode) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_window_mode(mode);
+        }
+    }
+
+    pub fn set_window_bands(&mut self, style: Option<WindowBandStyle>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_window_bands(style);
+        }
+    }
+
+    /// Columns at or above the keycount are ignored.
+    pub fn set_column_pressed(&mut self, column: usize, pressed: bool) {
+        if column >= self.keycount() {
+            trace_warn!(column, "pressed column out of range, ignoring");
+            return;
+        }
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_column_pressed(column, pressed);
+        }
+    }
+
+    pub fn clear_pressed(&mut self) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.clear_pressed();
+        }
+    }
+
+    pub fn is_column_pressed(&self, column: usize) -> bool {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.is_column_pressed(column),
+        }
+    }
+
+    /// Audio and background files found next to the .osu file, `None`
+    /// unless loaded with `from_path` or found with `resolve_assets`.
+    pub fn assets(&self) -> Option<&MapAssets> {
+        self.assets.as_ref()
+    }
+
+    /// Resolves the files of the current map as if it was read from
+    /// `osu_path`, e.g. for maps loaded from bytes.
+    pub fn resolve_assets(&mut self, osu_path: impl AsRef<Path>) {
+        self.assets = Some(MapAssets::resolve(osu_path, &self.beatmap));
+    }
+
+    /// The resolved audio file once the assets are, see `assets`. Before
+    /// that, the name the map gives, relative to its folder.
+    pub fn audio_filename(&self) -> Option<&Path> {
+        match &self.assets {
+            Some(assets) => assets.audio.as_deref(),
+            None => named_file(&self.beatmap.audio_file),
+        }
+    }
+
+    /// Like `audio_filename`, for the background image.
+    pub fn background_filename(&self) -> Option<&Path> {
+        match &self.assets {
+            Some(assets) => assets.background.as_deref(),
+            None => named_file(&self.beatmap.background_file),
+        }
+    }
+
+    /// Background changes read from the `[Events]` section, empty unless
+    /// loaded with `from_bytes`. Their files can be preloaded in the
+    /// provider given to `set_image_provider`.
+    pub fn background_events(&self) -> &[BackgroundEvent] {
+        &self.background_events
+    }
+
+    /// See `ManiaRenderer::set_image_provider`.
+    pub fn set_image_provider(&mut self, provider: Arc<dyn ImageProvider>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_image_provider(provider);
+        }
+    }
+
+    pub fn set_letterbox(&mut self, letterbox: Option<Letterbox>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_letterbox(letterbox);
+        }
+    }
+
+    pub fn set_playfield_padding(&mut self, padding: egui::Margin) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_playfield_padding(padding);
+        }
+    }
+
+    /// See `ManiaRenderer::set_clip_margin`.
+    pub fn set_clip_margin(&mut self, margin: egui::Margin) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_clip_margin(margin);
+        }
+    }
+
+    pub fn set_motion_blur(&mut self, motion_blur: Option<MotionBlur>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_motion_blur(motion_blur);
+        }
+    }
+
+    /// Draws with `hook` after `layer`, see `ManiaRenderer::set_layer_hook`.
+    pub fn set_layer_hook(&mut self, layer: Layer, hook: LayerHook) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_layer_hook(layer, hook);
+        }
+    }
+
+    pub fn remove_layer_hook(&mut self, layer: Layer) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.remove_layer_hook(layer);
+        }
+    }
+
+    pub fn set_layer_order(&mut self, layer_order: LayerOrder) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_layer_order(layer_order);
+        }
+    }
+
+    /// Upscroll draws the stage flipped vertically, judgment line included.
+    pub fn set_scroll_direction(&mut self, direction: ScrollDirection) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_scroll_direction(direction);
+        }
+    }
+
+    pub fn scroll_direction(&self) -> ScrollDirection {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.scroll_direction(),
+        }
+    }
+
+    pub fn set_min_visual_gap(&mut self, px: Option<f32>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_min_visual_gap(px);
+        }
+    }
+
+    /// Distance from the judgment line to the bottom of the stage, or its
+    /// top in upscroll. `DEFAULT_HIT_POSITION` by default.
+    pub fn set_hit_position(&mut self, offset_px: f32) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_hit_position(offset_px);
+        }
+    }
+
+    /// Below this size `render_in` draws a placeholder, see
+    /// `ManiaRenderer::set_min_render_size`.
+    pub fn set_min_render_size(&mut self, size: egui::Vec2) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_min_render_size(size);
+        }
+    }
+
+    pub fn set_fit_policy(&mut self, fit_policy: FitPolicy) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_fit_policy(fit_policy);
+        }
+    }
+
+    pub fn effect_clock(&self) -> &EffectClock {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.effect_clock(),
+        }
+    }
+
+    pub fn effect_clock_mut(&mut self) -> &mut EffectClock {
+        match &mut self.renderer {
+            GameModeRenderer::Mania(mania) => mania.effect_clock_mut(),
+        }
+    }
+
+    pub fn column_at(&self, pos: egui::Pos2) -> Option<usize> {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.column_at(pos),
+        }
+    }
+
+    /// Overrides OD/AR/CS/HP without editing the map. In mania, CS is the
+    /// keycount, so overriding it changes the column layout.
+    pub fn set_difficulty_override(&mut self, difficulty_override: DifficultyOverride) {
+        if let Some(cs) = difficulty_override.cs {
+            if !(1.0..=MAX_KEYCOUNT as f32).contains(&cs) {
+                trace_warn!(cs, "keycount override out of range, clamping");
+            }
+        }
+        self.difficulty_override = difficulty_override;
+        self.apply_transforms();
+        self.sync_difficulty();
+    }
+
+    fn sync_difficulty(&mut self) {
+        let od = self.difficulty_settings().od.value;
+
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_overall_difficulty(od);
+        }
+    }
+
+    pub fn difficulty_settings(&self) -> DifficultySettings {
+        DifficultySettings::resolve(&self.beatmap, &self.difficulty_override)
+    }
+
+    fn keycount(&self) -> usize {
+        keycount_for(self.difficulty_settings().cs.value)
+    }
+
+    pub fn get_required_size(&self) -> [f32; 2] {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => {
+                let keycount = self.keycount();
+                let stages = if self.comparison { 2 } else { 1 };
+                [
+                    mania.required_stages_width(keycount, stages),
+                    mania.required_height(),
+                ]
+            }
+            // TODO: Ajouter d'autres modes
+            // GameModeRenderer::Standard(std) => std.get_required_size(),
+            // GameModeRenderer::Taiko(taiko) => taiko.get_required_size(),
+            // GameModeRenderer::Catch(catch) => catch.get_required_size(),
+        }
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = if !speed.is_finite() || speed <= 0.0 {
+            trace_warn!(speed, "invalid playback speed, using 1.0");
+            1.0
+        } else {
+            speed
+        };
+        self.log_timeline();
+        // The line time jumps with the new mapping; treat it like a seek
+        self.passed_notes.resync();
+        self.refresh_sections();
+    }
+
+    /// Varies the rate with the map position, e.g. from 1.0x to 1.3x over
+    /// the map for speed training. Replaces `set_speed` while set. Note
+    /// spacing, `duration` and `sections` follow the ramp.
+    pub fn set_rate_ramp(&mut self, rate_ramp: Option<RateRamp>) {
+        self.rate_ramp = match rate_ramp {
+            Some(ramp) if !ramp.is_valid() => {
+                trace_warn!(?ramp, "invalid rate ramp, ignoring it");
+                None
+            }
+            rate_ramp => rate_ramp,
+        };
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_rate_ramp(self.rate_ramp);
+        }
+        self.log_timeline();
+        self.passed_notes.resync();
+        self.refresh_sections();
+    }
+
+    pub fn set_scroll_time(&mut self, ms: f32) {
+        if ms.is_nan() {
+            trace_warn!("NaN scroll time, keeping the previous value");
+            return;
+        }
+        if ms < MIN_SCROLL_TIME_MS {
+            trace_warn!(ms, "scroll time too small, clamping");
+        }
+        let now = self.current_time();
+        let from_ms = self.effective_scroll_time(now);
+        self.scroll_time_ms = ms.max(MIN_SCROLL_TIME_MS);
+        self.scroll_animation = self
+            .scroll_animation_ms
+            .filter(|_| from_ms != self.scroll_time_ms)
+            .map(|duration_ms| ScrollAnimation {
+                from_ms,
+                started_at: now,
+                duration_ms,
+            });
+        self.log_timeline();
+        // No resync: the map position doesn't change, only where the line
+        // sits in it, and the tracker won't report notes the line passes again
+        self.refresh_sections();
+    }
+
+    /// Makes `set_scroll_time` ease from the current scroll time to the new
+    /// one over `duration_ms` of playback, instead of rescaling the stage at
+    /// once. `None` (the default) applies it instantly. While easing,
+    /// `scroll_transform` and the queries built on it use the eased value.
+    pub fn set_scroll_animation(&mut self, duration_ms: Option<f64>) {
+        self.scroll_animation_ms = match duration_ms {
+            Some(ms) if ms.is_nan() || ms <= 0.0 => {
+                trace_warn!(ms, "invalid scroll animation duration, changing instantly");
+                None
+            }
+            duration_ms => duration_ms,
+        };
+    }
+
+    pub fn scroll_transform(&self) -> ScrollTransform {
+        ScrollTransform {
+            speed: self.speed,
+            scroll_time_ms: self.effective_scroll_time(self.current_time()),
+            rate_ramp: self.rate_ramp,
+        }
+    }
+
+    // Scroll time drawn at clock time `now`, eased while animating
+    fn effective_scroll_time(&self, now: f64) -> f32 {
+        match &self.scroll_animation {
+            Some(animation) => animation.scroll_time_at(now, self.scroll_time_ms),
+            None => self.scroll_time_ms,
+        }
+    }
+
+    /// Clock time (as in `current_time`) at which the object at `note_index`
+    /// of `hit_objects()` visually reaches the judgment line.
+    pub fn line_crossing_time(&self, note_index: usize) -> Option<f64> {
+        let hit_object = self.hit_objects.get(note_index)?;
+        Some(self.scroll_transform().crossing_time(hit_object.start_time))
+    }
+
+    /// `(note_index, crossing time)` for every object crossing the judgment
+    /// line within `range`, in object order.
+    pub fn line_crossings(&self, range: std::ops::Range<f64>) -> Vec<(usize, f64)> {
+        let scroll = self.scroll_transform();
+        self.hit_objects
+            .iter()
+            .enumerate()
+            .map(|(index, h)| (index, scroll.crossing_time(h.start_time)))
+            .filter(|(_, time)| range.contains(time))
+            .collect()
+    }
+
+    /// Renders the hold at `note_index` of `hit_objects` as dropped from map
+    /// time `at_time`. Seeking back before the break restores it.
+    pub fn report_hold_break(&mut self, note_index: usize, at_time: f64) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.record_judgment(at_time, Recorded::HoldBreak { note_index });
+            mania.report_hold_break(note_index, at_time);
+        }
+    }
+
+    /// Draws element outlines and object indices over the frame, to debug
+    /// layout issues.
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_debug_overlay(enabled);
+        }
+    }
+
+    /// Replaces text overlays with boxes, for frames that must match across
+    /// platforms. See `ManiaRenderer::set_deterministic`.
+    pub fn set_deterministic(&mut self, enabled: bool) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_deterministic(enabled);
+        }
+    }
+
+    /// Shows hit offsets reported with `report_hit` above the judgment line.
+    pub fn set_timing_text(&mut self, style: Option<TimingTextStyle>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_timing_text(style);
+        }
+    }
+
+    /// Reports a hit in `column` judged at map time `at_time`, `offset_ms`
+    /// being negative when early. Seeking clears the readouts.
+    pub fn report_hit(&mut self, column: usize, offset_ms: f64, at_time: f64) {
+        if !self.hidden_column_events && !self.is_column_visible(column) {
+            return;
+        }
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.record_judgment(at_time, Recorded::Hit { column, offset_ms });
+            mania.report_hit(column, offset_ms, at_time);
+        }
+    }
+
+    /// Hits reported with `report_hit` since the map was loaded or the
+    /// judgments cleared, by map time. For `ui::hit_error_scatter`.
+    pub fn hit_errors(&self) -> Vec<TimedError> {
+        self.judgment_log().hit_errors().collect()
+    }
+
+    /// Plots the reported hits over map time, colored by judgment tier.
+    pub fn set_hit_error_scatter(&mut self, style: Option<ScatterStyle>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_hit_error_scatter(style);
+        }
+    }
+
+    /// Windows hits are judged with at the map's OD and the window mode.
+    pub fn windows(&self) -> Windows {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.windows(),
+        }
+    }
+
+    // Reports kept by the renderer, which plots the hits
+    fn judgment_log(&self) -> &JudgmentLog {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.judgment_log(),
+        }
+    }
+
+    /// Reports a press in `column` at map time `at_time` that matched no
+    /// note: counts it, emits `Event::GhostTap` and flashes the column.
+    /// Columns at or above the keycount are ignored.
+    pub fn report_ghost_tap(&mut self, column: usize, at_time: f64) {
+        if column >= self.keycount() {
+            trace_warn!(column, "ghost tap column out of range, ignoring");
+            return;
+        }
+        if !self.hidden_column_events && !self.is_column_visible(column) {
+            return;
+        }
+        self.ghost_taps += 1;
+        self.events.push(Event::GhostTap {
+            column,
+            at_map_time: at_time,
+        });
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.record_judgment(at_time, Recorded::GhostTap { column });
+            mania.report_ghost_tap(column, at_time);
+        }
+    }
+
+    /// Ghost taps reported since the map was loaded, flashes shown or not.
+    pub fn ghost_taps(&self) -> usize {
+        self.ghost_taps
+    }
+
+    /// Hits and hold breaks reported since the map was loaded or the
+    /// judgments cleared, judged with `windows`, against the chart as
+    /// currently transformed.
+    pub fn session_stats(&self) -> SessionStats {
+        let composition = self.composition();
+        stats::session_stats(
+            self.judgment_log()
+                .between(f64::NEG_INFINITY, f64::INFINITY),
+            self.windows(),
+            composition.rice + composition.holds,
+            self.ghost_taps,
+        )
+    }
+
+    /// Receptors drawn at the judgment line under the notes, `None` for none.
+    pub fn set_receptors(&mut self, style: Option<ReceptorStyle>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_receptors(style);
+        }
+    }
+
+    /// Panels hiding part of the stage over the notes, `None` for none.
+    pub fn set_lane_cover(&mut self, cover: Option<LaneCover>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_lane_cover(cover);
+        }
+    }
+
+    /// Measure lines from the timing points, off by default.
+    pub fn set_barlines(&mut self, style: Option<BarlineStyle>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_barlines(style);
+        }
+    }
+
+    pub fn barlines(&self) -> Option<BarlineStyle> {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.barlines(),
+        }
+    }
+
+    /// `None` hides the ghost tap flashes; they are still counted.
+    pub fn set_ghost_taps(&mut self, style: Option<GhostTapStyle>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_ghost_taps(style);
+        }
+    }
+
+    /// Drops every judgment-driven visual, such as hold breaks, along with
+    /// what `replay_window` would show again.
+    pub fn clear_judgments(&mut self) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.clear_judgments();
+        }
+    }
+
+    /// Notes whose head or tail crossed the judgment line since the last
+    /// drain, in chronological order. Seeking forward passes the notes
+    /// skipped over on the next frame, capped at `MAX_PASSED_NOTES` with
+    /// `PassedNotes::truncated` set; seeking back passes none.
+    pub fn drain_passed_notes(&mut self) -> PassedNotes {
+        self.events.drain_passed_notes()
+    }
+
+    /// Everything emitted while rendering: passed notes, hitches, loops,
+    /// sections and the end of the map. `drain_passed_notes` takes the note
+    /// and hitch events out of the same queue.
+    pub fn events(&mut self) -> &mut EventQueue {
+        &mut self.events
+    }
+
+    /// Delays hold tail events, e.g. by the release window for hosts doing
+    /// their own judging. Notes keep being drawn as before.
+    pub fn set_event_timing(&mut self, timing: EventTiming) {
+        self.event_timing = match timing.tail_grace {
+            GraceSource::Fixed(ms) if !ms.is_finite() || ms < 0.0 => {
+                trace_warn!(ms, "invalid tail grace, using none");
+                EventTiming {
+                    tail_grace: GraceSource::None,
+                }
+            }
+            _ => timing,
+        };
+    }
+
+    pub fn event_timing(&self) -> EventTiming {
+        self.event_timing
+    }
+
+    // How long after their time hold tails are emitted, in map ms
+    fn tail_grace_ms(&self) -> f64 {
+        match self.event_timing.tail_grace {
+            GraceSource::None => 0.0,
+            GraceSource::ReleaseWindow => match &self.renderer {
+                GameModeRenderer::Mania(mania) => f64::from(mania.release_windows().miss),
+            },
+            GraceSource::Fixed(ms) => ms,
+        }
+    }
+
+    // Queues the heads and tails crossing the judgment line up to
+    // `line_time`. At the end of the map the tails still within their grace
+    // are flushed, since the clock stops or wraps before it runs out.
+    fn emit_passed_notes(&mut self, line_time: f64, flush_tails: bool) {
+        let tail_grace = if flush_tails {
+            0.0
+        } else {
+            self.tail_grace_ms()
+        };
+        let (passed, truncated) = self.passed_notes.advance(line_time, tail_grace);
+        if truncated {
+            self.events.mark_truncated();
+        }
+        self.column_activity.record(&passed);
+        for note in passed {
+            if self.hidden_column_events || self.is_column_visible(note.column) {
+                self.events.push_passed(note);
+            }
+        }
+    }
+
+    /// Hides displayed columns, see `ManiaRenderer::set_column_visibility`.
+    pub fn set_column_visibility(&mut self, visible: Vec<bool>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_column_visibility(visible);
+        }
+    }
+
+    /// Applies every accessibility option at once. They stay applied across
+    /// `set_beatmap` and style changes until replaced.
+    pub fn apply_accessibility(&mut self, options: AccessibilityOptions) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_accessibility(options);
+        }
+    }
+
+    pub fn accessibility(&self) -> AccessibilityOptions {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.accessibility(),
+        }
+    }
+
+    /// Note size multiplier of each displayed column, one per column. Sizes
+    /// not matching the keycount, or not finite and positive, reset every
+    /// column to 1.0.
+    pub fn set_column_note_sizes(&mut self, sizes: Vec<f32>) {
+        let valid = sizes.len() == self.keycount()
+            && sizes.iter().all(|size| size.is_finite() && *size > 0.0);
+        let sizes = if valid {
+            sizes
+        } else {
+            trace_warn!(?sizes, "invalid column note sizes, using 1.0");
+            Vec::new()
+        };
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_column_note_sizes(sizes);
+        }
+    }
+
+    /// See `ManiaRenderer::set_column_shapes`.
+    pub fn set_column_shapes(&mut self, shapes: Vec<NoteShape>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_column_shapes(shapes);
+        }
+    }
+
+    /// Raises each displayed column's judgment line by that many points, one
+    /// value per column. Offsets not matching the keycount, or not finite,
+    /// reset to a single straight line.
+    pub fn set_column_hit_offsets(&mut self, offsets: Vec<f32>) {
+        let valid =
+            offsets.len() == self.keycount() && offsets.iter().all(|offset| offset.is_finite());
+        let offsets = if valid {
+            offsets
+        } else {
+            trace_warn!(?offsets, "invalid column hit offsets, using 0.0");
+            Vec::new()
+        };
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_column_hit_offsets(offsets);
+        }
+    }
 
-        Some(Self {
-            beatmap,
-            renderer,
-            start_time: Instant::now(),
-            speed: 1.0,
-            scroll_time_ms: 1000.0,
-        })
+    /// Stripes hold bodies, see `ManiaRenderer::set_hold_body_pattern`.
+    pub fn set_hold_body_pattern(&mut self, pattern: HoldBodyPattern) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_hold_body_pattern(pattern);
+        }
     }
 
-    pub fn set_note_style(&mut self, style: NoteStyle) {
+    /// Draws holds as ladders, see `ManiaRenderer::set_hold_body_style`.
+    pub fn set_hold_body_style(&mut self, style: HoldBodyStyle) {
         #[allow(irrefutable_let_patterns)]
         if let GameModeRenderer::Mania(mania) = &mut self.renderer {
-            mania.set_note_style(style);
+            mania.set_hold_body_style(style);
         }
     }
 
-    pub fn get_required_size(&self) -> [f32; 2] {
-        match &self.renderer {
-            GameModeRenderer::Mania(mania) => {
-                let keycount = self.beatmap.circle_size as usize;
-                [mania.required_width(keycount), mania.required_height()]
-            }
-            // TODO: Ajouter d'autres modes
-            // GameModeRenderer::Standard(std) => std.get_required_size(),
-            // GameModeRenderer::Taiko(taiko) => taiko.get_required_size(),
-            // GameModeRenderer::Catch(catch) => catch.get_required_size(),
+    /// Narrows the stage toward its top, see `ManiaRenderer::set_stage_tilt`.
+    pub fn set_stage_tilt(&mut self, tilt: Option<f32>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_stage_tilt(tilt);
         }
     }
 
-    pub fn set_speed(&mut self, speed: f64) {
-        self.speed = speed;
+    /// Lets `set_column_note_sizes` grow notes wider than their column.
+    pub fn set_note_overflow(&mut self, allow: bool) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_note_overflow(allow);
+        }
     }
 
-    pub fn set_scroll_time(&mut self, ms: f32) {
-        self.scroll_time_ms = ms;
+    /// Shows only the given displayed columns. An empty slice shows them all.
+    pub fn solo_columns(&mut self, columns: &[usize]) {
+        let visible = if columns.is_empty() {
+            Vec::new()
+        } else {
+            (0..self.keycount())
+                .map(|column| columns.contains(&column))
+                .collect()
+        };
+        self.set_column_visibility(visible);
+    }
+
+    pub fn is_column_visible(&self, column: usize) -> bool {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.is_column_visible(column),
+        }
+    }
+
+    /// Whether hidden columns still produce passed notes and hit readouts.
+    /// On by default.
+    pub fn set_hidden_column_events(&mut self, enabled: bool) {
+        self.hidden_column_events = enabled;
     }
 
     pub fn render(&mut self, ui: &mut egui::Ui) {
@@ -77,17 +1868,22 @@ impl Player {
     }
 
     pub fn render_at(&mut self, ui: &mut egui::Ui, position: egui::Pos2) {
-        let current_time = self.start_time.elapsed().as_secs_f64() * 1000.0;
-        let hit_objects = &self.beatmap.hit_objects;
+        let _span = trace_span!("render");
+        #[cfg(feature = "alloc-audit")]
+        let allocations = alloc_audit::allocations();
+        let current_time = self.begin_frame();
+        let keycount = self.keycount();
+        let scroll_time_ms = self.effective_scroll_time(current_time);
+        let all_stages = [self.original_objects.as_slice(), &self.hit_objects];
+        let stages = stages(self.comparison, &all_stages);
 
         match &mut self.renderer {
             GameModeRenderer::Mania(mania) => {
-                let keycount = self.beatmap.circle_size as usize;
-                mania.render_at(
+                mania.render_stages_at(
                     ui,
-                    hit_objects,
+                    stages,
                     current_time,
-                    self.scroll_time_ms,
+                    scroll_time_ms,
                     self.speed,
                     keycount,
                     position,
@@ -98,17 +1894,863 @@ impl Player {
             // GameModeRenderer::Taiko(taiko) => taiko.render_at(...),
             // GameModeRenderer::Catch(catch) => catch.render_at(...),
         }
+        self.report_hook_panics();
+        if self.layout_edit {
+            self.edit_layout(ui);
+        }
+        #[cfg(feature = "alloc-audit")]
+        {
+            self.frame_allocations = alloc_audit::allocations() - allocations;
+        }
+    }
+
+    /// Renders into exactly `rect`, scaled according to the fit policy.
+    /// Returns the rect of the playfield, which `column_at` hit-tests against.
+    pub fn render_in(&mut self, ui: &mut egui::Ui, rect: egui::Rect) -> egui::Rect {
+        let _span = trace_span!("render");
+        #[cfg(feature = "alloc-audit")]
+        let allocations = alloc_audit::allocations();
+        let current_time = self.begin_frame();
+        let keycount = self.keycount();
+        let scroll_time_ms = self.effective_scroll_time(current_time);
+        let all_stages = [self.original_objects.as_slice(), &self.hit_objects];
+        let stages = stages(self.comparison, &all_stages);
+
+        let playfield = match &mut self.renderer {
+            GameModeRenderer::Mania(mania) => mania.render_stages_in(
+                ui,
+                stages,
+                current_time,
+                scroll_time_ms,
+                self.speed,
+                keycount,
+                rect,
+            ),
+        };
+        self.report_hook_panics();
+        if self.layout_edit {
+            self.edit_layout(ui);
+        }
+        #[cfg(feature = "alloc-audit")]
+        {
+            self.frame_allocations = alloc_audit::allocations() - allocations;
+        }
+        playfield
+    }
+
+    // Layer hooks that panicked this frame were skipped; they are dropped
+    // now. Formatters that panicked already fell back to plain numbers, and
+    // a panicking image provider to missing images.
+    fn report_hook_panics(&mut self) {
+        let (hooks, formatters, images) = match &mut self.renderer {
+            GameModeRenderer::Mania(mania) => (
+                mania.take_hook_panics(),
+                mania.take_formatter_panics(),
+                mania.take_image_panic(),
+            ),
+        };
+        let at_map_time = self.last_line_time.unwrap_or(0.0);
+        let hooks = hooks
+            .into_iter()
+            .map(|(layer, message)| (CallbackKind::LayerHook(layer), message));
+        let formatters = formatters
+            .into_iter()
+            .map(|message| (CallbackKind::Formatter, message));
+        let images = images
+            .into_iter()
+            .map(|message| (CallbackKind::ImageProvider, message));
+        for (which, message) in hooks.chain(formatters).chain(images) {
+            self.events.push(Event::CallbackPanicked {
+                which,
+                message,
+                at_map_time,
+            });
+        }
+    }
+
+    /// Layout edit mode: the top of each column becomes a handle to drag it
+    /// left or right, previewing the new order while notes keep scrolling.
+    /// Dropping applies it as an `Arrangement::Custom` with the mirror off
+    /// and emits `Event::LayoutChanged` with it, e.g. for an `on_event`
+    /// callback to save it; Escape or a right click cancels the drag.
+    pub fn set_layout_edit(&mut self, enabled: bool) {
+        self.layout_edit = enabled;
+        self.column_drag = None;
+    }
+
+    pub fn is_editing_layout(&self) -> bool {
+        self.layout_edit
+    }
+
+    // Handles and drag interaction of layout edit mode, over the frame
+    // just rendered
+    fn edit_layout(&mut self, ui: &mut egui::Ui) {
+        let keycount = self.keycount();
+        let columns: Option<Vec<egui::Rect>> = match &self.renderer {
+            GameModeRenderer::Mania(mania) => (0..keycount).map(|c| mania.column_rect(c)).collect(),
+        };
+        let Some(columns) = columns else {
+            return;
+        };
+        let handles: Vec<egui::Rect> = columns
+            .iter()
+            .map(|column| {
+                let height = LAYOUT_HANDLE_HEIGHT.min(column.height());
+                egui::Rect::from_min_size(column.min, egui::vec2(column.width(), height))
+            })
+            .collect();
+        let strip = handles[0].union(handles[keycount - 1]);
+        let response = ui.interact(strip, ui.id().with("layout_edit"), egui::Sense::drag());
+
+        let cancel =
+            ui.input(|i| i.key_pressed(egui::Key::Escape) || i.pointer.secondary_clicked());
+        if cancel {
+            self.column_drag = None;
+        } else if response.drag_started() {
+            let origin = ui.input(|i| i.pointer.press_origin());
+            self.column_drag = origin
+                .and_then(|origin| handles.iter().position(|handle| handle.contains(origin)))
+                .map(ColumnDrag::new);
+        }
+        if let (Some(drag), Some(pointer)) =
+            (&mut self.column_drag, response.interact_pointer_pos())
+        {
+            let hovered = (pointer.x - strip.min.x) / columns[0].width() - 0.5;
+            drag.hover(hovered, keycount);
+        }
+        if response.drag_stopped() {
+            if let Some(drag) = self.column_drag.take() {
+                self.commit_column_drag(drag);
+            }
+        }
+
+        let dragged = self.column_drag.map(|drag| drag.target());
+        for (column, handle) in handles.iter().enumerate() {
+            let color = if dragged == Some(column) {
+                ui.visuals().selection.bg_fill
+            } else {
+                ui.visuals().widgets.inactive.bg_fill
+            };
+            ui.painter().rect_filled(handle.shrink(2.0), 2.0, color);
+        }
+    }
+
+    fn commit_column_drag(&mut self, drag: ColumnDrag) {
+        if drag.target() == drag.from {
+            return;
+        }
+        let mapping = drag.reorder(&self.column_mapping);
+        // The preview already shows the new order, no swap animation. The
+        // mirror is folded into the mapping, and a special lane it moved is
+        // no longer kept in place.
+        self.arrangement = Arrangement::Custom(mapping.clone());
+        self.mirror = false;
+        if let Some(lane) = self.special_lane() {
+            self.special_lane_excluded = mapping[lane] == lane;
+        }
+        self.apply_transforms();
+        let at_map_time = self.last_line_time.unwrap_or(0.0);
+        self.events.push(Event::LayoutChanged {
+            mapping,
+            at_map_time,
+        });
+    }
+
+    /// Replays map times `from_ms..to_ms` at `rate`, showing the hits, hold
+    /// breaks and ghost taps reported in it again at their times, then
+    /// resumes where playback was with the previous speed. Passed notes and
+    /// other events aren't emitted again. Returns `false` for an empty window
+    /// or an invalid rate.
+    pub fn replay_window(&mut self, from_ms: f64, to_ms: f64, rate: f64) -> bool {
+        let valid = rate.is_finite() && rate > 0.0 && from_ms < to_ms;
+        if !valid {
+            trace_warn!(from_ms, to_ms, rate, "invalid replay window, ignoring it");
+            return false;
+        }
+        self.end_replay();
+        let mut replay = Replay {
+            to_ms,
+            shown_up_to: from_ms,
+            resume_time: self.current_time(),
+            speed: self.speed,
+            rate_ramp: self.rate_ramp,
+        };
+        self.set_rate_ramp(None);
+        self.set_speed(rate);
+        // The clock can't go before 0, so neither can the window
+        let scroll = self.scroll_transform();
+        replay.shown_up_to = from_ms.max(scroll.map_time_at_line(0.0));
+        self.set_current_time(scroll.crossing_time(replay.shown_up_to));
+        self.replay = Some(replay);
+        true
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Stops a `replay_window` early, restoring the position and speed.
+    pub fn end_replay(&mut self) {
+        let Some(replay) = self.replay.take() else {
+            return;
+        };
+        self.set_speed(replay.speed);
+        self.set_rate_ramp(replay.rate_ramp);
+        self.set_current_time(replay.resume_time);
+        // The seeks dropped the breaks past the window start
+        let line_time = self.scroll_transform().map_time_at_line(replay.resume_time);
+        let breaks: Vec<_> = self
+            .judgment_log()
+            .between(f64::NEG_INFINITY, line_time)
+            .filter(|(_, recorded)| matches!(recorded, Recorded::HoldBreak { .. }))
+            .copied()
+            .collect();
+        for (at_time, recorded) in breaks {
+            self.show_recorded(at_time, recorded);
+        }
+    }
+
+    // Shows the recorded visuals the judgment line passed during a replay
+    fn advance_replay(&mut self) {
+        let line_time = self
+            .scroll_transform()
+            .map_time_at_line(self.current_time());
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        let (from, to_ms) = (replay.shown_up_to, replay.to_ms);
+        replay.shown_up_to = line_time.max(from);
+        let recorded: Vec<_> = self
+            .judgment_log()
+            .between(from, line_time.min(to_ms))
+            .copied()
+            .collect();
+        for (at_time, recorded) in recorded {
+            self.show_recorded(at_time, recorded);
+        }
+        if line_time >= to_ms {
+            self.end_replay();
+        }
+    }
+
+    fn show_recorded(&mut self, at_time: f64, recorded: Recorded) {
+        match &mut self.renderer {
+            GameModeRenderer::Mania(mania) => match recorded {
+                Recorded::Hit { column, offset_ms } => mania.report_hit(column, offset_ms, at_time),
+                Recorded::HoldBreak { note_index } => mania.report_hold_break(note_index, at_time),
+                Recorded::GhostTap { column } => mania.report_ghost_tap(column, at_time),
+            },
+        }
+    }
+
+    // Clock time of the frame about to be drawn
+    fn begin_frame(&mut self) -> f64 {
+        self.poll_caches(false);
+        self.advance_replay();
+        let elapsed = self.elapsed_ms();
+        if let Some(end) = self.end_time().filter(|end| elapsed >= *end) {
+            if self.end_behavior == EndBehavior::Loop {
+                // Notes up to the end still pass once before wrapping
+                let end_line = self.scroll_transform().map_time_at_line(end);
+                if !self.scrubbing && self.replay.is_none() {
+                    self.emit_passed_notes(end_line, true);
+                }
+                self.set_current_time(elapsed % end);
+                // Resumes from the start of the clock rather than the seek
+                let start_line = self.scroll_transform().map_time_at_line(0.0);
+                self.passed_notes.advance(start_line, self.tail_grace_ms());
+                self.events.push(Event::LoopWrapped {
+                    at_map_time: end_line,
+                });
+                // So the first section is entered again
+                self.current_section = None;
+            }
+        }
+        let current_time = self.current_time();
+        if self
+            .scroll_animation
+            .is_some_and(|animation| animation.is_over(current_time))
+        {
+            self.scroll_animation = None;
+            self.log_timeline();
+            self.refresh_sections();
+        }
+        let transform = self.scroll_transform();
+        let line_time = transform.map_time_at_line(current_time);
+        // Both ends under the current transform, so speed and scroll time
+        // changes, animated or not, don't count as time passing
+        self.frame_delta_ms = self
+            .last_frame_clock
+            .map(|last| line_time - transform.map_time_at_line(last));
+        self.last_line_time = Some(line_time);
+        self.last_frame_clock = Some(current_time);
+        self.hitch = false;
+        let section = self.section_index(current_time);
+        let finished = self.is_finished();
+        if !self.scrubbing && self.replay.is_none() {
+            self.emit_passed_notes(line_time, finished);
+            if let Some(index) = section.filter(|index| self.current_section != Some(*index)) {
+                self.events.push(Event::SectionEntered {
+                    kind: self.sections[index].kind,
+                    at_map_time: line_time,
+                });
+            }
+            if finished && !self.finished {
+                self.events.push(Event::Finished {
+                    at_map_time: line_time,
+                });
+            }
+            let threshold = self.hitch_threshold_ms;
+            let hitch = self
+                .frame_delta_ms
+                .filter(|delta| threshold.is_some_and(|threshold| *delta > threshold));
+            if let Some(delta_ms) = hitch {
+                self.hitch = true;
+                self.events.push(Event::FrameHitch(FrameHitch {
+                    delta_ms,
+                    at_map_time: line_time,
+                }));
+            }
+        }
+        self.current_section = section;
+        self.finished = finished;
+        self.last_frame = Some(Instant::now());
+
+        let offsets = self
+            .column_swap
+            .as_ref()
+            .and_then(|swap| swap.offsets_at(current_time));
+        if offsets.is_none() {
+            self.column_swap = None;
+        }
+        let offsets = match &self.column_drag {
+            Some(drag) => drag.offsets(self.keycount()),
+            None => offsets.unwrap_or_default(),
+        };
+        let camera = self.camera_track.at(current_time);
+        let keycount = self.keycount();
+        match &mut self.renderer {
+            GameModeRenderer::Mania(mania) => {
+                mania.set_column_offsets(offsets);
+                mania.set_camera(camera);
+                if let Some(window_ms) = mania.activity_meter().map(|s| s.window_ms) {
+                    let nps = self.column_activity.nps(line_time, window_ms, keycount);
+                    mania.set_column_nps(nps);
+                }
+            }
+        }
+        current_time
+    }
+
+    /// Keyframed zoom and pan of the playfield, for trailer-style renders.
+    /// Outside the track the playfield is drawn as usual. `column_at` and the
+    /// rect returned by `render_in` account for the camera.
+    pub fn set_camera_track(&mut self, keyframes: Vec<CameraKeyframe>) {
+        self.camera_track = CameraTrack::new(keyframes);
+    }
+
+    /// See `ManiaRenderer::set_image_note_limit`.
+    pub fn set_image_note_limit(&mut self, limit: Option<usize>) {
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_image_note_limit(limit);
+        }
+    }
+
+    pub fn playfield_rect(&self) -> Option<egui::Rect> {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.playfield_rect(),
+        }
+    }
+
+    /// Crops a screenshot of the whole viewport, as delivered by
+    /// `egui::Event::Screenshot`, to the playfield of the last frame. The
+    /// result is at the native resolution of the screen.
+    pub fn crop_screenshot(
+        &self,
+        screenshot: &egui::ColorImage,
+        pixels_per_point: f32,
+    ) -> Option<egui::ColorImage> {
+        let [width, height] = screenshot.size;
+        let screen = egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(width as f32, height as f32) / pixels_per_point,
+        );
+        let playfield = self.playfield_rect()?.intersect(screen);
+        if !playfield.is_positive() {
+            return None;
+        }
+        Some(screenshot.region(&playfield, Some(pixels_per_point)))
+    }
+
+    /// Scroll and column math of the last rendered frame, for drawings that
+    /// line up with its notes. `None` before the first frame.
+    pub fn frame_transform(&self) -> Option<FrameTransform> {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.frame_transform(),
+        }
+    }
+
+    /// What `render*` would draw at clock time `time_ms`, as plain data for
+    /// engines drawing the notes with their own sprites. Laid out like the
+    /// last render; when comparing, describes the stage of `hit_objects`.
+    pub fn frame_description(&self, time_ms: f64) -> FrameDescription {
+        let stage = usize::from(self.comparison);
+        let scroll_time_ms = self.effective_scroll_time(time_ms);
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.frame_description(
+                &self.hit_objects,
+                time_ms,
+                scroll_time_ms,
+                self.speed,
+                self.keycount(),
+                stage,
+            ),
+        }
+    }
+
+    pub fn frame_stats(&self) -> FrameStats {
+        let stats = match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.frame_stats(),
+        };
+        FrameStats {
+            hitch: self.hitch,
+            ..stats
+        }
+    }
+
+    /// Heap allocations made by the last `render*` call, see `alloc_audit`.
+    #[cfg(feature = "alloc-audit")]
+    pub fn frame_allocations(&self) -> usize {
+        self.frame_allocations
+    }
+
+    /// Map time between the judgment line of the last two rendered frames,
+    /// `None` on the first frame after loading or seeking. Measured at the
+    /// current speed and scroll time, so changing them doesn't add to it.
+    pub fn last_frame_delta_ms(&self) -> Option<f64> {
+        self.frame_delta_ms
+    }
+
+    /// Frames more than `ms` of map time after the previous one add a
+    /// `FrameHitch` to `drain_passed_notes`, e.g. to annotate recordings.
+    /// `None` (the default) disables detection.
+    pub fn set_hitch_threshold(&mut self, ms: Option<f64>) {
+        self.hitch_threshold_ms = match ms {
+            Some(ms) if ms.is_nan() || ms <= 0.0 => {
+                trace_warn!(ms, "invalid hitch threshold, disabling detection");
+                None
+            }
+            ms => ms,
+        };
+    }
+
+    /// Does the lazy work of the first frame ahead of time, e.g. while a
+    /// song select list is idle. Optional and safe to call repeatedly, also
+    /// before `ctx` ran its first pass.
+    pub fn warm_up(&self, ctx: &egui::Context) -> WarmUpTimings {
+        match &self.renderer {
+            GameModeRenderer::Mania(mania) => mania.warm_up(ctx, self.keycount()),
+        }
+    }
+
+    /// Per-column note rate bars, fed by the notes passing the judgment line.
+    /// Seeking empties them.
+    pub fn set_activity_meter(&mut self, style: Option<ActivityMeterStyle>) {
+        let style = match style {
+            Some(style) if !style.is_valid() => {
+                trace_warn!(?style, "invalid activity meter, disabling it");
+                None
+            }
+            style => style,
+        };
+        self.column_activity.clear();
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_activity_meter(style);
+            mania.set_column_nps(Vec::new());
+        }
+    }
+
+    /// Set by hosts while a seek slider is dragged. Frames then only draw:
+    /// no passed notes are collected, no motion blur or hit readouts are
+    /// drawn. Clearing it resyncs the passed notes once, like a seek.
+    pub fn set_scrub_hint(&mut self, scrubbing: bool) {
+        if self.scrubbing && !scrubbing {
+            self.passed_notes.resync();
+        }
+        self.scrubbing = scrubbing;
+        #[allow(irrefutable_let_patterns)]
+        if let GameModeRenderer::Mania(mania) = &mut self.renderer {
+            mania.set_scrub_hint(scrubbing);
+        }
+    }
+
+    /// Caps how often `repaint_after` asks for a new frame, e.g. while the
+    /// window is unfocused. `None` means every frame.
+    pub fn set_max_fps(&mut self, max_fps: Option<f32>) {
+        self.max_fps = match max_fps {
+            Some(fps) if fps.is_nan() || fps <= 0.0 => {
+                trace_warn!(fps, "invalid frame rate cap, rendering every frame");
+                None
+            }
+            max_fps => max_fps,
+        };
+    }
+
+    /// Time left before the next frame is due. Pass it to
+    /// `egui::Context::request_repaint_after` instead of calling
+    /// `request_repaint` every frame; playback keeps advancing either way.
+    pub fn repaint_after(&self) -> Duration {
+        let (Some(fps), Some(last_frame)) = (self.max_fps, self.last_frame) else {
+            return Duration::ZERO;
+        };
+        Duration::from_secs_f32(1.0 / fps).saturating_sub(last_frame.elapsed())
+    }
+
+    pub fn needs_repaint(&self) -> bool {
+        self.repaint_after().is_zero()
     }
 
+    /// Back to the start, 0 unless a start behavior says otherwise.
     pub fn reset_time(&mut self) {
-        self.start_time = Instant::now();
+        self.set_current_time(self.start_clock_time());
+    }
+
+    /// Applies from the next `reset_time`. Defaults to `FromZero`.
+    pub fn set_start_behavior(&mut self, behavior: StartBehavior) {
+        self.start_behavior = match behavior {
+            StartBehavior::FromFirstNote { lead_ms } if !lead_ms.is_finite() || lead_ms < 0.0 => {
+                trace_warn!(lead_ms, "invalid start lead, using none");
+                StartBehavior::FromFirstNote { lead_ms: 0.0 }
+            }
+            behavior => behavior,
+        };
     }
 
+    pub fn start_behavior(&self) -> StartBehavior {
+        self.start_behavior
+    }
+
+    /// Map time of the earliest object, `None` for an empty map.
+    pub fn first_note_time(&self) -> Option<f64> {
+        self.hit_objects
+            .iter()
+            .map(|h| h.start_time)
+            .reduce(f64::min)
+    }
+
+    // Clock time `reset_time` seeks to
+    fn start_clock_time(&self) -> f64 {
+        let scroll = self.scroll_transform();
+        let time = match self.start_behavior {
+            StartBehavior::FromZero => return 0.0,
+            StartBehavior::FromFirstNote { lead_ms } => self
+                .first_note_time()
+                .map(|first| scroll.crossing_time(first) - lead_ms),
+            StartBehavior::FromPreviewPoint => (self.beatmap.preview_time >= 0)
+                .then(|| scroll.crossing_time(f64::from(self.beatmap.preview_time))),
+        };
+        time.unwrap_or(0.0).max(0.0)
+    }
+
+    /// Times before 0 seek to 0, where the clock starts.
     pub fn set_current_time(&mut self, time_ms: f64) {
-        self.start_time = Instant::now() - std::time::Duration::from_secs_f64(time_ms / 1000.0);
+        let now = Instant::now();
+        let start_time = Duration::try_from_secs_f64(time_ms.max(0.0) / 1000.0)
+            .ok()
+            .filter(|_| !time_ms.is_nan())
+            .and_then(|elapsed| now.checked_sub(elapsed));
+        let Some(start_time) = start_time else {
+            trace_warn!(time_ms, "invalid clock time, keeping the current one");
+            return;
+        };
+        self.start_time = start_time;
+        self.notify_seek();
+    }
+
+    fn notify_seek(&mut self) {
+        self.scroll_animation = None;
+        self.last_line_time = None;
+        self.last_frame_clock = None;
+        self.log_timeline();
+        let line_time = self
+            .scroll_transform()
+            .map_time_at_line(self.current_time());
+        self.passed_notes.seek(line_time);
+        self.column_activity.clear();
+        self.column_swap = None;
+        self.current_section = self.section_index(self.current_time());
+        self.finished = self.is_finished();
+        match &mut self.renderer {
+            GameModeRenderer::Mania(mania) => {
+                mania.notify_seek();
+                mania.set_column_offsets(Vec::new());
+                mania.clear_hold_breaks_after(line_time);
+            }
+        }
+    }
+
+    /// Seeks to the next head or tail, so "next" from inside a hold goes to
+    /// its tail. With `land_on_line` the note ends up exactly on the judgment
+    /// line, otherwise the clock jumps to the note's time. Returns `false`
+    /// when there is no next note.
+    pub fn seek_next_note(&mut self, land_on_line: bool) -> bool {
+        self.seek_note(true, None, land_on_line)
+    }
+
+    pub fn seek_prev_note(&mut self, land_on_line: bool) -> bool {
+        self.seek_note(false, None, land_on_line)
+    }
+
+    /// `seek_next_note` only considering notes of `column`.
+    pub fn seek_next_note_in(&mut self, column: usize, land_on_line: bool) -> bool {
+        self.seek_note(true, Some(column), land_on_line)
+    }
+
+    pub fn seek_prev_note_in(&mut self, column: usize, land_on_line: bool) -> bool {
+        self.seek_note(false, Some(column), land_on_line)
+    }
+
+    fn seek_note(&mut self, forward: bool, column: Option<usize>, land_on_line: bool) -> bool {
+        let scroll = self.scroll_transform();
+        let current_time = self.current_time();
+        let now = if land_on_line {
+            scroll.map_time_at_line(current_time)
+        } else {
+            scroll.map_time(current_time)
+        };
+
+        let events = self.passed_notes.events();
+        let in_column = |e: &&PassedNote| column.is_none_or(|column| e.column == column);
+        let target = if forward {
+            let start = events.partition_point(|e| e.time_ms <= now + SEEK_NOTE_TOLERANCE_MS);
+            events[start..].iter().find(in_column)
+        } else {
+            let end = events.partition_point(|e| e.time_ms < now - SEEK_NOTE_TOLERANCE_MS);
+            events[..end].iter().rev().find(in_column)
+        };
+        let Some(target) = target.map(|e| e.time_ms) else {
+            return false;
+        };
+
+        if land_on_line {
+            self.set_current_time(scroll.crossing_time(target));
+        } else {
+            self.set_current_time(scroll.clock_time(target));
+        }
+        true
+    }
+
+    /// Intro, notes, breaks, kiai and outro spans of the clock, back to back
+    /// from 0 to `duration`. Follows the playback speed and scroll time.
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// Clock time at which the map ends, 0 for an empty map.
+    pub fn duration(&self) -> f64 {
+        self.sections.last().map_or(0.0, |section| section.end_ms)
+    }
+
+    /// Seeks to a fraction of `duration`, clamped to [0, 1], then snaps
+    /// to the closest beat line if seek snapping is on.
+    pub fn seek_fraction(&mut self, fraction: f64) {
+        let time = self.fraction_time(fraction);
+        let Some(divisor) = self.seek_snapping else {
+            self.set_current_time(time);
+            return;
+        };
+
+        let scroll = self.scroll_transform();
+        let line_time = scroll.map_time_at_line(time);
+        let snapped = snap::nearest_line(
+            &self.beatmap.control_points,
+            line_time,
+            divisor.get() as f64,
+        );
+        // Lines before the map's first timing point can cross before 0
+        let snapped = snapped.map_or(time, |line| scroll.crossing_time(line).max(0.0));
+        self.set_current_time(snapped);
+    }
+
+    /// `seek_fraction` ignoring seek snapping, e.g. while a modifier key is
+    /// held.
+    pub fn seek_fraction_unsnapped(&mut self, fraction: f64) {
+        self.set_current_time(self.fraction_time(fraction));
     }
 
+    fn fraction_time(&self, fraction: f64) -> f64 {
+        let fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        fraction * self.duration()
+    }
+
+    /// Makes `seek_fraction` land the judgment line exactly on the closest
+    /// 1/`divisor` line of the timing points.
+    pub fn set_seek_snapping(&mut self, divisor: Option<Divisor>) {
+        self.seek_snapping = divisor;
+    }
+
+    /// Playback time, held or wrapped at `end_time` by the end behavior.
     pub fn current_time(&self) -> f64 {
+        let elapsed = self.elapsed_ms();
+        match (self.end_behavior, self.end_time()) {
+            (EndBehavior::Loop, Some(end)) => elapsed % end,
+            (_, Some(end)) => elapsed.min(end),
+            (_, None) => elapsed,
+        }
+    }
+
+    fn elapsed_ms(&self) -> f64 {
         self.start_time.elapsed().as_secs_f64() * 1000.0
     }
+
+    /// Defaults to `EndBehavior::StopAt` one scroll time after `duration`.
+    pub fn set_end_behavior(&mut self, behavior: EndBehavior) {
+        self.end_behavior = match behavior {
+            EndBehavior::StopAt { tail_ms: Some(ms) } if ms.is_nan() || ms < 0.0 => {
+                trace_warn!(ms, "invalid end tail, waiting one scroll time");
+                EndBehavior::StopAt { tail_ms: None }
+            }
+            behavior => behavior,
+        };
+    }
+
+    pub fn end_behavior(&self) -> EndBehavior {
+        self.end_behavior
+    }
+
+    /// Clock time at which playback stops or loops back, `None` when it
+    /// runs forever or the map is empty.
+    pub fn end_time(&self) -> Option<f64> {
+        let duration = self.duration();
+        let end = match self.end_behavior {
+            EndBehavior::RunForever => return None,
+            EndBehavior::StopAt { tail_ms } => {
+                duration + tail_ms.unwrap_or(f64::from(self.scroll_time_ms))
+            }
+            EndBehavior::Loop | EndBehavior::FreezeLastFrame => duration,
+        };
+        (end > 0.0).then_some(end)
+    }
+
+    /// The clock reached `end_time`, or `duration` when running forever.
+    /// Never true while looping.
+    pub fn is_finished(&self) -> bool {
+        let elapsed = self.elapsed_ms();
+        match (self.end_behavior, self.end_time()) {
+            (EndBehavior::Loop, _) => false,
+            (_, Some(end)) => elapsed >= end,
+            (_, None) => !self.sections.is_empty() && elapsed >= self.duration(),
+        }
+    }
+
+    /// Map time playing at `wall`, the one a seek to it with
+    /// `land_on_line == false` would jump to; notes reach the judgment line
+    /// `scroll_time_ms` later. Past instants use the speed and seeks in
+    /// effect back then, future ones assume nothing changes. `None` before
+    /// the player existed.
+    pub fn wall_to_map_time(&self, wall: Instant) -> Option<f64> {
+        self.timeline.map_time_at(wall)
+    }
+
+    /// Inverse of `wall_to_map_time`. Seeks make the mapping discontinuous:
+    /// a map time played several times gives the latest instant, and `None`
+    /// means every seek skipped past it. Upcoming map times are predicted
+    /// assuming no further seek or speed change.
+    pub fn map_time_to_wall(&self, map_time: f64) -> Option<Instant> {
+        self.timeline.wall_at(map_time)
+    }
+
+    // Starts a timeline segment after a seek or a speed change
+    fn log_timeline(&mut self) {
+        self.timeline
+            .push(Instant::now(), self.start_time, self.scroll_transform());
+    }
+}
+
+// Scroll time easing to the current one, see `set_scroll_animation`
+#[derive(Clone, Copy, Debug)]
+struct ScrollAnimation {
+    from_ms: f32,
+    // Clock time
+    started_at: f64,
+    duration_ms: f64,
+}
+
+impl ScrollAnimation {
+    fn is_over(&self, now: f64) -> bool {
+        now - self.started_at >= self.duration_ms
+    }
+
+    fn scroll_time_at(&self, now: f64, to_ms: f32) -> f32 {
+        let t = ((now - self.started_at) / self.duration_ms).clamp(0.0, 1.0) as f32;
+        self.from_ms + (to_ms - self.from_ms) * Easing::EaseOut.apply(t)
+    }
+}
+
+// Original on the left when comparing
+fn stages<'a, 'b>(comparison: bool, all: &'b [&'a [HitObject]; 2]) -> &'b [&'a [HitObject]] {
+    if comparison {
+        all
+    } else {
+        &all[1..]
+    }
+}
+
+// File a map names, `None` when it names none
+fn named_file(name: &str) -> Option<&Path> {
+    let name = name.trim();
+    (!name.is_empty()).then(|| Path::new(name))
+}
+
+// Caches `parallel_load` is still building, see `Player::poll_caches`
+#[derive(Default)]
+struct PendingCaches {
+    column_stats: Option<JoinHandle<Vec<ColumnStats>>>,
+    composition: Option<JoinHandle<Composition>>,
+    sections: Option<JoinHandle<Vec<Section>>>,
+    hitsound_issues: Option<JoinHandle<Vec<HitsoundIssue>>>,
+}
+
+impl PendingCaches {
+    fn is_empty(&self) -> bool {
+        self.column_stats.is_none()
+            && self.composition.is_none()
+            && self.sections.is_none()
+            && self.hitsound_issues.is_none()
+    }
 }
+
+// Result of the cache thread in `pending` once it's done, waiting for it
+// with `wait`. Its panic is carried over to the calling thread.
+fn finished<T>(pending: &mut Option<JoinHandle<T>>, wait: bool) -> Option<T> {
+    if !wait && !pending.as_ref()?.is_finished() {
+        return None;
+    }
+    let handle = pending.take()?;
+    Some(
+        handle
+            .join()
+            .unwrap_or_else(|payload| std::panic::resume_unwind(payload)),
+    )
+}
+
+// Hosts build frames on worker threads; keep everything they may move there Send
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+const _: fn() = || {
+    assert_send::<Player>();
+    assert_sync::<Player>();
+    assert_send::<PlayerBuilder>();
+    assert_send::<ManiaRenderer>();
+    assert_send::<NoteStyle>();
+    assert_send::<Letterbox>();
+    assert_send::<LoadReport>();
+    assert_send::<PassedNotes>();
+    assert_send::<EffectClock>();
+};
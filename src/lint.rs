@@ -0,0 +1,186 @@
+//! Ranking-criteria style checks for mania charts. Nothing here runs at load,
+//! `Player::lint` computes the list on demand.
+
+use crate::hitsounds::resolved_sample;
+use crate::layout::mania::column_for_x;
+use crate::snap::nearest_line;
+use crate::stats::chord_sizes;
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+use rosu_map::section::timing_points::ControlPoints;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintConfig {
+    /// Notes of a column closer than this, in ms, are reported.
+    pub min_jack_gap_ms: f64,
+    /// Holds shorter than this, in ms, are reported.
+    pub min_hold_ms: f64,
+    /// Max notes pressed or held at once, unchecked when `None`.
+    pub max_chord: Option<usize>,
+    /// Max distance, in ms, from the closest 1/16 or 1/12 line.
+    pub snap_tolerance_ms: f64,
+    /// Notes quieter than this, in percent, are reported. Unchecked when
+    /// `None`; the ranking criteria ask for at least 5.
+    pub min_volume: Option<i32>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            min_jack_gap_ms: 10.0,
+            min_hold_ms: 30.0,
+            max_chord: None,
+            snap_tolerance_ms: 2.0,
+            min_volume: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LintKind {
+    JackTooClose {
+        gap_ms: f64,
+    },
+    ShortHold {
+        duration_ms: f64,
+    },
+    ChordTooLarge {
+        notes: usize,
+    },
+    BeforeStart,
+    /// `offset_ms` is the signed distance to the closest snap line.
+    Unsnapped {
+        offset_ms: f64,
+    },
+    /// Volume after the sample point, see `hitsounds::resolved_sample`.
+    LowVolume {
+        volume: i32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lint {
+    /// Map time, as in `PassedNote::time_ms`.
+    pub time_ms: f64,
+    /// `None` for chord issues, which span several columns.
+    pub column: Option<usize>,
+    pub kind: LintKind,
+}
+
+// Snap divisors objects are expected to sit on
+const SNAP_DIVISORS: [f64; 2] = [16.0, 12.0];
+
+pub(crate) fn lint(
+    hit_objects: &[HitObject],
+    control_points: &ControlPoints,
+    keycount: usize,
+    config: &LintConfig,
+) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    // (start, end, column) of every mania object
+    let mut objects: Vec<(f64, f64, usize)> = hit_objects
+        .iter()
+        .filter_map(|h| match &h.kind {
+            HitObjectKind::Circle(c) => Some((h.start_time, h.start_time, c.pos.x)),
+            HitObjectKind::Hold(hold) => {
+                Some((h.start_time, h.start_time + hold.duration, hold.pos_x))
+            }
+            _ => None,
+        })
+        .map(|(start, end, x)| (start, end, column_for_x(x, keycount)))
+        .collect();
+    objects.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut last_in_column: Vec<Option<f64>> = vec![None; keycount];
+    for &(start, end, column) in &objects {
+        let mut push = |kind| {
+            lints.push(Lint {
+                time_ms: start,
+                column: Some(column),
+                kind,
+            })
+        };
+
+        if start < 0.0 {
+            push(LintKind::BeforeStart);
+        }
+        if end > start && end - start < config.min_hold_ms {
+            push(LintKind::ShortHold {
+                duration_ms: end - start,
+            });
+        }
+        if let Some(last) = last_in_column[column] {
+            if start - last < config.min_jack_gap_ms {
+                push(LintKind::JackTooClose {
+                    gap_ms: start - last,
+                });
+            }
+        }
+        // Holds are checked against their tail
+        last_in_column[column] = Some(end);
+
+        for time in [start, end] {
+            if let Some(offset_ms) = snap_offset(control_points, time) {
+                if offset_ms.abs() > config.snap_tolerance_ms {
+                    lints.push(Lint {
+                        time_ms: time,
+                        column: Some(column),
+                        kind: LintKind::Unsnapped { offset_ms },
+                    });
+                }
+            }
+            if end == start {
+                break;
+            }
+        }
+    }
+
+    if let Some(min_volume) = config.min_volume {
+        for hit_object in hit_objects {
+            let x = match &hit_object.kind {
+                HitObjectKind::Circle(c) => c.pos.x,
+                HitObjectKind::Hold(hold) => hold.pos_x,
+                _ => continue,
+            };
+            // Nothing to weigh, see `HitsoundIssueKind::MissingBank`
+            if hit_object.samples.is_empty() {
+                continue;
+            }
+            let volume = resolved_sample(hit_object, control_points).volume;
+            if volume < min_volume {
+                lints.push(Lint {
+                    time_ms: hit_object.start_time,
+                    column: Some(column_for_x(x, keycount)),
+                    kind: LintKind::LowVolume { volume },
+                });
+            }
+        }
+    }
+
+    if let Some(max_chord) = config.max_chord {
+        let spans: Vec<_> = objects
+            .iter()
+            .map(|&(start, end, _)| (start, end))
+            .collect();
+        for (time, notes) in chord_sizes(&spans) {
+            if notes > max_chord {
+                lints.push(Lint {
+                    time_ms: time,
+                    column: None,
+                    kind: LintKind::ChordTooLarge { notes },
+                });
+            }
+        }
+    }
+
+    lints.sort_by(|a, b| a.time_ms.total_cmp(&b.time_ms));
+    lints
+}
+
+// Signed distance to the closest 1/16 or 1/12 line
+fn snap_offset(control_points: &ControlPoints, time: f64) -> Option<f64> {
+    SNAP_DIVISORS
+        .iter()
+        .filter_map(|divisor| nearest_line(control_points, time, *divisor))
+        .map(|line| time - line)
+        .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+}
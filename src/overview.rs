@@ -0,0 +1,189 @@
+//! A chart squeezed into a horizontal strip, one row per column, for seeking
+//! at a glance. Drawn by `ui::overview`; zoomed in, only the notes of the
+//! shown window are visited.
+
+use crate::layout::mania::column_for_x;
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+
+/// Map times an overview strip shows. At `zoom` 1 the strip spans `range`,
+/// above it a window `zoom` times shorter follows the judgment line, kept
+/// inside `range`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OverviewView {
+    pub range: (f64, f64),
+    pub zoom: f64,
+}
+
+impl OverviewView {
+    /// The whole of `from..to`.
+    pub fn new(from: f64, to: f64) -> Self {
+        Self {
+            range: (from, to),
+            zoom: 1.0,
+        }
+    }
+
+    /// From 0 to the end of the last object, e.g. of `Player::hit_objects`.
+    pub fn of(hit_objects: &[HitObject]) -> Self {
+        let end = hit_objects
+            .iter()
+            .map(|h| match &h.kind {
+                HitObjectKind::Hold(hold) => h.start_time + hold.duration,
+                _ => h.start_time,
+            })
+            .fold(0.0, f64::max);
+        Self::new(0.0, end)
+    }
+
+    /// Shows `window_ms` of the range at a time.
+    pub fn with_window(mut self, window_ms: f64) -> Self {
+        self.zoom = (self.range.1 - self.range.0) / window_ms;
+        self
+    }
+
+    /// Map times shown with the judgment line at map time `line_time`.
+    pub fn visible(&self, line_time: f64) -> (f64, f64) {
+        let (from, to) = self.range;
+        if !(from.is_finite() && to.is_finite() && line_time.is_finite()) {
+            return (0.0, 0.0);
+        }
+        let to = to.max(from);
+        let zoom = if self.zoom.is_finite() {
+            self.zoom.max(1.0)
+        } else {
+            1.0
+        };
+        let window = (to - from) / zoom;
+        // `to - window` can round below `from` at zoom 1
+        let start = (line_time - window / 2.0).min(to - window).max(from);
+        (start, start + window)
+    }
+
+    /// Map time at `fraction` of the strip's width, from its left edge.
+    pub fn time_at(&self, fraction: f64, line_time: f64) -> f64 {
+        let (from, to) = self.visible(line_time);
+        let fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        from + fraction * (to - from)
+    }
+}
+
+/// Heads of one column closer than a strip pixel to the previous one,
+/// merged. A lone note starts and ends at its own time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OverviewMark {
+    pub column: usize,
+    pub start_ms: f64,
+    pub end_ms: f64,
+    pub count: usize,
+}
+
+// Heads of `hit_objects`, sorted by start time, within `visible`, merged
+// per column for a strip `width_px` wide
+pub(crate) fn marks(
+    hit_objects: &[HitObject],
+    keycount: usize,
+    visible: (f64, f64),
+    width_px: f32,
+) -> Vec<OverviewMark> {
+    let (from, to) = visible;
+    let ms_per_px = (to - from) / f64::from(width_px.max(1.0));
+    let first = hit_objects.partition_point(|h| h.start_time < from);
+    let last = hit_objects.partition_point(|h| h.start_time <= to);
+
+    let mut marks = Vec::new();
+    let mut open: Vec<Option<OverviewMark>> = vec![None; keycount];
+    for h in &hit_objects[first..last.max(first)] {
+        let x = match &h.kind {
+            HitObjectKind::Circle(circle) => circle.pos.x,
+            HitObjectKind::Hold(hold) => hold.pos_x,
+            _ => continue,
+        };
+        let column = column_for_x(x, keycount);
+        match &mut open[column] {
+            Some(mark) if h.start_time - mark.end_ms < ms_per_px => {
+                mark.end_ms = h.start_time;
+                mark.count += 1;
+            }
+            slot => marks.extend(slot.replace(OverviewMark {
+                column,
+                start_ms: h.start_time,
+                end_ms: h.start_time,
+                count: 1,
+            })),
+        }
+    }
+    marks.extend(open.into_iter().flatten());
+    marks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::mania::column_center_x;
+    use rosu_map::section::hit_objects::HitObjectCircle;
+    use rosu_map::util::Pos;
+
+    fn note(time: f64, column: usize) -> HitObject {
+        HitObject {
+            start_time: time,
+            kind: HitObjectKind::Circle(HitObjectCircle {
+                pos: Pos::new(column_center_x(column, 2), 192.0),
+                new_combo: false,
+                combo_offset: 0,
+            }),
+            samples: Vec::new(),
+        }
+    }
+
+    // Pairs 20 ms apart every 500 ms in column 0, one note between each
+    // pair in column 1
+    fn chart() -> Vec<HitObject> {
+        (0..20)
+            .flat_map(|i| {
+                let time = f64::from(i) * 500.0;
+                [note(time, 0), note(time + 10.0, 1), note(time + 20.0, 0)]
+            })
+            .collect()
+    }
+
+    fn counts(view: OverviewView, line_time: f64, width_px: f32) -> Vec<(usize, usize)> {
+        let marks = marks(&chart(), 2, view.visible(line_time), width_px);
+        let mut counts: Vec<_> = marks.iter().map(|m| (m.column, m.count)).collect();
+        counts.sort_unstable();
+        counts
+    }
+
+    #[test]
+    fn visible_window_stays_in_range() {
+        let view = OverviewView::new(0.0, 10_000.0).with_window(1000.0);
+        assert_eq!(view.visible(5000.0), (4500.0, 5500.0));
+        assert_eq!(view.visible(-300.0), (0.0, 1000.0));
+        assert_eq!(view.visible(9900.0), (9000.0, 10_000.0));
+
+        let view = OverviewView::new(2485.486636824229, 515240.5557274743);
+        let (from, to) = view.visible(1000.0);
+        assert_eq!(from, view.range.0);
+        assert!((to - view.range.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn marks_merge_notes_closer_than_a_pixel() {
+        let view = OverviewView::new(0.0, 10_000.0);
+        // 100 ms a pixel: every pair merges, lone notes stay apart
+        let mut expected = vec![(0, 2); 20];
+        expected.extend([(1, 1); 20]);
+        assert_eq!(counts(view, 5000.0, 100.0), expected);
+        // 1000 ms a pixel: one mark a column
+        assert_eq!(counts(view, 5000.0, 10.0), [(0, 40), (1, 20)]);
+        // Zoomed to 1000 ms, 10 ms a pixel: nothing merges
+        let zoomed = view.with_window(1000.0);
+        assert_eq!(
+            counts(zoomed, 5000.0, 100.0),
+            [(0, 1), (0, 1), (0, 1), (0, 1), (0, 1), (1, 1), (1, 1)]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Playback rate varying over the map. The clock is the integral of
+//! `1 / rate` over map time, so a note's position always follows the rate
+//! at that point of the map.
+
+use crate::camera::Easing;
+
+// Simpson intervals over the ramp, plenty for the smooth easing curves
+const INTEGRATION_STEPS: usize = 32;
+const INVERSION_ITERATIONS: usize = 48;
+
+/// Rate going from `from` to `to` between map times `start_ms` and
+/// `end_ms`. Before the ramp the rate is `from`, after it `to`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateRamp {
+    pub from: f64,
+    pub to: f64,
+    pub start_ms: f64,
+    pub end_ms: f64,
+    pub curve: Easing,
+}
+
+impl RateRamp {
+    pub(crate) fn is_valid(&self) -> bool {
+        let rate_ok = |rate: f64| rate.is_finite() && rate > 0.0;
+        rate_ok(self.from)
+            && rate_ok(self.to)
+            && self.start_ms.is_finite()
+            && self.end_ms.is_finite()
+            && self.end_ms > self.start_ms
+    }
+
+    /// Rate at map time `map_time`.
+    pub fn rate_at(&self, map_time: f64) -> f64 {
+        if map_time <= self.start_ms {
+            return self.from;
+        }
+        if map_time >= self.end_ms {
+            return self.to;
+        }
+        let t = (map_time - self.start_ms) / (self.end_ms - self.start_ms);
+        self.from + (self.to - self.from) * self.curve.apply(t as f32) as f64
+    }
+
+    /// Clock ms elapsed between map time 0 and `map_time`.
+    pub fn clock_time(&self, map_time: f64) -> f64 {
+        self.since_start(map_time) - self.since_start(0.0)
+    }
+
+    /// Inverse of `clock_time`.
+    pub fn map_time(&self, clock_time: f64) -> f64 {
+        let target = clock_time + self.since_start(0.0);
+        if target <= 0.0 {
+            return self.start_ms + target * self.from;
+        }
+        let ramp = self.since_start(self.end_ms);
+        if target >= ramp {
+            return self.end_ms + (target - ramp) * self.to;
+        }
+
+        // The clock strictly increases with map time
+        let (mut low, mut high) = (self.start_ms, self.end_ms);
+        for _ in 0..INVERSION_ITERATIONS {
+            let middle = (low + high) / 2.0;
+            if self.since_start(middle) < target {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        (low + high) / 2.0
+    }
+
+    // Signed clock ms between the start of the ramp and `map_time`
+    fn since_start(&self, map_time: f64) -> f64 {
+        if map_time <= self.start_ms {
+            return (map_time - self.start_ms) / self.from;
+        }
+        let ramp_end = map_time.min(self.end_ms);
+        let during = integrate(|m| 1.0 / self.rate_at(m), self.start_ms, ramp_end);
+        during + (map_time - ramp_end).max(0.0) / self.to
+    }
+}
+
+/// Composite Simpson integral of `f` over `[a, b]`.
+pub(crate) fn integrate(f: impl Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    let h = (b - a) / INTEGRATION_STEPS as f64;
+    let inner: f64 = (1..INTEGRATION_STEPS)
+        .map(|i| {
+            let weight = if i % 2 == 1 { 4.0 } else { 2.0 };
+            weight * f(a + i as f64 * h)
+        })
+        .sum();
+    (f(a) + inner + f(b)) * h / 3.0
+}
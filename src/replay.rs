@@ -0,0 +1,101 @@
+//! Judgment visuals reported to the player, kept by map time so
+//! `Player::replay_window` can show them again.
+
+use crate::rate::RateRamp;
+use crate::scatter::TimedError;
+use std::collections::VecDeque;
+
+// Oldest reports are dropped past this many
+const MAX_RECORDED: usize = 16384;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Recorded {
+    Hit { column: usize, offset_ms: f64 },
+    HoldBreak { note_index: usize },
+    GhostTap { column: usize },
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct JudgmentLog {
+    // (map time, report), sorted by time
+    entries: VecDeque<(f64, Recorded)>,
+}
+
+impl JudgmentLog {
+    pub(crate) fn record(&mut self, at_time: f64, recorded: Recorded) {
+        if self.entries.len() == MAX_RECORDED {
+            self.entries.pop_front();
+        }
+        let index = self.entries.partition_point(|(time, _)| *time <= at_time);
+        self.entries.insert(index, (at_time, recorded));
+    }
+
+    /// Reports in `from..to`, in order.
+    pub(crate) fn between(
+        &self,
+        from: f64,
+        to: f64,
+    ) -> impl Iterator<Item = &(f64, Recorded)> + '_ {
+        let start = self.entries.partition_point(|(time, _)| *time < from);
+        let end = self.entries.partition_point(|(time, _)| *time < to);
+        self.entries.range(start..end.max(start))
+    }
+
+    /// Reported hits, in order.
+    pub(crate) fn hit_errors(&self) -> impl Iterator<Item = TimedError> + Clone + '_ {
+        self.entries
+            .iter()
+            .filter_map(|(time_ms, recorded)| match recorded {
+                Recorded::Hit { offset_ms, .. } => Some(TimedError {
+                    time_ms: *time_ms,
+                    offset_ms: *offset_ms,
+                }),
+                _ => None,
+            })
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+// A window being replayed, and what to restore once it is over
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Replay {
+    pub(crate) to_ms: f64,
+    // Map time up to which recorded visuals were shown again
+    pub(crate) shown_up_to: f64,
+    pub(crate) resume_time: f64,
+    pub(crate) speed: f64,
+    pub(crate) rate_ramp: Option<RateRamp>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn times(log: &JudgmentLog, from: f64, to: f64) -> Vec<f64> {
+        log.between(from, to).map(|(time, _)| *time).collect()
+    }
+
+    #[test]
+    fn between_is_sorted_and_half_open() {
+        let mut log = JudgmentLog::default();
+        for time in [300.0, 100.0, 200.0, 200.0] {
+            log.record(time, Recorded::GhostTap { column: 0 });
+        }
+        assert_eq!(times(&log, 100.0, 300.0), [100.0, 200.0, 200.0]);
+        assert_eq!(times(&log, 250.0, 150.0), [] as [f64; 0]);
+    }
+
+    #[test]
+    fn oldest_reports_are_dropped() {
+        let mut log = JudgmentLog::default();
+        for i in 0..MAX_RECORDED + 2 {
+            log.record(i as f64, Recorded::GhostTap { column: 0 });
+        }
+        let kept = times(&log, f64::NEG_INFINITY, f64::INFINITY);
+        assert_eq!(kept.len(), MAX_RECORDED);
+        assert_eq!(kept[0], 2.0);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::trace::trace_warn;
+
+/// Anything the loader had to correct or found suspicious in the beatmap.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadIssue {
+    /// The map's keycount was outside `1..=MAX_KEYCOUNT` and got clamped.
+    KeycountClamped { circle_size: f32, keycount: usize },
+    /// A mania object's x is not near its column's canonical center, which
+    /// usually means a broken generator. Only reported with `strict_columns`.
+    OffCenterColumn {
+        index: usize,
+        time: f64,
+        x: f32,
+        column: usize,
+    },
+    /// A hold of a user-constructed object list had a negative or non-finite
+    /// duration. Inverted holds get start and end swapped, others a zero
+    /// duration. `index` is in the list sorted afterwards, `time` and
+    /// `duration` are the original values.
+    HoldNormalized {
+        index: usize,
+        time: f64,
+        duration: f64,
+    },
+    /// A consumed beatmap field was invalid and replaced, see the table in
+    /// the `defaults` module.
+    FieldDefaulted {
+        field: &'static str,
+        value: f64,
+        fallback: f64,
+    },
+    /// A break with non-finite times or ending before it starts.
+    BreakDropped { start: f64, end: f64 },
+    /// An object with a non-finite time, `index` being its position in the
+    /// file.
+    ObjectDropped { index: usize },
+    /// Lines of the `[Events]` section with a type the storyboard-lite
+    /// backgrounds don't support, see the `storyboard` module.
+    EventsIgnored { count: usize },
+    /// A hold shorter than the hold epsilon, drawn and passed as a note.
+    ShortHoldConverted {
+        index: usize,
+        time: f64,
+        duration: f64,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoadReport {
+    issues: Vec<LoadIssue>,
+}
+
+impl LoadReport {
+    pub fn push(&mut self, issue: LoadIssue) {
+        trace_warn!(?issue, "beatmap load issue");
+        self.issues.push(issue);
+    }
+
+    pub fn issues(&self) -> &[LoadIssue] {
+        &self.issues
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
@@ -0,0 +1,145 @@
+//! Hit errors plotted over map time, the scatterplot players use to spot
+//! drift over a play. Drawn by `ui::hit_error_scatter` and by the overlay of
+//! `Player::set_hit_error_scatter`.
+
+use crate::judge::windows::Windows;
+use crate::layout::mania::{AnchorTarget, OverlayAnchor, WindowTier};
+use egui::{pos2, Color32, Rect, Vec2};
+
+// Past this many errors in range, dots landing on the same pixel are drawn once
+const MAX_DOTS: usize = 10_000;
+
+// Pixel rows told apart when merging dots, lower ones share the last
+const MERGED_ROWS: usize = 4096;
+
+/// A hit judged at map time `time_ms`, `offset_ms` being negative when early.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimedError {
+    pub time_ms: f64,
+    pub offset_ms: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScatterStyle {
+    /// Dot colors, indexed by `WindowTier as usize`.
+    pub colors: [Color32; 6],
+    pub dot_radius: f32,
+    pub size: Vec2,
+    /// Map times shown, from the first to the last error when `None`.
+    pub time_range: Option<(f64, f64)>,
+    /// Where the overlay goes; the widget lays itself out instead.
+    pub anchor: OverlayAnchor,
+}
+
+impl ScatterStyle {
+    pub fn color(&self, tier: WindowTier) -> Color32 {
+        self.colors[tier as usize]
+    }
+
+    /// Zooms on map times `from..to`.
+    pub fn with_time_range(mut self, from: f64, to: f64) -> Self {
+        self.time_range = Some((from, to));
+        self
+    }
+}
+
+impl Default for ScatterStyle {
+    fn default() -> Self {
+        Self {
+            colors: [
+                Color32::WHITE,
+                Color32::from_rgb(255, 220, 60),
+                Color32::from_rgb(60, 220, 90),
+                Color32::from_rgb(60, 140, 255),
+                Color32::from_rgb(170, 90, 255),
+                Color32::from_rgb(255, 60, 60),
+            ],
+            dot_radius: 1.5,
+            size: Vec2::new(240.0, 80.0),
+            time_range: None,
+            anchor: OverlayAnchor {
+                relative_to: AnchorTarget::Window,
+                alignment: egui::Align2::RIGHT_BOTTOM,
+                offset: Vec2::new(-8.0, -8.0),
+            },
+        }
+    }
+}
+
+/// Best tier whose window holds `offset_ms`, `Miss` past every window.
+pub fn tier_for(offset_ms: f64, windows: &Windows) -> WindowTier {
+    let offset = offset_ms.abs() as f32;
+    WindowTier::ALL
+        .into_iter()
+        .find(|tier| offset <= tier.window_ms(windows))
+        .unwrap_or(WindowTier::Miss)
+}
+
+// Early errors go up, late ones down, the miss window filling `rect`. Errors
+// further out sit on its edges. `cursor` marks a map time, e.g. the line's.
+// Dots are merged a pixel column at a time, so `errors` should be sorted by
+// time for all of them to be.
+pub(crate) fn paint(
+    painter: &egui::Painter,
+    rect: Rect,
+    errors: impl Iterator<Item = TimedError> + Clone,
+    windows: &Windows,
+    style: &ScatterStyle,
+    cursor: Option<f64>,
+) {
+    painter.rect_filled(rect, 2.0, Color32::from_black_alpha(160));
+    let center_y = rect.center().y;
+    painter.hline(rect.x_range(), center_y, (1.0, Color32::from_gray(90)));
+
+    let range = style.time_range.or_else(|| {
+        let first = errors.clone().map(|e| e.time_ms).min_by(f64::total_cmp)?;
+        let last = errors.clone().map(|e| e.time_ms).max_by(f64::total_cmp)?;
+        Some((first, last))
+    });
+    let Some((from, to)) = range else {
+        return;
+    };
+    let x_for = |time: f64| {
+        if to > from {
+            rect.left() + ((time - from) / (to - from)) as f32 * rect.width()
+        } else {
+            rect.center().x
+        }
+    };
+    let half_height = rect.height() / 2.0;
+    let y_for = |offset_ms: f64| {
+        let distance = (offset_ms as f32 / windows.miss.max(1.0)).clamp(-1.0, 1.0);
+        center_y + distance * half_height
+    };
+
+    let in_range = |e: &TimedError| e.time_ms >= from && e.time_ms <= to;
+    let merge = errors.clone().filter(in_range).count() > MAX_DOTS;
+    let columns = rect.width().max(1.0) as usize;
+    let rows = rect.height().max(1.0) as usize;
+    // Pixel column being drawn, and a bit per row set once a dot was drawn there
+    let mut column = None;
+    let mut drawn = [0u64; MERGED_ROWS / 64];
+    for error in errors.filter(in_range) {
+        let pos = pos2(x_for(error.time_ms), y_for(error.offset_ms));
+        if merge {
+            let cell = (pos - rect.min).floor().max(Vec2::ZERO);
+            let x = (cell.x as usize).min(columns - 1);
+            let y = (cell.y as usize).min(rows - 1).min(MERGED_ROWS - 1);
+            if column != Some(x) {
+                column = Some(x);
+                drawn = [0; MERGED_ROWS / 64];
+            }
+            let (word, bit) = (y / 64, 1 << (y % 64));
+            if drawn[word] & bit != 0 {
+                continue;
+            }
+            drawn[word] |= bit;
+        }
+        let color = style.color(tier_for(error.offset_ms, windows));
+        painter.circle_filled(pos, style.dot_radius, color);
+    }
+
+    if let Some(cursor) = cursor.filter(|time| *time >= from && *time <= to) {
+        painter.vline(x_for(cursor), rect.y_range(), (1.0, Color32::WHITE));
+    }
+}
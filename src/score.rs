@@ -0,0 +1,328 @@
+//! Scores independent of renderer settings, on a 0..=1,000,000 scale, for
+//! leaderboards storing judgments.
+//!
+//! - `Classic`: the base score of osu!mania ScoreV1, the mean hit value
+//!   over `map_note_count` with Marv 320, Perfect 300, Great 200, Good 100,
+//!   Ok 50 and Miss 0, divided by 320. The combo-dependent bonus half of
+//!   ScoreV1 depends on the judgment order, which counts don't keep.
+//! - `ScoreV2`: 99% accuracy, with Marv worth 305 and the rest as above,
+//!   and 1% combo, `max_combo / map_note_count`.
+//! - `Wife3`: Etterna's curve at judge 4, 2 points for a hit within 5 ms,
+//!   falling along an erf to 0 at 65 ms, then linearly to -5.5 at 180 ms
+//!   and for misses, over the 2 points of a perfect play. Negative totals
+//!   score 0.
+//!
+//! Notes of the map that were never judged count as misses.
+
+use crate::judge::windows::Windows;
+use std::fmt;
+
+const MAX_SCORE: f64 = 1_000_000.0;
+
+// ScoreV1 and ScoreV2 hit values, Marv to Miss
+const CLASSIC_VALUES: [f64; 6] = [320.0, 300.0, 200.0, 100.0, 50.0, 0.0];
+const V2_VALUES: [f64; 6] = [305.0, 300.0, 200.0, 100.0, 50.0, 0.0];
+const V2_COMBO_SHARE: f64 = 0.01;
+
+// Wife3 curve at judge 4
+const WIFE_MAX_POINTS: f64 = 2.0;
+const WIFE_MISS_POINTS: f64 = -5.5;
+const WIFE_RIDICULOUS_MS: f64 = 5.0;
+const WIFE_ZERO_MS: f64 = 65.0;
+const WIFE_DEVIATION_MS: f64 = 22.7;
+const WIFE_MAX_BOO_MS: f64 = 180.0;
+// Error standing in for each judgment when Wife3 only gets counts: the
+// middle of the matching judge 4 window, Marv to Ok
+const WIFE_COUNT_ERRORS: [f64; 5] = [11.25, 33.75, 67.5, 112.5, 157.5];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScoreMode {
+    #[default]
+    Classic,
+    ScoreV2,
+    Wife3,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JudgmentCounts {
+    pub marv: usize,
+    pub perfect: usize,
+    pub great: usize,
+    pub good: usize,
+    pub ok: usize,
+    pub miss: usize,
+}
+
+impl JudgmentCounts {
+    /// Bins signed hit errors, `None` being a miss, with `windows`.
+    pub fn from_errors(errors: &[Option<f64>], windows: &Windows) -> Self {
+        let mut counts = Self::default();
+        for error in errors {
+            let error = error.map_or(f64::INFINITY, f64::abs) as f32;
+            let count = match error {
+                e if e <= windows.marv => &mut counts.marv,
+                e if e <= windows.perfect => &mut counts.perfect,
+                e if e <= windows.great => &mut counts.great,
+                e if e <= windows.good => &mut counts.good,
+                e if e <= windows.ok => &mut counts.ok,
+                _ => &mut counts.miss,
+            };
+            *count += 1;
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.as_array().iter().sum()
+    }
+
+    // Marv to Miss
+    fn as_array(&self) -> [usize; 6] {
+        [
+            self.marv,
+            self.perfect,
+            self.great,
+            self.good,
+            self.ok,
+            self.miss,
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreError {
+    /// More judgments than the map has notes, from another map or a
+    /// pipeline judging notes twice.
+    TooManyJudgments {
+        judged: usize,
+        map_note_count: usize,
+    },
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoreError::TooManyJudgments {
+                judged,
+                map_note_count,
+            } => write!(f, "{judged} judgments for a map of {map_note_count} notes"),
+        }
+    }
+}
+
+impl std::error::Error for ScoreError {}
+
+/// Input of `normalize`. Wife3 is defined on hit errors: with `Counts`,
+/// each judgment stands for the middle of its window, an approximation.
+/// Classic and ScoreV2 bin `Errors` with `windows`.
+#[derive(Clone, Copy, Debug)]
+pub enum Judgments<'a> {
+    Counts(JudgmentCounts),
+    Errors {
+        /// Signed hit error of every judgment in ms, `None` for misses.
+        errors: &'a [Option<f64>],
+        windows: Windows,
+    },
+}
+
+impl Judgments<'_> {
+    /// Number of judgments, misses included.
+    pub fn total(&self) -> usize {
+        match self {
+            Judgments::Counts(counts) => counts.total(),
+            Judgments::Errors { errors, .. } => errors.len(),
+        }
+    }
+}
+
+/// Score of a play on a 0..=1,000,000 scale, see the module docs for the
+/// formulas. `map_note_count` is the number of judgments of a full play,
+/// which `judgments` can't exceed.
+pub fn normalize(
+    judgments: Judgments,
+    max_combo: usize,
+    map_note_count: usize,
+    mode: ScoreMode,
+) -> Result<f64, ScoreError> {
+    let judged = judgments.total();
+    if judged > map_note_count {
+        return Err(ScoreError::TooManyJudgments {
+            judged,
+            map_note_count,
+        });
+    }
+    if map_note_count == 0 {
+        return Ok(0.0);
+    }
+    let notes = map_note_count as f64;
+    let unjudged = (map_note_count - judged) as f64 * WIFE_MISS_POINTS;
+    let score = match (mode, judgments) {
+        (ScoreMode::Wife3, Judgments::Errors { errors, .. }) => {
+            let points: f64 = errors.iter().map(|error| wife3_points(*error)).sum();
+            (points + unjudged) / (WIFE_MAX_POINTS * notes)
+        }
+        (ScoreMode::Wife3, Judgments::Counts(counts)) => {
+            let counts = counts.as_array();
+            let hits: f64 = WIFE_COUNT_ERRORS
+                .iter()
+                .zip(counts)
+                .map(|(error, count)| wife3_points(Some(*error)) * count as f64)
+                .sum();
+            let misses = counts[5] as f64 * WIFE_MISS_POINTS;
+            (hits + misses + unjudged) / (WIFE_MAX_POINTS * notes)
+        }
+        (ScoreMode::Classic, judgments) => {
+            hit_value(&counts_of(judgments), &CLASSIC_VALUES) / (CLASSIC_VALUES[0] * notes)
+        }
+        (ScoreMode::ScoreV2, judgments) => {
+            let accuracy = hit_value(&counts_of(judgments), &V2_VALUES) / (V2_VALUES[0] * notes);
+            let combo = (max_combo as f64 / notes).min(1.0);
+            (1.0 - V2_COMBO_SHARE) * accuracy + V2_COMBO_SHARE * combo
+        }
+    };
+    Ok(MAX_SCORE * score.clamp(0.0, 1.0))
+}
+
+fn counts_of(judgments: Judgments) -> JudgmentCounts {
+    match judgments {
+        Judgments::Counts(counts) => counts,
+        Judgments::Errors { errors, windows } => JudgmentCounts::from_errors(errors, &windows),
+    }
+}
+
+fn hit_value(counts: &JudgmentCounts, values: &[f64; 6]) -> f64 {
+    counts
+        .as_array()
+        .iter()
+        .zip(values)
+        .map(|(count, value)| *count as f64 * value)
+        .sum()
+}
+
+fn wife3_points(error: Option<f64>) -> f64 {
+    let Some(error) = error.map(f64::abs) else {
+        return WIFE_MISS_POINTS;
+    };
+    if error <= WIFE_RIDICULOUS_MS {
+        WIFE_MAX_POINTS
+    } else if error <= WIFE_ZERO_MS {
+        WIFE_MAX_POINTS * erf((WIFE_ZERO_MS - error) / WIFE_DEVIATION_MS)
+    } else if error <= WIFE_MAX_BOO_MS {
+        (error - WIFE_ZERO_MS) * WIFE_MISS_POINTS / (WIFE_MAX_BOO_MS - WIFE_ZERO_MS)
+    } else {
+        WIFE_MISS_POINTS
+    }
+}
+
+// Abramowitz and Stegun 7.1.26, within 1.5e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    y.copysign(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::judge::windows::{mania_windows, WindowMode};
+
+    fn counts(marv: usize, good: usize, miss: usize) -> Judgments<'static> {
+        Judgments::Counts(JudgmentCounts {
+            marv,
+            good,
+            miss,
+            ..Default::default()
+        })
+    }
+
+    fn assert_score(actual: Result<f64, ScoreError>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() < 0.5, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn full_marvelous_plays_score_the_maximum() {
+        for mode in [ScoreMode::Classic, ScoreMode::ScoreV2] {
+            assert_score(normalize(counts(10, 0, 0), 10, 10, mode), MAX_SCORE);
+        }
+        // Counted Marvs stand for 11.25 ms, just short of the maximum
+        let wife = normalize(counts(10, 0, 0), 10, 10, ScoreMode::Wife3);
+        assert_score(wife, 999_187.78);
+        let errors = [Some(-5.0), Some(0.0), Some(4.5)];
+        let windows = mania_windows(8.0, WindowMode::ScoreV1);
+        let judgments = Judgments::Errors {
+            errors: &errors,
+            windows,
+        };
+        assert_score(normalize(judgments, 3, 3, ScoreMode::Wife3), MAX_SCORE);
+    }
+
+    #[test]
+    fn all_good_plays_score_their_hit_value() {
+        assert_score(
+            normalize(counts(0, 8, 0), 8, 8, ScoreMode::Classic),
+            312_500.0,
+        );
+        // 99% of 100 / 305 plus the full combo share
+        let v2 = normalize(counts(0, 8, 0), 8, 8, ScoreMode::ScoreV2);
+        assert_score(v2, 334_590.163_934);
+        // A Good stands for 112.5 ms, past the zero of the Wife3 curve
+        let wife = normalize(counts(0, 8, 0), 8, 8, ScoreMode::Wife3);
+        assert_score(wife, 0.0);
+    }
+
+    #[test]
+    fn wife3_charges_misses_and_unjudged_notes() {
+        // 4 Marv at 11.25 ms, 2 Perfect at 33.75 ms, a miss, one unjudged
+        let judgments = Judgments::Counts(JudgmentCounts {
+            marv: 4,
+            perfect: 2,
+            miss: 1,
+            ..Default::default()
+        });
+        assert_score(normalize(judgments, 6, 8, ScoreMode::Wife3), 49_206.66);
+
+        let errors = [Some(0.0), Some(-3.0), Some(2.0)];
+        let windows = mania_windows(8.0, WindowMode::ScoreV1);
+        let judgments = Judgments::Errors {
+            errors: &errors,
+            windows,
+        };
+        // (3 * 2 - 5.5) / (4 * 2)
+        assert_score(normalize(judgments, 3, 4, ScoreMode::Wife3), 62_500.0);
+    }
+
+    #[test]
+    fn unjudged_notes_score_nothing() {
+        assert_score(
+            normalize(counts(5, 0, 0), 5, 10, ScoreMode::Classic),
+            500_000.0,
+        );
+        // Half the accuracy share and half the combo share
+        assert_score(
+            normalize(counts(5, 0, 0), 5, 10, ScoreMode::ScoreV2),
+            500_000.0,
+        );
+        assert_score(normalize(counts(0, 0, 0), 0, 0, ScoreMode::Classic), 0.0);
+    }
+
+    #[test]
+    fn more_judgments_than_notes_is_an_error() {
+        let error = ScoreError::TooManyJudgments {
+            judged: 4,
+            map_note_count: 3,
+        };
+        for mode in [ScoreMode::Classic, ScoreMode::ScoreV2, ScoreMode::Wife3] {
+            assert_eq!(normalize(counts(2, 1, 1), 4, 3, mode), Err(error));
+        }
+        let errors = [None; 2];
+        let judgments = Judgments::Errors {
+            errors: &errors,
+            windows: mania_windows(8.0, WindowMode::ScoreV1),
+        };
+        assert!(normalize(judgments, 0, 1, ScoreMode::Wife3).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+//! Scroll speed independent of the playfield height. `scroll_time_ms` is
+//! the time a note takes to cross the stage, so the same value looks
+//! faster on a taller stage; a `ScrollSpeed` keeps the speed on screen.
+
+/// On-screen note speed, in points per ms of playback.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollSpeed {
+    pub px_per_ms: f32,
+}
+
+impl ScrollSpeed {
+    /// Speed of notes crossing `height_px` in `scroll_time_ms`.
+    pub fn from_scroll_time(scroll_time_ms: f32, height_px: f32) -> Self {
+        Self {
+            px_per_ms: height_px / scroll_time_ms,
+        }
+    }
+
+    /// Scroll time giving this speed on a stage `height_px` tall.
+    pub fn scroll_time_ms(&self, height_px: f32) -> f32 {
+        height_px / self.px_per_ms
+    }
+
+    /// Scroll time for a stage `actual_height_px` tall that looks like
+    /// `reference_scroll_time_ms` on one `reference_height_px` tall, e.g.
+    /// to carry a setting over from a 1080p osu!mania playfield. Returns
+    /// the reference scroll time unchanged if either height isn't positive.
+    pub fn calibrate(
+        reference_height_px: f32,
+        reference_scroll_time_ms: f32,
+        actual_height_px: f32,
+    ) -> f32 {
+        if !(reference_height_px > 0.0 && actual_height_px > 0.0) {
+            return reference_scroll_time_ms;
+        }
+        Self::from_scroll_time(reference_scroll_time_ms, reference_height_px)
+            .scroll_time_ms(actual_height_px)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calibration_keeps_the_speed_on_screen() {
+        let reference = ScrollSpeed::from_scroll_time(450.0, 1080.0);
+        for height in [240.0, 600.0, 720.0, 1080.0, 1440.0, 2160.0] {
+            let scroll_time = ScrollSpeed::calibrate(1080.0, 450.0, height);
+            let speed = ScrollSpeed::from_scroll_time(scroll_time, height);
+            assert!(
+                (speed.px_per_ms - reference.px_per_ms).abs() < 1e-6,
+                "{height}"
+            );
+        }
+    }
+
+    #[test]
+    fn calibration_scales_with_the_height() {
+        for (height, expected) in [(1080.0, 450.0), (540.0, 225.0), (2160.0, 900.0)] {
+            let scroll_time = ScrollSpeed::calibrate(1080.0, 450.0, height);
+            assert!(
+                (scroll_time - expected).abs() < 1e-3,
+                "{height}: {scroll_time}"
+            );
+        }
+    }
+
+    #[test]
+    fn calibration_ignores_heights_that_are_not_positive() {
+        for (reference, actual) in [(0.0, 600.0), (1080.0, -1.0), (f32::NAN, 600.0)] {
+            assert_eq!(ScrollSpeed::calibrate(reference, 450.0, actual), 450.0);
+        }
+    }
+
+    #[test]
+    fn scroll_time_round_trips() {
+        let speed = ScrollSpeed::from_scroll_time(800.0, 600.0);
+        assert!((speed.scroll_time_ms(600.0) - 800.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::layout::mania::ScrollTransform;
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+use rosu_map::Beatmap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionKind {
+    /// Before the first object reaches the judgment line.
+    Intro,
+    Notes,
+    Break,
+    Kiai,
+    /// After the last object, up to the last break or effect point.
+    Outro,
+}
+
+/// Span of the player clock, in the same ms as `Player::current_time`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Section {
+    pub start_ms: f64,
+    pub end_ms: f64,
+    pub kind: SectionKind,
+}
+
+/// What the clock does once the map is over, see `Player::set_end_behavior`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EndBehavior {
+    /// Keeps going, showing an empty stage.
+    RunForever,
+    /// Stops `tail_ms` after `Player::duration`, one scroll time when
+    /// `None`. Seeking back resumes playback.
+    StopAt { tail_ms: Option<f64> },
+    /// Jumps back to 0 at `Player::duration`.
+    Loop,
+    /// Stops at `Player::duration`, without a lead-out.
+    FreezeLastFrame,
+}
+
+impl Default for EndBehavior {
+    fn default() -> Self {
+        Self::StopAt { tail_ms: None }
+    }
+}
+
+/// Where `Player::reset_time` puts the clock, see
+/// `Player::set_start_behavior`. Loops still wrap to 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StartBehavior {
+    #[default]
+    FromZero,
+    /// The first note reaches the judgment line `lead_ms` after the start,
+    /// so a lead of one scroll time starts with it entering the stage.
+    FromFirstNote { lead_ms: f64 },
+    /// The map's preview point is on the judgment line, or 0 when the map
+    /// has none.
+    FromPreviewPoint,
+}
+
+/// Splits the clock into contiguous sections starting at 0. Empty when the
+/// map has no objects.
+pub(crate) fn compute_sections(
+    beatmap: &Beatmap,
+    hit_objects: &[HitObject],
+    scroll: ScrollTransform,
+) -> Vec<Section> {
+    let Some(first) = hit_objects.iter().map(|h| h.start_time).reduce(f64::min) else {
+        return Vec::new();
+    };
+    let last = hit_objects.iter().map(end_time).fold(first, f64::max);
+    let effect_points = &beatmap.control_points.effect_points;
+
+    let kiai_at = |time: f64| {
+        effect_points
+            .iter()
+            .take_while(|point| point.time <= time)
+            .last()
+            .is_some_and(|point| point.kiai)
+    };
+    let in_break = |time: f64| {
+        beatmap
+            .breaks
+            .iter()
+            .any(|b| b.start_time <= time && time < b.end_time)
+    };
+
+    // Every time the kind may change, in map time
+    let start = scroll.map_time_at_line(0.0);
+    let mut bounds = vec![start, first, last];
+    for b in &beatmap.breaks {
+        bounds.extend([b.start_time, b.end_time]);
+    }
+    bounds.extend(effect_points.iter().map(|point| point.time));
+    bounds.retain(|time| *time >= start);
+    bounds.sort_by(f64::total_cmp);
+    bounds.dedup();
+
+    let mut sections: Vec<Section> = Vec::new();
+    for pair in bounds.windows(2) {
+        let middle = (pair[0] + pair[1]) / 2.0;
+        let kind = if middle < first {
+            SectionKind::Intro
+        } else if middle >= last {
+            SectionKind::Outro
+        } else if in_break(middle) {
+            SectionKind::Break
+        } else if kiai_at(middle) {
+            SectionKind::Kiai
+        } else {
+            SectionKind::Notes
+        };
+        let end_ms = scroll.crossing_time(pair[1]);
+        match sections.last_mut() {
+            Some(section) if section.kind == kind => section.end_ms = end_ms,
+            _ => sections.push(Section {
+                start_ms: scroll.crossing_time(pair[0]),
+                end_ms,
+                kind,
+            }),
+        }
+    }
+    sections
+}
+
+fn end_time(hit_object: &HitObject) -> f64 {
+    match &hit_object.kind {
+        HitObjectKind::Hold(h) => hit_object.start_time + h.duration,
+        _ => hit_object.start_time,
+    }
+}
@@ -0,0 +1,272 @@
+//! osu! skins loaded from `.osk` archives, entirely in memory. Only what
+//! the renderer can draw is read: the images and the `[Mania]` sections of
+//! skin.ini.
+
+use crate::images::MemoryImages;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+// Entries larger than this once inflated are skipped
+const MAX_ENTRY_SIZE: usize = 64 * 1024 * 1024;
+// Default note image of every column when a section doesn't name one
+const DEFAULT_NOTE_IMAGE: &str = "mania-note1";
+
+#[derive(Debug)]
+pub enum SkinError {
+    Io(io::Error),
+    /// Not a zip archive, or a truncated one.
+    InvalidArchive,
+}
+
+impl fmt::Display for SkinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkinError::Io(e) => write!(f, "failed to read skin: {e}"),
+            SkinError::InvalidArchive => f.write_str("not a valid .osk archive"),
+        }
+    }
+}
+
+impl std::error::Error for SkinError {}
+
+impl From<io::Error> for SkinError {
+    fn from(e: io::Error) -> Self {
+        SkinError::Io(e)
+    }
+}
+
+/// Problems that didn't prevent loading, drawn with shapes instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SkinWarning {
+    /// skin.ini names an image the archive doesn't contain.
+    MissingImage {
+        keycount: usize,
+        key: String,
+    },
+    /// Entry using something else than stored or deflate, or too large.
+    UnreadableEntry {
+        name: String,
+    },
+    NoSkinIni,
+}
+
+/// One `[Mania]` section of skin.ini.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManiaSkinConfig {
+    pub keys: usize,
+    /// Image key of each column's notes, see `Skin::images`.
+    pub note_images: Vec<String>,
+}
+
+pub struct Skin {
+    images: Arc<MemoryImages>,
+    mania: Vec<ManiaSkinConfig>,
+    warnings: Vec<SkinWarning>,
+}
+
+impl Skin {
+    pub fn from_osk(path: impl AsRef<Path>) -> Result<Self, SkinError> {
+        Self::from_osk_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn from_osk_bytes(bytes: &[u8]) -> Result<Self, SkinError> {
+        let mut images = MemoryImages::new();
+        let mut warnings = Vec::new();
+        let mut skin_ini = None;
+        for entry in zip_entries(bytes).ok_or(SkinError::InvalidArchive)? {
+            if entry.name.ends_with('/') {
+                continue;
+            }
+            let Some(data) = entry.inflate() else {
+                warnings.push(SkinWarning::UnreadableEntry { name: entry.name });
+                continue;
+            };
+            let name = entry.name.replace('\\', "/").to_lowercase();
+            if name == "skin.ini" {
+                skin_ini = Some(String::from_utf8_lossy(&data).into_owned());
+            } else if let Some(key) = image_key(&name) {
+                // Keep the @2x version when both are there
+                if key.1 || !images.contains(&key.0) {
+                    images.insert(key.0, data);
+                }
+            }
+        }
+
+        let mania = match &skin_ini {
+            Some(ini) => parse_mania_sections(ini),
+            None => {
+                warnings.push(SkinWarning::NoSkinIni);
+                Vec::new()
+            }
+        };
+        for config in &mania {
+            for key in &config.note_images {
+                if !images.contains(key) {
+                    warnings.push(SkinWarning::MissingImage {
+                        keycount: config.keys,
+                        key: key.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            images: Arc::new(images),
+            mania,
+            warnings,
+        })
+    }
+
+    /// Every image of the archive, keyed by its path without extension or
+    /// `@2x`, lowercase, e.g. `mania-note1`. Shared, so it can be given to
+    /// `Player::set_image_provider` as is.
+    pub fn images(&self) -> &Arc<MemoryImages> {
+        &self.images
+    }
+
+    /// Section for `keycount`, or the defaults when skin.ini has none.
+    pub fn mania(&self, keycount: usize) -> ManiaSkinConfig {
+        self.mania
+            .iter()
+            .find(|config| config.keys == keycount)
+            .cloned()
+            .unwrap_or_else(|| ManiaSkinConfig::defaults(keycount))
+    }
+
+    pub fn warnings(&self) -> &[SkinWarning] {
+        &self.warnings
+    }
+}
+
+impl ManiaSkinConfig {
+    fn defaults(keys: usize) -> Self {
+        Self {
+            keys,
+            note_images: vec![DEFAULT_NOTE_IMAGE.to_owned(); keys],
+        }
+    }
+}
+
+// (key, is @2x) of an image path
+fn image_key(name: &str) -> Option<(String, bool)> {
+    let stem = name
+        .strip_suffix(".png")
+        .or_else(|| name.strip_suffix(".jpg"))
+        .or_else(|| name.strip_suffix(".jpeg"))?;
+    match stem.strip_suffix("@2x") {
+        Some(stem) => Some((stem.to_owned(), true)),
+        None => Some((stem.to_owned(), false)),
+    }
+}
+
+fn parse_mania_sections(ini: &str) -> Vec<ManiaSkinConfig> {
+    let mut sections = Vec::new();
+    let mut in_mania = false;
+    for line in ini.lines() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_mania = line.eq_ignore_ascii_case("[mania]");
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if !in_mania {
+            continue;
+        }
+        if key == "Keys" {
+            if let Ok(keys) = value.parse() {
+                sections.push(ManiaSkinConfig::defaults(keys));
+            }
+            continue;
+        }
+        let Some(config) = sections.last_mut() else {
+            continue;
+        };
+        let column = key
+            .strip_prefix("NoteImage")
+            .and_then(|column| column.parse::<usize>().ok());
+        if let Some(image) = column.and_then(|column| config.note_images.get_mut(column)) {
+            *image = value.replace('\\', "/").to_lowercase();
+        }
+    }
+    sections
+}
+
+struct ZipEntry<'a> {
+    name: String,
+    method: u16,
+    data: &'a [u8],
+    size: usize,
+}
+
+impl ZipEntry<'_> {
+    fn inflate(&self) -> Option<Vec<u8>> {
+        if self.size > MAX_ENTRY_SIZE {
+            return None;
+        }
+        match self.method {
+            0 => Some(self.data.to_vec()),
+            8 => miniz_oxide::inflate::decompress_to_vec_with_limit(self.data, self.size).ok(),
+            _ => None,
+        }
+    }
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+// Entries listed in the central directory
+fn zip_entries(bytes: &[u8]) -> Option<Vec<ZipEntry<'_>>> {
+    const END_SIGNATURE: u32 = 0x0605_4b50;
+    const ENTRY_SIGNATURE: u32 = 0x0201_4b50;
+    const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+
+    // The end record is 22 bytes plus a comment of up to 64 KiB
+    let search_start = bytes.len().saturating_sub(22 + u16::MAX as usize);
+    let end = (search_start..=bytes.len().checked_sub(22)?)
+        .rev()
+        .find(|&at| u32_at(bytes, at) == Some(END_SIGNATURE))?;
+    let count = u16_at(bytes, end + 10)? as usize;
+    let mut at = u32_at(bytes, end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(bytes, at)? != ENTRY_SIGNATURE {
+            return None;
+        }
+        let method = u16_at(bytes, at + 10)?;
+        let compressed = u32_at(bytes, at + 20)? as usize;
+        let size = u32_at(bytes, at + 24)? as usize;
+        let name_len = u16_at(bytes, at + 28)? as usize;
+        let extra_len = u16_at(bytes, at + 30)? as usize;
+        let comment_len = u16_at(bytes, at + 32)? as usize;
+        let local = u32_at(bytes, at + 42)? as usize;
+        let name = bytes.get(at + 46..at + 46 + name_len)?;
+
+        if u32_at(bytes, local)? != LOCAL_SIGNATURE {
+            return None;
+        }
+        let data_start =
+            local + 30 + u16_at(bytes, local + 26)? as usize + u16_at(bytes, local + 28)? as usize;
+        entries.push(ZipEntry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            method,
+            data: bytes.get(data_start..data_start.checked_add(compressed)?)?,
+            size,
+        });
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Some(entries)
+}
@@ -0,0 +1,149 @@
+//! Beat lines of the timing points, shared by seek snapping, the lint pass
+//! and the renderer's ladders and stripes.
+
+use rosu_map::section::timing_points::{ControlPoints, TimingPoint};
+
+// Keeps a time sitting on a line from being found again
+const LINE_EPSILON: f64 = 1e-6;
+
+/// Beat division, e.g. 4 for 1/4 lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Divisor(u32);
+
+impl Divisor {
+    pub const WHOLE: Self = Self(1);
+    pub const HALF: Self = Self(2);
+    pub const QUARTER: Self = Self(4);
+
+    /// `None` for 0.
+    pub fn new(divisor: u32) -> Option<Self> {
+        (divisor > 0).then_some(Self(divisor))
+    }
+
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// Map time of the 1/`divisor` line closest to `time`. Lines of a timing
+/// point stop at the next one, whose start counts as a line too. `None`
+/// without a usable timing point.
+pub(crate) fn nearest_line(control_points: &ControlPoints, time: f64, divisor: f64) -> Option<f64> {
+    let point = control_points.timing_point_at(time)?;
+    let step = point.beat_len / divisor;
+    if !step.is_finite() || step <= 0.0 {
+        return None;
+    }
+    let line = point.time + ((time - point.time) / step).round() * step;
+
+    let next = control_points
+        .timing_points
+        .iter()
+        .find(|next| next.time > point.time);
+    match next {
+        Some(next) if line > next.time || (next.time - time).abs() < (line - time).abs() => {
+            Some(next.time)
+        }
+        _ => Some(line),
+    }
+}
+
+/// Timing point active at `time` out of `beat_lengths`, the start and beat
+/// length of each uninherited point by start: its start, its beat length and
+/// the index of the next point. The first point also covers the time before
+/// it, and without any the beat is `TimingPoint::DEFAULT_BEAT_LEN` from 0.
+pub(crate) fn active_beat_length(beat_lengths: &[(f64, f64)], time: f64) -> (f64, f64, usize) {
+    let next = beat_lengths.partition_point(|(start, _)| *start <= time);
+    let (start, beat_len) = match next {
+        0 => beat_lengths.first().copied(),
+        i => beat_lengths.get(i - 1).copied(),
+    }
+    .unwrap_or((0.0, TimingPoint::DEFAULT_BEAT_LEN));
+    (start, beat_len, next)
+}
+
+/// Distance in ms from `time` to the closest 1/`divisor` line of the point
+/// active then out of `beat_lengths`, the next point's start included.
+/// `None` when the beat length gives no lines.
+pub(crate) fn off_line_ms(beat_lengths: &[(f64, f64)], time: f64, divisor: u32) -> Option<f64> {
+    let (start, beat_len, next) = active_beat_length(beat_lengths, time);
+    let step = beat_len / f64::from(divisor.max(1));
+    if !step.is_finite() || step <= 0.0 {
+        return None;
+    }
+    let lines = (time - start) / step;
+    let off_line = (lines - lines.round()).abs() * step;
+    let off_next = beat_lengths
+        .get(next)
+        .map_or(f64::INFINITY, |(next_start, _)| next_start - time);
+    Some(off_line.min(off_next))
+}
+
+/// Map times of the 1/`divisor` lines after `from` up to `to`, from
+/// `beat_lengths` as in `active_beat_length`. A timing point restarts the
+/// lines at its own time.
+pub(crate) fn lines_between(
+    beat_lengths: &[(f64, f64)],
+    from: f64,
+    to: f64,
+    divisor: u32,
+) -> impl Iterator<Item = f64> + '_ {
+    let next = move |time: f64| {
+        let (start, beat_len, next) = active_beat_length(beat_lengths, time);
+        let step = beat_len / f64::from(divisor.max(1));
+        if !step.is_finite() || step <= 0.0 {
+            return None;
+        }
+        let lines = ((time - start) / step + LINE_EPSILON).floor() + 1.0;
+        let line = start + lines * step;
+        let line = match beat_lengths.get(next) {
+            Some((next_start, _)) if *next_start < line => *next_start,
+            _ => line,
+        };
+        (line <= to).then_some(line)
+    };
+    std::iter::successors(next(from), move |time| next(*time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINTS: [(f64, f64); 2] = [(100.0, 400.0), (1000.0, 300.0)];
+
+    #[test]
+    fn beat_length_before_the_first_point_is_the_first() {
+        assert_eq!(active_beat_length(&POINTS, 0.0), (100.0, 400.0, 0));
+        assert_eq!(active_beat_length(&POINTS, 500.0), (100.0, 400.0, 1));
+        assert_eq!(active_beat_length(&POINTS, 1000.0), (1000.0, 300.0, 2));
+        assert_eq!(active_beat_length(&[], 500.0), (0.0, 1000.0, 0));
+    }
+
+    #[test]
+    fn off_line_distance_follows_the_active_point() {
+        let off = |time, divisor| off_line_ms(&POINTS, time, divisor).unwrap();
+        assert!(off(500.0, 1) < 1e-9);
+        assert!((off(301.5, 2) - 1.5).abs() < 1e-9);
+        assert!((off(300.0, 1) - 200.0).abs() < 1e-9);
+        // 1000 is a line of the second point only
+        assert!(off(1075.0, 4) < 1e-9);
+        assert!((off(998.5, 1) - 1.5).abs() < 1e-9);
+        assert_eq!(off_line_ms(&[(0.0, 0.0)], 10.0, 1), None);
+    }
+
+    #[test]
+    fn lines_restart_on_timing_points() {
+        let lines: Vec<_> = lines_between(&POINTS, 100.0, 1600.0, 2).collect();
+        assert_eq!(
+            lines,
+            [300.0, 500.0, 700.0, 900.0, 1000.0, 1150.0, 1300.0, 1450.0, 1600.0]
+        );
+    }
+
+    #[test]
+    fn lines_start_after_from() {
+        // Within rounding of a line counts as on it
+        let lines: Vec<_> = lines_between(&POINTS, 500.0 - 1e-9, 1000.0, 1).collect();
+        assert_eq!(lines, [900.0, 1000.0]);
+    }
+}
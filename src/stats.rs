@@ -0,0 +1,250 @@
+use crate::judge::windows::Windows;
+use crate::layout::mania::column_for_x;
+use crate::replay::Recorded;
+use crate::score::{self, Judgments, ScoreError, ScoreMode};
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ColumnStats {
+    pub notes: usize,
+    pub holds: usize,
+    /// Sum of hold durations, in map ms.
+    pub hold_time_ms: f64,
+}
+
+impl ColumnStats {
+    pub fn objects(&self) -> usize {
+        self.notes + self.holds
+    }
+}
+
+pub(crate) fn column_stats(hit_objects: &[HitObject], keycount: usize) -> Vec<ColumnStats> {
+    let mut stats = vec![ColumnStats::default(); keycount];
+    for hit_object in hit_objects {
+        match &hit_object.kind {
+            HitObjectKind::Circle(h) => stats[column_for_x(h.pos.x, keycount)].notes += 1,
+            HitObjectKind::Hold(h) => {
+                let column = &mut stats[column_for_x(h.pos_x, keycount)];
+                column.holds += 1;
+                column.hold_time_ms += h.duration;
+            }
+            _ => {}
+        }
+    }
+    stats
+}
+
+/// How `Composition::ln_percent` is computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LnPercent {
+    /// Holds out of all objects, the usual community definition.
+    #[default]
+    Count,
+    /// Share of the time from the first object to the last end during which
+    /// at least one hold is held.
+    Duration,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Composition {
+    /// Single notes.
+    pub rice: usize,
+    pub holds: usize,
+    /// In `0.0..=100.0`, see `LnPercent`.
+    pub ln_percent: f64,
+    pub avg_ln_length_ms: f64,
+    pub longest_ln_ms: f64,
+    /// Most notes pressed or held at once.
+    pub max_chord_size: usize,
+}
+
+pub(crate) fn composition(hit_objects: &[HitObject], mode: LnPercent) -> Composition {
+    let mut composition = Composition::default();
+    // (start, end) of every mania object, sorted by start
+    let mut objects: Vec<(f64, f64)> = Vec::new();
+    for hit_object in hit_objects {
+        let start = hit_object.start_time;
+        match &hit_object.kind {
+            HitObjectKind::Circle(_) => {
+                composition.rice += 1;
+                objects.push((start, start));
+            }
+            HitObjectKind::Hold(h) => {
+                composition.holds += 1;
+                composition.longest_ln_ms = composition.longest_ln_ms.max(h.duration);
+                composition.avg_ln_length_ms += h.duration;
+                objects.push((start, start + h.duration));
+            }
+            _ => {}
+        }
+    }
+    if composition.holds > 0 {
+        composition.avg_ln_length_ms /= composition.holds as f64;
+    }
+    objects.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    composition.ln_percent = match mode {
+        LnPercent::Count if !objects.is_empty() => {
+            100.0 * composition.holds as f64 / objects.len() as f64
+        }
+        LnPercent::Duration => held_percent(&objects),
+        LnPercent::Count => 0.0,
+    };
+
+    composition.max_chord_size = chord_sizes(&objects)
+        .into_iter()
+        .map(|(_, size)| size)
+        .max()
+        .unwrap_or(0);
+    composition
+}
+
+// (time, heads + holds still down) for every distinct start time of `objects`,
+// which must be sorted by start
+pub(crate) fn chord_sizes(objects: &[(f64, f64)]) -> Vec<(f64, usize)> {
+    let mut chords = Vec::new();
+    // Ends of the earlier objects still held, earliest on top
+    let mut held: BinaryHeap<HeldEnd> = BinaryHeap::new();
+    let mut i = 0;
+    while i < objects.len() {
+        let time = objects[i].0;
+        while held.peek().is_some_and(|end| end.0 <= time) {
+            held.pop();
+        }
+        let heads = objects[i..].iter().take_while(|o| o.0 == time).count();
+        chords.push((time, heads + held.len()));
+        let ends = objects[i..i + heads].iter().map(|o| o.1);
+        held.extend(ends.filter(|end| !end.is_nan()).map(HeldEnd));
+        i += heads;
+    }
+    chords
+}
+
+// Hold end ordered so the max-heap pops the earliest one first
+#[derive(PartialEq)]
+struct HeldEnd(f64);
+
+impl Eq for HeldEnd {}
+
+impl PartialOrd for HeldEnd {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeldEnd {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+// Share of the chart's span covered by at least one hold
+fn held_percent(objects: &[(f64, f64)]) -> f64 {
+    let Some(first) = objects.first() else {
+        return 0.0;
+    };
+    let last = objects.iter().map(|o| o.1).fold(first.1, f64::max);
+    let span = last - first.0;
+    if span <= 0.0 {
+        return 0.0;
+    }
+
+    let mut held = 0.0;
+    let mut covered_until = f64::NEG_INFINITY;
+    for &(start, end) in objects.iter().filter(|o| o.1 > o.0) {
+        let start = start.max(covered_until);
+        if end > start {
+            held += end - start;
+            covered_until = end;
+        }
+    }
+    100.0 * held / span
+}
+
+/// Judgments reported to the player, see `Player::session_stats`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionStats {
+    /// Signed error of every reported hit in ms by map time, `None` for
+    /// broken holds.
+    pub errors: Vec<Option<f64>>,
+    /// Longest run of hits within the Ok window, ended by anything else.
+    pub max_combo: usize,
+    /// Judgments of a full play, one per note or hold of the chart.
+    pub map_note_count: usize,
+    pub ghost_taps: usize,
+    /// Windows `errors` are judged with.
+    pub windows: Windows,
+}
+
+impl SessionStats {
+    /// `score::normalize` of the session, unreported notes being misses.
+    pub fn normalized_score(&self, mode: ScoreMode) -> Result<f64, ScoreError> {
+        let judgments = Judgments::Errors {
+            errors: &self.errors,
+            windows: self.windows,
+        };
+        score::normalize(judgments, self.max_combo, self.map_note_count, mode)
+    }
+
+    /// The stats as a JSON object with the field names, broken holds being
+    /// `null` errors, for hosts without serde.
+    pub fn to_json(&self) -> String {
+        let errors: Vec<_> = self
+            .errors
+            .iter()
+            .map(|error| match error {
+                Some(error) if error.is_finite() => error.to_string(),
+                _ => "null".to_owned(),
+            })
+            .collect();
+        let w = &self.windows;
+        format!(
+            "{{\"errors\":[{}],\"max_combo\":{},\"map_note_count\":{},\"ghost_taps\":{},\
+             \"windows\":{{\"marv\":{},\"perfect\":{},\"great\":{},\"good\":{},\"ok\":{},\
+             \"miss\":{}}}}}",
+            errors.join(","),
+            self.max_combo,
+            self.map_note_count,
+            self.ghost_taps,
+            w.marv,
+            w.perfect,
+            w.great,
+            w.good,
+            w.ok,
+            w.miss
+        )
+    }
+}
+
+pub(crate) fn session_stats<'a>(
+    recorded: impl IntoIterator<Item = &'a (f64, Recorded)>,
+    windows: Windows,
+    map_note_count: usize,
+    ghost_taps: usize,
+) -> SessionStats {
+    let mut errors = Vec::new();
+    let (mut combo, mut max_combo) = (0, 0);
+    for (_, recorded) in recorded {
+        let error = match recorded {
+            Recorded::Hit { offset_ms, .. } => Some(*offset_ms),
+            Recorded::HoldBreak { .. } => None,
+            Recorded::GhostTap { .. } => continue,
+        };
+        combo = match error {
+            Some(error) if error.abs() <= f64::from(windows.ok) => combo + 1,
+            _ => 0,
+        };
+        max_combo = max_combo.max(combo);
+        errors.push(error);
+    }
+    SessionStats {
+        errors,
+        max_combo,
+        map_note_count,
+        ghost_taps,
+        windows,
+    }
+}
@@ -0,0 +1,172 @@
+//! Per-map settings saved to a directory, one small text file per beatmap
+//! hash. Entries that fail to parse are ignored as if they didn't exist.
+
+use crate::layout::mania::NoteShape;
+use egui::Color32;
+use std::fmt::Write as _;
+use std::io;
+use std::path::PathBuf;
+
+/// Settings remembered for a map. Image note shapes aren't saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapSettings {
+    pub speed: f64,
+    pub scroll_time_ms: f32,
+    pub note_color: Color32,
+    pub hold_body_color: Color32,
+    pub hold_cap_color: Color32,
+    /// `None` keeps the current shape when applied.
+    pub shape: Option<SavedShape>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SavedShape {
+    Circle,
+    Rectangle { width: f32, height: f32 },
+    Arrow { width: f32, height: f32 },
+}
+
+impl SavedShape {
+    pub fn from_shape(shape: &NoteShape) -> Option<Self> {
+        match *shape {
+            NoteShape::Circle => Some(Self::Circle),
+            NoteShape::Rectangle { width, height } => Some(Self::Rectangle { width, height }),
+            NoteShape::Arrow { width, height } => Some(Self::Arrow { width, height }),
+            NoteShape::Image(_) | NoteShape::Keyed(_) => None,
+        }
+    }
+
+    pub fn to_shape(self) -> NoteShape {
+        match self {
+            Self::Circle => NoteShape::Circle,
+            Self::Rectangle { width, height } => NoteShape::Rectangle { width, height },
+            Self::Arrow { width, height } => NoteShape::Arrow { width, height },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SettingsStore {
+    dir: PathBuf,
+}
+
+impl SettingsStore {
+    /// Store in `dir`, created on the first save.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn save(&self, hash: &str, settings: &MapSettings) -> io::Result<()> {
+        let path = self
+            .path(hash)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a beatmap hash"))?;
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(path, encode(settings))
+    }
+
+    /// `None` if nothing was saved for `hash` or the entry is corrupt.
+    pub fn load(&self, hash: &str) -> Option<MapSettings> {
+        let text = std::fs::read_to_string(self.path(hash)?).ok()?;
+        decode(&text)
+    }
+
+    pub fn remove(&self, hash: &str) -> io::Result<()> {
+        match self.path(hash) {
+            Some(path) => std::fs::remove_file(path),
+            None => Ok(()),
+        }
+    }
+
+    // Only MD5 hex digests, so a hash can't point outside the directory
+    fn path(&self, hash: &str) -> Option<PathBuf> {
+        let valid = hash.len() == 32 && hash.bytes().all(|b| b.is_ascii_hexdigit());
+        valid.then(|| self.dir.join(format!("{}.cfg", hash.to_ascii_lowercase())))
+    }
+}
+
+fn encode(settings: &MapSettings) -> String {
+    let color = |c: Color32| format!("{} {} {} {}", c.r(), c.g(), c.b(), c.a());
+    let mut text = String::new();
+    let _ = writeln!(text, "speed = {}", settings.speed);
+    let _ = writeln!(text, "scroll_time_ms = {}", settings.scroll_time_ms);
+    let _ = writeln!(text, "note_color = {}", color(settings.note_color));
+    let _ = writeln!(
+        text,
+        "hold_body_color = {}",
+        color(settings.hold_body_color)
+    );
+    let _ = writeln!(text, "hold_cap_color = {}", color(settings.hold_cap_color));
+    match settings.shape {
+        Some(SavedShape::Circle) => {
+            let _ = writeln!(text, "shape = circle");
+        }
+        Some(SavedShape::Rectangle { width, height }) => {
+            let _ = writeln!(text, "shape = rectangle {width} {height}");
+        }
+        Some(SavedShape::Arrow { width, height }) => {
+            let _ = writeln!(text, "shape = arrow {width} {height}");
+        }
+        None => {}
+    }
+    text
+}
+
+fn decode(text: &str) -> Option<MapSettings> {
+    let mut speed = None;
+    let mut scroll_time_ms = None;
+    let mut colors = [None; 3];
+    let mut shape = None;
+
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let (key, value) = line.split_once('=')?;
+        let values: Vec<&str> = value.split_whitespace().collect();
+        match key.trim() {
+            "speed" => speed = Some(value.trim().parse().ok()?),
+            "scroll_time_ms" => scroll_time_ms = Some(value.trim().parse().ok()?),
+            "note_color" => colors[0] = Some(decode_color(&values)?),
+            "hold_body_color" => colors[1] = Some(decode_color(&values)?),
+            "hold_cap_color" => colors[2] = Some(decode_color(&values)?),
+            "shape" => shape = Some(decode_shape(&values)?),
+            _ => return None,
+        }
+    }
+
+    let [note_color, hold_body_color, hold_cap_color] = colors;
+    Some(MapSettings {
+        speed: speed?,
+        scroll_time_ms: scroll_time_ms?,
+        note_color: note_color?,
+        hold_body_color: hold_body_color?,
+        hold_cap_color: hold_cap_color?,
+        shape,
+    })
+}
+
+fn decode_color(values: &[&str]) -> Option<Color32> {
+    let [r, g, b, a] = values else {
+        return None;
+    };
+    let channel = |value: &str| value.parse::<u8>().ok();
+    Some(Color32::from_rgba_premultiplied(
+        channel(r)?,
+        channel(g)?,
+        channel(b)?,
+        channel(a)?,
+    ))
+}
+
+fn decode_shape(values: &[&str]) -> Option<SavedShape> {
+    let size = |width: &str, height: &str| Some((width.parse().ok()?, height.parse().ok()?));
+    match values {
+        ["circle"] => Some(SavedShape::Circle),
+        ["rectangle", width, height] => {
+            let (width, height) = size(width, height)?;
+            Some(SavedShape::Rectangle { width, height })
+        }
+        ["arrow", width, height] => {
+            let (width, height) = size(width, height)?;
+            Some(SavedShape::Arrow { width, height })
+        }
+        _ => None,
+    }
+}
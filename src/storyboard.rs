@@ -0,0 +1,98 @@
+//! Storyboard-lite: background images switching at given times, read from
+//! the `[Events]` section. Only the map's background line and sprites on
+//! the Background layer are understood; a sprite shows from its first
+//! command on, fading in over its first fade command if it starts there.
+//! Everything else a storyboard can do is ignored.
+
+/// The background becomes `file` at map time `time_ms`, crossfading from
+/// the previous one over `fade_ms`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackgroundEvent {
+    pub time_ms: f64,
+    pub fade_ms: f64,
+    /// Path relative to the beatmap folder, as written in the .osu file.
+    pub file: String,
+}
+
+/// Background events of a .osu file sorted by time, and the number of
+/// event lines skipped because their type isn't supported.
+pub(crate) fn parse_background_events(osu_bytes: &[u8]) -> (Vec<BackgroundEvent>, usize) {
+    let text = String::from_utf8_lossy(osu_bytes);
+    let mut events: Vec<BackgroundEvent> = Vec::new();
+    let mut ignored = 0;
+    // The last event is a sprite still reading its commands
+    let mut in_sprite = false;
+    let mut in_events = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_events = trimmed == "[Events]";
+            continue;
+        }
+        if !in_events || trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
+        }
+
+        // Commands are indented under their sprite
+        if line.starts_with([' ', '_']) {
+            if in_sprite {
+                read_command(events.last_mut(), trimmed);
+            }
+            continue;
+        }
+        in_sprite = false;
+
+        let fields: Vec<&str> = trimmed.split(',').map(str::trim).collect();
+        match fields.as_slice() {
+            ["0" | "Background", time, file, ..] => events.push(BackgroundEvent {
+                time_ms: time.parse().unwrap_or(0.0),
+                fade_ms: 0.0,
+                file: unquote(file),
+            }),
+            ["4" | "Sprite", "0" | "Background", _, file, ..] => {
+                events.push(BackgroundEvent {
+                    time_ms: f64::NAN,
+                    fade_ms: 0.0,
+                    file: unquote(file),
+                });
+                in_sprite = true;
+            }
+            // Breaks are read by rosu-map
+            ["2" | "Break", ..] => {}
+            _ => ignored += 1,
+        }
+    }
+
+    // Sprites without any command never show
+    events.retain(|event| event.time_ms.is_finite());
+    events.sort_by(|a, b| a.time_ms.total_cmp(&b.time_ms));
+    (events, ignored)
+}
+
+// Starts the sprite at its first command, fading in if that's a fade
+fn read_command(sprite: Option<&mut BackgroundEvent>, command: &str) {
+    let Some(sprite) = sprite else {
+        return;
+    };
+    let fields: Vec<&str> = command.split(',').map(str::trim).collect();
+    let (Some(kind), Some(start)) = (fields.first(), fields.get(2)) else {
+        return;
+    };
+    let Ok(start) = start.parse::<f64>() else {
+        return;
+    };
+    if sprite.time_ms.is_finite() && start >= sprite.time_ms {
+        return;
+    }
+    sprite.time_ms = start;
+    sprite.fade_ms = 0.0;
+    if *kind == "F" {
+        let end = fields.get(3).and_then(|end| end.parse::<f64>().ok());
+        sprite.fade_ms = end.map_or(0.0, |end| (end - start).max(0.0));
+    }
+}
+
+fn unquote(file: &str) -> String {
+    file.trim_matches('"').to_owned()
+}
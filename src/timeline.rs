@@ -0,0 +1,172 @@
+//! Log of how the player clock related to wall time, for hosts scheduling
+//! external events against map time.
+
+use crate::layout::mania::ScrollTransform;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Oldest segments are dropped past this many seeks and rate changes
+const MAX_SEGMENTS: usize = 4096;
+
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    // Wall time from which this segment applies
+    since: Instant,
+    // Wall time at which the clock read 0
+    clock_origin: Instant,
+    scroll: ScrollTransform,
+}
+
+/// Piecewise mapping from wall time to map time. A new segment starts on
+/// every seek and rate change, so seeks are discontinuities of the mapping.
+#[derive(Clone, Debug)]
+pub(crate) struct Timeline {
+    segments: VecDeque<Segment>,
+}
+
+impl Timeline {
+    pub(crate) fn new(since: Instant, clock_origin: Instant, scroll: ScrollTransform) -> Self {
+        let mut timeline = Self {
+            segments: VecDeque::new(),
+        };
+        timeline.push(since, clock_origin, scroll);
+        timeline
+    }
+
+    /// Starts a segment at `since`, which must not be earlier than the last
+    /// one's.
+    pub(crate) fn push(&mut self, since: Instant, clock_origin: Instant, scroll: ScrollTransform) {
+        if self.segments.len() == MAX_SEGMENTS {
+            self.segments.pop_front();
+        }
+        self.segments.push_back(Segment {
+            since,
+            clock_origin,
+            scroll,
+        });
+    }
+
+    /// Map time playing at `wall`, `None` before the oldest logged segment.
+    pub(crate) fn map_time_at(&self, wall: Instant) -> Option<f64> {
+        let index = self.segments.partition_point(|s| s.since <= wall);
+        let segment = self.segments.get(index.checked_sub(1)?)?;
+        Some(
+            segment
+                .scroll
+                .map_time(signed_ms(wall, segment.clock_origin)),
+        )
+    }
+
+    /// Latest wall time at which `map_time` played or will play if nothing
+    /// changes. `None` if every segment skipped past it.
+    pub(crate) fn wall_at(&self, map_time: f64) -> Option<Instant> {
+        let mut until: Option<Instant> = None;
+        for segment in self.segments.iter().rev() {
+            let clock = segment.scroll.clock_time(map_time);
+            let wall = add_ms(segment.clock_origin, clock);
+            let in_segment = wall.is_some_and(|wall| {
+                wall >= segment.since && until.is_none_or(|until| wall < until)
+            });
+            if in_segment {
+                return wall;
+            }
+            until = Some(segment.since);
+        }
+        None
+    }
+}
+
+// `to - from` in ms, negative when `to` is earlier
+fn signed_ms(to: Instant, from: Instant) -> f64 {
+    match to.checked_duration_since(from) {
+        Some(elapsed) => elapsed.as_secs_f64() * 1000.0,
+        None => -from.duration_since(to).as_secs_f64() * 1000.0,
+    }
+}
+
+fn add_ms(instant: Instant, ms: f64) -> Option<Instant> {
+    let duration = Duration::try_from_secs_f64(ms.abs() / 1000.0).ok()?;
+    if ms >= 0.0 {
+        instant.checked_add(duration)
+    } else {
+        instant.checked_sub(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scroll(speed: f64) -> ScrollTransform {
+        ScrollTransform {
+            speed,
+            scroll_time_ms: 1000.0,
+            rate_ramp: None,
+        }
+    }
+
+    fn at(base: Instant, ms: u64) -> Instant {
+        base + Duration::from_millis(ms)
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+    }
+
+    // Plays 1 s at 1x, pauses for 1 s, plays 1 s, seeks from 2000 to
+    // 5000 ms, then switches to 1.5x 1 s later
+    fn pause_seek_rate() -> (Instant, Timeline) {
+        let base = Instant::now();
+        let mut timeline = Timeline::new(base, base, scroll(1.0));
+        // The clock has no pause; hosts resume by seeking back to where
+        // they paused
+        timeline.push(at(base, 2000), at(base, 1000), scroll(1.0));
+        timeline.push(at(base, 3000), base - Duration::from_secs(2), scroll(1.0));
+        // Clock time is kept, map time jumps from 6000 to 9000 ms
+        timeline.push(at(base, 4000), base - Duration::from_secs(2), scroll(1.5));
+        (base, timeline)
+    }
+
+    #[test]
+    fn map_time_follows_pause_seek_and_rate_change() {
+        let (base, timeline) = pause_seek_rate();
+        assert_eq!(timeline.map_time_at(base - Duration::from_millis(1)), None);
+        assert_close(timeline.map_time_at(at(base, 500)), 500.0);
+        // Logged once the host resumes, so the paused second reads as playing
+        assert_close(timeline.map_time_at(at(base, 1500)), 1500.0);
+        assert_close(timeline.map_time_at(at(base, 2000)), 1000.0);
+        assert_close(timeline.map_time_at(at(base, 2500)), 1500.0);
+        assert_close(timeline.map_time_at(at(base, 3000)), 5000.0);
+        assert_close(timeline.map_time_at(at(base, 3500)), 5500.0);
+        assert_close(timeline.map_time_at(at(base, 4000)), 9000.0);
+        assert_close(timeline.map_time_at(at(base, 5000)), 10500.0);
+    }
+
+    #[test]
+    fn wall_time_is_the_latest_play_of_a_map_time() {
+        let (base, timeline) = pause_seek_rate();
+        let wall = |map_time| timeline.wall_at(map_time);
+        assert_eq!(wall(500.0), Some(at(base, 500)));
+        // Played again after the pause
+        assert_eq!(wall(1500.0), Some(at(base, 2500)));
+        // Skipped by the seek
+        assert_eq!(wall(3000.0), None);
+        assert_eq!(wall(5500.0), Some(at(base, 3500)));
+        // Skipped by the rate change
+        assert_eq!(wall(7000.0), None);
+        assert_eq!(wall(10500.0), Some(at(base, 5000)));
+    }
+
+    #[test]
+    fn oldest_segments_are_dropped() {
+        let base = Instant::now();
+        let mut timeline = Timeline::new(base, base, scroll(1.0));
+        for i in 1..=MAX_SEGMENTS as u64 {
+            timeline.push(at(base, i), base, scroll(1.0));
+        }
+        assert_eq!(timeline.segments.len(), MAX_SEGMENTS);
+        assert_eq!(timeline.map_time_at(base), None);
+        assert_close(timeline.map_time_at(at(base, 1)), 1.0);
+    }
+}
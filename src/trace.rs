@@ -0,0 +1,29 @@
+// Wrappers over `tracing` that expand to nothing when the feature is off.
+
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+    ($name:literal) => {
+        tracing::info_span!($name).entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_span {
+    ($name:literal) => {
+        ()
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! trace_warn {
+    ($($arg:tt)*) => {
+        tracing::warn!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_warn {
+    ($($arg:tt)*) => {};
+}
+
+pub(crate) use {trace_span, trace_warn};
@@ -0,0 +1,403 @@
+use crate::layout::mania::{column_center_x, column_for_x};
+use crate::report::{LoadIssue, LoadReport};
+use crate::rng::SeededRng;
+use rosu_map::section::hit_objects::{HitObject, HitObjectCircle, HitObjectKind};
+use rosu_map::util::Pos;
+
+pub const DEFAULT_HOLD_EPSILON_MS: f64 = 1.0;
+
+// y used by the editor for every mania object
+const MANIA_Y: f32 = 192.0;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Arrangement {
+    #[default]
+    Normal,
+    Mirror,
+    Random,
+    /// Swaps columns two by two, 0 with 1, 2 with 3... The last column of an
+    /// odd keycount stays.
+    SwapPairs,
+    /// Shifts every column `n` columns right, wrapping around.
+    Rotate(usize),
+    /// Swaps the halves, each outer column going to the inner column of the
+    /// other half: 0 with 2 and 1 with 3 in 4K. The middle column of an odd
+    /// keycount stays.
+    Cross,
+    /// Displayed column of each source column, e.g. from
+    /// `Event::LayoutChanged`. Falls back to `Normal` unless it is a
+    /// permutation of the keycount's columns.
+    Custom(Vec<usize>),
+}
+
+impl Arrangement {
+    /// Displayed column for each source column.
+    pub fn column_mapping(&self, keycount: usize, rng: &mut SeededRng) -> Vec<usize> {
+        let mut mapping: Vec<usize> = (0..keycount).collect();
+        match self {
+            Arrangement::Normal => {}
+            Arrangement::Mirror => mapping.reverse(),
+            Arrangement::Random => rng.shuffle(&mut mapping),
+            Arrangement::SwapPairs => {
+                for pair in mapping.chunks_exact_mut(2) {
+                    pair.swap(0, 1);
+                }
+            }
+            Arrangement::Rotate(n) => {
+                if keycount > 0 {
+                    mapping.rotate_left(n % keycount);
+                }
+            }
+            Arrangement::Cross => {
+                let half = keycount / 2;
+                let (left, right) = mapping.split_at_mut(keycount - half);
+                left[..half].swap_with_slice(&mut right[..half]);
+            }
+            Arrangement::Custom(custom) => {
+                let mut sorted = custom.clone();
+                sorted.sort_unstable();
+                if sorted == mapping {
+                    mapping.clone_from(custom);
+                }
+            }
+        }
+        mapping
+    }
+
+    /// `column_mapping` with `special_lane` kept in place, the arrangement
+    /// only moving the other columns, then mirrored when `mirror` is set.
+    /// The mirror always comes last: `Rotate(1)` mirrored sends column 0 of
+    /// 4K to 2, and `Mirror` mirrored is `Normal`. A `Custom` mapping moving
+    /// the special lane falls back to `Normal`.
+    pub fn column_mapping_with(
+        &self,
+        keycount: usize,
+        mirror: bool,
+        special_lane: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<usize> {
+        let special_lane = special_lane.filter(|lane| *lane < keycount);
+        let lanes = keycount - usize::from(special_lane.is_some());
+        // Between columns and their index among the other lanes
+        let to_lane =
+            |column: usize| column - usize::from(special_lane.is_some_and(|s| s < column));
+        let to_column = |lane: usize| lane + usize::from(special_lane.is_some_and(|s| s <= lane));
+
+        let mut arranged = match (self, special_lane) {
+            (Arrangement::Custom(_), Some(lane)) => {
+                let custom = self.column_mapping(keycount, rng);
+                if custom[lane] == lane {
+                    let others = (0..keycount).filter(|column| *column != lane);
+                    others.map(|column| to_lane(custom[column])).collect()
+                } else {
+                    (0..lanes).collect()
+                }
+            }
+            _ => self.column_mapping(lanes, rng),
+        };
+        if mirror {
+            for lane in &mut arranged {
+                *lane = lanes - 1 - *lane;
+            }
+        }
+
+        let mut mapping: Vec<usize> = (0..keycount).collect();
+        for (lane, to) in arranged.into_iter().enumerate() {
+            mapping[to_column(lane)] = to_column(to);
+        }
+        mapping
+    }
+}
+
+/// Moves every mania object to its mapped column, rewriting x to the
+/// canonical column center.
+pub fn apply_column_mapping(
+    hit_objects: &[HitObject],
+    keycount: usize,
+    mapping: &[usize],
+) -> Vec<HitObject> {
+    if keycount == 0 {
+        return hit_objects.to_vec();
+    }
+
+    let remap = |x: f32| {
+        let column = column_for_x(x, keycount);
+        column_center_x(mapping[column], keycount)
+    };
+
+    hit_objects
+        .iter()
+        .cloned()
+        .map(|mut hit_object| {
+            match &mut hit_object.kind {
+                HitObjectKind::Circle(h) => h.pos.x = remap(h.pos.x),
+                HitObjectKind::Hold(h) => h.pos_x = remap(h.pos_x),
+                _ => {}
+            }
+            hit_object
+        })
+        .collect()
+}
+
+/// Fixes hold timings of object lists that didn't come from a .osu file:
+/// inverted holds get start and end swapped, non-finite durations become 0.
+/// The list is then stably sorted by start time, which user lists may not
+/// be and swapped holds break.
+pub(crate) fn normalize_holds(hit_objects: &mut Vec<HitObject>, report: &mut LoadReport) {
+    // Each object with its original (time, duration) if it was fixed
+    let mut objects: Vec<_> = std::mem::take(hit_objects)
+        .into_iter()
+        .map(|mut hit_object| {
+            let HitObjectKind::Hold(h) = &mut hit_object.kind else {
+                return (hit_object, None);
+            };
+            let original = (hit_object.start_time, h.duration);
+            if !h.duration.is_finite() {
+                h.duration = 0.0;
+            } else if h.duration < 0.0 {
+                hit_object.start_time += h.duration;
+                h.duration = -h.duration;
+            } else {
+                return (hit_object, None);
+            }
+            (hit_object, Some(original))
+        })
+        .collect();
+    objects.sort_by(|(a, _), (b, _)| a.start_time.total_cmp(&b.start_time));
+
+    for (index, (hit_object, original)) in objects.into_iter().enumerate() {
+        if let Some((time, duration)) = original {
+            report.push(LoadIssue::HoldNormalized {
+                index,
+                time,
+                duration,
+            });
+        }
+        hit_objects.push(hit_object);
+    }
+}
+
+/// Turns holds shorter than `epsilon_ms` into regular notes, so they draw
+/// with the note shape and pass the judgment line once.
+pub fn convert_short_holds(hit_objects: &mut [HitObject], epsilon_ms: f64) {
+    for hit_object in hit_objects {
+        let HitObjectKind::Hold(h) = &hit_object.kind else {
+            continue;
+        };
+        if h.duration < epsilon_ms {
+            hit_object.kind = HitObjectKind::Circle(HitObjectCircle {
+                pos: Pos::new(h.pos_x, MANIA_Y),
+                new_combo: false,
+                combo_offset: 0,
+            });
+        }
+    }
+}
+
+// Columns sliding from their previous arrangement to the current one
+pub(crate) struct ColumnSwap {
+    // Per displayed column, distance in columns back to where its notes were
+    offsets: Vec<f32>,
+    started_at: f64,
+    duration_ms: f64,
+}
+
+impl ColumnSwap {
+    pub(crate) fn new(
+        previous: &[usize],
+        current: &[usize],
+        started_at: f64,
+        duration_ms: f64,
+    ) -> Self {
+        let mut offsets = vec![0.0; current.len()];
+        for (&from, &to) in previous.iter().zip(current) {
+            offsets[to] = from as f32 - to as f32;
+        }
+        Self {
+            offsets,
+            started_at,
+            duration_ms,
+        }
+    }
+
+    /// Offsets at clock time `now`, or `None` once the swap is over.
+    pub(crate) fn offsets_at(&self, now: f64) -> Option<Vec<f32>> {
+        let t = ((now - self.started_at) / self.duration_ms) as f32;
+        if !(0.0..1.0).contains(&t) {
+            return None;
+        }
+        // Smoothstep from the old columns to the new ones
+        let remaining = 1.0 - t * t * (3.0 - 2.0 * t);
+        let offsets = self.offsets.iter().map(|offset| offset * remaining);
+        Some(offsets.collect())
+    }
+}
+
+// Displayed column being dragged in layout edit mode
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ColumnDrag {
+    pub(crate) from: usize,
+    // Pointer position, in columns from the left edge of the first one
+    hovered: f32,
+}
+
+impl ColumnDrag {
+    pub(crate) fn new(from: usize) -> Self {
+        Self {
+            from,
+            hovered: from as f32,
+        }
+    }
+
+    pub(crate) fn hover(&mut self, hovered: f32, keycount: usize) {
+        self.hovered = hovered.clamp(0.0, keycount.saturating_sub(1) as f32);
+    }
+
+    /// Column the dragged one lands in if dropped now.
+    pub(crate) fn target(&self) -> usize {
+        self.hovered.round() as usize
+    }
+
+    /// Per displayed column, as in `ColumnSwap`: the dragged column follows
+    /// the pointer and the ones it passed make room.
+    pub(crate) fn offsets(&self, keycount: usize) -> Vec<f32> {
+        let mut offsets = vec![0.0; keycount];
+        let to = self.target();
+        if to > self.from {
+            offsets[self.from + 1..=to].fill(-1.0);
+        } else {
+            offsets[to..self.from].fill(1.0);
+        }
+        offsets[self.from] = self.hovered - self.from as f32;
+        offsets
+    }
+
+    /// `mapping` with the dragged column moved to `target`.
+    pub(crate) fn reorder(&self, mapping: &[usize]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..mapping.len()).collect();
+        let column = order.remove(self.from);
+        order.insert(self.target(), column);
+
+        let mut position = vec![0; order.len()];
+        for (index, column) in order.into_iter().enumerate() {
+            position[column] = index;
+        }
+        mapping.iter().map(|column| position[*column]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(arrangement: Arrangement, keycount: usize) -> Vec<usize> {
+        arrangement.column_mapping(keycount, &mut SeededRng::new(7))
+    }
+
+    fn mapping_with(
+        arrangement: Arrangement,
+        keycount: usize,
+        mirror: bool,
+        special_lane: Option<usize>,
+    ) -> Vec<usize> {
+        let mut rng = SeededRng::new(7);
+        arrangement.column_mapping_with(keycount, mirror, special_lane, &mut rng)
+    }
+
+    #[test]
+    fn presets() {
+        assert_eq!(mapping(Arrangement::SwapPairs, 5), [1, 0, 3, 2, 4]);
+        assert_eq!(mapping(Arrangement::Rotate(1), 4), [1, 2, 3, 0]);
+        assert_eq!(mapping(Arrangement::Rotate(9), 4), [1, 2, 3, 0]);
+        assert_eq!(mapping(Arrangement::Cross, 4), [2, 3, 0, 1]);
+        assert_eq!(mapping(Arrangement::Cross, 7), [4, 5, 6, 3, 0, 1, 2]);
+        assert_ne!(
+            mapping(Arrangement::Cross, 4),
+            mapping(Arrangement::SwapPairs, 4)
+        );
+    }
+
+    #[test]
+    fn custom_must_be_a_bijection() {
+        assert_eq!(mapping(Arrangement::Custom(vec![2, 0, 1]), 3), [2, 0, 1]);
+        for custom in [vec![0, 0, 1], vec![0, 1], vec![0, 1, 3]] {
+            assert_eq!(mapping(Arrangement::Custom(custom), 3), [0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn every_preset_is_a_permutation() {
+        let arrangements = [
+            Arrangement::Normal,
+            Arrangement::Mirror,
+            Arrangement::Random,
+            Arrangement::SwapPairs,
+            Arrangement::Rotate(3),
+            Arrangement::Cross,
+        ];
+        for keycount in 0..=18 {
+            for arrangement in &arrangements {
+                for (mirror, special_lane) in [(false, None), (true, None), (true, Some(0))] {
+                    let mut sorted =
+                        mapping_with(arrangement.clone(), keycount, mirror, special_lane);
+                    sorted.sort_unstable();
+                    assert_eq!(sorted, (0..keycount).collect::<Vec<_>>());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mirror_applies_last() {
+        assert_eq!(
+            mapping_with(Arrangement::Rotate(1), 4, true, None),
+            [2, 1, 0, 3]
+        );
+        assert_eq!(
+            mapping_with(Arrangement::Cross, 5, true, None),
+            [1, 0, 2, 4, 3]
+        );
+        assert_eq!(
+            mapping_with(Arrangement::Mirror, 4, true, None),
+            [0, 1, 2, 3]
+        );
+        let custom = Arrangement::Custom(vec![1, 2, 0]);
+        assert_eq!(mapping_with(custom, 3, true, None), [1, 0, 2]);
+    }
+
+    #[test]
+    fn special_lane_stays() {
+        assert_eq!(
+            mapping_with(Arrangement::Normal, 8, true, Some(0)),
+            [0, 7, 6, 5, 4, 3, 2, 1]
+        );
+        assert_eq!(
+            mapping_with(Arrangement::SwapPairs, 8, false, Some(0)),
+            [0, 2, 1, 4, 3, 6, 5, 7]
+        );
+        assert_eq!(
+            mapping_with(Arrangement::Rotate(1), 4, false, Some(3)),
+            [1, 2, 0, 3]
+        );
+        for seed in 0..20 {
+            let mut rng = SeededRng::new(seed);
+            let random = Arrangement::Random.column_mapping_with(8, true, Some(0), &mut rng);
+            assert_eq!(random[0], 0);
+        }
+        // Out of range lanes are ignored
+        assert_eq!(
+            mapping_with(Arrangement::Mirror, 3, false, Some(3)),
+            [2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn custom_keeps_the_special_lane_or_falls_back() {
+        let custom = Arrangement::Custom(vec![0, 3, 1, 2]);
+        assert_eq!(mapping_with(custom, 4, false, Some(0)), [0, 3, 1, 2]);
+        let custom = Arrangement::Custom(vec![0, 3, 1, 2]);
+        assert_eq!(mapping_with(custom, 4, true, Some(0)), [0, 1, 3, 2]);
+        let moved = Arrangement::Custom(vec![1, 0, 2, 3]);
+        assert_eq!(mapping_with(moved, 4, false, Some(0)), [0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::calibration::OffsetCalibration;
+use crate::judge::windows::Windows;
+use crate::layout::mania::keycount_for;
+use crate::overview::OverviewView;
+use crate::scatter::{self, ScatterStyle, TimedError};
+use crate::scroll::ScrollSpeed;
+use crate::stats::ColumnStats;
+use crate::Player;
+use rosu_map::Beatmap;
+use std::path::Path;
+use std::time::Instant;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapSummary {
+    pub version: String,
+    pub keycount: usize,
+    pub object_count: usize,
+}
+
+impl MapSummary {
+    pub fn from_beatmap(beatmap: &Beatmap) -> Self {
+        Self {
+            version: beatmap.version.clone(),
+            keycount: keycount_for(beatmap.circle_size),
+            object_count: beatmap.hit_objects.len(),
+        }
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "[{}K] {} ({} objects)",
+            self.keycount, self.version, self.object_count
+        )
+    }
+}
+
+/// Combo box listing the difficulties of a mapset. Returns the index of the
+/// newly picked difficulty on the frame the selection changes.
+pub fn difficulty_picker(ui: &mut egui::Ui, maps: &[MapSummary]) -> Option<usize> {
+    let id = ui.make_persistent_id("difficulty_picker");
+    let previous = ui.data(|d| d.get_temp::<usize>(id)).unwrap_or(0);
+    let mut selected = previous.min(maps.len().saturating_sub(1));
+
+    let selected_text = maps
+        .get(selected)
+        .map(MapSummary::label)
+        .unwrap_or_default();
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            for (index, map) in maps.iter().enumerate() {
+                ui.selectable_value(&mut selected, index, map.label());
+            }
+        });
+
+    ui.data_mut(|d| d.insert_temp(id, selected));
+    (selected != previous).then_some(selected)
+}
+
+/// Bar chart of how objects are spread over the columns, with the share of
+/// each column under its bar.
+pub fn column_balance(ui: &mut egui::Ui, columns: &[ColumnStats]) -> egui::Response {
+    let bar_width = 24.0;
+    let bar_height = 60.0;
+    let label_height = 14.0;
+    let size = egui::vec2(bar_width * columns.len() as f32, bar_height + label_height);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+
+    let total: usize = columns.iter().map(ColumnStats::objects).sum();
+    let most = columns.iter().map(ColumnStats::objects).max().unwrap_or(0);
+    if total == 0 {
+        return response;
+    }
+
+    let painter = ui.painter_at(rect);
+    let text_color = ui.visuals().text_color();
+    for (i, column) in columns.iter().enumerate() {
+        let x = rect.min.x + i as f32 * bar_width;
+        let height = bar_height * column.objects() as f32 / most as f32;
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(x + 2.0, rect.min.y + bar_height - height),
+            egui::pos2(x + bar_width - 2.0, rect.min.y + bar_height),
+        );
+        painter.rect_filled(bar, 0.0, ui.visuals().selection.bg_fill);
+
+        let share = 100.0 * column.objects() as f32 / total as f32;
+        painter.text(
+            egui::pos2(x + bar_width / 2.0, rect.max.y),
+            egui::Align2::CENTER_BOTTOM,
+            format!("{share:.0}%"),
+            egui::FontId::proportional(9.0),
+            text_color,
+        );
+    }
+    response
+}
+
+/// Hit errors over map time, early ones up and late ones down, colored by
+/// judgment tier. `style.time_range` zooms on part of the play. `errors` are
+/// sorted by time, as `Player::hit_errors` returns them.
+pub fn hit_error_scatter(
+    ui: &mut egui::Ui,
+    errors: &[TimedError],
+    windows: &Windows,
+    style: &ScatterStyle,
+) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(style.size, egui::Sense::hover());
+    let errors = errors.iter().copied();
+    scatter::paint(&ui.painter_at(rect), rect, errors, windows, style, None);
+    response
+}
+
+// Notes per strip pixel at which overview marks are fully opaque
+const OVERVIEW_FULL_DENSITY: f32 = 3.0;
+
+/// Strip of the chart's notes, one row per column, showing the part of it
+/// `view` picks around the judgment line. Returns the clock time to seek to,
+/// as in `Player::set_current_time`, while clicked or dragged.
+pub fn overview(
+    ui: &mut egui::Ui,
+    player: &Player,
+    view: &OverviewView,
+    size: egui::Vec2,
+) -> (egui::Response, Option<f64>) {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(20));
+
+    let scroll = player.scroll_transform();
+    let line_time = scroll.map_time_at_line(player.current_time());
+    let (from, to) = view.visible(line_time);
+    if to <= from {
+        return (response, None);
+    }
+    let x_at = |time: f64| rect.min.x + ((time - from) / (to - from)) as f32 * rect.width();
+
+    let row_height = rect.height() / player.keycount() as f32;
+    let color = ui.visuals().selection.bg_fill;
+    for mark in player.overview_marks((from, to), rect.width()) {
+        // Merged marks are as wide as their span, denser ones brighter
+        let width = (x_at(mark.end_ms) - x_at(mark.start_ms)).max(1.0);
+        let density = (mark.count as f32 / width / OVERVIEW_FULL_DENSITY).clamp(0.3, 1.0);
+        let top = rect.min.y + mark.column as f32 * row_height;
+        let mark_rect = egui::Rect::from_min_size(
+            egui::pos2(x_at(mark.start_ms), top + 1.0),
+            egui::vec2(width, (row_height - 2.0).max(1.0)),
+        );
+        painter.rect_filled(mark_rect, 0.0, color.gamma_multiply(density));
+    }
+    let playhead = x_at(line_time);
+    painter.line_segment(
+        [
+            egui::pos2(playhead, rect.min.y),
+            egui::pos2(playhead, rect.max.y),
+        ],
+        egui::Stroke::new(2.0, egui::Color32::WHITE),
+    );
+
+    let seek = response.interact_pointer_pos().map(|pointer| {
+        let fraction = ((pointer.x - rect.min.x) / rect.width()) as f64;
+        scroll.crossing_time(view.time_at(fraction, line_time))
+    });
+    (response, seek)
+}
+
+// Gap between the notes of the calibration stream, in ms of playback
+const CALIBRATION_NOTE_GAP_MS: f32 = 250.0;
+
+/// One column `height` tall scrolling a steady stream of notes at `speed`,
+/// with a slider to adjust it. Returns the speed on the frame the user
+/// confirms it feels right.
+pub fn scroll_calibration(
+    ui: &mut egui::Ui,
+    speed: &mut ScrollSpeed,
+    height: f32,
+) -> Option<ScrollSpeed> {
+    let column_width = 60.0;
+    let note_height = 12.0;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(column_width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(20));
+
+    // Notes cross the whole column in the scroll time, down to the bottom edge
+    let gap_px = CALIBRATION_NOTE_GAP_MS * speed.px_per_ms;
+    if gap_px >= note_height {
+        let time_ms = ui.input(|i| i.time) * 1000.0;
+        let travelled = (time_ms * f64::from(speed.px_per_ms)) % f64::from(gap_px);
+        let mut y = rect.max.y + travelled as f32;
+        while y > rect.min.y {
+            let note = egui::Rect::from_min_max(
+                egui::pos2(rect.min.x + 6.0, y - note_height),
+                egui::pos2(rect.max.x - 6.0, y),
+            );
+            painter.rect_filled(note, 2.0, ui.visuals().selection.bg_fill);
+            y -= gap_px;
+        }
+        ui.ctx().request_repaint();
+    }
+
+    let mut scroll_time_ms = speed.scroll_time_ms(height);
+    ui.add(
+        egui::Slider::new(&mut scroll_time_ms, 100.0..=3000.0)
+            .suffix(" ms")
+            .text("Scroll time"),
+    );
+    *speed = ScrollSpeed::from_scroll_time(scroll_time_ms, height);
+    ui.button("Feels right").clicked().then_some(*speed)
+}
+
+// How long the beat light stays lit after each beat, in ms of map time
+const BEAT_FLASH_MS: f64 = 80.0;
+
+/// Light flashing on the beats of the player's map, collecting a tap per
+/// key press into `taps`. Once `wanted` taps are in, returns their
+/// calibration and starts over. Taps are timed when the frame reads them,
+/// so they run up to a frame late.
+pub fn offset_calibration(
+    ui: &mut egui::Ui,
+    player: &Player,
+    taps: &mut Vec<f64>,
+    wanted: usize,
+) -> Option<OffsetCalibration> {
+    let now = player.wall_to_map_time(Instant::now())?;
+    let since_beat = player.nearest_beat(now).map(|beat| now - beat);
+    let lit = since_beat.is_some_and(|since| (0.0..BEAT_FLASH_MS).contains(&since));
+
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(60.0, 60.0), egui::Sense::hover());
+    let color = if lit {
+        ui.visuals().selection.bg_fill
+    } else {
+        egui::Color32::from_gray(20)
+    };
+    ui.painter().rect_filled(rect, 8.0, color);
+    ui.ctx().request_repaint();
+
+    let presses = ui.input(|i| {
+        i.events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    egui::Event::Key {
+                        pressed: true,
+                        repeat: false,
+                        ..
+                    }
+                )
+            })
+            .count()
+    });
+    taps.extend(std::iter::repeat_n(now, presses));
+    ui.label(format!("Tap any key on the beat: {}/{wanted}", taps.len()));
+    if ui.button("Restart").clicked() {
+        taps.clear();
+    }
+
+    if taps.len() < wanted.max(1) {
+        return None;
+    }
+    let calibration = player.calibrate_offset(taps);
+    taps.clear();
+    calibration
+}
+
+/// Button taking a screenshot of the player's playfield, without the
+/// surrounding panels. The capture arrives a frame or two after the click;
+/// it is returned on the frame it does.
+pub fn screenshot_button(ui: &mut egui::Ui, player: &Player) -> Option<egui::ColorImage> {
+    let id = ui.make_persistent_id("screenshot_button");
+    if ui.button("📷 Screenshot").clicked() {
+        let user_data = egui::UserData::new(id);
+        ui.ctx()
+            .send_viewport_cmd(egui::ViewportCommand::Screenshot(user_data));
+    }
+
+    let screenshot = ui.input(|i| {
+        i.events.iter().find_map(|event| match event {
+            egui::Event::Screenshot {
+                user_data, image, ..
+            } => {
+                let ours = user_data.data.as_ref()?.downcast_ref::<egui::Id>() == Some(&id);
+                ours.then(|| image.clone())
+            }
+            _ => None,
+        })
+    })?;
+    player.crop_screenshot(&screenshot, ui.ctx().pixels_per_point())
+}
+
+/// Writes a screenshot to `path`, in the format its extension implies.
+pub fn save_image(image: &egui::ColorImage, path: impl AsRef<Path>) -> image::ImageResult<()> {
+    let [width, height] = image.size;
+    let bytes: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|color| color.to_srgba_unmultiplied())
+        .collect();
+    image::save_buffer(
+        path,
+        &bytes,
+        width as u32,
+        height as u32,
+        image::ColorType::Rgba8,
+    )
+}
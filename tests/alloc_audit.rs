@@ -0,0 +1,100 @@
+#![cfg(feature = "alloc-audit")]
+
+mod common;
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use rosu_renderer::alloc_audit::CountingAllocator;
+use rosu_renderer::layout::mania::{
+    CompositionTextStyle, HoldBodyPattern, Layer, LayerOrder, Period, TimingTextStyle,
+};
+use rosu_renderer::Player;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// egui's own buffers still grow now and then while notes scroll in
+const PLAYING_FRAME_ALLOCATIONS: usize = 2;
+
+fn player() -> Player {
+    common::player(common::stream(400, 60.0, 7), 7)
+}
+
+// Allocations of each frame after the first few, parked at `clock` or
+// playing from it
+fn frame_allocations(player: &mut Player, clock: f64, parked: bool) -> Vec<usize> {
+    let ctx = egui::Context::default();
+    player.set_current_time(clock);
+    (0..8)
+        .map(|_| {
+            if parked {
+                player.set_current_time(clock);
+            } else {
+                sleep(Duration::from_millis(4));
+            }
+            common::frame(&ctx, |ui| player.render(ui));
+            player.frame_allocations()
+        })
+        .skip(3)
+        .collect()
+}
+
+#[test]
+fn parked_frames_make_no_allocations() {
+    let mut player = player();
+    assert_eq!(frame_allocations(&mut player, 3000.0, true), [0; 5]);
+}
+
+fn assert_bounded(allocations: Vec<usize>) {
+    assert!(
+        allocations.iter().all(|&n| n <= PLAYING_FRAME_ALLOCATIONS),
+        "{allocations:?}"
+    );
+}
+
+#[test]
+fn playing_frames_make_bounded_allocations() {
+    let mut player = player();
+    assert_bounded(frame_allocations(&mut player, 3000.0, false));
+
+    player.set_layer_order(LayerOrder::Interleaved);
+    assert_bounded(frame_allocations(&mut player, 5000.0, false));
+}
+
+#[test]
+fn striped_holds_make_bounded_allocations() {
+    let mut player = player();
+    player.set_hold_body_pattern(HoldBodyPattern::Striped {
+        period: Period::Ms(20.0),
+        color_a: egui::Color32::WHITE,
+        color_b: egui::Color32::GRAY,
+    });
+    assert_bounded(frame_allocations(&mut player, 3000.0, false));
+}
+
+#[test]
+fn overlay_text_is_laid_out_once() {
+    let mut player = player();
+    player.set_timing_text(Some(TimingTextStyle {
+        duration_ms: 10_000.0,
+        ..Default::default()
+    }));
+    player.set_composition_text(Some(CompositionTextStyle::default()));
+    player.set_current_time(3000.0);
+    for column in 0..7 {
+        player.report_hit(column, column as f64 - 3.0, 1900.0);
+    }
+    assert_eq!(frame_allocations(&mut player, 3000.0, true), [0; 5]);
+    assert_bounded(frame_allocations(&mut player, 3000.0, false));
+}
+
+#[test]
+fn layer_hooks_are_free() {
+    let mut player = player();
+    for layer in Layer::ALL {
+        player.set_layer_hook(layer, std::sync::Arc::new(|_, _| {}));
+    }
+    assert_eq!(frame_allocations(&mut player, 3000.0, true), [0; 5]);
+    assert_bounded(frame_allocations(&mut player, 3000.0, false));
+}
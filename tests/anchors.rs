@@ -0,0 +1,70 @@
+use egui::{pos2, vec2, Align2, Rect};
+use rosu_renderer::layout::mania::{AnchorTarget, OverlayAnchor};
+
+const STAGE: Rect = Rect::from_min_max(pos2(100.0, 50.0), pos2(420.0, 850.0));
+const WINDOW: Rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(1280.0, 960.0));
+
+fn resolve(relative_to: AnchorTarget, alignment: Align2) -> egui::Pos2 {
+    let anchor = OverlayAnchor {
+        relative_to,
+        alignment,
+        offset: vec2(4.0, -6.0),
+    };
+    anchor.resolve(STAGE, WINDOW)
+}
+
+#[test]
+fn every_alignment_picks_its_point_of_the_target() {
+    let alignments = [
+        (Align2::LEFT_TOP, pos2(100.0, 50.0), pos2(0.0, 0.0)),
+        (Align2::CENTER_TOP, pos2(260.0, 50.0), pos2(640.0, 0.0)),
+        (Align2::RIGHT_TOP, pos2(420.0, 50.0), pos2(1280.0, 0.0)),
+        (Align2::LEFT_CENTER, pos2(100.0, 450.0), pos2(0.0, 480.0)),
+        (
+            Align2::CENTER_CENTER,
+            pos2(260.0, 450.0),
+            pos2(640.0, 480.0),
+        ),
+        (
+            Align2::RIGHT_CENTER,
+            pos2(420.0, 450.0),
+            pos2(1280.0, 480.0),
+        ),
+        (Align2::LEFT_BOTTOM, pos2(100.0, 850.0), pos2(0.0, 960.0)),
+        (
+            Align2::CENTER_BOTTOM,
+            pos2(260.0, 850.0),
+            pos2(640.0, 960.0),
+        ),
+        (
+            Align2::RIGHT_BOTTOM,
+            pos2(420.0, 850.0),
+            pos2(1280.0, 960.0),
+        ),
+    ];
+    for (alignment, stage, window) in alignments {
+        let offset = vec2(4.0, -6.0);
+        assert_eq!(resolve(AnchorTarget::Stage, alignment), stage + offset);
+        assert_eq!(resolve(AnchorTarget::Window, alignment), window + offset);
+    }
+}
+
+#[test]
+fn stage_anchors_follow_the_stage() {
+    let anchor = OverlayAnchor {
+        relative_to: AnchorTarget::Stage,
+        alignment: Align2::CENTER_TOP,
+        offset: vec2(0.0, 10.0),
+    };
+    let moved = STAGE.translate(vec2(200.0, 30.0));
+    assert_eq!(
+        anchor.resolve(moved, WINDOW) - anchor.resolve(STAGE, WINDOW),
+        vec2(200.0, 30.0)
+    );
+
+    let anchor = OverlayAnchor {
+        relative_to: AnchorTarget::Window,
+        ..anchor
+    };
+    assert_eq!(anchor.resolve(moved, WINDOW), anchor.resolve(STAGE, WINDOW));
+}
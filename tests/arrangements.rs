@@ -0,0 +1,65 @@
+mod common;
+
+use rosu_map::section::general::GameMode;
+use rosu_map::Beatmap;
+use rosu_renderer::layout::mania::column_for_x;
+use rosu_renderer::transform::Arrangement;
+use rosu_renderer::Player;
+
+fn special_style_player(special_style: bool) -> Player {
+    let beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 8.0,
+        special_style,
+        hit_objects: common::stream(64, 50.0, 8),
+        ..Default::default()
+    };
+    Player::builder(beatmap).seed(7).build().unwrap()
+}
+
+// Displayed column of every object
+fn columns(player: &Player) -> Vec<usize> {
+    let objects = player.hit_objects().iter();
+    objects.map(|h| column_for_x(common::x_of(h), 8)).collect()
+}
+
+#[test]
+fn special_lane_stays_in_place() {
+    let mut player = special_style_player(true);
+    let source = columns(&player);
+    player.set_special_lane_excluded(true);
+    assert_eq!(player.special_lane(), Some(0));
+    for arrangement in [
+        Arrangement::Random,
+        Arrangement::Cross,
+        Arrangement::Rotate(3),
+    ] {
+        player.set_arrangement(arrangement);
+        player.set_mirror(true);
+        for (from, to) in source.iter().zip(columns(&player)) {
+            assert_eq!(*from == 0, to == 0);
+        }
+    }
+
+    // Only maps with a special style have one
+    let mut plain = special_style_player(false);
+    plain.set_special_lane_excluded(true);
+    assert_eq!(plain.special_lane(), None);
+    plain.set_mirror(true);
+    assert_eq!(columns(&plain)[0], 7 - source[0]);
+}
+
+#[test]
+fn mirror_composes_with_the_arrangement() {
+    let mut player = special_style_player(false);
+    let source = columns(&player);
+    player.set_arrangement(Arrangement::SwapPairs);
+    player.set_mirror(true);
+    assert!(player.is_mirrored());
+    for (from, to) in source.iter().zip(columns(&player)) {
+        assert_eq!(to, 7 - (from ^ 1));
+    }
+    // Mirrored twice is the source
+    player.set_arrangement(Arrangement::Mirror);
+    assert_eq!(columns(&player), source);
+}
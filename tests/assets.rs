@@ -0,0 +1,67 @@
+mod common;
+
+use std::path::Path;
+
+use rosu_map::Beatmap;
+use rosu_renderer::assets::{AssetWarning, MapAssets};
+use rosu_renderer::Player;
+
+const MAP: &str = "tests/fixtures/case_mismatch/map.osu";
+
+#[test]
+fn names_are_matched_ignoring_case() {
+    let beatmap = Beatmap::from_path(MAP).unwrap();
+    let assets = MapAssets::resolve(MAP, &beatmap);
+    let folder = Path::new("tests/fixtures/case_mismatch");
+    let audio = folder.join("audio.mp3");
+    let background = folder.join("Backgrounds/bg.JPG");
+    assert_eq!(assets.audio.as_ref(), Some(&audio));
+    assert_eq!(assets.background.as_ref(), Some(&background));
+    assert_eq!(
+        assets.warnings,
+        [
+            AssetWarning::CaseMismatch {
+                name: "Audio.MP3".to_owned(),
+                found: audio,
+            },
+            AssetWarning::CaseMismatch {
+                name: "backgrounds/BG.jpg".to_owned(),
+                found: background,
+            },
+        ]
+    );
+}
+
+#[test]
+fn missing_files_are_reported() {
+    let mut beatmap = Beatmap::from_path(MAP).unwrap();
+    beatmap.audio_file = "song.ogg".to_owned();
+    beatmap.background_file = String::new();
+    let assets = MapAssets::resolve(MAP, &beatmap);
+    assert_eq!(assets.audio, None);
+    assert_eq!(assets.background, None);
+    let missing = AssetWarning::NotFound {
+        name: "song.ogg".to_owned(),
+    };
+    assert_eq!(assets.warnings, [missing]);
+}
+
+#[test]
+fn player_names_the_files_of_maps_loaded_from_bytes() {
+    let bytes = std::fs::read(MAP).unwrap();
+    let mut player = Player::from_bytes(&bytes, 100.0, 50.0, 600.0).unwrap();
+    assert!(player.assets().is_none());
+    assert_eq!(player.audio_filename(), Some(Path::new("Audio.MP3")));
+    let background = Path::new("backgrounds/BG.jpg");
+    assert_eq!(player.background_filename(), Some(background));
+
+    player.resolve_assets(MAP);
+    let audio = Path::new("tests/fixtures/case_mismatch/audio.mp3");
+    assert_eq!(player.audio_filename(), Some(audio));
+    assert_eq!(player.assets().unwrap().warnings.len(), 2);
+
+    let player = Player::from_path(MAP, 100.0, 50.0, 600.0).unwrap();
+    assert_eq!(player.audio_filename(), Some(audio));
+    let background = Path::new("tests/fixtures/case_mismatch/Backgrounds/bg.JPG");
+    assert_eq!(player.background_filename(), Some(background));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use rosu_renderer::report::LoadIssue;
+use rosu_renderer::Player;
+
+// format_v14.osu with a background, a video (not supported) and a
+// background sprite switching at 2 s
+fn osu_bytes() -> Vec<u8> {
+    let osu = std::fs::read_to_string("tests/fixtures/format_v14.osu").unwrap();
+    osu.replace(
+        "//Background and Video events",
+        "//Background and Video events\n0,0,\"bg.png\",0,0\nVideo,0,\"intro.mp4\"\n\
+         Sprite,Background,Centre,\"bg2.png\",320,240\n F,0,2000,2500,0,1",
+    )
+    .into_bytes()
+}
+
+fn events_ignored(player: &Player) -> Vec<usize> {
+    player
+        .load_report()
+        .issues()
+        .iter()
+        .filter_map(|issue| match issue {
+            LoadIssue::EventsIgnored { count } => Some(*count),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn set_beatmap_bytes_reads_the_events_again() {
+    let loaded = Player::from_bytes(&osu_bytes(), 100.0, 50.0, 600.0).unwrap();
+    let files: Vec<_> = loaded
+        .background_events()
+        .iter()
+        .map(|event| event.file.as_str())
+        .collect();
+    assert_eq!(files, ["bg.png", "bg2.png"]);
+    assert_eq!(events_ignored(&loaded), [1]);
+
+    // Another map first, so nothing is left over from the builder
+    let mut player = common::player(common::stream(10, 100.0, 4), 4);
+    assert!(player.background_events().is_empty());
+    assert!(player.set_beatmap_bytes(&osu_bytes()));
+    assert_eq!(player.background_events(), loaded.background_events());
+    assert_eq!(events_ignored(&player), [1]);
+}
+
+#[test]
+fn set_beatmap_clears_the_events() {
+    let mut player = Player::from_bytes(&osu_bytes(), 100.0, 50.0, 600.0).unwrap();
+    let beatmap = player.beatmap().clone();
+    assert!(player.set_beatmap(beatmap));
+    assert!(player.background_events().is_empty());
+    assert!(events_ignored(&player).is_empty());
+}
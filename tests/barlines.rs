@@ -0,0 +1,74 @@
+mod common;
+
+use rosu_map::section::general::GameMode;
+use rosu_map::section::timing_points::{TimeSignature, TimingPoint};
+use rosu_map::Beatmap;
+use rosu_renderer::layout::mania::BarlineStyle;
+use rosu_renderer::{Player, PlayerBuilder};
+
+// 3/4 measures of 750 ms from 0, then 4/4 measures of 800 ms from 2000
+// without their first line
+fn player() -> Player {
+    let mut beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 4.0,
+        hit_objects: common::stream(40, 150.0, 4),
+        ..Default::default()
+    };
+    let points = [
+        (0.0, 250.0, false, TimeSignature::new_simple_triple()),
+        (2000.0, 200.0, true, TimeSignature::new_simple_quadruple()),
+    ];
+    for (time, beat_len, omit_first, signature) in points {
+        beatmap
+            .control_points
+            .add(TimingPoint::new(time, beat_len, omit_first, signature));
+    }
+    PlayerBuilder::new(beatmap).build().unwrap()
+}
+
+// y of the lines described at the time of a frame rendered at `clock`, the
+// y of every measure start then, and of those well inside the stage
+fn barlines(player: &mut Player, clock: f64) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+    player.set_current_time(clock);
+    common::frame(&egui::Context::default(), |ui| player.render(ui));
+    let transform = player.frame_transform().unwrap();
+    let description = player.frame_description(transform.current_time);
+    let starts = [0.0, 750.0, 1500.0]
+        .into_iter()
+        .chain((1..10).map(|i| 2000.0 + f64::from(i) * 800.0));
+    let expected: Vec<_> = starts.map(|time| transform.y_for(time)).collect();
+    let (stage, line_y) = (description.stage, description.judgment_line_y);
+    let inside = expected
+        .iter()
+        .copied()
+        .filter(|y| *y > stage.min.y + 1.0 && *y < line_y - 1.0)
+        .collect();
+    (description.barlines, expected, inside)
+}
+
+#[test]
+fn barlines_are_off_by_default() {
+    let mut player = player();
+    assert_eq!(player.barlines(), None);
+    for clock in [1500.0, 3000.0] {
+        let (drawn, _, inside) = barlines(&mut player, clock);
+        assert!(drawn.is_empty());
+        assert!(!inside.is_empty());
+    }
+}
+
+#[test]
+fn barlines_follow_time_signatures() {
+    let mut player = player();
+    player.set_barlines(Some(BarlineStyle::default()));
+    let mut seen = 0;
+    for clock in (0..12).map(|i| 600.0 + f64::from(i) * 350.0) {
+        let (drawn, expected, inside) = barlines(&mut player, clock);
+        let near = |ys: &[f32], y: f32| ys.iter().any(|other| (other - y).abs() < 0.01);
+        assert!(inside.iter().all(|y| near(&drawn, *y)), "at {clock}");
+        assert!(drawn.iter().all(|y| near(&expected, *y)), "at {clock}");
+        seen += drawn.len();
+    }
+    assert!(seen >= 8);
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rosu_renderer::events::{CallbackKind, Event};
+use rosu_renderer::format::{Formatter, SharedFormatter};
+use rosu_renderer::images::{ImageProvider, LoadedTexture};
+use rosu_renderer::layout::mania::{Layer, NoteShape, NoteStyle, TimingTextStyle};
+use rosu_renderer::Player;
+
+fn player() -> Player {
+    let mut player = common::player(common::stream(16, 100.0, 4), 4);
+    player.set_current_time(2000.0);
+    player
+}
+
+fn shape_count(output: &egui::FullOutput) -> usize {
+    output.shapes.len()
+}
+
+fn panics(player: &mut Player) -> Vec<(CallbackKind, String)> {
+    player
+        .events()
+        .drain()
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::CallbackPanicked { which, message, .. } => Some((which, message)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn panicking_layer_hook_is_skipped_and_the_frame_completes() {
+    let ctx = egui::Context::default();
+    let mut baseline = player();
+    let expected = shape_count(&common::output(&ctx, |ui| baseline.render(ui)));
+
+    let mut player = player();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let hook_calls = calls.clone();
+    player.set_layer_hook(
+        Layer::StageBackground,
+        Arc::new(move |_, _| {
+            hook_calls.fetch_add(1, Ordering::Relaxed);
+            panic!("hook broke");
+        }),
+    );
+    // Everything after the hook, judgment line included, is still drawn
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert_eq!(shape_count(&output), expected);
+    assert_eq!(
+        panics(&mut player),
+        [(
+            CallbackKind::LayerHook(Layer::StageBackground),
+            "hook broke".to_owned()
+        )]
+    );
+
+    common::frame(&ctx, |ui| player.render(ui));
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    assert!(panics(&mut player).is_empty());
+}
+
+#[test]
+fn panicking_event_callback_is_disabled() {
+    let ctx = egui::Context::default();
+    let mut player = player();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let callback_calls = calls.clone();
+    player.events().on_event(Arc::new(|_| {}));
+    player.events().on_event(Arc::new(move |_| {
+        callback_calls.fetch_add(1, Ordering::Relaxed);
+        panic!("callback broke");
+    }));
+
+    player.report_ghost_tap(0, 1000.0);
+    player.report_ghost_tap(1, 1010.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    assert_eq!(
+        panics(&mut player),
+        [(CallbackKind::Event(1), "callback broke".to_owned())]
+    );
+}
+
+struct PanickingFormatter(Arc<AtomicUsize>);
+
+impl Formatter for PanickingFormatter {
+    fn number(&self, _: f64, _: usize) -> String {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        panic!("formatter broke");
+    }
+}
+
+#[test]
+fn panicking_formatter_falls_back_to_plain_numbers() {
+    let ctx = egui::Context::default();
+    let mut player = player();
+    let calls = Arc::new(AtomicUsize::new(0));
+    player.set_timing_text(Some(TimingTextStyle {
+        formatter: SharedFormatter::new(PanickingFormatter(calls.clone())),
+        ..Default::default()
+    }));
+
+    player.report_hit(0, -12.0, 1000.0);
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert!(common::texts(&output).contains(&"-12 ms".to_owned()));
+    assert_eq!(
+        panics(&mut player),
+        [(CallbackKind::Formatter, "formatter broke".to_owned())]
+    );
+
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert!(common::texts(&output).contains(&"-12 ms".to_owned()));
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    assert!(panics(&mut player).is_empty());
+}
+
+// Counts its calls, panicking on them if `panics`
+struct Images {
+    calls: Arc<AtomicUsize>,
+    panics: bool,
+}
+
+impl ImageProvider for Images {
+    fn resolve(&self, _: &egui::Context, _: &str) -> Option<LoadedTexture> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        assert!(!self.panics, "provider broke");
+        None
+    }
+}
+
+fn keyed_player(calls: &Arc<AtomicUsize>, panics: bool) -> Player {
+    let mut player = player();
+    player.set_note_style(NoteStyle {
+        shape: NoteShape::Keyed("note".to_owned()),
+        ..Default::default()
+    });
+    player.set_image_provider(Arc::new(Images {
+        calls: calls.clone(),
+        panics,
+    }));
+    player
+}
+
+#[test]
+fn panicking_image_provider_is_disabled() {
+    let ctx = egui::Context::default();
+    let missing = Arc::new(AtomicUsize::new(0));
+    let mut baseline = keyed_player(&missing, false);
+    let expected = shape_count(&common::output(&ctx, |ui| baseline.render(ui)));
+    assert!(missing.load(Ordering::Relaxed) > 1);
+
+    // Drawn as if every image were missing
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut player = keyed_player(&calls, true);
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert_eq!(shape_count(&output), expected);
+    assert_eq!(
+        panics(&mut player),
+        [(CallbackKind::ImageProvider, "provider broke".to_owned())]
+    );
+    common::frame(&ctx, |ui| player.render(ui));
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    assert!(panics(&mut player).is_empty());
+
+    // A new provider is called again
+    let replaced = Arc::new(AtomicUsize::new(0));
+    player.set_image_provider(Arc::new(Images {
+        calls: replaced.clone(),
+        panics: false,
+    }));
+    common::frame(&ctx, |ui| player.render(ui));
+    assert!(replaced.load(Ordering::Relaxed) > 0);
+}
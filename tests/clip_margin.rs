@@ -0,0 +1,108 @@
+mod common;
+
+use std::sync::Arc;
+
+use egui::{Color32, Margin, Rect};
+use rosu_renderer::layout::mania::{Layer, Letterbox};
+use rosu_renderer::Player;
+
+const GLOW: Color32 = Color32::from_rgb(255, 200, 40);
+const GLOW_RADIUS: f32 = 16.0;
+
+// A glow on the left edge of column 0
+fn glowing_player() -> Player {
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    player.set_layer_hook(
+        Layer::Effects,
+        Arc::new(|painter, context| {
+            let center = egui::pos2(context.stage_rect.left(), context.stage_rect.center().y);
+            painter.circle_filled(center, GLOW_RADIUS, GLOW);
+        }),
+    );
+    player.set_current_time(1500.0);
+    player
+}
+
+// Clip rect of the glow, and the glow itself
+fn glow(output: &egui::FullOutput) -> (Rect, Rect) {
+    output
+        .shapes
+        .iter()
+        .find_map(|clipped| match &clipped.shape {
+            egui::Shape::Circle(circle) if circle.fill == GLOW => {
+                Some((clipped.clip_rect, circle.visual_bounding_rect()))
+            }
+            _ => None,
+        })
+        .expect("the glow is drawn")
+}
+
+// Renders into a rect exactly the size of the playfield
+fn glow_in(player: &mut Player) -> (Rect, Rect, Rect) {
+    let ctx = egui::Context::default();
+    let mut rect = Rect::NOTHING;
+    common::frame(&ctx, |ui| {
+        rect = player.render_in(
+            ui,
+            Rect::from_min_size(egui::pos2(100.0, 0.0), ui.max_rect().size()),
+        );
+    });
+    let output = common::output(&ctx, |ui| {
+        player.render_in(ui, rect);
+    });
+    let (clip, glow) = glow(&output);
+    (rect, clip, glow)
+}
+
+#[test]
+fn edge_glows_are_cut_without_a_margin() {
+    let (rect, clip, glow) = glow_in(&mut glowing_player());
+    assert_eq!(clip, rect);
+    assert!(glow.left() < clip.left());
+}
+
+#[test]
+fn clip_margin_keeps_edge_glows_whole() {
+    let mut player = glowing_player();
+    player.set_clip_margin(Margin::same(20));
+    let (rect, clip, glow) = glow_in(&mut player);
+    assert_eq!(clip, rect + Margin::same(20));
+    assert!(clip.contains_rect(glow), "{glow:?} cut by {clip:?}");
+}
+
+#[test]
+fn clip_margin_grows_the_ui_clip_for_render_at() {
+    for margin in [0, 20] {
+        let mut player = glowing_player();
+        player.set_clip_margin(Margin::same(margin));
+        let ctx = egui::Context::default();
+        let stage = Rect::from_min_size(egui::pos2(100.0, 0.0), egui::vec2(400.0, 960.0));
+        let output = common::output(&ctx, |ui| {
+            ui.set_clip_rect(stage);
+            player.render_at(ui, stage.min);
+        });
+        let (clip, glow) = glow(&output);
+        assert_eq!(clip, stage + Margin::same(margin));
+        assert_eq!(clip.contains_rect(glow), margin > 0);
+    }
+}
+
+#[test]
+fn letterbox_stays_inside_the_rect() {
+    let mut player = glowing_player();
+    player.set_clip_margin(Margin::same(20));
+    player.set_letterbox(Some(Letterbox::Color(Color32::DARK_BLUE)));
+    let ctx = egui::Context::default();
+    let rect = Rect::from_min_size(egui::pos2(100.0, 100.0), egui::vec2(800.0, 600.0));
+    let output = common::output(&ctx, |ui| {
+        player.render_in(ui, rect);
+    });
+    let letterbox = output
+        .shapes
+        .iter()
+        .find_map(|clipped| match &clipped.shape {
+            egui::Shape::Rect(shape) if shape.fill == Color32::DARK_BLUE => Some(shape.rect),
+            _ => None,
+        });
+    assert_eq!(letterbox, Some(rect));
+}
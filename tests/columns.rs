@@ -0,0 +1,130 @@
+mod common;
+
+use rosu_renderer::layout::mania::{
+    column_center_x, column_for_x, column_stretch, FitPolicy, ManiaRenderer, MAX_KEYCOUNT,
+};
+use rosu_renderer::report::LoadIssue;
+use rosu_renderer::transform::Arrangement;
+use rosu_renderer::PlayerBuilder;
+
+// x values written by the osu! editor, as found in ranked 4K and 7K maps
+const CENTERS_4K: [f32; 4] = [64.0, 192.0, 320.0, 448.0];
+const CENTERS_7K: [f32; 7] = [36.0, 109.0, 182.0, 256.0, 329.0, 402.0, 475.0];
+
+#[test]
+fn canonical_centers_map_to_their_column() {
+    for (column, x) in CENTERS_4K.into_iter().enumerate() {
+        assert_eq!(column_for_x(x, 4), column);
+        assert_eq!(column_center_x(column, 4), x);
+    }
+    for (column, x) in CENTERS_7K.into_iter().enumerate() {
+        assert_eq!(column_for_x(x, 7), column);
+        assert_eq!(column_center_x(column, 7), x);
+    }
+}
+
+#[test]
+fn off_center_x_is_floored_and_clamped() {
+    assert_eq!(column_for_x(127.9, 4), 0);
+    assert_eq!(column_for_x(128.0, 4), 1);
+    assert_eq!(column_for_x(512.0, 4), 3);
+    assert_eq!(column_for_x(1000.0, 7), 6);
+    assert_eq!(column_for_x(-20.0, 7), 0);
+    assert_eq!(column_for_x(f32::NAN, 7), 0);
+}
+
+#[test]
+fn fixtures_fill_every_column() {
+    for (path, keycount) in [
+        ("tests/fixtures/columns_4k.osu", 4),
+        ("tests/fixtures/columns_7k.osu", 7),
+    ] {
+        let player = PlayerBuilder::from_path(path)
+            .unwrap()
+            .strict_columns(true)
+            .build()
+            .unwrap();
+        assert_eq!(player.column_counts().len(), keycount);
+        for column in player.column_counts() {
+            assert_eq!((column.notes, column.holds), (1, 1));
+        }
+        assert!(player.load_report().is_empty(), "{path}");
+    }
+}
+
+#[test]
+fn strict_columns_reports_off_center_objects() {
+    let mut hit_objects = common::stream(4, 100.0, 4);
+    if let rosu_map::section::hit_objects::HitObjectKind::Circle(circle) = &mut hit_objects[1].kind
+    {
+        circle.pos.x = 100.0;
+    }
+    let player = common::builder(hit_objects, 4)
+        .strict_columns(true)
+        .build()
+        .unwrap();
+    let issues = player.load_report().issues();
+    assert!(matches!(
+        issues,
+        [LoadIssue::OffCenterColumn {
+            index: 1,
+            column: 0,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn tiny_keycounts_widen_their_columns() {
+    assert_eq!(column_stretch(1), 2.0);
+    assert_eq!(column_stretch(2), 2.0);
+    assert_eq!(column_stretch(3), 4.0 / 3.0);
+    assert_eq!(column_stretch(4), 1.0);
+    assert_eq!(column_stretch(MAX_KEYCOUNT), 1.0);
+
+    let mania = ManiaRenderer::with_sizes(80.0, 60.0, 800.0);
+    let padding = mania.required_width(0);
+    let stage = |keycount| mania.required_width(keycount) - padding;
+    assert_eq!(stage(1), 160.0);
+    assert_eq!(stage(2), 320.0);
+    assert_eq!(stage(3), 320.0);
+    assert_eq!(stage(4), 320.0);
+    assert_eq!(stage(7), 560.0);
+}
+
+#[test]
+fn one_key_maps_everything_to_column_zero() {
+    for x in [-10.0, 0.0, 255.9, 256.0, 511.9, 512.0, 1000.0, f32::NAN] {
+        assert_eq!(column_for_x(x, 1), 0, "{x}");
+    }
+    assert_eq!(column_center_x(0, 1), 256.0);
+}
+
+#[test]
+fn mirror_is_a_no_op_for_one_key() {
+    let mut player = common::player(common::stream(12, 100.0, 1), 1);
+    let before: Vec<f32> = player.hit_objects().iter().map(common::x_of).collect();
+    player.set_arrangement(Arrangement::Mirror);
+    let after: Vec<f32> = player.hit_objects().iter().map(common::x_of).collect();
+    assert_eq!(before, after);
+    assert!(player.load_report().is_empty());
+}
+
+#[test]
+fn every_keycount_fits_the_playfield() {
+    let ctx = egui::Context::default();
+    for keycount in 1..=MAX_KEYCOUNT {
+        let mut player = common::player(common::stream(40, 50.0, keycount), keycount);
+        player.set_fit_policy(FitPolicy::ScaleToFit);
+        player.set_current_time(1500.0);
+        let area = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(640.0, 480.0));
+        let mut playfield = egui::Rect::NOTHING;
+        common::frame(&ctx, |ui| playfield = player.render_in(ui, area));
+        assert!(playfield.width() > 0.0, "{keycount}K");
+        assert!(
+            area.expand(0.01).contains_rect(playfield),
+            "{keycount}K: {playfield:?}"
+        );
+        assert!(player.frame_stats().visible_notes > 0, "{keycount}K");
+    }
+}
@@ -0,0 +1,106 @@
+// Shared by the integration tests; each one uses a different part
+#![allow(dead_code)]
+
+use rosu_map::section::general::GameMode;
+use rosu_map::section::hit_objects::{HitObject, HitObjectCircle, HitObjectHold, HitObjectKind};
+use rosu_map::util::Pos;
+use rosu_renderer::{Player, PlayerBuilder};
+
+/// osu! x of the center of `column`.
+pub fn column_x(column: usize, keycount: usize) -> f32 {
+    (column as f32 + 0.5) * 512.0 / keycount as f32
+}
+
+pub fn x_of(hit_object: &HitObject) -> f32 {
+    match &hit_object.kind {
+        HitObjectKind::Circle(circle) => circle.pos.x,
+        HitObjectKind::Hold(hold) => hold.pos_x,
+        _ => f32::NAN,
+    }
+}
+
+pub fn note(time: f64, column: usize, keycount: usize) -> HitObject {
+    HitObject {
+        start_time: time,
+        kind: HitObjectKind::Circle(HitObjectCircle {
+            pos: Pos {
+                x: column_x(column, keycount),
+                y: 192.0,
+            },
+            new_combo: false,
+            combo_offset: 0,
+        }),
+        samples: Vec::new(),
+    }
+}
+
+pub fn hold(time: f64, duration: f64, column: usize, keycount: usize) -> HitObject {
+    HitObject {
+        start_time: time,
+        kind: HitObjectKind::Hold(HitObjectHold {
+            pos_x: column_x(column, keycount),
+            duration,
+        }),
+        samples: Vec::new(),
+    }
+}
+
+/// `count` notes `gap_ms` apart cycling through the columns, every third
+/// one a hold.
+pub fn stream(count: usize, gap_ms: f64, keycount: usize) -> Vec<HitObject> {
+    (0..count)
+        .map(|i| {
+            let time = 1000.0 + i as f64 * gap_ms;
+            let column = (i * 3 + i / keycount) % keycount;
+            if i % 3 == 0 {
+                hold(time, gap_ms * 2.5, column, keycount)
+            } else {
+                note(time, column, keycount)
+            }
+        })
+        .collect()
+}
+
+pub fn builder(hit_objects: Vec<HitObject>, keycount: usize) -> PlayerBuilder {
+    PlayerBuilder::from_hit_objects(GameMode::Mania, hit_objects, keycount as f32).seed(7)
+}
+
+pub fn player(hit_objects: Vec<HitObject>, keycount: usize) -> Player {
+    builder(hit_objects, keycount).build().unwrap()
+}
+
+/// Runs `f` inside a headless egui frame.
+pub fn frame(ctx: &egui::Context, f: impl FnMut(&mut egui::Ui)) {
+    let _ = output(ctx, f);
+}
+
+/// `frame`, returning what egui produced.
+pub fn output(ctx: &egui::Context, f: impl FnMut(&mut egui::Ui)) -> egui::FullOutput {
+    let mut f = f;
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(1280.0, 960.0),
+        )),
+        ..Default::default()
+    };
+    ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| f(ui));
+    })
+}
+
+/// Every text drawn by a frame, in paint order.
+pub fn texts(output: &egui::FullOutput) -> Vec<String> {
+    fn collect(shape: &egui::Shape, texts: &mut Vec<String>) {
+        match shape {
+            egui::Shape::Text(text) => texts.push(text.galley.text().to_owned()),
+            egui::Shape::Vec(shapes) => shapes.iter().for_each(|s| collect(s, texts)),
+            _ => {}
+        }
+    }
+    let mut texts = Vec::new();
+    for clipped in &output.shapes {
+        collect(&clipped.shape, &mut texts);
+    }
+    texts
+}
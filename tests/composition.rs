@@ -0,0 +1,62 @@
+mod common;
+
+use rosu_renderer::stats::{Composition, LnPercent};
+use rosu_renderer::PlayerBuilder;
+
+// 4 notes, then 4 holds of 200 ms from 2000 to 2950
+const FIXTURE: &str = "tests/fixtures/columns_4k.osu";
+
+#[test]
+fn fixture_counts() {
+    let mut player = PlayerBuilder::from_path(FIXTURE).unwrap().build().unwrap();
+    let expected = Composition {
+        rice: 4,
+        holds: 4,
+        ln_percent: 50.0,
+        avg_ln_length_ms: 200.0,
+        longest_ln_ms: 200.0,
+        max_chord_size: 1,
+    };
+    assert_eq!(player.composition(), expected);
+
+    // Held for 800 of the 1950 ms from the first note to the last tail
+    player.set_ln_percent(LnPercent::Duration);
+    let composition = player.composition();
+    assert!((composition.ln_percent - 800.0 / 1950.0 * 100.0).abs() < 1e-9);
+    assert_eq!((composition.rice, composition.holds), (4, 4));
+}
+
+#[test]
+fn chords_count_held_notes() {
+    let hit_objects = vec![
+        common::hold(1000.0, 500.0, 0, 4),
+        common::hold(1100.0, 200.0, 1, 4),
+        common::note(1200.0, 2, 4),
+        common::note(1200.0, 3, 4),
+        common::note(2000.0, 1, 4),
+    ];
+    let composition = common::player(hit_objects, 4).composition();
+    assert_eq!((composition.rice, composition.holds), (3, 2));
+    assert_eq!(composition.ln_percent, 40.0);
+    assert_eq!(composition.avg_ln_length_ms, 350.0);
+    assert_eq!(composition.longest_ln_ms, 500.0);
+    // Both holds held while the two notes land
+    assert_eq!(composition.max_chord_size, 4);
+}
+
+#[test]
+fn charts_without_holds_have_no_ln() {
+    // Every hold turned into a note, as NoLN does
+    let mut player = PlayerBuilder::from_path(FIXTURE)
+        .unwrap()
+        .hold_epsilon(f64::INFINITY)
+        .build()
+        .unwrap();
+    for mode in [LnPercent::Count, LnPercent::Duration] {
+        player.set_ln_percent(mode);
+        let composition = player.composition();
+        assert_eq!((composition.rice, composition.holds), (8, 0), "{mode:?}");
+        assert_eq!(composition.ln_percent, 0.0, "{mode:?}");
+        assert_eq!(composition.longest_ln_ms, 0.0, "{mode:?}");
+    }
+}
@@ -0,0 +1,37 @@
+mod common;
+
+fn player() -> rosu_renderer::Player {
+    common::player(
+        vec![
+            common::note(1000.0, 0, 4),
+            common::note(2000.0, 1, 4),
+            common::hold(4000.0, 500.0, 2, 4),
+        ],
+        4,
+    )
+}
+
+#[test]
+fn crossing_follows_speed_and_scroll_time() {
+    let mut player = player();
+    assert_eq!(player.line_crossing_time(1), Some(3000.0));
+
+    player.set_speed(2.0);
+    assert_eq!(player.line_crossing_time(1), Some(2000.0));
+
+    player.set_scroll_time(500.0);
+    assert_eq!(player.line_crossing_time(1), Some(1500.0));
+    assert_eq!(player.line_crossing_time(3), None);
+}
+
+#[test]
+fn crossings_in_range() {
+    let mut player = player();
+    assert_eq!(
+        player.line_crossings(1500.0..5500.0),
+        [(0, 2000.0), (1, 3000.0), (2, 5000.0)]
+    );
+
+    player.set_speed(0.5);
+    assert_eq!(player.line_crossings(0.0..5000.0), [(0, 3000.0)]);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use rosu_renderer::events::Event;
+use rosu_renderer::sections::EndBehavior;
+use rosu_renderer::Player;
+
+fn player(behavior: EndBehavior) -> Player {
+    let mut player = common::player(common::stream(10, 50.0, 4), 4);
+    player.set_end_behavior(behavior);
+    player
+}
+
+#[test]
+fn stops_one_scroll_time_after_the_map_by_default() {
+    let mut player = player(EndBehavior::default());
+    let end = player.duration() + 1000.0;
+    assert_eq!(player.end_time(), Some(end));
+
+    player.set_current_time(end - 50.0);
+    assert!(!player.is_finished());
+    assert!(player.current_time() < end);
+
+    // The clock holds at the end instead of snapping back
+    player.set_current_time(end + 500.0);
+    assert!(player.is_finished());
+    assert_eq!(player.current_time(), end);
+    sleep(Duration::from_millis(20));
+    assert_eq!(player.current_time(), end);
+
+    // Seeking back resumes
+    player.set_current_time(end - 50.0);
+    assert!(!player.is_finished());
+}
+
+#[test]
+fn stop_at_uses_its_own_tail() {
+    let mut player = player(EndBehavior::StopAt { tail_ms: Some(0.0) });
+    let end = player.duration();
+    assert_eq!(player.end_time(), Some(end));
+    player.set_current_time(end + 10.0);
+    assert!(player.is_finished());
+    assert_eq!(player.current_time(), end);
+
+    // An invalid tail falls back to one scroll time
+    player.set_end_behavior(EndBehavior::StopAt {
+        tail_ms: Some(f64::NAN),
+    });
+    assert_eq!(player.end_time(), Some(end + 1000.0));
+}
+
+#[test]
+fn freeze_last_frame_stops_at_the_map_end() {
+    let mut player = player(EndBehavior::FreezeLastFrame);
+    let end = player.duration();
+    assert_eq!(player.end_time(), Some(end));
+    player.set_current_time(end - 10.0);
+    assert!(!player.is_finished());
+    player.set_current_time(end + 10.0);
+    assert!(player.is_finished());
+    assert_eq!(player.current_time(), end);
+}
+
+#[test]
+fn run_forever_keeps_the_clock_going() {
+    let mut player = player(EndBehavior::RunForever);
+    let duration = player.duration();
+    assert_eq!(player.end_time(), None);
+    player.set_current_time(duration - 10.0);
+    assert!(!player.is_finished());
+    player.set_current_time(duration + 5000.0);
+    assert!(player.is_finished());
+    assert!(player.current_time() >= duration + 5000.0);
+}
+
+#[test]
+fn loop_wraps_on_the_boundary_frame() {
+    let mut player = player(EndBehavior::Loop);
+    let end = player.duration();
+    assert_eq!(player.end_time(), Some(end));
+    let ctx = egui::Context::default();
+
+    // egui's first frame is slow, keep it away from the boundary
+    common::frame(&ctx, |ui| player.render(ui));
+    player.set_current_time(end - 30.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    player.events().drain();
+    sleep(Duration::from_millis(60));
+    assert!(!player.is_finished());
+    assert!(player.current_time() < end - 30.0);
+
+    common::frame(&ctx, |ui| player.render(ui));
+    let wrapped = player
+        .events()
+        .drain()
+        .into_iter()
+        .filter(|event| matches!(event, Event::LoopWrapped { .. }))
+        .count();
+    assert_eq!(wrapped, 1);
+    assert!(player.current_time() < end - 30.0);
+}
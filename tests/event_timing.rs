@@ -0,0 +1,163 @@
+mod common;
+
+use rosu_renderer::difficulty::DifficultyOverride;
+use rosu_renderer::events::{EventTiming, GraceSource, PassedKind, PassedNote};
+use rosu_renderer::judge::windows::WindowMode;
+use rosu_renderer::sections::EndBehavior;
+use rosu_renderer::Player;
+use std::thread::sleep;
+use std::time::Duration;
+
+// Clock time putting map time `line_time` on the judgment line, at the
+// default speed and scroll time
+fn clock(line_time: f64) -> f64 {
+    line_time + 1000.0
+}
+
+fn tails(notes: &[PassedNote]) -> Vec<PassedNote> {
+    notes
+        .iter()
+        .filter(|note| note.kind == PassedKind::HoldTail)
+        .copied()
+        .collect()
+}
+
+fn render_at(player: &mut Player, ctx: &egui::Context, clock_ms: f64) -> Vec<PassedNote> {
+    player.set_current_time(clock_ms);
+    common::frame(ctx, |ui| player.render(ui));
+    player.drain_passed_notes().notes
+}
+
+// The release miss window, so the tail passes that long after 2000
+const RELEASE_MISS: [(WindowMode, f32, f64); 9] = [
+    (WindowMode::ScoreV1, 0.0, 282.75),
+    (WindowMode::ScoreV1, 5.0, 260.25),
+    (WindowMode::ScoreV1, 8.0, 246.75),
+    (WindowMode::ScoreV1, 10.0, 237.75),
+    (WindowMode::ScoreV2, 0.0, 188.5),
+    (WindowMode::ScoreV2, 8.0, 164.5),
+    (WindowMode::Lazer, 0.0, 282.0),
+    (WindowMode::Lazer, 5.0, 259.5),
+    (WindowMode::Lazer, 10.0, 237.0),
+];
+
+#[test]
+fn tail_waits_for_the_release_window() {
+    let ctx = egui::Context::default();
+    for (mode, od, miss) in RELEASE_MISS {
+        let mut player = common::player(vec![common::hold(1000.0, 1000.0, 0, 4)], 4);
+        player.set_difficulty_override(DifficultyOverride {
+            od: Some(od),
+            ..Default::default()
+        });
+        player.set_window_mode(mode);
+        player.set_event_timing(EventTiming {
+            tail_grace: GraceSource::ReleaseWindow,
+        });
+        render_at(&mut player, &ctx, clock(1500.0));
+
+        let early = render_at(&mut player, &ctx, clock(2000.0 + miss - 5.0));
+        assert!(tails(&early).is_empty(), "{mode:?} OD{od}: {early:?}");
+        let late = render_at(&mut player, &ctx, clock(2000.0 + miss + 0.5));
+        let late = tails(&late);
+        assert_eq!(late.len(), 1, "{mode:?} OD{od}");
+        assert_eq!(late[0].time_ms, 2000.0);
+    }
+}
+
+#[test]
+fn fixed_and_no_grace() {
+    let ctx = egui::Context::default();
+    for (grace, delay) in [(GraceSource::None, 0.0), (GraceSource::Fixed(100.0), 100.0)] {
+        let mut player = common::player(vec![common::hold(1000.0, 1000.0, 0, 4)], 4);
+        player.set_event_timing(EventTiming { tail_grace: grace });
+        render_at(&mut player, &ctx, clock(1500.0));
+
+        let early = render_at(&mut player, &ctx, clock(2000.0 + delay - 5.0));
+        assert!(tails(&early).is_empty(), "{grace:?}");
+        let late = render_at(&mut player, &ctx, clock(2000.0 + delay + 0.5));
+        assert_eq!(tails(&late).len(), 1, "{grace:?}");
+    }
+}
+
+#[test]
+fn growing_grace_never_repeats_a_tail() {
+    let ctx = egui::Context::default();
+    let hit_objects = common::stream(30, 50.0, 4);
+    let holds = hit_objects.len().div_ceil(3);
+    let mut player = common::player(hit_objects, 4);
+    player.set_difficulty_override(DifficultyOverride {
+        od: Some(5.0),
+        ..Default::default()
+    });
+    render_at(&mut player, &ctx, clock(900.0));
+
+    let mut passed = Vec::new();
+    for line in (1000..=3000).step_by(100) {
+        match line {
+            1200 => player.set_event_timing(EventTiming {
+                tail_grace: GraceSource::Fixed(25.0),
+            }),
+            // Moves the tail time back over the tail at 1575
+            1700 => {
+                player.set_window_mode(WindowMode::ScoreV2);
+                player.set_event_timing(EventTiming {
+                    tail_grace: GraceSource::ReleaseWindow,
+                });
+            }
+            // Releases get more leniency
+            2300 => player.set_window_mode(WindowMode::ScoreV1),
+            _ => {}
+        }
+        passed.extend(tails(&render_at(&mut player, &ctx, clock(line as f64))));
+    }
+    let mut indexes: Vec<_> = passed.iter().map(|note| note.note_index).collect();
+    indexes.dedup();
+    assert_eq!(indexes.len(), passed.len());
+    assert_eq!(passed.len(), holds);
+}
+
+fn ending_player(behavior: EndBehavior) -> Player {
+    // A grace longer than the map, so the last tail never runs out of it
+    let mut player = common::player(
+        vec![
+            common::hold(1000.0, 500.0, 0, 4),
+            common::note(1200.0, 1, 4),
+        ],
+        4,
+    );
+    player.set_end_behavior(behavior);
+    player.set_event_timing(EventTiming {
+        tail_grace: GraceSource::Fixed(60_000.0),
+    });
+    player
+}
+
+#[test]
+fn tails_are_flushed_when_the_map_stops() {
+    let ctx = egui::Context::default();
+    let mut player = ending_player(EndBehavior::default());
+    let end = player.end_time().unwrap();
+    render_at(&mut player, &ctx, clock(1100.0));
+
+    let passed = render_at(&mut player, &ctx, end + 100.0);
+    assert!(player.is_finished());
+    assert_eq!(tails(&passed).len(), 1);
+    assert!(tails(&render_at(&mut player, &ctx, end + 200.0)).is_empty());
+}
+
+#[test]
+fn tails_are_flushed_before_a_loop_wraps() {
+    let ctx = egui::Context::default();
+    let mut player = ending_player(EndBehavior::Loop);
+    let end = player.end_time().unwrap();
+    // egui's first frame is slow, keep it away from the boundary
+    render_at(&mut player, &ctx, clock(1100.0));
+
+    let mut passed = render_at(&mut player, &ctx, end - 30.0);
+    sleep(Duration::from_millis(60));
+    common::frame(&ctx, |ui| player.render(ui));
+    passed.extend(player.drain_passed_notes().notes);
+    assert!(player.current_time() < end - 30.0);
+    assert_eq!(tails(&passed).len(), 1);
+}
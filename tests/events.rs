@@ -0,0 +1,167 @@
+mod common;
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+use rosu_renderer::events::{Event, PassedKind, PassedNote, MAX_PASSED_NOTES, MAX_QUEUED_EVENTS};
+use rosu_renderer::sections::EndBehavior;
+use rosu_renderer::Player;
+
+// Clock time putting map time `line_time` on the judgment line, at the
+// default speed and scroll time
+fn clock(line_time: f64) -> f64 {
+    line_time + 1000.0
+}
+
+// Renders a frame with the line at `line_time`, returning what it emitted
+fn frame_at(player: &mut Player, ctx: &egui::Context, line_time: f64) -> Vec<Event> {
+    player.set_current_time(clock(line_time));
+    common::frame(ctx, |ui| player.render(ui));
+    player.events().drain()
+}
+
+fn passed(events: &[Event]) -> Vec<PassedNote> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::NotePassed(note) | Event::HoldHead(note) | Event::HoldTail(note) => Some(*note),
+            _ => None,
+        })
+        .collect()
+}
+
+// Every head and tail in `(from, to]`, as (note index, kind)
+fn expected(hit_objects: &[HitObject], from: f64, to: f64) -> Vec<(usize, PassedKind)> {
+    let mut expected = Vec::new();
+    for (index, hit_object) in hit_objects.iter().enumerate() {
+        let (head, tail) = match &hit_object.kind {
+            HitObjectKind::Hold(h) => (
+                PassedKind::HoldHead,
+                Some(hit_object.start_time + h.duration),
+            ),
+            _ => (PassedKind::Note, None),
+        };
+        if from < hit_object.start_time && hit_object.start_time <= to {
+            expected.push((index, head));
+        }
+        if tail.is_some_and(|tail| from < tail && tail <= to) {
+            expected.push((index, PassedKind::HoldTail));
+        }
+    }
+    expected.sort_by_key(|(index, kind)| (*index, *kind == PassedKind::HoldTail));
+    expected
+}
+
+fn emitted(notes: &[PassedNote]) -> Vec<(usize, PassedKind)> {
+    let mut emitted: Vec<_> = notes.iter().map(|n| (n.note_index, n.kind)).collect();
+    emitted.sort_by_key(|(index, kind)| (*index, *kind == PassedKind::HoldTail));
+    emitted
+}
+
+#[test]
+fn every_note_passes_once_across_forward_seeks() {
+    let hit_objects = common::stream(40, 50.0, 4);
+    let mut player = common::player(hit_objects.clone(), 4);
+    let ctx = egui::Context::default();
+
+    let mut events = frame_at(&mut player, &ctx, 900.0);
+    for step in 1..=6 {
+        events.extend(frame_at(&mut player, &ctx, 900.0 + 50.0 * step as f64));
+    }
+    // Skips a second, the next frame passes what was skipped
+    for step in 0..=10 {
+        events.extend(frame_at(&mut player, &ctx, 2200.0 + 30.0 * step as f64));
+    }
+    let notes = passed(&events);
+    assert_eq!(emitted(&notes), expected(&hit_objects, 900.0, 2500.0));
+    assert!(notes
+        .windows(2)
+        .all(|pair| pair[0].time_ms <= pair[1].time_ms));
+}
+
+#[test]
+fn seeking_back_passes_the_replayed_notes_once_more() {
+    let hit_objects = common::stream(40, 50.0, 4);
+    let mut player = common::player(hit_objects.clone(), 4);
+    let ctx = egui::Context::default();
+    frame_at(&mut player, &ctx, 900.0);
+    frame_at(&mut player, &ctx, 3500.0);
+
+    // Back before notes that already passed, then over them again
+    let mut events = frame_at(&mut player, &ctx, 1500.0);
+    assert!(passed(&events).is_empty());
+    for step in 1..=20 {
+        events.extend(frame_at(&mut player, &ctx, 1500.0 + 100.0 * step as f64));
+    }
+    assert_eq!(
+        emitted(&passed(&events)),
+        expected(&hit_objects, 1500.0, 3500.0)
+    );
+}
+
+#[test]
+fn every_note_passes_once_per_loop() {
+    // Starts right away so a loop takes a few hundred ms of clock time
+    let hit_objects: Vec<_> = common::stream(12, 20.0, 4)
+        .into_iter()
+        .map(|mut hit_object| {
+            hit_object.start_time -= 1000.0;
+            hit_object
+        })
+        .collect();
+    let mut player = common::player(hit_objects.clone(), 4);
+    player.set_end_behavior(EndBehavior::Loop);
+    player.set_scroll_time(100.0);
+    let ctx = egui::Context::default();
+    common::frame(&ctx, |ui| player.render(ui));
+    player.set_current_time(0.0);
+    player.events().drain();
+
+    // Events of each loop, split at the wraps
+    let mut loops = vec![Vec::new()];
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while loops.len() < 3 && Instant::now() < deadline {
+        common::frame(&ctx, |ui| player.render(ui));
+        for event in player.events().drain() {
+            match event {
+                Event::LoopWrapped { .. } => loops.push(Vec::new()),
+                event => loops.last_mut().unwrap().push(event),
+            }
+        }
+        sleep(Duration::from_millis(2));
+    }
+    assert_eq!(loops.len(), 3, "looped twice");
+
+    let all = expected(&hit_objects, f64::NEG_INFINITY, f64::INFINITY);
+    for events in &loops[..2] {
+        assert_eq!(emitted(&passed(events)), all);
+    }
+}
+
+#[test]
+fn each_kind_of_event_has_its_own_cap() {
+    let mut player = common::player(common::stream(5000, 10.0, 7), 7);
+    let ctx = egui::Context::default();
+    frame_at(&mut player, &ctx, 0.0);
+
+    // The jump fills the notes' cap, ghost taps still queue up to theirs
+    player.set_current_time(clock(100_000.0));
+    common::frame(&ctx, |ui| player.render(ui));
+    for tap in 0..MAX_QUEUED_EVENTS + 10 {
+        player.report_ghost_tap(tap % 7, 1000.0);
+    }
+    assert!(player.events().truncated());
+    let events = player.events().drain();
+    assert_eq!(passed(&events).len(), MAX_PASSED_NOTES);
+    let taps = events
+        .iter()
+        .filter(|event| matches!(event, Event::GhostTap { .. }))
+        .count();
+    assert_eq!(taps, MAX_QUEUED_EVENTS);
+    assert!(!player.events().truncated());
+
+    // Draining the notes makes room for more of them only
+    player.report_ghost_tap(0, 1000.0);
+    assert_eq!(player.events().len(), 1);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use rosu_map::section::hit_objects::HitObject;
+use rosu_renderer::rng::SeededRng;
+
+// Beat-snapped chart of `count` onsets, some of them chords
+fn chart(seed: u64, count: usize) -> Vec<HitObject> {
+    let mut rng = SeededRng::new(seed);
+    let mut time = 1000.0;
+    let mut hit_objects = Vec::new();
+    for _ in 0..count {
+        time += [100.0, 150.0, 200.0, 300.0][rng.below(4) as usize];
+        let column = rng.below(4) as usize;
+        hit_objects.push(common::note(time, column, 4));
+        if rng.below(5) == 0 {
+            hit_objects.push(common::note(time, (column + 2) % 4, 4));
+        }
+    }
+    hit_objects
+}
+
+// Object times as a re-upload at `rate` would write them, in whole ms
+fn rated(hit_objects: &[HitObject], rate: f64, offset: f64) -> Vec<HitObject> {
+    hit_objects
+        .iter()
+        .map(|h| HitObject {
+            start_time: (h.start_time / rate + offset).round(),
+            ..h.clone()
+        })
+        .collect()
+}
+
+fn similarity(a: Vec<HitObject>, b: Vec<HitObject>) -> f32 {
+    let a = common::player(a, 4).fingerprint();
+    let b = common::player(b, 4).fingerprint();
+    let similarity = a.similarity(&b);
+    assert_eq!(similarity, b.similarity(&a));
+    similarity
+}
+
+#[test]
+fn rate_changes_and_offsets_keep_the_fingerprint() {
+    let original = chart(1, 400);
+    assert_eq!(similarity(original.clone(), original.clone()), 1.0);
+    let sped_up = similarity(original.clone(), rated(&original, 1.1, 0.0));
+    assert!(sped_up > 0.95, "{sped_up}");
+    let shifted = similarity(original.clone(), rated(&original, 1.0, 37.0));
+    assert!(shifted > 0.95, "{shifted}");
+    let both = similarity(original.clone(), rated(&original, 0.75, -200.0));
+    assert!(both > 0.95, "{both}");
+}
+
+#[test]
+fn unrelated_charts_differ() {
+    for seed in 2..6 {
+        let score = similarity(chart(1, 400), chart(seed, 400));
+        assert!(score < 0.3, "seed {seed}: {score}");
+    }
+}
+
+#[test]
+fn cuts_score_about_their_share() {
+    let full = chart(1, 400);
+    let half = full[..full.len() / 2].to_vec();
+    let score = similarity(full, half);
+    assert!((0.3..0.7).contains(&score), "{score}");
+}
+
+#[test]
+fn short_charts_only_match_each_other() {
+    let short = chart(1, 3);
+    assert!(common::player(short.clone(), 4)
+        .fingerprint()
+        .signature()
+        .is_empty());
+    assert_eq!(similarity(short.clone(), chart(2, 3)), 1.0);
+    assert_eq!(similarity(short, chart(1, 400)), 0.0);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use rosu_renderer::format::{Formatter, SharedFormatter};
+use rosu_renderer::layout::mania::{CompositionTextStyle, TimingTextStyle};
+
+// "1.234,56": dots group thousands, a comma separates decimals
+struct German;
+
+impl Formatter for German {
+    fn number(&self, value: f64, decimals: usize) -> String {
+        let plain = format!("{value:.decimals$}");
+        let (whole, fraction) = plain.split_once('.').unwrap_or((&plain, ""));
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push('.');
+            }
+            grouped.push(digit);
+        }
+        if fraction.is_empty() {
+            grouped
+        } else {
+            format!("{grouped},{fraction}")
+        }
+    }
+}
+
+#[test]
+fn custom_formatters_format_numbers() {
+    let formatter = SharedFormatter::new(German);
+    assert_eq!(formatter.number(1234.56, 2), "1.234,56");
+    assert_eq!(formatter.number(1234567.0, 0), "1.234.567");
+    assert_eq!(formatter.signed(-1234.56, 2), "-1.234,56");
+    assert_eq!(formatter.signed(12.0, 1), "+12,0");
+    assert_eq!(SharedFormatter::default().signed(-1234.56, 2), "-1234.56");
+}
+
+#[test]
+fn overlays_use_their_formatter() {
+    let ctx = egui::Context::default();
+    let mut player = common::player(common::stream(30, 100.0, 4), 4);
+    let formatter = SharedFormatter::new(German);
+    player.set_timing_text(Some(TimingTextStyle {
+        formatter: formatter.clone(),
+        ..Default::default()
+    }));
+    player.set_composition_text(Some(CompositionTextStyle {
+        formatter,
+        ..Default::default()
+    }));
+    player.set_current_time(2000.0);
+    player.report_hit(1, -1234.4, 1000.0);
+
+    let texts = common::texts(&common::output(&ctx, |ui| player.render(ui)));
+    assert!(texts.iter().any(|text| text == "-1.234 ms"), "{texts:?}");
+    assert!(
+        texts.iter().any(|text| text.starts_with("LN ")),
+        "{texts:?}"
+    );
+}
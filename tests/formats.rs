@@ -0,0 +1,80 @@
+mod common;
+
+use rosu_renderer::report::LoadIssue;
+use rosu_renderer::{Player, PlayerBuilder};
+
+fn load(version: u8) -> Player {
+    let path = format!("tests/fixtures/format_v{version}.osu");
+    PlayerBuilder::from_path(&path).unwrap().build().unwrap()
+}
+
+// What the renderer decides from a file, independent of how it was written
+fn decisions(player: &Player) -> impl PartialEq + std::fmt::Debug {
+    let objects: Vec<_> = player
+        .hit_objects()
+        .iter()
+        .map(|h| (h.start_time, format!("{:?}", h.kind)))
+        .collect();
+    let columns: Vec<_> = player
+        .column_counts()
+        .iter()
+        .map(|c| (c.notes, c.holds))
+        .collect();
+    (
+        objects,
+        columns,
+        player.duration().to_bits(),
+        format!("{:?}", player.beatmap().breaks),
+        player.beatmap().overall_difficulty.to_bits(),
+    )
+}
+
+#[test]
+fn old_formats_render_like_modern_ones() {
+    let modern = load(14);
+    assert!(modern.load_report().is_empty());
+    for version in [5, 9] {
+        let player = load(version);
+        assert_eq!(decisions(&player), decisions(&modern), "v{version}");
+        assert!(player.load_report().is_empty(), "v{version}");
+    }
+}
+
+#[test]
+fn frames_match_across_formats() {
+    let ctx = egui::Context::default();
+    let mut visible = Vec::new();
+    for version in [5, 9, 14] {
+        let mut player = load(version);
+        player.set_current_time(2600.0);
+        common::frame(&ctx, |ui| player.render(ui));
+        visible.push(player.frame_stats().visible_notes);
+    }
+    assert!(visible[0] > 0);
+    assert_eq!(visible, [visible[0]; 3]);
+}
+
+#[test]
+fn invalid_fields_are_reported() {
+    let bytes = std::fs::read_to_string("tests/fixtures/format_v14.osu")
+        .unwrap()
+        .replace("OverallDifficulty:8", "OverallDifficulty:15")
+        .replace("2,3500,6000", "2,6000,3500");
+    let player = PlayerBuilder::from_bytes(bytes.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(player.beatmap().overall_difficulty, 10.0);
+    assert!(player.beatmap().breaks.is_empty());
+    let issues = player.load_report().issues();
+    assert!(issues.contains(&LoadIssue::FieldDefaulted {
+        field: "OverallDifficulty",
+        value: 15.0,
+        fallback: 10.0,
+    }));
+    // rosu-map raises the end to the start
+    assert!(issues.contains(&LoadIssue::BreakDropped {
+        start: 6000.0,
+        end: 6000.0,
+    }));
+}
@@ -0,0 +1,203 @@
+mod common;
+
+use egui::{Color32, Rect, Shape};
+use rosu_map::section::general::GameMode;
+use rosu_map::section::timing_points::{TimeSignature, TimingPoint};
+use rosu_map::Beatmap;
+use rosu_renderer::description::{Bounds, FrameDescription};
+use rosu_renderer::layout::mania::{
+    BarlineStyle, LaneCover, NoteShape, NoteStyle, ReceptorStyle, ScrollDirection,
+};
+use rosu_renderer::{Player, PlayerBuilder};
+
+// Every circle, rect and horizontal line drawn, in paint order
+#[derive(Default)]
+struct Captured {
+    circles: Vec<(egui::Pos2, f32, Color32)>,
+    rects: Vec<(Rect, Color32)>,
+    lines: Vec<(f32, Color32)>,
+}
+
+fn capture(output: &egui::FullOutput) -> Captured {
+    fn collect(shape: &Shape, captured: &mut Captured) {
+        match shape {
+            Shape::Circle(circle) => {
+                captured
+                    .circles
+                    .push((circle.center, circle.radius, circle.fill));
+            }
+            Shape::Rect(rect) => captured.rects.push((rect.rect, rect.fill)),
+            Shape::LineSegment { points, stroke } if points[0].y == points[1].y => {
+                captured.lines.push((points[0].y, stroke.color));
+            }
+            Shape::Vec(shapes) => shapes.iter().for_each(|s| collect(s, captured)),
+            _ => {}
+        }
+    }
+    let mut captured = Captured::default();
+    for clipped in &output.shapes {
+        collect(&clipped.shape, &mut captured);
+    }
+    captured
+}
+
+fn rgba(color: Color32) -> [u8; 4] {
+    color.to_srgba_unmultiplied()
+}
+
+fn near(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.01
+}
+
+fn same_rect(rect: Rect, bounds: Bounds) -> bool {
+    near(rect.min.x, bounds.min.x)
+        && near(rect.min.y, bounds.min.y)
+        && near(rect.max.x, bounds.max.x)
+        && near(rect.max.y, bounds.max.y)
+}
+
+const RECEPTOR_COLOR: Color32 = Color32::from_rgb(10, 200, 30);
+const BARLINE_COLOR: Color32 = Color32::from_rgb(200, 20, 180);
+const COVER_COLOR: Color32 = Color32::from_rgb(5, 6, 7);
+
+fn player() -> Player {
+    let mut beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 4.0,
+        hit_objects: common::stream(80, 90.0, 4),
+        ..Default::default()
+    };
+    let signature = TimeSignature::new_simple_quadruple();
+    beatmap
+        .control_points
+        .add(TimingPoint::new(1000.0, 450.0, false, signature));
+    let mut player = PlayerBuilder::new(beatmap).build().unwrap();
+    player.set_note_style(NoteStyle {
+        shape: NoteShape::Circle,
+        ..Default::default()
+    });
+    player.set_receptors(Some(ReceptorStyle {
+        shape: Some(NoteShape::Rectangle {
+            width: 1.0,
+            height: 0.5,
+        }),
+        color: RECEPTOR_COLOR,
+        ..Default::default()
+    }));
+    player.set_barlines(Some(BarlineStyle {
+        color: BARLINE_COLOR,
+        thickness: 1.0,
+    }));
+    player.set_lane_cover(Some(LaneCover {
+        sudden: 0.2,
+        hidden: 0.1,
+        color: COVER_COLOR,
+    }));
+    player
+}
+
+// Renders at `clock`, describing the frame at the time it was drawn at
+fn rendered(player: &mut Player, clock: f64) -> (FrameDescription, Captured) {
+    player.set_current_time(clock);
+    let output = common::output(&egui::Context::default(), |ui| player.render(ui));
+    let drawn_at = player.frame_transform().unwrap().current_time;
+    (player.frame_description(drawn_at), capture(&output))
+}
+
+#[test]
+fn description_matches_the_drawn_shapes() {
+    let mut player = player();
+    let mut described = 0;
+    for clock in [1500.0, 3500.0, 7000.0] {
+        let (description, captured) = rendered(&mut player, clock);
+
+        assert_eq!(captured.circles.len(), description.notes.len());
+        for note in &description.notes {
+            let drawn = captured.circles.iter().any(|(center, radius, fill)| {
+                near(center.x, note.center.x)
+                    && near(center.y, note.center.y)
+                    && near(radius * 2.0, note.size.width)
+                    && rgba(*fill) == note.color
+            });
+            assert!(drawn, "{note:?} not drawn at {clock}");
+        }
+
+        for (color, bounds) in [
+            (
+                RECEPTOR_COLOR,
+                Vec::from_iter(description.receptors.iter().map(|r| {
+                    let half = egui::vec2(r.size.width, r.size.height) / 2.0;
+                    let center = egui::pos2(r.center.x, r.center.y);
+                    Bounds {
+                        min: (center - half).into(),
+                        max: (center + half).into(),
+                    }
+                })),
+            ),
+            (
+                COVER_COLOR,
+                Vec::from_iter(description.covers.iter().map(|c| c.bounds)),
+            ),
+        ] {
+            let drawn: Vec<_> = captured
+                .rects
+                .iter()
+                .filter(|(_, fill)| *fill == color)
+                .collect();
+            assert_eq!(drawn.len(), bounds.len(), "at {clock}");
+            for bounds in bounds {
+                assert!(drawn.iter().any(|(rect, _)| same_rect(*rect, bounds)));
+            }
+        }
+
+        for hold in &description.holds {
+            let parts = [(hold.body, hold.body_color), (hold.cap, hold.cap_color)];
+            for (bounds, color) in parts {
+                let Some(bounds) = bounds else { continue };
+                let drawn = captured
+                    .rects
+                    .iter()
+                    .any(|(rect, fill)| same_rect(*rect, bounds) && rgba(*fill) == color);
+                assert!(drawn, "{hold:?} not drawn at {clock}");
+            }
+        }
+
+        let barlines: Vec<_> = captured
+            .lines
+            .iter()
+            .filter(|(_, color)| *color == BARLINE_COLOR)
+            .map(|(y, _)| *y)
+            .collect();
+        assert_eq!(barlines.len(), description.barlines.len());
+        for (drawn, described) in barlines.iter().zip(&description.barlines) {
+            assert!(near(*drawn, *described));
+        }
+
+        assert_eq!(description.receptors.len(), 4);
+        assert_eq!(description.covers.len(), 2);
+        described += description.notes.len() + description.holds.len();
+    }
+    assert!(described > 20, "only {described} objects described");
+}
+
+#[test]
+fn lane_covers_hide_the_entry_and_the_line() {
+    let mut player = player();
+    for direction in [ScrollDirection::Down, ScrollDirection::Up] {
+        player.set_scroll_direction(direction);
+        let (description, _) = rendered(&mut player, 3500.0);
+        let (stage, line_y) = (description.stage, description.judgment_line_y);
+        let height = stage.max.y - stage.min.y;
+        let [sudden, hidden] = [0, 1].map(|i| description.covers[i].bounds);
+        // (stage edge notes enter from, far end of the sudden panel, line
+        // end and far end of the hidden panel)
+        let (far_edge, sudden_end, line_edge, hidden_end) = match direction {
+            ScrollDirection::Down => (stage.min.y, sudden.max.y, hidden.max.y, hidden.min.y),
+            ScrollDirection::Up => (stage.max.y, sudden.min.y, hidden.min.y, hidden.max.y),
+        };
+        assert!(near((sudden_end - far_edge).abs(), height * 0.2));
+        assert!(near(line_edge, line_y));
+        assert!(near((line_edge - hidden_end).abs(), height * 0.1));
+        assert!(near(sudden.min.x, stage.min.x) && near(sudden.max.x, stage.max.x));
+    }
+}
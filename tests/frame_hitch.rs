@@ -0,0 +1,55 @@
+mod common;
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use rosu_renderer::Player;
+
+fn hitches(player: &mut Player, ctx: &egui::Context) -> usize {
+    common::frame(ctx, |ui| player.render(ui));
+    player.drain_passed_notes().hitches.len()
+}
+
+fn started_player(ctx: &egui::Context) -> Player {
+    let mut player = common::player(common::stream(200, 50.0, 4), 4);
+    player.set_hitch_threshold(Some(50.0));
+    player.set_current_time(3000.0);
+    assert_eq!(hitches(&mut player, ctx), 0);
+    player
+}
+
+#[test]
+fn a_late_frame_is_a_hitch() {
+    let ctx = egui::Context::default();
+    let mut player = started_player(&ctx);
+    sleep(Duration::from_millis(80));
+    assert_eq!(hitches(&mut player, &ctx), 1);
+    assert!(player.last_frame_delta_ms().unwrap() >= 80.0);
+}
+
+#[test]
+fn speed_changes_are_not_hitches() {
+    let ctx = egui::Context::default();
+    let mut player = started_player(&ctx);
+    // At 3 s of clock the line jumps by seconds of map time
+    player.set_speed(2.0);
+    assert_eq!(hitches(&mut player, &ctx), 0);
+    player.set_speed(0.5);
+    assert_eq!(hitches(&mut player, &ctx), 0);
+    assert!(player.last_frame_delta_ms().unwrap() < 50.0);
+}
+
+#[test]
+fn scroll_time_changes_are_not_hitches() {
+    let ctx = egui::Context::default();
+    let mut player = started_player(&ctx);
+    player.set_scroll_time(400.0);
+    assert_eq!(hitches(&mut player, &ctx), 0);
+
+    player.set_scroll_animation(Some(60.0));
+    player.set_scroll_time(1600.0);
+    for _ in 0..10 {
+        sleep(Duration::from_millis(10));
+        assert_eq!(hitches(&mut player, &ctx), 0);
+    }
+}
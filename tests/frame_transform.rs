@@ -0,0 +1,105 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use rosu_renderer::camera::Easing;
+use rosu_renderer::layout::mania::{
+    column_for_x, FrameTransform, Layer, NoteShape, NoteStyle, ScrollDirection,
+};
+use rosu_renderer::rate::RateRamp;
+use rosu_renderer::Player;
+
+// Centers of every circle drawn, notes included. Rects would give their
+// center back from min and max, which doesn't round trip exactly.
+fn centers(output: &egui::FullOutput) -> Vec<egui::Pos2> {
+    fn collect(shape: &egui::Shape, centers: &mut Vec<egui::Pos2>) {
+        match shape {
+            egui::Shape::Circle(circle) => centers.push(circle.center),
+            egui::Shape::Vec(shapes) => shapes.iter().for_each(|s| collect(s, centers)),
+            _ => {}
+        }
+    }
+    let mut centers = Vec::new();
+    for clipped in &output.shapes {
+        collect(&clipped.shape, &mut centers);
+    }
+    centers
+}
+
+// Renders at `clock`, checking every note on screen was drawn exactly where
+// the snapshot puts it
+fn assert_notes_line_up(player: &mut Player, clock: f64) -> FrameTransform {
+    let ctx = egui::Context::default();
+    let hook_transform = Arc::new(Mutex::new(None));
+    let seen = hook_transform.clone();
+    player.set_layer_hook(
+        Layer::Notes,
+        Arc::new(move |_, context| *seen.lock().unwrap() = Some(context.transform)),
+    );
+    player.set_current_time(clock);
+    let output = common::output(&ctx, |ui| player.render(ui));
+    let transform = player.frame_transform().unwrap();
+    assert_eq!(*hook_transform.lock().unwrap(), Some(transform));
+
+    let centers = centers(&output);
+    let playfield = transform.playfield;
+    let line_time = transform.line_time();
+    let mut checked = 0;
+    for hit_object in player.hit_objects() {
+        let column = column_for_x(common::x_of(hit_object), transform.columns.keycount);
+        let pos = transform.note_pos(column, hit_object.start_time);
+        let on_screen = pos.y > playfield.top() + 20.0 && pos.y < playfield.bottom() - 20.0;
+        if hit_object.start_time <= line_time || !on_screen {
+            continue;
+        }
+        assert!(
+            centers.contains(&pos),
+            "note at {} expected at {pos:?}",
+            hit_object.start_time
+        );
+        checked += 1;
+    }
+    assert!(checked > 5, "only {checked} notes on screen");
+    transform
+}
+
+fn player() -> Player {
+    let mut player = common::player(common::stream(60, 37.3, 7), 7);
+    player.set_note_style(NoteStyle {
+        shape: NoteShape::Circle,
+        ..Default::default()
+    });
+    player
+}
+
+#[test]
+fn notes_are_drawn_at_the_snapshot_positions() {
+    let mut player = player();
+    player.set_speed(1.37);
+    player.set_scroll_time(777.7);
+    let transform = assert_notes_line_up(&mut player, 1333.3);
+    assert_eq!(transform.scroll.speed, 1.37);
+    assert_eq!(transform.columns.keycount, 7);
+}
+
+#[test]
+fn upscroll_notes_are_drawn_at_the_snapshot_positions() {
+    let mut player = player();
+    player.set_scroll_direction(ScrollDirection::Up);
+    let transform = assert_notes_line_up(&mut player, 1612.9);
+    assert_eq!(transform.direction, ScrollDirection::Up);
+}
+
+#[test]
+fn rate_ramp_notes_are_drawn_at_the_snapshot_positions() {
+    let mut player = player();
+    player.set_rate_ramp(Some(RateRamp {
+        from: 0.8,
+        to: 1.3,
+        start_ms: 1000.0,
+        end_ms: 3000.0,
+        curve: Easing::EaseInOut,
+    }));
+    let transform = assert_notes_line_up(&mut player, 2600.0);
+    assert!(transform.rate > 0.8 && transform.rate < 1.3);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use rosu_renderer::events::Event;
+
+#[test]
+fn out_of_range_columns_are_ignored() {
+    let mut player = common::player(common::stream(8, 100.0, 4), 4);
+    let ctx = egui::Context::default();
+    for column in [4, 17, 18, usize::MAX] {
+        player.report_ghost_tap(column, 1000.0);
+    }
+    common::frame(&ctx, |ui| player.render(ui));
+    assert_eq!(player.ghost_taps(), 0);
+    assert_eq!(player.session_stats().ghost_taps, 0);
+    assert!(!player
+        .events()
+        .drain()
+        .iter()
+        .any(|event| matches!(event, Event::GhostTap { .. })));
+
+    player.report_ghost_tap(3, 1000.0);
+    assert_eq!(player.ghost_taps(), 1);
+}
+
+#[test]
+fn ghost_taps_are_in_the_stats_json() {
+    let mut player = common::player(common::stream(4, 100.0, 4), 4);
+    player.report_hit(0, 2.5, 1000.0);
+    player.report_hold_break(0, 1100.0);
+    player.report_ghost_tap(1, 1150.0);
+    player.report_ghost_tap(2, 1160.0);
+
+    let json = player.session_stats().to_json();
+    assert!(json.starts_with("{\"errors\":[2.5,null],\"max_combo\":1,"));
+    assert!(json.contains("\"map_note_count\":4,\"ghost_taps\":2,"));
+    assert!(json.contains("\"windows\":{\"marv\":16.5,"));
+    assert!(json.ends_with("}}"));
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::fmt::Write;
+
+use egui::epaint::{CircleShape, PathShape, RectShape};
+use egui::{Color32, Shape};
+use rosu_renderer::layout::mania::{ManiaRenderer, TimingTextStyle};
+
+const GOLDEN: &str = "tests/fixtures/golden_frame.json";
+
+fn color(c: Color32) -> String {
+    let [r, g, b, a] = c.to_array();
+    format!("\"#{r:02x}{g:02x}{b:02x}{a:02x}\"")
+}
+
+// One JSON object per shape, floats in Rust's shortest round-trip form so
+// any difference in the math shows
+fn describe(shape: &Shape, out: &mut Vec<String>) {
+    let line = match shape {
+        Shape::Noop => return,
+        Shape::Vec(shapes) => return shapes.iter().for_each(|s| describe(s, out)),
+        Shape::Rect(RectShape {
+            rect, fill, stroke, ..
+        }) => format!(
+            "{{\"rect\":[{},{},{},{}],\"fill\":{},\"stroke\":[{},{}]}}",
+            rect.min.x,
+            rect.min.y,
+            rect.max.x,
+            rect.max.y,
+            color(*fill),
+            stroke.width,
+            color(stroke.color)
+        ),
+        Shape::Circle(CircleShape {
+            center,
+            radius,
+            fill,
+            stroke,
+        }) => format!(
+            "{{\"circle\":[{},{},{}],\"fill\":{},\"stroke\":[{},{}]}}",
+            center.x,
+            center.y,
+            radius,
+            color(*fill),
+            stroke.width,
+            color(stroke.color)
+        ),
+        Shape::LineSegment { points, stroke } => format!(
+            "{{\"line\":[{},{},{},{}],\"stroke\":[{},{}]}}",
+            points[0].x,
+            points[0].y,
+            points[1].x,
+            points[1].y,
+            stroke.width,
+            color(stroke.color)
+        ),
+        Shape::Path(PathShape { points, fill, .. }) => {
+            let mut coords = String::new();
+            for (i, p) in points.iter().enumerate() {
+                let sep = if i == 0 { "" } else { "," };
+                write!(coords, "{sep}{},{}", p.x, p.y).unwrap();
+            }
+            format!("{{\"path\":[{coords}],\"fill\":{}}}", color(*fill))
+        }
+        Shape::Text(_) => panic!("text drawn in deterministic mode"),
+        other => {
+            let rect = other.visual_bounding_rect();
+            format!(
+                "{{\"other\":[{},{},{},{}]}}",
+                rect.min.x, rect.min.y, rect.max.x, rect.max.y
+            )
+        }
+    };
+    out.push(line);
+}
+
+fn golden_frame() -> String {
+    let mut hit_objects = common::stream(24, 75.0, 4);
+    hit_objects.push(common::hold(1400.0, 600.0, 2, 4));
+    let mut renderer = ManiaRenderer::with_sizes(80.0, 60.0, 600.0);
+    renderer.set_deterministic(true);
+    renderer.set_debug_overlay(true);
+    renderer.set_timing_text(Some(TimingTextStyle::default()));
+    // The line is at 700 ms
+    renderer.report_hit(1, -12.0, 680.0);
+    renderer.report_hit(3, 31.0, 690.0);
+
+    let ctx = egui::Context::default();
+    let output = common::output(&ctx, |ui| {
+        renderer.render(ui, &hit_objects, 1500.0, 800.0, 1.0, 4);
+    });
+    let mut lines = Vec::new();
+    for clipped in &output.shapes {
+        describe(&clipped.shape, &mut lines);
+    }
+    format!("[\n{}\n]\n", lines.join(",\n"))
+}
+
+// Run with UPDATE_GOLDEN=1 to rewrite the stored frame after an intended
+// change to what gets drawn
+#[test]
+fn frame_matches_the_golden_shapes() {
+    let frame = golden_frame();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(GOLDEN, &frame).unwrap();
+    }
+    let golden = std::fs::read_to_string(GOLDEN).unwrap();
+    assert!(frame == golden, "frame differs from {GOLDEN}:\n{frame}");
+}
+
+#[test]
+fn frames_are_drawn_identically_twice() {
+    assert_eq!(golden_frame(), golden_frame());
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "widgets")]
+
+mod common;
+
+use rosu_renderer::scatter::{ScatterStyle, TimedError};
+use rosu_renderer::{ui, Player};
+
+// Centers of the scatter's dots
+fn dots(output: &egui::FullOutput, style: &ScatterStyle) -> Vec<egui::Pos2> {
+    output
+        .shapes
+        .iter()
+        .filter_map(|clipped| match &clipped.shape {
+            egui::Shape::Circle(circle) if circle.radius == style.dot_radius => Some(circle.center),
+            _ => None,
+        })
+        .collect()
+}
+
+fn player() -> Player {
+    common::player(common::stream(40, 100.0, 4), 4)
+}
+
+#[test]
+fn overlay_plots_each_reported_hit_once() {
+    let ctx = egui::Context::default();
+    let style = ScatterStyle::default();
+    let mut player = player();
+    player.set_hit_error_scatter(Some(style.clone()));
+    player.set_current_time(3400.0);
+    for (column, offset) in [-30.0, 5.0, 42.0].into_iter().enumerate() {
+        player.report_hit(column, offset, 1000.0 + 100.0 * column as f64);
+    }
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert_eq!(dots(&output, &style).len(), 3);
+
+    // Replayed hits are shown again, not recorded again
+    assert!(player.replay_window(900.0, 1500.0, 1.0));
+    player.set_current_time(2300.0);
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert_eq!(dots(&output, &style).len(), 3);
+    assert_eq!(player.hit_errors().len(), 3);
+
+    player.end_replay();
+    player.clear_judgments();
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert!(dots(&output, &style).is_empty());
+    assert!(player.hit_errors().is_empty());
+}
+
+#[test]
+fn dense_plots_draw_a_dot_per_pixel() {
+    let ctx = egui::Context::default();
+    let style = ScatterStyle::default();
+    let windows = player().windows();
+    let half_height = style.size.y as f64 / 2.0;
+    // 1.5 pixels under the center line, at the left edge
+    let mut errors = vec![TimedError {
+        time_ms: 0.0,
+        offset_ms: windows.miss as f64 * 1.5 / half_height,
+    }];
+    errors.extend((1..20_000).map(|i| TimedError {
+        time_ms: i as f64,
+        offset_ms: 0.0,
+    }));
+
+    let mut rect = egui::Rect::NOTHING;
+    let output = common::output(&ctx, |ui| {
+        rect = ui::hit_error_scatter(ui, &errors, &windows, &style).rect;
+    });
+    let dots = dots(&output, &style);
+    // One per pixel column on the center line, the right edge sharing the
+    // last column, and the one under it
+    assert_eq!(dots.len(), style.size.x as usize + 1);
+    assert!(dots
+        .iter()
+        .any(|dot| dot.x == rect.left() && (dot.y - rect.center().y - 1.5).abs() < 0.01));
+    assert!(dots.iter().all(|dot| dot.x <= rect.right()));
+}
+
+#[test]
+fn sparse_plots_draw_every_dot() {
+    let ctx = egui::Context::default();
+    let style = ScatterStyle::default();
+    let windows = player().windows();
+    let errors = vec![
+        TimedError {
+            time_ms: 1000.0,
+            offset_ms: 10.0,
+        };
+        100
+    ];
+    let output = common::output(&ctx, |ui| {
+        ui::hit_error_scatter(ui, &errors, &windows, &style);
+    });
+    assert_eq!(dots(&output, &style).len(), 100);
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use rosu_renderer::layout::mania::{ManiaRenderer, DEFAULT_HIT_POSITION};
+
+#[test]
+fn invalid_hit_positions_fall_back_to_the_default() {
+    let hit_objects = common::stream(20, 60.0, 4);
+    for offset_px in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        let mut renderer = ManiaRenderer::with_sizes(80.0, 60.0, 600.0);
+        renderer.set_hit_position(40.0);
+        renderer.set_hit_position(offset_px);
+        assert_eq!(renderer.hit_position(), DEFAULT_HIT_POSITION);
+
+        let ctx = egui::Context::default();
+        common::frame(&ctx, |ui| {
+            renderer.render(ui, &hit_objects, 1000.0, 800.0, 1.0, 4);
+        });
+        let transform = renderer.frame_transform().unwrap();
+        assert!(transform.judgment_line_y.is_finite());
+    }
+}
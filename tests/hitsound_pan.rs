@@ -0,0 +1,31 @@
+mod common;
+
+use rosu_renderer::hitsounds::HitsoundConfig;
+use rosu_renderer::transform::Arrangement;
+
+#[test]
+fn pan_follows_the_displayed_column() {
+    let config = HitsoundConfig::default().pan_by_column(1.0);
+    // One note in each column, left to right
+    let hit_objects = (0..4)
+        .map(|column| common::note(1000.0 + 100.0 * column as f64, column, 4))
+        .collect();
+    let mut player = common::player(hit_objects, 4);
+    player.set_arrangement(Arrangement::Mirror);
+    let ctx = egui::Context::default();
+    player.set_current_time(1900.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    player.set_current_time(2500.0);
+    common::frame(&ctx, |ui| player.render(ui));
+
+    let notes = player.drain_passed_notes().notes;
+    let pans: Vec<f32> = notes
+        .iter()
+        .map(|note| config.pan(note.column, 4))
+        .collect();
+    // Mirrored, the first note plays in the rightmost column
+    assert_eq!(notes.len(), 4);
+    assert_eq!(pans.first(), Some(&1.0));
+    assert_eq!(pans.last(), Some(&-1.0));
+    assert!(pans.windows(2).all(|pair| pair[0] > pair[1]), "{pans:?}");
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use rosu_renderer::description::HoldDescription;
+use rosu_renderer::Player;
+
+// A hold from 2000 to 2400 in the first column
+fn player() -> Player {
+    common::player(vec![common::hold(2000.0, 400.0, 0, 4)], 4)
+}
+
+// Puts map time `line_time` on the judgment line
+fn seek(player: &mut Player, line_time: f64) {
+    player.set_current_time(line_time + 1000.0);
+}
+
+// The hold as drawn, and the frame's y of `times`
+fn hold(player: &mut Player, times: [f64; 2]) -> (HoldDescription, [f32; 2]) {
+    let ctx = egui::Context::default();
+    common::frame(&ctx, |ui| player.render(ui));
+    let transform = player.frame_transform().unwrap();
+    let ys = times.map(|time| transform.y_for(time));
+    let description = player.frame_description(transform.current_time);
+    (description.holds[0].clone(), ys)
+}
+
+#[test]
+fn break_in_the_middle_drops_the_rest() {
+    let mut player = player();
+    seek(&mut player, 2100.0);
+    player.report_hold_break(0, 2200.0);
+    let (hold, [break_y, tail_y]) = hold(&mut player, [2200.0, 2400.0]);
+    assert!(hold.broken);
+
+    let dropped = hold.dropped.expect("dropped part");
+    let body = hold.body.unwrap();
+    assert!(
+        (dropped.max.y - break_y).abs() < 1e-3,
+        "{dropped:?} {break_y}"
+    );
+    assert!(
+        (dropped.min.y - tail_y).abs() < 1e-3,
+        "{dropped:?} {tail_y}"
+    );
+    assert!(dropped.min.y >= body.min.y && dropped.max.y <= body.max.y);
+}
+
+#[test]
+fn break_at_the_tail_only_breaks_the_cap() {
+    let mut player = player();
+    seek(&mut player, 2100.0);
+    let (whole, _) = hold(&mut player, [0.0; 2]);
+    player.report_hold_break(0, 2400.0);
+    // Described at the same time as the unbroken hold
+    let t = player.frame_transform().unwrap().current_time;
+    let broken = player.frame_description(t).holds[0].clone();
+
+    assert!(broken.broken);
+    assert_eq!(broken.dropped, None);
+    assert_eq!(broken.body, whole.body);
+    assert_eq!(broken.cap, whole.cap);
+    assert_ne!(broken.cap_color, whole.cap_color);
+}
+
+#[test]
+fn seeking_back_past_the_break_restores_the_hold() {
+    let mut player = player();
+    seek(&mut player, 2300.0);
+    player.report_hold_break(0, 2200.0);
+    assert!(hold(&mut player, [0.0; 2]).0.broken);
+
+    // Seeking back to after the break keeps it
+    seek(&mut player, 2250.0);
+    assert!(hold(&mut player, [0.0; 2]).0.broken);
+
+    seek(&mut player, 1700.0);
+    let (restored, _) = hold(&mut player, [0.0; 2]);
+    assert!(!restored.broken);
+    assert_eq!(restored.dropped, None);
+    // Playing past it again doesn't bring the break back
+    seek(&mut player, 2300.0);
+    assert!(!hold(&mut player, [0.0; 2]).0.broken);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use rosu_renderer::description::NoteKind;
+use rosu_renderer::layout::mania::ScrollDirection;
+
+// Map time on the judgment line
+const LINE_TIME: f64 = 1000.0;
+
+// Holds by whether their head and tail are on screen
+const CASES: [(&str, f64, f64, [bool; 2]); 4] = [
+    ("head and tail inside", 1200.0, 1600.0, [true, true]),
+    (
+        "head inside, tail past the spawn",
+        1500.0,
+        5000.0,
+        [true, false],
+    ),
+    (
+        "head past the line, tail inside",
+        500.0,
+        1500.0,
+        [false, true],
+    ),
+    ("head and tail outside", 500.0, 5000.0, [false, false]),
+];
+
+fn check(direction: ScrollDirection) {
+    for (case, head, tail, expected) in CASES {
+        let mut player = common::player(vec![common::hold(head, tail - head, 0, 4)], 4);
+        player.set_scroll_direction(direction);
+        let ctx = egui::Context::default();
+        player.set_current_time(LINE_TIME + 1000.0);
+        common::frame(&ctx, |ui| player.render(ui));
+        let transform = player.frame_transform().unwrap();
+        let description = player.frame_description(transform.current_time);
+
+        // From the judgment line to the far edge of the stage
+        let line_y = transform.judgment_line_y;
+        let spawn_y = match direction {
+            ScrollDirection::Down => transform.playfield.min.y,
+            ScrollDirection::Up => transform.playfield.max.y,
+        };
+        let (top_y, bottom_y) = (line_y.min(spawn_y), line_y.max(spawn_y));
+        let clamp = |y: f32| y.clamp(top_y, bottom_y);
+        let (head_y, tail_y) = (transform.y_for(head), transform.y_for(tail));
+        let inside = |y: f32| (top_y..=bottom_y).contains(&y);
+        assert_eq!([inside(head_y), inside(tail_y)], expected, "{case}");
+
+        let hold = &description.holds[0];
+        let body = hold.body.unwrap_or_else(|| panic!("{case}: no body"));
+        let (min_y, max_y) = (head_y.min(tail_y), head_y.max(tail_y));
+        assert!((body.min.y - clamp(min_y)).abs() < 1e-3, "{case}: {body:?}");
+        assert!((body.max.y - clamp(max_y)).abs() < 1e-3, "{case}: {body:?}");
+        // The cap hangs off the tail toward the head, inside the range
+        assert_eq!(hold.cap.is_some(), inside(tail_y), "{case}");
+        if let Some(cap) = hold.cap {
+            assert!(
+                cap.min.y >= top_y && cap.max.y <= bottom_y,
+                "{case}: {cap:?}"
+            );
+            let edge = match direction {
+                ScrollDirection::Down => cap.min.y,
+                ScrollDirection::Up => cap.max.y,
+            };
+            assert!((edge - tail_y).abs() < 1e-3, "{case}: {cap:?} {tail_y}");
+        }
+        let heads = description
+            .notes
+            .iter()
+            .filter(|note| note.kind == NoteKind::HoldHead)
+            .count();
+        assert_eq!(heads == 1, inside(head_y), "{case}");
+    }
+}
+
+#[test]
+fn downscroll_clips_every_part_to_the_stage() {
+    check(ScrollDirection::Down);
+}
+
+#[test]
+fn upscroll_clips_every_part_to_the_stage() {
+    check(ScrollDirection::Up);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use egui::{Color32, Rect, Shape};
+use rosu_renderer::layout::mania::{HoldBodyPattern, ManiaRenderer, Period};
+
+const STRIPE: Color32 = Color32::from_rgb(1, 2, 3);
+
+// `color_b` stripes drawn with the line at `current_time - 800`
+fn stripes(period: Period, current_time: f64) -> Vec<Rect> {
+    let hold = [common::hold(1000.0, 600_000.0, 1, 4)];
+    let mut renderer = ManiaRenderer::with_sizes(80.0, 60.0, 600.0);
+    renderer.set_hold_body_pattern(HoldBodyPattern::Striped {
+        period,
+        color_a: Color32::GRAY,
+        color_b: STRIPE,
+    });
+    let ctx = egui::Context::default();
+    let output = common::output(&ctx, |ui| {
+        renderer.render(ui, &hold, current_time, 800.0, 1.0, 4);
+    });
+    output
+        .shapes
+        .iter()
+        .filter_map(|clipped| match &clipped.shape {
+            Shape::Rect(rect) if rect.fill == STRIPE => Some(rect.rect),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn stripes_show_deep_into_long_holds() {
+    for period in [Period::Ms(25.0), Period::Beats(0.05)] {
+        // Hundreds of thousands of stripes past the head
+        let drawn = stripes(period, 300_000.0);
+        assert!(drawn.len() > 5, "{period:?}: {}", drawn.len());
+    }
+}
+
+#[test]
+fn stripes_stay_anchored_to_the_head() {
+    // Two periods later the body moved by exactly one stripe pair
+    let at = |time| stripes(Period::Ms(25.0), time);
+    assert_eq!(at(300_000.0), at(300_050.0));
+    assert_ne!(at(300_000.0), at(300_025.0));
+    assert_eq!(at(300_010.0), at(300_060.0));
+}
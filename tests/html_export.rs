@@ -0,0 +1,28 @@
+#![cfg(feature = "html-export")]
+
+mod common;
+
+use rosu_renderer::html::HtmlExportOptions;
+use rosu_renderer::layout::mania::ScrollDirection;
+use rosu_renderer::Player;
+
+#[test]
+fn export_embeds_the_chart_and_direction() {
+    let bytes = std::fs::read("tests/fixtures/format_v14.osu").unwrap();
+    let mut player = Player::from_bytes(&bytes, 100.0, 50.0, 600.0).unwrap();
+    player.set_scroll_direction(ScrollDirection::Up);
+    let path = std::env::temp_dir().join(format!("rosu-preview-{}.html", std::process::id()));
+    let options = HtmlExportOptions {
+        title: Some("Preview".to_owned()),
+    };
+    player.export_html(&path, &options).unwrap();
+    let page = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(page.contains("<title>Preview</title>"));
+    assert!(page.contains("\"timing_points\":[[1000,500,4,0]]"));
+    assert!(page.contains("[0,1000,0],[1,1250,0]"));
+    assert!(page.contains("[0,2000,400]"));
+    assert!(page.contains("const DIRECTION = -1;"));
+    assert!(page.contains("const COLUMN_WIDTH = 100;"));
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::sync::Arc;
+
+use egui::{ColorImage, TextureId, TextureOptions};
+use rosu_renderer::images::{ImageProvider, LoadedTexture, MemoryImages, TextureImages};
+use rosu_renderer::layout::mania::{NoteShape, NoteStyle};
+use rosu_renderer::Player;
+
+// Textures of every image a frame drew
+fn textures(output: &egui::FullOutput) -> Vec<TextureId> {
+    fn collect(shape: &egui::Shape, textures: &mut Vec<TextureId>) {
+        match shape {
+            egui::Shape::Rect(rect) => {
+                textures.extend(rect.brush.as_ref().map(|brush| brush.fill_texture_id))
+            }
+            egui::Shape::Mesh(mesh) if mesh.texture_id != TextureId::default() => {
+                textures.push(mesh.texture_id)
+            }
+            egui::Shape::Vec(shapes) => shapes.iter().for_each(|s| collect(s, textures)),
+            _ => {}
+        }
+    }
+    let mut textures = Vec::new();
+    for clipped in &output.shapes {
+        collect(&clipped.shape, &mut textures);
+    }
+    textures
+}
+
+fn png() -> Vec<u8> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image::RgbaImage::from_pixel(2, 2, image::Rgba([255; 4]))
+        .write_to(&mut bytes, image::ImageFormat::Png)
+        .unwrap();
+    bytes.into_inner()
+}
+
+fn keyed_notes(player: &mut Player) {
+    player.set_note_style(NoteStyle {
+        shape: NoteShape::Keyed("note".to_owned()),
+        ..NoteStyle::default()
+    });
+    player.set_current_time(1500.0);
+}
+
+#[test]
+fn keyed_notes_are_drawn_from_the_provider() {
+    let ctx = egui::Context::default();
+    let texture = ctx.load_texture(
+        "note",
+        ColorImage::from_rgba_unmultiplied([2, 2], &[255; 16]),
+        TextureOptions::default(),
+    );
+    let mut provider = TextureImages::new();
+    provider.insert(
+        "note",
+        LoadedTexture {
+            id: texture.id(),
+            size: texture.size_vec2(),
+        },
+    );
+
+    let mut player = common::player(common::stream(8, 100.0, 4), 4);
+    keyed_notes(&mut player);
+    // Circles until the provider has the key
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert!(!textures(&output).contains(&texture.id()));
+
+    player.set_image_provider(Arc::new(provider));
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert!(textures(&output).contains(&texture.id()));
+}
+
+#[test]
+fn memory_images_decode_once_and_skip_unknown_keys() {
+    let ctx = egui::Context::default();
+    let mut images = MemoryImages::new();
+    images.insert("note", png());
+    images.insert("broken", vec![1, 2, 3]);
+
+    let first = images.resolve(&ctx, "note").unwrap();
+    assert_eq!(first.size, egui::vec2(2.0, 2.0));
+    assert_eq!(images.resolve(&ctx, "note"), Some(first));
+    assert_eq!(images.resolve(&ctx, "broken"), None);
+    assert_eq!(images.resolve(&ctx, "missing"), None);
+
+    let mut player = common::player(common::stream(8, 100.0, 4), 4);
+    keyed_notes(&mut player);
+    player.set_image_provider(Arc::new(images));
+    let output = common::output(&ctx, |ui| player.render(ui));
+    assert!(textures(&output).contains(&first.id));
+}
+
+#[test]
+fn backgrounds_are_drawn_from_the_provider() {
+    let osu = std::fs::read_to_string("tests/fixtures/format_v14.osu").unwrap();
+    let osu = osu.replace(
+        "//Background and Video events",
+        "//Background and Video events\n0,0,\"bg.png\",0,0",
+    );
+    let mut player = Player::from_bytes(osu.as_bytes(), 100.0, 50.0, 600.0).unwrap();
+    assert_eq!(player.background_events()[0].file, "bg.png");
+    player.set_current_time(1500.0);
+
+    let ctx = egui::Context::default();
+    let mut images = MemoryImages::new();
+    images.insert("bg.png", png());
+    let images = Arc::new(images);
+    player.set_image_provider(images.clone());
+    let output = common::output(&ctx, |ui| player.render(ui));
+    let background = images.resolve(&ctx, "bg.png").unwrap();
+    assert!(textures(&output).contains(&background.id));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use rosu_map::section::general::GameMode;
+use rosu_map::Beatmap;
+use rosu_renderer::layout::mania::KeycountChangePolicy;
+use rosu_renderer::Player;
+
+fn beatmap(keycount: usize) -> Beatmap {
+    Beatmap {
+        mode: GameMode::Mania,
+        circle_size: keycount as f32,
+        hit_objects: common::stream(60, 50.0, keycount),
+        ..Default::default()
+    }
+}
+
+// Renders a frame and checks the stage matches the loaded keycount
+fn assert_layout(ctx: &egui::Context, player: &mut Player, keycount: usize) {
+    common::frame(ctx, |ui| player.render(ui));
+    assert_eq!(player.column_counts().len(), keycount);
+    let playfield = player.playfield_rect().unwrap();
+    assert_eq!(playfield.width(), player.get_required_size()[0]);
+    assert!(player.frame_stats().visible_notes > 0);
+}
+
+#[test]
+fn swapping_keycounts_while_playing() {
+    let ctx = egui::Context::default();
+    let mut player = common::player(common::stream(60, 50.0, 4), 4);
+    player.set_current_time(1500.0);
+    assert_layout(&ctx, &mut player, 4);
+    let width_4k = player.playfield_rect().unwrap().width();
+
+    for keycount in [7, 4, 7, 4] {
+        assert!(player.set_beatmap(beatmap(keycount)));
+        assert_layout(&ctx, &mut player, keycount);
+    }
+    assert_eq!(player.playfield_rect().unwrap().width(), width_4k);
+}
+
+#[test]
+fn swapping_keycounts_while_paused() {
+    let ctx = egui::Context::default();
+    let mut player = common::player(common::stream(60, 50.0, 4), 4);
+    for keycount in [7, 4, 7, 4] {
+        // Held still by seeking to the same time every frame
+        player.set_current_time(1500.0);
+        assert!(player.set_beatmap(beatmap(keycount)));
+        player.set_current_time(1500.0);
+        assert_layout(&ctx, &mut player, keycount);
+    }
+}
+
+#[test]
+fn adapt_truncates_per_column_settings() {
+    let mut player = common::player(common::stream(60, 50.0, 7), 7);
+    player.set_column_pressed(1, true);
+    player.set_column_pressed(6, true);
+    player.set_column_visibility(vec![true, false, true, true, true, true, false]);
+
+    assert!(player.set_beatmap(beatmap(4)));
+    assert!(player.is_column_pressed(1));
+    assert!(!player.is_column_visible(1));
+
+    assert!(player.set_beatmap(beatmap(7)));
+    // Extended with defaults, not restored
+    assert!(!player.is_column_pressed(6));
+    assert!(player.is_column_visible(6));
+}
+
+#[test]
+fn reset_clears_per_column_settings() {
+    let mut player = common::player(common::stream(60, 50.0, 7), 7);
+    player.set_keycount_change_policy(KeycountChangePolicy::Reset);
+    player.set_column_pressed(1, true);
+    player.set_column_visibility(vec![true, false]);
+
+    assert!(player.set_beatmap(beatmap(4)));
+    assert!(!player.is_column_pressed(1));
+    assert!(player.is_column_visible(1));
+}
+
+#[test]
+fn reject_keeps_the_current_map() {
+    let ctx = egui::Context::default();
+    let mut player = common::player(common::stream(60, 50.0, 4), 4);
+    player.set_keycount_change_policy(KeycountChangePolicy::Reject);
+    player.set_column_pressed(2, true);
+
+    assert!(!player.set_beatmap(beatmap(7)));
+    assert!(player.is_column_pressed(2));
+    player.set_current_time(1500.0);
+    assert_layout(&ctx, &mut player, 4);
+
+    // The same keycount still loads
+    assert!(player.set_beatmap(beatmap(4)));
+    assert!(player.is_column_pressed(2));
+}
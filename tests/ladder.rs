@@ -0,0 +1,69 @@
+mod common;
+
+use rosu_map::section::general::GameMode;
+use rosu_map::section::timing_points::{TimeSignature, TimingPoint};
+use rosu_map::Beatmap;
+use rosu_renderer::description::{NoteDescription, NoteKind};
+use rosu_renderer::layout::mania::HoldBodyStyle;
+use rosu_renderer::{Player, PlayerBuilder};
+
+fn player(hold: (f64, f64), timing_points: &[(f64, f64)], divisor: u32) -> Player {
+    let mut beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 4.0,
+        hit_objects: vec![common::hold(hold.0, hold.1, 1, 4)],
+        ..Default::default()
+    };
+    for &(time, beat_len) in timing_points {
+        let signature = TimeSignature::new_simple_quadruple();
+        beatmap
+            .control_points
+            .add(TimingPoint::new(time, beat_len, false, signature));
+    }
+    let mut player = PlayerBuilder::new(beatmap).build().unwrap();
+    player.set_hold_body_style(HoldBodyStyle::Ladder {
+        divisor,
+        shape_scale: 0.5,
+    });
+    let ctx = egui::Context::default();
+    common::frame(&ctx, |ui| player.render(ui));
+    player
+}
+
+// Rungs described with the judgment line at map time `line_time`, from the
+// line up
+fn rungs(player: &Player, line_time: f64) -> Vec<NoteDescription> {
+    let description = player.frame_description(line_time + 1000.0);
+    let mut rungs: Vec<_> = description
+        .notes
+        .into_iter()
+        .filter(|note| note.kind == NoteKind::LadderRung)
+        .collect();
+    rungs.sort_by(|a, b| b.center.y.total_cmp(&a.center.y));
+    rungs
+}
+
+#[test]
+fn rungs_show_deep_into_long_holds() {
+    // Far more 1/1024 lines than the cap between the line and the top
+    let player = player((1000.0, 600_000.0), &[], 1024);
+    let line_y = player.frame_transform().unwrap().judgment_line_y;
+    let rungs = rungs(&player, 300_000.0);
+    assert_eq!(rungs.len(), 256);
+    assert!(rungs.iter().all(|rung| rung.center.y <= line_y));
+}
+
+#[test]
+fn rungs_follow_bpm_changes() {
+    let player = player((1000.0, 750.0), &[(0.0, 200.0), (1400.0, 100.0)], 1);
+    let rungs = rungs(&player, 900.0);
+    // On 1200 and 1400, then every 100 ms up to the tail
+    assert_eq!(rungs.len(), 5);
+    let gaps: Vec<f32> = rungs
+        .windows(2)
+        .map(|pair| pair[0].center.y - pair[1].center.y)
+        .collect();
+    for gap in &gaps[1..] {
+        assert!((gaps[0] / gap - 2.0).abs() < 1e-3, "{gaps:?}");
+    }
+}
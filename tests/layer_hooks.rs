@@ -0,0 +1,114 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use rosu_renderer::layout::mania::{BarlineStyle, Layer, LayerOrder, ReceptorStyle};
+use rosu_renderer::Player;
+
+// (layer, stage, shapes painted so far) of every hook call
+type Calls = Arc<Mutex<Vec<(Layer, usize, usize)>>>;
+
+fn record_hooks(player: &mut Player) -> Calls {
+    let calls = Calls::default();
+    for layer in Layer::ALL {
+        let calls = calls.clone();
+        player.set_layer_hook(
+            layer,
+            Arc::new(move |painter, context| {
+                let painted = painter.ctx().graphics(|graphics| {
+                    graphics
+                        .get(painter.layer_id())
+                        .map_or(0, |shapes| shapes.next_idx().0)
+                });
+                calls.lock().unwrap().push((layer, context.stage, painted));
+            }),
+        );
+    }
+    calls
+}
+
+fn order(calls: &Calls) -> Vec<(Layer, usize)> {
+    let calls = calls.lock().unwrap();
+    calls
+        .iter()
+        .map(|(layer, stage, _)| (*layer, *stage))
+        .collect()
+}
+
+fn rendered(mut player: Player) -> Calls {
+    let calls = record_hooks(&mut player);
+    player.set_current_time(1500.0);
+    common::frame(&egui::Context::default(), |ui| player.render(ui));
+    calls
+}
+
+#[test]
+fn hooks_run_in_layer_order() {
+    let calls = rendered(common::player(common::stream(20, 100.0, 4), 4));
+    let expected: Vec<_> = Layer::ALL.into_iter().map(|layer| (layer, 0)).collect();
+    assert_eq!(order(&calls), expected);
+}
+
+#[test]
+fn compared_stages_run_their_hooks_in_turn() {
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    player.set_comparison(true);
+    let calls = rendered(player);
+
+    let stage = |stage| {
+        Layer::ALL
+            .into_iter()
+            .filter(|layer| *layer != Layer::Overlays)
+            .map(move |layer| (layer, stage))
+    };
+    let mut expected: Vec<_> = stage(0).chain(stage(1)).collect();
+    // Overlays are drawn once every stage is
+    expected.extend([(Layer::Overlays, 0), (Layer::Overlays, 1)]);
+    assert_eq!(order(&calls), expected);
+}
+
+#[test]
+fn layer_order_swaps_the_object_hooks() {
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    player.set_layer_order(LayerOrder::NotesBelowHolds);
+    let order = order(&rendered(player));
+    let position = |layer| order.iter().position(|(l, _)| *l == layer).unwrap();
+    assert!(position(Layer::Notes) < position(Layer::HoldBodies));
+    assert!(position(Layer::Barlines) < position(Layer::Notes));
+    assert!(position(Layer::Receptors) < position(Layer::HoldBodies));
+}
+
+#[test]
+fn hooks_run_after_their_layer_is_drawn() {
+    let path = "tests/fixtures/format_v14.osu";
+    let mut player = Player::from_bytes(&std::fs::read(path).unwrap(), 100.0, 50.0, 600.0).unwrap();
+    player.set_barlines(Some(BarlineStyle::default()));
+    player.set_receptors(Some(ReceptorStyle::default()));
+    for compare in [false, true] {
+        player.set_comparison(compare);
+        let calls = record_hooks(&mut player);
+        player.set_current_time(3500.0);
+        common::frame(&egui::Context::default(), |ui| player.render(ui));
+
+        let calls = calls.lock().unwrap();
+        for stage in 0..=usize::from(compare) {
+            let painted = |layer| {
+                calls
+                    .iter()
+                    .find(|(l, s, _)| *l == layer && *s == stage)
+                    .unwrap()
+                    .2
+            };
+            let background = painted(Layer::StageBackground);
+            assert!(painted(Layer::Barlines) > background, "stage {stage}");
+            assert!(
+                painted(Layer::Receptors) > painted(Layer::Barlines),
+                "stage {stage}"
+            );
+            assert!(
+                painted(Layer::Notes) > painted(Layer::Receptors),
+                "stage {stage}"
+            );
+        }
+    }
+}
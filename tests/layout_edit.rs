@@ -0,0 +1,100 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use egui::{pos2, Event as InputEvent, Modifiers, PointerButton, Pos2};
+use rosu_renderer::events::Event;
+use rosu_renderer::transform::Arrangement;
+use rosu_renderer::Player;
+
+fn frame(ctx: &egui::Context, player: &mut Player, time: f64, events: Vec<InputEvent>) {
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            Pos2::ZERO,
+            egui::vec2(1280.0, 960.0),
+        )),
+        time: Some(time),
+        events,
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| player.render(ui));
+    });
+}
+
+fn button(pos: Pos2, pressed: bool) -> InputEvent {
+    InputEvent::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Modifiers::NONE,
+    }
+}
+
+// Drags the handle of column `from` over column `to`, with `before_release`
+// sent just before letting go
+fn drag(player: &mut Player, from: usize, to: usize, before_release: Vec<InputEvent>) {
+    let ctx = egui::Context::default();
+    player.set_layout_edit(true);
+    frame(&ctx, player, 0.0, Vec::new());
+    let stage = player.playfield_rect().unwrap();
+    let handle = |column: usize| {
+        let width = stage.width() / 4.0;
+        pos2(
+            stage.min.x + width * (column as f32 + 0.5),
+            stage.min.y + 8.0,
+        )
+    };
+    let (start, end) = (handle(from), handle(to));
+
+    frame(
+        &ctx,
+        player,
+        0.1,
+        vec![InputEvent::PointerMoved(start), button(start, true)],
+    );
+    let middle = pos2((start.x + end.x) / 2.0, start.y);
+    frame(&ctx, player, 0.2, vec![InputEvent::PointerMoved(middle)]);
+    frame(&ctx, player, 0.3, vec![InputEvent::PointerMoved(end)]);
+    frame(&ctx, player, 0.4, before_release);
+    frame(&ctx, player, 0.5, vec![button(end, false)]);
+}
+
+fn layout_changes(events: &[Event]) -> Vec<Vec<usize>> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::LayoutChanged { mapping, .. } => Some(mapping.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn dropping_a_column_calls_back_with_the_new_mapping() {
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    player.events().on_event(Arc::new(move |event| {
+        sink.lock().unwrap().push(event.clone())
+    }));
+
+    drag(&mut player, 0, 3, Vec::new());
+    assert_eq!(layout_changes(&seen.lock().unwrap()), [vec![3, 0, 1, 2]]);
+    assert_eq!(player.arrangement(), &Arrangement::Custom(vec![3, 0, 1, 2]));
+}
+
+#[test]
+fn escape_cancels_the_drag() {
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    let escape = InputEvent::Key {
+        key: egui::Key::Escape,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: Modifiers::NONE,
+    };
+    drag(&mut player, 0, 3, vec![escape]);
+    assert!(layout_changes(&player.events().drain()).is_empty());
+    assert_eq!(player.arrangement(), &Arrangement::Normal);
+}
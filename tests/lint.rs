@@ -0,0 +1,52 @@
+mod common;
+
+use rosu_renderer::lint::{LintConfig, LintKind};
+
+fn chords(player: &rosu_renderer::Player, max_chord: usize) -> Vec<(f64, usize)> {
+    let config = LintConfig {
+        max_chord: Some(max_chord),
+        ..LintConfig::default()
+    };
+    player
+        .lint(&config)
+        .into_iter()
+        .filter_map(|lint| match lint.kind {
+            LintKind::ChordTooLarge { notes } => Some((lint.time_ms, notes)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn chords_count_holds_still_down() {
+    let player = common::player(
+        vec![
+            common::hold(1000.0, 500.0, 0, 4),
+            common::hold(1000.0, 200.0, 1, 4),
+            common::note(1100.0, 2, 4),
+            common::note(1100.0, 3, 4),
+            // Column 1's hold ended at 1200
+            common::note(1200.0, 1, 4),
+            common::note(1200.0, 2, 4),
+            // Nothing held anymore
+            common::note(1500.0, 0, 4),
+            common::note(1500.0, 1, 4),
+        ],
+        4,
+    );
+    assert_eq!(chords(&player, 2), vec![(1100.0, 4), (1200.0, 3)]);
+    assert_eq!(chords(&player, 3), vec![(1100.0, 4)]);
+    assert!(chords(&player, 4).is_empty());
+}
+
+#[test]
+fn chord_check_scales_to_long_charts() {
+    // A quadratic scan would take minutes over this many overlapping holds
+    let hit_objects = (0..200_000)
+        .map(|i| common::hold(i as f64 * 10.0, 35.0, i % 7, 7))
+        .collect();
+    let player = common::player(hit_objects, 7);
+    let chords = chords(&player, 3);
+    assert_eq!(chords.len(), 199_997);
+    assert!(chords.iter().all(|&(_, notes)| notes == 4));
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use rosu_renderer::description::FrameDescription;
+use rosu_renderer::Player;
+
+fn describe(player: &mut Player) -> FrameDescription {
+    let ctx = egui::Context::default();
+    player.set_current_time(2500.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    let t = player.frame_transform().unwrap().current_time;
+    player.frame_description(t)
+}
+
+#[test]
+fn dense_notes_stay_countable() {
+    // 5 notes 10 ms apart in one column
+    let hit_objects = (0..5)
+        .map(|i| common::note(2000.0 + 10.0 * i as f64, 0, 4))
+        .collect();
+    let mut player = common::player(hit_objects, 4);
+
+    let merged = describe(&mut player);
+    let height = merged.notes[0].size.height;
+    let gaps = |description: &FrameDescription| {
+        let mut ys: Vec<f32> = description.notes.iter().map(|n| n.center.y).collect();
+        ys.sort_by(f32::total_cmp);
+        ys.windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect::<Vec<_>>()
+    };
+    // At default sizes the shapes overlap into a blob
+    assert_eq!(merged.notes.len(), 5);
+    assert!(gaps(&merged).iter().all(|&gap| gap < height));
+
+    player.set_min_visual_gap(Some(height));
+    let nudged = describe(&mut player);
+    assert_eq!(nudged.notes.len(), 5);
+    let gaps = gaps(&nudged);
+    assert!(gaps.iter().all(|&gap| gap >= height - 1e-3), "{gaps:?}");
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use rosu_map::section::hit_objects::HitObjectKind;
+use rosu_renderer::report::LoadIssue;
+use rosu_renderer::Player;
+
+// Start, duration (`None` for notes) and column of every object
+fn objects(player: &Player) -> Vec<(f64, Option<f64>, usize)> {
+    player
+        .hit_objects()
+        .iter()
+        .map(|hit_object| {
+            let duration = match &hit_object.kind {
+                HitObjectKind::Hold(h) => Some(h.duration),
+                _ => None,
+            };
+            let x = common::x_of(hit_object);
+            let column = (0..4).find(|&c| common::column_x(c, 4) == x).unwrap();
+            (hit_object.start_time, duration, column)
+        })
+        .collect()
+}
+
+#[test]
+fn inverted_holds_are_swapped_and_sorted() {
+    let player = common::player(
+        vec![
+            common::note(1000.0, 0, 4),
+            common::hold(2000.0, -1500.0, 1, 4),
+            common::note(3000.0, 2, 4),
+        ],
+        4,
+    );
+    // The hold now starts first
+    assert_eq!(
+        objects(&player),
+        [
+            (500.0, Some(1500.0), 1),
+            (1000.0, None, 0),
+            (3000.0, None, 2)
+        ]
+    );
+    let normalized = LoadIssue::HoldNormalized {
+        index: 0,
+        time: 2000.0,
+        duration: -1500.0,
+    };
+    assert_eq!(player.load_report().issues(), [normalized]);
+    // Nothing is lost from the overview, which relies on the order
+    let marks = player.overview_marks((0.0, 800.0), 800.0);
+    assert_eq!(marks.len(), 1);
+    assert_eq!((marks[0].column, marks[0].start_ms), (1, 500.0));
+}
+
+#[test]
+fn non_finite_durations_become_zero() {
+    let player = common::player(
+        vec![
+            common::hold(1000.0, f64::NAN, 0, 4),
+            common::hold(2000.0, f64::INFINITY, 1, 4),
+            common::hold(3000.0, 200.0, 2, 4),
+        ],
+        4,
+    );
+    // Zero-length holds are then drawn as notes
+    assert_eq!(
+        objects(&player),
+        [
+            (1000.0, None, 0),
+            (2000.0, None, 1),
+            (3000.0, Some(200.0), 2)
+        ]
+    );
+
+    let normalized: Vec<_> = player
+        .load_report()
+        .issues()
+        .iter()
+        .filter_map(|issue| match *issue {
+            LoadIssue::HoldNormalized {
+                index,
+                time,
+                duration,
+            } => Some((index, time, duration)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(normalized.len(), 2);
+    assert!(normalized[0].2.is_nan());
+    assert_eq!((normalized[0].0, normalized[0].1), (0, 1000.0));
+    assert_eq!((normalized[1].0, normalized[1].1), (1, 2000.0));
+    assert_eq!(normalized[1].2, f64::INFINITY);
+}
+
+#[test]
+fn unsorted_lists_are_sorted_stably() {
+    let player = common::player(
+        vec![
+            common::note(3000.0, 0, 4),
+            common::note(1000.0, 1, 4),
+            common::note(1000.0, 2, 4),
+            common::hold(1300.0, -300.0, 3, 4),
+        ],
+        4,
+    );
+    assert_eq!(
+        objects(&player),
+        [
+            (1000.0, None, 1),
+            (1000.0, None, 2),
+            (1000.0, Some(300.0), 3),
+            (3000.0, None, 0),
+        ]
+    );
+    let normalized = LoadIssue::HoldNormalized {
+        index: 2,
+        time: 1300.0,
+        duration: -300.0,
+    };
+    assert_eq!(player.load_report().issues(), [normalized]);
+}
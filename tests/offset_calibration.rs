@@ -0,0 +1,83 @@
+#![cfg(feature = "widgets")]
+
+mod common;
+
+use rosu_renderer::calibration::OffsetCalibration;
+use rosu_renderer::{ui, Player};
+
+fn player() -> Player {
+    let bytes = std::fs::read("tests/fixtures/format_v14.osu").unwrap();
+    Player::from_bytes(&bytes, 100.0, 50.0, 600.0).unwrap()
+}
+
+fn key(pressed: bool, repeat: bool) -> egui::Event {
+    egui::Event::Key {
+        key: egui::Key::Space,
+        physical_key: None,
+        pressed,
+        repeat,
+        modifiers: egui::Modifiers::NONE,
+    }
+}
+
+// One frame of the widget, reading `events`
+fn calibration_frame(
+    ctx: &egui::Context,
+    player: &Player,
+    taps: &mut Vec<f64>,
+    events: Vec<egui::Event>,
+) -> (Option<OffsetCalibration>, Vec<String>) {
+    let input = egui::RawInput {
+        events,
+        ..Default::default()
+    };
+    let mut calibration = None;
+    let output = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            calibration = ui::offset_calibration(ui, player, taps, 3);
+        });
+    });
+    (calibration, common::texts(&output))
+}
+
+#[test]
+fn calibration_ignores_the_playback_rate() {
+    let mut player = player();
+    // The beats are every 500 ms from 1000 ms
+    let taps = [1512.0, 2008.0, 2510.0, 3011.0, 3200.0];
+    let calibration = player.calibrate_offset(&taps).unwrap();
+    assert_eq!(calibration.median, 10.5);
+    assert_eq!(calibration.rejected, 1);
+
+    player.set_speed(1.5);
+    assert_eq!(player.calibrate_offset(&taps), Some(calibration));
+    assert_eq!(player.nearest_beat(1740.0), Some(1500.0));
+    assert_eq!(player.nearest_beat(1760.0), Some(2000.0));
+}
+
+#[test]
+fn widget_counts_key_presses_until_enough_taps() {
+    let ctx = egui::Context::default();
+    let mut player = player();
+    // Taps before the first timing point are near no beat
+    player.set_current_time(2500.0);
+    let mut taps = Vec::new();
+
+    let (calibration, texts) = calibration_frame(&ctx, &player, &mut taps, Vec::new());
+    assert_eq!(calibration, None);
+    assert!(texts.iter().any(|text| text.ends_with("0/3")), "{texts:?}");
+
+    // Repeats and releases are not taps
+    let events = vec![key(true, false), key(true, true), key(false, false)];
+    let (calibration, texts) = calibration_frame(&ctx, &player, &mut taps, events);
+    assert_eq!(calibration, None);
+    assert_eq!(taps.len(), 1);
+    assert!(texts.iter().any(|text| text.ends_with("1/3")), "{texts:?}");
+
+    let events = vec![key(true, false), key(false, false), key(true, false)];
+    let (calibration, _) = calibration_frame(&ctx, &player, &mut taps, events);
+    let calibration = calibration.unwrap();
+    assert_eq!(calibration.samples + calibration.rejected, 3);
+    // The flow starts over once it reports
+    assert!(taps.is_empty());
+}
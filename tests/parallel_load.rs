@@ -0,0 +1,132 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use rosu_map::section::events::BreakPeriod;
+use rosu_map::section::general::GameMode;
+use rosu_map::section::timing_points::{EffectPoint, TimeSignature, TimingPoint};
+use rosu_map::Beatmap;
+use rosu_renderer::events::{CacheKind, Event};
+use rosu_renderer::stats::LnPercent;
+use rosu_renderer::transform::Arrangement;
+use rosu_renderer::{Player, PlayerBuilder};
+
+// About an hour of 7K stream with kiai sections and a break
+fn marathon() -> Beatmap {
+    let mut hit_objects = common::stream(40_000, 45.0, 7);
+    hit_objects.retain(|h| !(600_000.0..660_000.0).contains(&h.start_time));
+    let mut beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 7.0,
+        hit_objects,
+        breaks: vec![BreakPeriod {
+            start_time: 600_500.0,
+            end_time: 659_500.0,
+        }],
+        ..Default::default()
+    };
+    let signature = TimeSignature::new_simple_quadruple();
+    beatmap
+        .control_points
+        .add(TimingPoint::new(1000.0, 360.0, false, signature));
+    for minute in 0..30 {
+        let time = f64::from(minute) * 60_000.0;
+        beatmap
+            .control_points
+            .add(EffectPoint::new(time, minute % 3 == 1));
+    }
+    beatmap
+}
+
+fn build(parallel: bool) -> Player {
+    let builder = PlayerBuilder::new(marathon())
+        .seed(7)
+        .parallel_load(parallel);
+    builder.build().unwrap()
+}
+
+fn assert_same_caches(serial: &Player, parallel: &Player) {
+    assert_eq!(serial.hit_objects(), parallel.hit_objects());
+    assert_eq!(serial.column_counts(), parallel.column_counts());
+    assert_eq!(serial.composition(), parallel.composition());
+    assert_eq!(serial.sections(), parallel.sections());
+    assert_eq!(serial.hitsound_issues(), parallel.hitsound_issues());
+}
+
+#[test]
+fn parallel_load_matches_serial_load() {
+    let (mut serial, mut parallel) = (build(false), build(true));
+    parallel.wait_for_caches();
+    assert!(parallel.caches_ready());
+    assert!(serial.sections().len() > 20);
+    assert_same_caches(&serial, &parallel);
+
+    // Rebuilt the same way when the objects are derived again
+    for player in [&mut serial, &mut parallel] {
+        player.set_arrangement(Arrangement::Random);
+        player.wait_for_caches();
+    }
+    assert_same_caches(&serial, &parallel);
+}
+
+#[test]
+fn caches_are_announced_once_ready() {
+    let serial = build(false);
+    let mut parallel = build(true);
+    // Nothing is picked up before a frame, the notes render regardless
+    assert!(!parallel.caches_ready());
+    assert!(parallel.column_counts().is_empty());
+    assert!(parallel.sections().is_empty());
+    parallel.set_current_time(5000.0);
+    let description = parallel.frame_description(5000.0);
+    assert!(!description.notes.is_empty());
+
+    let ctx = egui::Context::default();
+    let started = Instant::now();
+    let mut ready = Vec::new();
+    while !parallel.caches_ready() {
+        assert!(started.elapsed() < Duration::from_secs(60));
+        common::frame(&ctx, |ui| parallel.render(ui));
+        ready.extend(
+            parallel
+                .events()
+                .drain()
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::CacheReady { cache, .. } => Some(cache),
+                    _ => None,
+                }),
+        );
+    }
+    ready.sort_by_key(|cache| *cache as u8);
+    let expected = [
+        CacheKind::ColumnStats,
+        CacheKind::Composition,
+        CacheKind::Sections,
+        CacheKind::HitsoundIssues,
+    ];
+    assert_eq!(ready, expected);
+    assert_same_caches(&serial, &parallel);
+}
+
+#[test]
+fn refreshing_a_pending_cache_settles_it() {
+    let mut parallel = build(true);
+    parallel.set_ln_percent(LnPercent::Duration);
+    let ready: Vec<_> = parallel.events().drain();
+    let composition = Event::CacheReady {
+        cache: CacheKind::Composition,
+        at_map_time: 0.0,
+    };
+    assert_eq!(ready, std::slice::from_ref(&composition));
+
+    // The composition of the load thread doesn't replace it later
+    parallel.wait_for_caches();
+    let ready = parallel.events().drain();
+    assert_eq!(ready.len(), 3);
+    assert!(!ready.contains(&composition));
+
+    let mut serial = build(false);
+    serial.set_ln_percent(LnPercent::Duration);
+    assert_same_caches(&serial, &parallel);
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use rosu_map::section::general::GameMode;
+use rosu_map::section::hit_objects::hit_samples::SampleBank;
+use rosu_map::section::timing_points::SamplePoint;
+use rosu_map::Beatmap;
+use rosu_renderer::events::{PassedKind, MAX_PASSED_NOTES};
+use rosu_renderer::PlayerBuilder;
+
+// Clock time putting map time `line_time` on the judgment line, at the
+// default speed and scroll time
+fn clock(line_time: f64) -> f64 {
+    line_time + 1000.0
+}
+
+#[test]
+fn five_second_gap_passes_everything_in_order() {
+    let mut hit_objects = common::stream(97, 50.0, 4);
+    hit_objects.push(common::note(6500.0, 0, 4));
+    let mut player = common::player(hit_objects, 4);
+    let ctx = egui::Context::default();
+
+    player.set_current_time(clock(900.0));
+    common::frame(&ctx, |ui| player.render(ui));
+    assert!(player.drain_passed_notes().notes.is_empty());
+
+    // Nothing is drawn for five seconds, then a single late frame
+    player.set_current_time(clock(5950.0));
+    common::frame(&ctx, |ui| player.render(ui));
+    let passed = player.drain_passed_notes();
+    assert!(!passed.truncated);
+
+    // Every head up to 5800 and the tails up to 5925, none twice
+    let heads = passed
+        .notes
+        .iter()
+        .filter(|n| n.kind != PassedKind::HoldTail)
+        .count();
+    let tails = passed
+        .notes
+        .iter()
+        .filter(|n| n.kind == PassedKind::HoldTail)
+        .count();
+    assert_eq!(heads, 97);
+    assert_eq!(tails, 33);
+    assert!(passed
+        .notes
+        .windows(2)
+        .all(|pair| pair[0].time_ms <= pair[1].time_ms));
+    for tail in passed
+        .notes
+        .iter()
+        .filter(|n| n.kind == PassedKind::HoldTail)
+    {
+        let head = passed
+            .notes
+            .iter()
+            .position(|n| n.note_index == tail.note_index)
+            .unwrap();
+        let tail = passed.notes.iter().position(|n| n == tail).unwrap();
+        assert!(head < tail);
+    }
+}
+
+#[test]
+fn huge_jump_is_truncated_to_the_first_notes() {
+    let mut player = common::player(common::stream(5000, 10.0, 7), 7);
+    let ctx = egui::Context::default();
+    player.set_current_time(clock(0.0));
+    common::frame(&ctx, |ui| player.render(ui));
+
+    player.set_current_time(clock(100_000.0));
+    common::frame(&ctx, |ui| player.render(ui));
+    let passed = player.drain_passed_notes();
+    assert!(passed.truncated);
+    assert_eq!(passed.notes.len(), MAX_PASSED_NOTES);
+    assert_eq!(passed.notes[0].time_ms, 1000.0);
+}
+
+#[test]
+fn seeking_back_passes_nothing() {
+    let mut player = common::player(common::stream(100, 50.0, 4), 4);
+    let ctx = egui::Context::default();
+    player.set_current_time(clock(5000.0));
+    common::frame(&ctx, |ui| player.render(ui));
+    player.drain_passed_notes();
+
+    player.set_current_time(clock(2000.0));
+    common::frame(&ctx, |ui| player.render(ui));
+    assert!(player.drain_passed_notes().notes.is_empty());
+}
+
+#[test]
+fn animated_scroll_time_passes_no_note_twice() {
+    let mut player = common::player(common::stream(400, 10.0, 4), 4);
+    player.set_scroll_animation(Some(100.0));
+    let ctx = egui::Context::default();
+    player.set_current_time(clock(2000.0));
+
+    let mut passed = Vec::new();
+    let mut render_for = |player: &mut rosu_renderer::Player, ms: u64| {
+        let until = std::time::Instant::now() + std::time::Duration::from_millis(ms);
+        while std::time::Instant::now() < until {
+            common::frame(&ctx, |ui| player.render(ui));
+            passed.extend(player.drain_passed_notes().notes);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+    };
+    render_for(&mut player, 50);
+    // The line eases 500 ms back, then forward again over the same notes
+    player.set_scroll_time(1500.0);
+    render_for(&mut player, 150);
+    player.set_scroll_time(1000.0);
+    render_for(&mut player, 150);
+
+    assert!(!passed.is_empty());
+    for (i, note) in passed.iter().enumerate() {
+        let twice = passed[..i]
+            .iter()
+            .any(|prev| prev.note_index == note.note_index && prev.kind == note.kind);
+        assert!(!twice, "{note:?} passed twice");
+    }
+}
+
+#[test]
+fn passed_notes_carry_their_resolved_sample() {
+    let mut beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 4.0,
+        hit_objects: vec![
+            common::note(1000.0, 0, 4),
+            common::hold(1500.0, 498.0, 1, 4),
+        ],
+        ..Default::default()
+    };
+    let points = [
+        (0.0, SampleBank::Soft, 60, 2),
+        (2000.0, SampleBank::Drum, 30, 0),
+    ];
+    for (time, bank, volume, index) in points {
+        beatmap
+            .control_points
+            .add(SamplePoint::new(time, bank, volume, index));
+    }
+    let mut player = PlayerBuilder::new(beatmap).build().unwrap();
+    let ctx = egui::Context::default();
+    player.set_current_time(clock(900.0));
+    common::frame(&ctx, |ui| player.render(ui));
+    player.set_current_time(clock(2500.0));
+    common::frame(&ctx, |ui| player.render(ui));
+
+    let notes = player.drain_passed_notes().notes;
+    assert_eq!(notes.len(), 3);
+    for note in &notes {
+        assert_eq!(Some(note.sample), player.resolved_sample(note.note_index));
+    }
+    assert_eq!(
+        (notes[0].sample.set, notes[0].sample.volume),
+        (SampleBank::Soft, 60)
+    );
+    assert_eq!(notes[0].sample.index, 2);
+    // The hold ends within the leniency of the second point
+    for hold in &notes[1..] {
+        assert_eq!(
+            (hold.sample.set, hold.sample.volume),
+            (SampleBank::Drum, 30)
+        );
+    }
+}
+
+#[test]
+fn invalid_speed_change_passes_nothing() {
+    let mut player = common::player(common::stream(200, 25.0, 4), 4);
+    let ctx = egui::Context::default();
+    player.set_speed(0.5);
+    player.set_current_time(clock(1000.0));
+    common::frame(&ctx, |ui| player.render(ui));
+    player.drain_passed_notes();
+
+    // Falling back to 1.0 moves the line like a seek
+    for speed in [f64::NAN, 0.0, -2.0, f64::INFINITY] {
+        player.set_speed(0.5);
+        common::frame(&ctx, |ui| player.render(ui));
+        player.drain_passed_notes();
+        player.set_speed(speed);
+        common::frame(&ctx, |ui| player.render(ui));
+        let passed = player.drain_passed_notes();
+        assert!(passed.notes.is_empty(), "{speed}: {:?}", passed.notes);
+    }
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use rosu_renderer::layout::mania::{ManiaRenderer, MAX_KEYCOUNT};
+
+#[test]
+fn columns_past_the_keycount_are_ignored() {
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    player.set_column_pressed(3, true);
+    player.set_column_pressed(4, true);
+    player.set_column_pressed(usize::MAX, true);
+    assert!(player.is_column_pressed(3));
+    assert!(!player.is_column_pressed(4));
+    assert!(!player.is_column_pressed(usize::MAX));
+}
+
+#[test]
+fn renderer_caps_pressed_columns() {
+    let mut mania = ManiaRenderer::with_sizes(80.0, 60.0, 800.0);
+    mania.set_column_pressed(usize::MAX, true);
+    mania.set_column_pressed(MAX_KEYCOUNT, true);
+    mania.set_column_pressed(MAX_KEYCOUNT - 1, true);
+    assert!(!mania.is_column_pressed(usize::MAX));
+    assert!(!mania.is_column_pressed(MAX_KEYCOUNT));
+    assert!(mania.is_column_pressed(MAX_KEYCOUNT - 1));
+}
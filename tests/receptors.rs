@@ -0,0 +1,29 @@
+mod common;
+
+use rosu_renderer::layout::mania::ReceptorStyle;
+
+#[test]
+fn receptors_slide_with_their_columns() {
+    let mut player = common::player(common::stream(40, 100.0, 4), 4);
+    player.set_receptors(Some(ReceptorStyle::default()));
+    player.set_swap_animation(Some(600_000.0));
+    player.set_mirror(true);
+    common::frame(&egui::Context::default(), |ui| player.render(ui));
+
+    let description = player.frame_description(2000.0);
+    let column_center =
+        |column: usize| description.stage.min.x + (column as f32 + 0.5) * description.column_width;
+    let receptor_x = |column: usize| {
+        let receptor = description.receptors.iter().find(|r| r.column == column);
+        receptor.unwrap().center.x
+    };
+    // Right after the swap starts every column is still far from its place
+    assert_eq!(description.receptors.len(), 4);
+    for receptor in &description.receptors {
+        assert!((receptor.center.x - column_center(receptor.column)).abs() > 1.0);
+    }
+    assert!(!description.notes.is_empty());
+    for note in &description.notes {
+        assert!((note.center.x - receptor_x(note.column)).abs() < 1e-3);
+    }
+}
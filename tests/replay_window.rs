@@ -0,0 +1,94 @@
+mod common;
+
+use rosu_renderer::layout::mania::TimingTextStyle;
+use rosu_renderer::Player;
+
+// Clock time putting map time `line_time` on the judgment line, at the
+// default speed and scroll time
+fn clock(line_time: f64) -> f64 {
+    line_time + 1000.0
+}
+
+fn player() -> Player {
+    let mut player = common::player(common::stream(40, 100.0, 4), 4);
+    player.set_timing_text(Some(TimingTextStyle {
+        shared: true,
+        ..Default::default()
+    }));
+    player
+}
+
+fn shows(player: &mut Player, ctx: &egui::Context, text: &str) -> bool {
+    let output = common::output(ctx, |ui| player.render(ui));
+    common::texts(&output).iter().any(|t| t == text)
+}
+
+#[test]
+fn invalid_windows_are_ignored() {
+    let mut player = player();
+    assert!(!player.replay_window(2000.0, 1000.0, 1.0));
+    assert!(!player.replay_window(1000.0, 1000.0, 1.0));
+    for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(!player.replay_window(1000.0, 2000.0, rate));
+    }
+    assert!(!player.is_replaying());
+}
+
+#[test]
+fn window_before_the_clock_start_begins_at_zero() {
+    let ctx = egui::Context::default();
+    let mut player = player();
+    player.set_current_time(3000.0);
+    assert!(player.replay_window(-3000.0, 500.0, 0.25));
+    assert!(player.is_replaying());
+    assert!(player.current_time() < 50.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    assert!(player.is_replaying());
+}
+
+#[test]
+fn recorded_visuals_reappear_at_their_times() {
+    let ctx = egui::Context::default();
+    let mut player = player();
+    player.set_current_time(clock(1400.0));
+    player.report_hit(0, -12.0, 1500.0);
+    player.report_hit(1, 7.0, 1800.0);
+    player.set_current_time(clock(5000.0));
+    assert!(!shows(&mut player, &ctx, "-12 ms"));
+
+    assert!(player.replay_window(1000.0, 2000.0, 1.0));
+    assert!(!shows(&mut player, &ctx, "-12 ms"));
+    player.set_current_time(clock(1550.0));
+    assert!(shows(&mut player, &ctx, "-12 ms"));
+    assert!(!shows(&mut player, &ctx, "+7 ms"));
+    player.set_current_time(clock(1850.0));
+    assert!(shows(&mut player, &ctx, "+7 ms"));
+    // Reports aren't recorded twice by being shown again
+    assert_eq!(player.session_stats().errors, [Some(-12.0), Some(7.0)]);
+}
+
+#[test]
+fn state_is_restored_after_the_window() {
+    let ctx = egui::Context::default();
+    let mut player = player();
+    player.set_speed(1.5);
+    player.set_current_time(6000.0);
+    let resume = player.current_time();
+
+    assert!(player.replay_window(1000.0, 2000.0, 0.25));
+    assert_eq!(player.scroll_transform().speed, 0.25);
+    // Past the end of the window
+    let end = player.scroll_transform().crossing_time(2000.0);
+    player.set_current_time(end + 10.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    assert!(!player.is_replaying());
+    assert_eq!(player.scroll_transform().speed, 1.5);
+    assert!((player.current_time() - resume).abs() < 50.0);
+
+    // Ending early restores it too
+    assert!(player.replay_window(1000.0, 2000.0, 0.5));
+    player.end_replay();
+    assert!(!player.is_replaying());
+    assert_eq!(player.scroll_transform().speed, 1.5);
+    assert!((player.current_time() - resume).abs() < 50.0);
+}
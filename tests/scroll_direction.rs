@@ -0,0 +1,76 @@
+mod common;
+
+use egui::{Color32, Rect};
+use rosu_renderer::layout::mania::{ManiaRenderer, ScrollDirection};
+
+// Every filled rect and circle drawn, circles by their bounding rects
+fn rects(output: &egui::FullOutput) -> Vec<(Rect, Color32)> {
+    fn collect(shape: &egui::Shape, rects: &mut Vec<(Rect, Color32)>) {
+        match shape {
+            egui::Shape::Rect(rect) => rects.push((rect.rect, rect.fill)),
+            egui::Shape::Circle(circle) => rects.push((
+                Rect::from_center_size(circle.center, egui::Vec2::splat(circle.radius * 2.0)),
+                circle.fill,
+            )),
+            egui::Shape::Vec(shapes) => shapes.iter().for_each(|s| collect(s, rects)),
+            _ => {}
+        }
+    }
+    let mut rects = Vec::new();
+    for clipped in &output.shapes {
+        collect(&clipped.shape, &mut rects);
+    }
+    rects
+}
+
+// Shapes drawn inside the playfield with `current_time` at the top of the
+// stage, scrolling in `direction`, and the y of the judgment line
+fn frame(direction: ScrollDirection, current_time: f64) -> (Vec<(Rect, Color32)>, f32) {
+    let hit_objects = common::stream(60, 60.0, 4);
+    let mut renderer = ManiaRenderer::with_sizes(80.0, 60.0, 600.0);
+    renderer.set_scroll_direction(direction);
+    renderer.report_hold_break(9, 1560.0);
+    let ctx = egui::Context::default();
+    let output = common::output(&ctx, |ui| {
+        renderer.render(ui, &hit_objects, current_time, 800.0, 1.0, 4);
+    });
+    let transform = renderer.frame_transform().unwrap();
+    let mut rects = rects(&output);
+    rects.retain(|(rect, _)| transform.playfield.contains_rect(*rect));
+    (rects, transform.judgment_line_y)
+}
+
+fn near(a: Rect, b: Rect) -> bool {
+    [
+        a.min.x - b.min.x,
+        a.max.x - b.max.x,
+        a.min.y - b.min.y,
+        a.max.y - b.max.y,
+    ]
+    .iter()
+    .all(|d| d.abs() < 0.01)
+}
+
+#[test]
+fn upscroll_mirrors_downscroll() {
+    // Before the first note, with holds crossing the line, a broken one
+    // among them, and notes entering at the far edge
+    let mut compared = 0;
+    for current_time in [0.0, 1000.0, 1870.0, 2650.0, 4300.0] {
+        let (down, down_line) = frame(ScrollDirection::Down, current_time);
+        let (up, up_line) = frame(ScrollDirection::Up, current_time);
+        assert_eq!(down.len(), up.len(), "at {current_time}");
+        // Both lines sit as far from their edge of the stage
+        let axis = down_line + up_line;
+        compared += down.len();
+        for (rect, color) in down {
+            let flipped =
+                Rect::from_x_y_ranges(rect.x_range(), axis - rect.max.y..=axis - rect.min.y);
+            assert!(
+                up.iter().any(|(r, c)| *c == color && near(*r, flipped)),
+                "{rect:?} not mirrored at {current_time}"
+            );
+        }
+    }
+    assert!(compared > 50, "only {compared} shapes");
+}
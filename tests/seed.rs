@@ -0,0 +1,58 @@
+mod common;
+
+use rosu_map::section::hit_objects::HitObject;
+use rosu_renderer::rng::SeededRng;
+use rosu_renderer::transform::Arrangement;
+
+fn randomized(seed: u64) -> Vec<HitObject> {
+    let mut player = common::builder(common::stream(200, 80.0, 7), 7)
+        .seed(seed)
+        .build()
+        .unwrap();
+    player.set_arrangement(Arrangement::Random);
+    player.hit_objects().to_vec()
+}
+
+// Bits of every time and x, so equal floats of different encodings differ
+fn bits(hit_objects: &[HitObject]) -> Vec<(u64, u32)> {
+    hit_objects
+        .iter()
+        .map(|h| (h.start_time.to_bits(), common::x_of(h).to_bits()))
+        .collect()
+}
+
+#[test]
+fn sequence_is_pinned() {
+    let mut rng = SeededRng::new(0);
+    let values: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+    assert_eq!(
+        values,
+        [
+            0x7bbc_b40d_5506_82d0,
+            0xde7f_e413_d00c_c9fd,
+            0xb3c6_3835_3c66_8c91
+        ]
+    );
+
+    let mut columns: Vec<usize> = (0..7).collect();
+    SeededRng::new(42).shuffle(&mut columns);
+    assert_eq!(columns, [0, 5, 6, 3, 4, 1, 2]);
+}
+
+#[test]
+fn same_seed_same_objects() {
+    assert_eq!(bits(&randomized(7)), bits(&randomized(7)));
+    assert_ne!(bits(&randomized(7)), bits(&randomized(8)));
+}
+
+#[test]
+fn transforms_rederive_from_the_seed() {
+    let mut player = common::player(common::stream(200, 80.0, 7), 7);
+    assert_eq!(player.seed(), 7);
+    player.set_arrangement(Arrangement::Random);
+    let first = bits(player.hit_objects());
+    player.set_arrangement(Arrangement::Mirror);
+    player.set_arrangement(Arrangement::Random);
+    assert_eq!(bits(player.hit_objects()), first);
+    assert_eq!(first, bits(&randomized(7)));
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use rosu_map::section::general::GameMode;
+use rosu_map::section::timing_points::{TimeSignature, TimingPoint};
+use rosu_map::Beatmap;
+use rosu_renderer::snap::Divisor;
+use rosu_renderer::{Player, PlayerBuilder};
+
+fn player(timing_points: &[(f64, f64)]) -> Player {
+    let mut beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 4.0,
+        hit_objects: common::stream(100, 50.0, 4),
+        ..Default::default()
+    };
+    for &(time, beat_len) in timing_points {
+        let signature = TimeSignature::new_simple_quadruple();
+        let point = TimingPoint::new(time, beat_len, false, signature);
+        beatmap.control_points.add(point);
+    }
+    PlayerBuilder::new(beatmap).build().unwrap()
+}
+
+// Map time on the judgment line, at the default speed and scroll time
+fn line_time(player: &Player) -> f64 {
+    player.current_time() - 1000.0
+}
+
+fn assert_near(actual: f64, expected: f64) {
+    // The clock runs between the seek and the read
+    assert!((actual - expected).abs() < 5.0, "{actual} != {expected}");
+}
+
+#[test]
+fn snapping_before_the_first_timing_point_stays_at_zero() {
+    let mut player = player(&[(800.0, 1000.0)]);
+    player.set_seek_snapping(Some(Divisor::WHOLE));
+    // The line at -1200 ms crosses at -200 ms
+    player.seek_fraction(0.0);
+    assert_near(player.current_time(), 0.0);
+}
+
+#[test]
+fn negative_and_invalid_times_dont_panic() {
+    let mut player = player(&[]);
+    player.set_current_time(-200.0);
+    assert_near(player.current_time(), 0.0);
+
+    player.set_current_time(1500.0);
+    for time in [f64::NAN, f64::INFINITY, f64::MAX] {
+        player.set_current_time(time);
+        assert_near(player.current_time(), 1500.0);
+    }
+    player.set_current_time(f64::NEG_INFINITY);
+    assert_near(player.current_time(), 0.0);
+}
+
+#[test]
+fn snapping_stops_at_bpm_changes() {
+    let mut player = player(&[(0.0, 700.0), (2000.0, 300.0)]);
+    player.set_seek_snapping(Some(Divisor::WHOLE));
+    let seek_line = |player: &mut Player, line_time: f64| {
+        player.seek_fraction((line_time + 1000.0) / player.duration());
+    };
+
+    // 2100 ms is the closest 700 ms line, but the next point starts at 2000
+    seek_line(&mut player, 1900.0);
+    assert_near(line_time(&player), 2000.0);
+    seek_line(&mut player, 1500.0);
+    assert_near(line_time(&player), 1400.0);
+    // Lines of the second point
+    seek_line(&mut player, 2250.0);
+    assert_near(line_time(&player), 2300.0);
+
+    player.set_seek_snapping(Some(Divisor::QUARTER));
+    seek_line(&mut player, 1900.0);
+    assert_near(line_time(&player), 1925.0);
+
+    player.seek_fraction_unsnapped((1900.0 + 1000.0) / player.duration());
+    assert_near(line_time(&player), 1900.0);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use rosu_renderer::score::{ScoreError, ScoreMode};
+
+#[test]
+fn session_stats_collect_the_reported_judgments() {
+    let mut player = common::player(common::stream(8, 100.0, 4), 4);
+    for (index, offset) in [0.0, 5.0, -10.0].into_iter().enumerate() {
+        player.report_hit(index % 4, offset, 1000.0 + 100.0 * index as f64);
+    }
+    player.report_hold_break(2, 1250.0);
+    player.report_hit(3, 3.0, 1300.0);
+    player.report_ghost_tap(0, 1350.0);
+    // Past every window, a miss
+    player.report_hit(0, 400.0, 1400.0);
+    player.report_hit(1, -2.0, 1500.0);
+
+    let stats = player.session_stats();
+    let errors = [0.0, 5.0, -10.0].map(Some);
+    assert_eq!(stats.errors[..3], errors);
+    assert_eq!(
+        stats.errors[3..],
+        [None, Some(3.0), Some(400.0), Some(-2.0)]
+    );
+    assert_eq!(stats.max_combo, 3);
+    assert_eq!(stats.map_note_count, 8);
+    assert_eq!(stats.ghost_taps, 1);
+    assert_eq!(stats.windows, player.windows());
+
+    // 5 Marvs out of 8 notes
+    assert_eq!(stats.normalized_score(ScoreMode::Classic), Ok(625_000.0));
+    let v2 = stats.normalized_score(ScoreMode::ScoreV2).unwrap();
+    assert!((v2 - 622_500.0).abs() < 1e-6, "{v2}");
+}
+
+#[test]
+fn session_stats_reject_more_judgments_than_notes() {
+    let mut player = common::player(common::stream(2, 100.0, 4), 4);
+    for index in 0..3 {
+        player.report_hit(0, 0.0, 1000.0 + 100.0 * index as f64);
+    }
+    let error = ScoreError::TooManyJudgments {
+        judged: 3,
+        map_note_count: 2,
+    };
+    let stats = player.session_stats();
+    assert_eq!(stats.normalized_score(ScoreMode::Wife3), Err(error));
+}
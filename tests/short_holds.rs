@@ -0,0 +1,80 @@
+mod common;
+
+use rosu_renderer::description::NoteKind;
+use rosu_renderer::events::PassedKind;
+use rosu_renderer::report::LoadIssue;
+
+// Holds of 0, 0.5 and 2 ms in the first three columns
+fn player() -> rosu_renderer::Player {
+    common::player(
+        vec![
+            common::hold(1000.0, 0.0, 0, 4),
+            common::hold(1200.0, 0.5, 1, 4),
+            common::hold(1400.0, 2.0, 2, 4),
+        ],
+        4,
+    )
+}
+
+#[test]
+fn holds_under_the_epsilon_are_reported() {
+    let player = player();
+    let converted = [(0, 1000.0, 0.0), (1, 1200.0, 0.5)].map(|(index, time, duration)| {
+        LoadIssue::ShortHoldConverted {
+            index,
+            time,
+            duration,
+        }
+    });
+    assert_eq!(player.load_report().issues(), converted);
+}
+
+#[test]
+fn short_holds_are_drawn_as_notes() {
+    let mut player = player();
+    let ctx = egui::Context::default();
+    player.set_current_time(1900.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    let t = player.frame_transform().unwrap().current_time;
+    let description = player.frame_description(t);
+
+    let kinds: Vec<_> = description
+        .notes
+        .iter()
+        .map(|note| (note.note_index, note.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (0, NoteKind::Note),
+            (1, NoteKind::Note),
+            (2, NoteKind::HoldHead)
+        ]
+    );
+    let holds: Vec<_> = description.holds.iter().map(|h| h.note_index).collect();
+    assert_eq!(holds, [2]);
+}
+
+#[test]
+fn short_holds_pass_once() {
+    let mut player = player();
+    let ctx = egui::Context::default();
+    player.set_current_time(1900.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    player.set_current_time(3000.0);
+    common::frame(&ctx, |ui| player.render(ui));
+
+    let passed: Vec<_> = player
+        .drain_passed_notes()
+        .notes
+        .iter()
+        .map(|note| (note.note_index, note.kind))
+        .collect();
+    let expected = [
+        (0, PassedKind::Note),
+        (1, PassedKind::Note),
+        (2, PassedKind::HoldHead),
+        (2, PassedKind::HoldTail),
+    ];
+    assert_eq!(passed, expected);
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "osk")]
+
+mod common;
+
+use rosu_renderer::images::ImageProvider;
+use rosu_renderer::skin::{Skin, SkinWarning};
+
+fn fixture() -> Skin {
+    Skin::from_osk("tests/fixtures/skin.osk").unwrap()
+}
+
+#[test]
+fn prefers_2x_images_whatever_the_archive_order() {
+    let skin = fixture();
+    let ctx = egui::Context::default();
+    // mania-note1@2x.png comes before mania-note1.png, Notes/Blue.png
+    // before Notes/Blue@2x.png
+    for key in ["mania-note1", "notes/blue"] {
+        let texture = skin.images().resolve(&ctx, key).unwrap();
+        assert_eq!(texture.size, egui::vec2(4.0, 4.0), "{key}");
+    }
+}
+
+#[test]
+fn reads_per_column_images_and_reports_missing_ones() {
+    let skin = fixture();
+    let config = skin.mania(4);
+    assert_eq!(
+        config.note_images,
+        [
+            "mania-note1",
+            "notes/blue",
+            "notes/blue",
+            "mania-note-missing"
+        ]
+    );
+    assert_eq!(
+        skin.warnings(),
+        [SkinWarning::MissingImage {
+            keycount: 4,
+            key: "mania-note-missing".to_owned(),
+        }]
+    );
+    // No section for 7K, so every column uses the default image
+    assert_eq!(skin.mania(7).note_images, vec!["mania-note1"; 7]);
+}
+
+#[test]
+fn apply_skin_draws_each_column_with_its_image() {
+    let skin = fixture();
+    let ctx = egui::Context::default();
+    let hit_objects = (0..4)
+        .map(|column| common::note(1200.0, column, 4))
+        .collect();
+    let mut player = common::player(hit_objects, 4);
+    assert!(player.apply_skin(&skin, &ctx));
+    player.set_current_time(1500.0);
+
+    let output = common::output(&ctx, |ui| player.render(ui));
+    let note1 = skin.images().resolve(&ctx, "mania-note1").unwrap().id;
+    let blue = skin.images().resolve(&ctx, "notes/blue").unwrap().id;
+    let mut textures = Vec::new();
+    for clipped in &output.shapes {
+        if let egui::Shape::Rect(rect) = &clipped.shape {
+            textures.extend(rect.brush.as_ref().map(|brush| brush.fill_texture_id));
+        }
+    }
+    // The column with the missing image keeps the default shape
+    assert_eq!(textures, [note1, blue, blue]);
+}
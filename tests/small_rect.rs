@@ -0,0 +1,74 @@
+mod common;
+
+use egui::{Color32, Rect};
+use rosu_renderer::layout::mania::FitPolicy;
+use rosu_renderer::Player;
+
+fn player() -> Player {
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    player.set_current_time(1500.0);
+    player
+}
+
+fn render_in(player: &mut Player, rect: Rect) -> (Rect, egui::FullOutput) {
+    let ctx = egui::Context::default();
+    let mut drawn = Rect::NOTHING;
+    let output = common::output(&ctx, |ui| drawn = player.render_in(ui, rect));
+    (drawn, output)
+}
+
+// Clip rects of what the player drew, after the panel's background
+fn clip_rects(output: &egui::FullOutput) -> impl Iterator<Item = Rect> + '_ {
+    output.shapes.iter().skip(1).map(|shape| shape.clip_rect)
+}
+
+fn small() -> Rect {
+    Rect::from_min_size(egui::pos2(10.0, 10.0), egui::vec2(60.0, 300.0))
+}
+
+#[test]
+fn clipping_below_the_minimum_draws_a_placeholder() {
+    let mut player = player();
+    let (drawn, output) = render_in(&mut player, small());
+    assert_eq!(drawn, small());
+    assert_eq!(common::texts(&output), ["Too small"]);
+    assert!(clip_rects(&output).all(|clip| small().contains_rect(clip)));
+    assert_eq!(player.playfield_rect(), None);
+
+    // A smaller minimum lets the stage draw, clipped
+    player.set_min_render_size(egui::vec2(40.0, 40.0));
+    let (drawn, output) = render_in(&mut player, small());
+    assert!(drawn.width() > small().width());
+    assert!(!common::texts(&output).contains(&"Too small".to_owned()));
+}
+
+#[test]
+fn scaling_policies_ignore_the_minimum() {
+    for policy in [FitPolicy::ScaleToFit, FitPolicy::ScaleToFill] {
+        let mut player = player();
+        player.set_fit_policy(policy);
+        let (drawn, output) = render_in(&mut player, small());
+        assert!(!common::texts(&output).contains(&"Too small".to_owned()));
+        assert_eq!(drawn.center(), small().center());
+        assert!(player.playfield_rect().is_some());
+    }
+}
+
+#[test]
+fn stages_larger_than_the_rect_are_clipped_and_shaded() {
+    let mut player = player();
+    let rect = Rect::from_min_size(egui::pos2(10.0, 10.0), egui::vec2(200.0, 300.0));
+    let (drawn, output) = render_in(&mut player, rect);
+    // The stage keeps its size, centered and overflowing the rect
+    assert!(drawn.width() > rect.width() && drawn.height() > rect.height());
+    assert_eq!(drawn.center(), rect.center());
+    assert!(clip_rects(&output).all(|clip| rect.contains_rect(clip)));
+
+    let shade = Color32::from_black_alpha(120);
+    let shades = output
+        .shapes
+        .iter()
+        .filter(|s| matches!(&s.shape, egui::Shape::Rect(r) if r.fill == shade))
+        .count();
+    assert_eq!(shades, 4);
+}
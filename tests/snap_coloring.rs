@@ -0,0 +1,86 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use rosu_map::section::general::GameMode;
+use rosu_map::section::timing_points::{TimeSignature, TimingPoint};
+use rosu_map::Beatmap;
+use rosu_renderer::layout::mania::SnapColoring;
+use rosu_renderer::{Player, PlayerBuilder};
+
+// 400 ms beats from 0, then 300 ms beats from 1000.7
+const NOTES: [f64; 7] = [400.0, 600.0, 999.0, 1001.5, 1076.7, 1100.7, 1028.7];
+
+fn player() -> Player {
+    let mut beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 4.0,
+        hit_objects: NOTES
+            .iter()
+            .enumerate()
+            .map(|(i, time)| common::note(*time, i % 4, 4))
+            .collect(),
+        ..Default::default()
+    };
+    beatmap
+        .hit_objects
+        .sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    for (time, beat_len) in [(0.0, 400.0), (1000.7, 300.0)] {
+        let signature = TimeSignature::new_simple_quadruple();
+        beatmap
+            .control_points
+            .add(TimingPoint::new(time, beat_len, false, signature));
+    }
+    PlayerBuilder::new(beatmap).build().unwrap()
+}
+
+// Divisor each note was colored for by its time, 0 for unsnapped
+fn divisors(coloring: SnapColoring) -> BTreeMap<String, u32> {
+    let mut player = player();
+    player.set_snap_coloring(Some(coloring.clone()));
+    common::frame(&egui::Context::default(), |ui| player.render(ui));
+    let mut divisors = BTreeMap::new();
+    for clock in [1000.0, 1500.0, 2000.0] {
+        for note in player.frame_description(clock).notes {
+            let divisor = coloring
+                .palette
+                .iter()
+                .find(|(_, color)| color.to_srgba_unmultiplied() == note.color)
+                .map_or(0, |(divisor, _)| *divisor);
+            let time = player.hit_objects()[note.note_index].start_time;
+            divisors.insert(time.to_string(), divisor);
+        }
+    }
+    divisors
+}
+
+#[test]
+fn notes_snap_to_the_active_timing_point() {
+    let divisors = divisors(SnapColoring::default());
+    let expected = [
+        ("400", 1),
+        ("600", 2),
+        // Just before the second point, whose start is a line
+        ("999", 1),
+        ("1001.5", 1),
+        ("1028.7", 0),
+        ("1076.7", 4),
+        ("1100.7", 3),
+    ];
+    let expected = expected.map(|(time, divisor)| (time.to_string(), divisor));
+    assert_eq!(divisors, BTreeMap::from(expected));
+}
+
+#[test]
+fn tolerance_bounds_the_distance_to_a_line() {
+    let coloring = SnapColoring {
+        tolerance_ms: 0.5,
+        ..Default::default()
+    };
+    let divisors = divisors(coloring);
+    assert_eq!(divisors["400"], 1);
+    assert_eq!(divisors["1100.7"], 3);
+    for off_line in ["999", "1001.5", "1076.7"] {
+        assert_eq!(divisors[off_line], 0, "{off_line}");
+    }
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use rosu_map::section::general::GameMode;
+use rosu_map::Beatmap;
+use rosu_renderer::sections::StartBehavior;
+use rosu_renderer::{Player, PlayerBuilder};
+
+// Clock time at which map time `time` reaches the judgment line, at the
+// default speed and scroll time
+fn crossing(time: f64) -> f64 {
+    time + 1000.0
+}
+
+// The clock keeps running between the seek and the check
+fn assert_near(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 50.0,
+        "{actual} is not near {expected}"
+    );
+}
+
+fn preview_player(preview_time: i32) -> Player {
+    let beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: 4.0,
+        hit_objects: common::stream(40, 100.0, 4),
+        preview_time,
+        ..Default::default()
+    };
+    PlayerBuilder::new(beatmap)
+        .start_behavior(StartBehavior::FromPreviewPoint)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn first_note_time() {
+    let mut hit_objects = common::stream(10, 100.0, 4);
+    hit_objects.rotate_left(3);
+    assert_eq!(
+        common::player(hit_objects, 4).first_note_time(),
+        Some(1000.0)
+    );
+    let empty = common::player(Vec::new(), 4);
+    assert_eq!(empty.first_note_time(), None);
+}
+
+#[test]
+fn builder_applies_the_behavior() {
+    let player = common::builder(common::stream(40, 100.0, 4), 4)
+        .start_behavior(StartBehavior::FromFirstNote { lead_ms: 500.0 })
+        .build()
+        .unwrap();
+    assert_near(player.current_time(), crossing(1000.0) - 500.0);
+
+    let player = common::player(common::stream(40, 100.0, 4), 4);
+    assert_near(player.current_time(), 0.0);
+}
+
+#[test]
+fn reset_time_honors_the_behavior_after_seeks() {
+    let mut player = common::player(common::stream(40, 100.0, 4), 4);
+    let duration = player.duration();
+    player.set_start_behavior(StartBehavior::FromFirstNote { lead_ms: 250.0 });
+
+    for seek in [5000.0, 0.0, 1200.0] {
+        player.set_current_time(seek);
+        player.reset_time();
+        assert_near(player.current_time(), crossing(1000.0) - 250.0);
+    }
+    player.seek_fraction(0.9);
+    player.reset_time();
+    assert_near(player.current_time(), crossing(1000.0) - 250.0);
+
+    player.set_start_behavior(StartBehavior::FromZero);
+    player.set_current_time(3000.0);
+    player.reset_time();
+    assert_near(player.current_time(), 0.0);
+    // The timeline still starts at 0
+    assert_eq!(player.duration(), duration);
+}
+
+#[test]
+fn lead_longer_than_the_intro_starts_at_zero() {
+    let mut player = common::player(common::stream(40, 100.0, 4), 4);
+    player.set_start_behavior(StartBehavior::FromFirstNote { lead_ms: 10_000.0 });
+    player.set_current_time(3000.0);
+    player.reset_time();
+    assert_near(player.current_time(), 0.0);
+}
+
+#[test]
+fn invalid_lead_means_none() {
+    let mut player = common::player(common::stream(40, 100.0, 4), 4);
+    for lead_ms in [f64::NAN, -100.0, f64::INFINITY] {
+        player.set_start_behavior(StartBehavior::FromFirstNote { lead_ms });
+        assert_eq!(
+            player.start_behavior(),
+            StartBehavior::FromFirstNote { lead_ms: 0.0 }
+        );
+    }
+    player.reset_time();
+    assert_near(player.current_time(), crossing(1000.0));
+}
+
+#[test]
+fn preview_point() {
+    let mut player = preview_player(3000);
+    assert_near(player.current_time(), crossing(3000.0));
+    player.set_current_time(100.0);
+    player.reset_time();
+    assert_near(player.current_time(), crossing(3000.0));
+
+    // -1 when the map has none
+    assert_near(preview_player(-1).current_time(), 0.0);
+}
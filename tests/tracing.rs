@@ -0,0 +1,91 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+// Keeps the warn events, formatted as "message field=value ..."
+#[derive(Clone, Default)]
+struct Capture {
+    warnings: Arc<Mutex<Vec<String>>>,
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}"));
+        } else {
+            self.0.push_str(&format!(" {}={value:?}", field.name()));
+        }
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        if *event.metadata().level() == Level::WARN {
+            let mut fields = Fields(String::new());
+            event.record(&mut fields);
+            self.warnings.lock().unwrap().push(fields.0);
+        }
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn cs_zero_map_warns_about_the_keycount() {
+    let capture = Capture::default();
+    tracing::subscriber::with_default(capture.clone(), || {
+        let player = common::player(common::stream(8, 100.0, 1), 0);
+        assert!(!player.load_report().is_empty());
+    });
+    let warnings = capture.warnings.lock().unwrap();
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.starts_with("beatmap load issue") && w.contains("KeycountClamped")),
+        "{warnings:?}"
+    );
+}
+
+#[test]
+fn infinite_speed_warns_and_plays_at_one() {
+    let capture = Capture::default();
+    let mut player = common::player(common::stream(8, 100.0, 4), 4);
+    tracing::subscriber::with_default(capture.clone(), || {
+        player.set_speed(f64::INFINITY);
+    });
+    let warnings = capture.warnings.lock().unwrap();
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.starts_with("invalid playback speed") && w.contains("speed=inf")),
+        "{warnings:?}"
+    );
+
+    let ctx = egui::Context::default();
+    player.set_current_time(1200.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    let transform = player.frame_transform().unwrap();
+    assert_eq!(transform.rate, 1.0);
+    assert!(transform.y_for(400.0).is_finite());
+}
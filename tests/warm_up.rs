@@ -0,0 +1,44 @@
+mod common;
+
+use rosu_renderer::layout::mania::{CompositionTextStyle, TimingTextStyle};
+
+fn fill_ratio(ctx: &egui::Context) -> f32 {
+    ctx.fonts(|f| f.texture_atlas().lock().fill_ratio())
+}
+
+#[test]
+fn works_before_the_first_pass() {
+    let ctx = egui::Context::default();
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    player.set_timing_text(Some(TimingTextStyle::default()));
+    player.warm_up(&ctx);
+    player.warm_up(&ctx);
+
+    player.set_current_time(1500.0);
+    common::frame(&ctx, |ui| player.render(ui));
+    assert!(player.frame_stats().visible_notes > 0);
+}
+
+#[test]
+fn caches_overlay_glyphs() {
+    let ctx = egui::Context::default();
+    common::frame(&ctx, |_| {});
+    let mut player = common::player(common::stream(20, 100.0, 4), 4);
+    // Sizes nothing else uses, so their glyphs aren't cached yet
+    player.set_timing_text(Some(TimingTextStyle {
+        font_size: 37.0,
+        ..Default::default()
+    }));
+    player.set_composition_text(Some(CompositionTextStyle {
+        font_size: 41.0,
+        ..Default::default()
+    }));
+
+    let before = fill_ratio(&ctx);
+    player.warm_up(&ctx);
+    let warm = fill_ratio(&ctx);
+    assert!(warm > before);
+    // Idempotent
+    player.warm_up(&ctx);
+    assert_eq!(fill_ratio(&ctx), warm);
+}